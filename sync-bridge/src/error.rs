@@ -46,9 +46,11 @@ impl From<ClientError> for SyncBridgeError {
             ClientError::ConnectionFailed(msg) => SyncBridgeError::ConnectionFailed(msg),
             ClientError::AllRelaysFailed(msg) => SyncBridgeError::AllRelaysFailed(msg),
             ClientError::Crypto(e) => SyncBridgeError::CryptoError(e.to_string()),
+            ClientError::SecureChannel(e) => SyncBridgeError::CryptoError(e.to_string()),
             ClientError::Transport(e) => SyncBridgeError::TransportError(e.to_string()),
             ClientError::Protocol(msg) => SyncBridgeError::ProtocolError(msg),
             ClientError::Serialization(msg) => SyncBridgeError::ProtocolError(msg),
+            ClientError::Pairing(e) => e.into(),
         }
     }
 }
@@ -62,6 +64,7 @@ impl From<PairingError> for SyncBridgeError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use zerok_sync_client::ChannelError;
     use zerok_sync_client::CryptoError;
     use zerok_sync_client::TransportError;
 
@@ -94,6 +97,13 @@ mod tests {
         assert!(err.to_string().contains("crypto"));
     }
 
+    #[test]
+    fn client_secure_channel_error_maps_to_crypto() {
+        let err: SyncBridgeError = ClientError::SecureChannel(ChannelError::Decrypt).into();
+        assert!(matches!(err, SyncBridgeError::CryptoError(_)));
+        assert!(err.to_string().contains("sealed frame"));
+    }
+
     #[test]
     fn client_transport_error_maps_correctly() {
         let err: SyncBridgeError = ClientError::Transport(TransportError::Timeout).into();
@@ -117,6 +127,12 @@ mod tests {
         assert!(err.to_string().contains("expired"));
     }
 
+    #[test]
+    fn client_pairing_maps_to_invalid_config() {
+        let err: SyncBridgeError = ClientError::Pairing(PairingError::Expired).into();
+        assert!(matches!(err, SyncBridgeError::InvalidConfig(_)));
+    }
+
     #[test]
     fn display_is_human_readable() {
         let err = SyncBridgeError::InvalidConfig("missing salt".to_string());
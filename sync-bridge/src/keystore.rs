@@ -0,0 +1,143 @@
+//! SyncKeystore — FFI-friendly wrapper around the encrypted keystore.
+//!
+//! Lets bindings persist group secrets and device keys sealed under a local
+//! master key instead of handling raw secret bytes themselves. The keystore
+//! is serialized to opaque bytes; storing them is up to the application.
+
+use crate::error::SyncBridgeError;
+use zerok_sync_client::{Keystore, KeystoreError, KEY_SIZE};
+
+/// Encrypted keystore for FFI consumers.
+///
+/// Secrets are returned as owned `Vec<u8>`; callers should drop them as
+/// soon as they have been handed to a [`SyncHandleConfig`](crate::SyncHandleConfig).
+#[derive(Debug)]
+pub struct SyncKeystore {
+    inner: Keystore,
+}
+
+impl SyncKeystore {
+    /// Create an empty keystore whose master key is derived from `passphrase`.
+    pub fn create_with_passphrase(passphrase: &str) -> Result<Self, SyncBridgeError> {
+        let inner = Keystore::create_with_passphrase(passphrase)?;
+        Ok(Self { inner })
+    }
+
+    /// Create an empty keystore under a 32-byte master key
+    /// (e.g., one held by the platform keychain).
+    pub fn create_with_key(key: &[u8]) -> Result<Self, SyncBridgeError> {
+        Ok(Self {
+            inner: Keystore::create_with_key(&master_key(key)?),
+        })
+    }
+
+    /// Open serialized keystore bytes with a passphrase.
+    pub fn open_with_passphrase(bytes: &[u8], passphrase: &str) -> Result<Self, SyncBridgeError> {
+        let inner = Keystore::open_with_passphrase(bytes, passphrase)?;
+        Ok(Self { inner })
+    }
+
+    /// Open serialized keystore bytes with a 32-byte master key.
+    pub fn open_with_key(bytes: &[u8], key: &[u8]) -> Result<Self, SyncBridgeError> {
+        let inner = Keystore::open_with_key(bytes, &master_key(key)?)?;
+        Ok(Self { inner })
+    }
+
+    /// Store a secret (e.g., a device key) under `name`.
+    pub fn insert(&mut self, name: &str, secret: &[u8]) -> Result<(), SyncBridgeError> {
+        self.inner.insert(name, secret)?;
+        Ok(())
+    }
+
+    /// Decrypt the secret stored under `name`.
+    pub fn get(&self, name: &str) -> Result<Option<Vec<u8>>, SyncBridgeError> {
+        Ok(self.inner.get(name)?.map(|secret| secret.to_vec()))
+    }
+
+    /// Remove the secret stored under `name`. Returns whether it existed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.inner.remove(name)
+    }
+
+    /// Entry names, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.inner.names().map(str::to_string).collect()
+    }
+
+    /// Store a 32-byte group secret under its group ID.
+    pub fn insert_group_secret(
+        &mut self,
+        group_id: &str,
+        secret_bytes: &[u8],
+    ) -> Result<(), SyncBridgeError> {
+        let secret = zerok_sync_client::GroupSecret::from_raw(master_key(secret_bytes)?);
+        self.inner.insert_group_secret(group_id, &secret)?;
+        Ok(())
+    }
+
+    /// Load the group secret stored under `group_id`, for
+    /// [`SyncHandleConfig::from_secret_bytes`](crate::SyncHandleConfig::from_secret_bytes).
+    pub fn group_secret(&self, group_id: &str) -> Result<Option<Vec<u8>>, SyncBridgeError> {
+        Ok(self
+            .inner
+            .group_secret(group_id)?
+            .map(|secret| secret.as_bytes().to_vec()))
+    }
+
+    /// Serialize the keystore. Entries remain encrypted.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+}
+
+impl From<KeystoreError> for SyncBridgeError {
+    fn from(err: KeystoreError) -> Self {
+        SyncBridgeError::CryptoError(err.to_string())
+    }
+}
+
+/// Check a key passed across FFI is 32 bytes.
+fn master_key(key: &[u8]) -> Result<[u8; KEY_SIZE], SyncBridgeError> {
+    key.try_into().map_err(|_| {
+        SyncBridgeError::InvalidConfig(format!("key must be 32 bytes, got {}", key.len()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_roundtrip_through_bytes() {
+        let mut keystore = SyncKeystore::create_with_key(&[7u8; 32]).unwrap();
+        keystore
+            .insert_group_secret("group-a", &[0x42; 32])
+            .unwrap();
+        keystore.insert("device:key", b"device secret").unwrap();
+
+        let bytes = keystore.to_bytes();
+        let reopened = SyncKeystore::open_with_key(&bytes, &[7u8; 32]).unwrap();
+        assert_eq!(reopened.names(), vec!["device:key", "group:group-a"]);
+        assert_eq!(
+            reopened.group_secret("group-a").unwrap(),
+            Some(vec![0x42; 32])
+        );
+        assert_eq!(
+            reopened.get("device:key").unwrap(),
+            Some(b"device secret".to_vec())
+        );
+    }
+
+    #[test]
+    fn keystore_errors_flatten() {
+        let bytes = SyncKeystore::create_with_key(&[7u8; 32])
+            .unwrap()
+            .to_bytes();
+        let err = SyncKeystore::open_with_key(&bytes, &[8u8; 32]).unwrap_err();
+        assert!(matches!(err, SyncBridgeError::CryptoError(_)));
+        assert!(err.to_string().contains("wrong keystore"));
+
+        let err = SyncKeystore::create_with_key(&[0u8; 16]).unwrap_err();
+        assert!(matches!(err, SyncBridgeError::InvalidConfig(_)));
+    }
+}
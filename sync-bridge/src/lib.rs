@@ -18,8 +18,10 @@
 
 pub mod error;
 pub mod handle;
+pub mod keystore;
 pub mod types;
 
 pub use error::SyncBridgeError;
 pub use handle::SyncHandle;
+pub use keystore::SyncKeystore;
 pub use types::{PushResult, SyncBlob, SyncHandleConfig, SyncInvite};
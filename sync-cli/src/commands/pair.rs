@@ -2,14 +2,215 @@
 
 use anyhow::{Context, Result};
 use std::path::Path;
-use zerok_sync_client::GroupSecret as ClientGroupSecret;
-use zerok_sync_core::{GroupSecret, Invite, RelayNodeId};
+use zerok_sync_client::{
+    GroupSecret as ClientGroupSecret, IrohTransport, MailboxClient, SyncClient, SyncConfig,
+    Transport,
+};
+use zerok_sync_core::{
+    passphrase_strength, GroupSecret, Invite, PairingCode, PassphraseGenerator, RelayNodeId,
+    MIN_PASSPHRASE_ENTROPY,
+};
 use zerok_sync_types::GroupId;
 
-use crate::config::{DeviceConfig, GroupConfig};
+use crate::config::{keystore_passphrase_from_env, DeviceConfig, GroupConfig};
 
 /// Create a new sync group and display invite code.
-pub async fn create(data_dir: &Path, passphrase: Option<&str>) -> Result<()> {
+///
+/// With `relay` (the relay's EndpointId), the invite is also published to
+/// the relay's mailbox so other devices can join with the short code.
+/// `relay_key` (the relay's secure channel public key) is pinned in the
+/// invite. With `max_uses`, the invite is registered on the relay and stops
+/// admitting devices after that many joins.
+pub async fn create(
+    data_dir: &Path,
+    passphrase: Option<&str>,
+    relay: Option<&str>,
+    relay_key: Option<&str>,
+    max_uses: Option<u32>,
+) -> Result<()> {
+    let invite = create_group(data_dir, passphrase, relay, relay_key).await?;
+    let invite = match max_uses {
+        Some(max_uses) => track_invite(data_dir, invite, max_uses).await?,
+        None => invite,
+    };
+    let qr_payload = invite.to_qr_payload();
+
+    let short_code = match relay {
+        Some(relay) => {
+            let transport = IrohTransport::new()
+                .await
+                .context("Failed to create iroh transport")?;
+            Some(publish_short_code(transport, relay, &invite).await?)
+        }
+        None => None,
+    };
+
+    println!("Sync group created!");
+    println!();
+    println!("  Group ID: {}", &invite.group_id.to_string()[..16]);
+    print_tracked_invite(&invite, max_uses);
+    println!();
+    println!("Share with other devices:");
+    println!();
+    if let Some(short_code) = &short_code {
+        println!("  Short code: {} (single use)", short_code);
+        println!();
+    }
+    println!("  QR payload: {}", qr_payload);
+    println!();
+    println!("Note: Other devices need the same passphrase to join.");
+    println!("The invite expires in 10 minutes.");
+    if short_code.is_none() {
+        println!("Use --relay <endpoint-id> to also get a short code.");
+    }
+
+    Ok(())
+}
+
+/// Create a new sync group and pair one device with a pairing code.
+///
+/// Displays a short numeric code and waits (up to 10 minutes) for the other
+/// device to enter it with `pair --join <code> --code`. The invite travels
+/// through `relay` encrypted under a key only the two devices share.
+/// `max_uses` registers the invite on the relay as in [`create`].
+pub async fn create_with_code(
+    data_dir: &Path,
+    passphrase: Option<&str>,
+    relay: &str,
+    relay_key: Option<&str>,
+    max_uses: Option<u32>,
+) -> Result<()> {
+    let invite = create_group(data_dir, passphrase, Some(relay), relay_key).await?;
+    let invite = match max_uses {
+        Some(max_uses) => track_invite(data_dir, invite, max_uses).await?,
+        None => invite,
+    };
+    let code = PairingCode::generate();
+
+    println!("Sync group created!");
+    println!();
+    println!("  Group ID:     {}", &invite.group_id.to_string()[..16]);
+    println!("  Pairing code: {}", code);
+    print_tracked_invite(&invite, max_uses);
+    println!();
+    println!("On the other device, run:");
+    println!("  sync-cli pair --join {} --code --relay {}", code, relay);
+    println!();
+    println!("Waiting for the other device...");
+
+    let transport = IrohTransport::new()
+        .await
+        .context("Failed to create iroh transport")?;
+    offer_with_code(transport, relay, &code, &invite).await?;
+
+    println!("Invite delivered. The other device has joined the group.");
+
+    Ok(())
+}
+
+/// Revoke an invite created with `pair --create --max-uses`.
+///
+/// Devices that already joined with it keep access.
+pub async fn revoke(data_dir: &Path, invite_id: &str) -> Result<()> {
+    let invite_id: [u8; 16] = hex::decode(invite_id)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Invite ID must be a 32-character hex string"))?;
+
+    let transport = device_transport(data_dir).await?;
+    revoke_invite(data_dir, transport, invite_id).await?;
+
+    println!("Invite revoked.");
+    println!("Devices that already joined with it keep access.");
+
+    Ok(())
+}
+
+/// Give the group's new invite an ID and register it on the relay.
+async fn track_invite(data_dir: &Path, invite: Invite, max_uses: u32) -> Result<Invite> {
+    let invite = invite.with_random_id();
+    let transport = device_transport(data_dir).await?;
+    register_invite(data_dir, transport, &invite, max_uses).await?;
+    Ok(invite)
+}
+
+/// Show the ID of a tracked invite and how to revoke it.
+fn print_tracked_invite(invite: &Invite, max_uses: Option<u32>) {
+    if let (Some(invite_id), Some(max_uses)) = (invite.invite_id, max_uses) {
+        let invite_id = hex::encode(invite_id);
+        println!("  Invite ID: {} (up to {} joins)", invite_id, max_uses);
+        println!("  Revoke with: sync-cli pair --revoke {}", invite_id);
+    }
+}
+
+/// Create an iroh transport with this device's endpoint key, so the relay
+/// recognizes it as the same group member.
+async fn device_transport(data_dir: &Path) -> Result<IrohTransport> {
+    let mut device = DeviceConfig::load(data_dir).await?;
+    let config = device.transport_config(data_dir).await?;
+    IrohTransport::with_config(config)
+        .await
+        .context("Failed to create iroh transport")
+}
+
+/// Connect to the group's primary relay as a member.
+async fn connect_group<T: Transport>(data_dir: &Path, transport: T) -> Result<SyncClient<T>> {
+    let device = DeviceConfig::load(data_dir).await?;
+    let group = GroupConfig::load(data_dir).await?;
+    let secret = group.load_secret(data_dir, None).await?;
+    let relay = group
+        .primary_relay()
+        .ok_or_else(|| anyhow::anyhow!("No relay addresses configured"))?;
+
+    let config = SyncConfig::from_secret_bytes(secret.as_bytes(), relay)
+        .with_device_name(&device.device_name);
+    let config = group
+        .relay_noise_keys()
+        .into_iter()
+        .fold(config, |config, (address, key)| {
+            config.with_relay_noise_key(address, key)
+        });
+
+    let client = SyncClient::new(config, transport);
+    client
+        .connect()
+        .await
+        .context("Failed to connect to relay")?;
+    Ok(client)
+}
+
+/// Register `invite` on the group's relay, limited to `max_uses` joins.
+async fn register_invite<T: Transport>(
+    data_dir: &Path,
+    transport: T,
+    invite: &Invite,
+    max_uses: u32,
+) -> Result<()> {
+    let client = connect_group(data_dir, transport).await?;
+    let result = client.register_invite(invite, max_uses).await;
+    let _ = client.disconnect().await;
+    result.context("Failed to register invite with relay")
+}
+
+/// Revoke a registered invite on the group's relay.
+async fn revoke_invite<T: Transport>(
+    data_dir: &Path,
+    transport: T,
+    invite_id: [u8; 16],
+) -> Result<()> {
+    let client = connect_group(data_dir, transport).await?;
+    let result = client.revoke_invite(invite_id).await;
+    let _ = client.disconnect().await;
+    result.context("Failed to revoke invite on relay")
+}
+
+/// Derive a new group, save its configuration and return its invite.
+async fn create_group(
+    data_dir: &Path,
+    passphrase: Option<&str>,
+    relay: Option<&str>,
+    relay_key: Option<&str>,
+) -> Result<Invite> {
     // Ensure device is initialized
     let _device = DeviceConfig::load(data_dir).await?;
 
@@ -21,55 +222,104 @@ pub async fn create(data_dir: &Path, passphrase: Option<&str>) -> Result<()> {
         );
     }
 
-    // Get or prompt for passphrase
+    // Get or prompt for passphrase (empty input generates one)
     let passphrase = match passphrase {
         Some(p) => p.to_string(),
-        None => prompt_passphrase("Enter passphrase for new sync group: ")?,
+        None => prompt_new_passphrase()?,
     };
 
-    // Use sync-client's GroupSecret for passphrase derivation (with random salt)
-    let (client_secret, salt) = ClientGroupSecret::from_passphrase(&passphrase);
+    // Use sync-client's GroupSecret for passphrase derivation (with random salt),
+    // refusing passphrases that are cheap to guess offline
+    let (client_secret, salt) =
+        ClientGroupSecret::from_passphrase_checked(&passphrase, MIN_PASSPHRASE_ENTROPY)
+            .with_context(|| {
+                format!(
+                    "Choose a longer passphrase, e.g. a generated one like: {}",
+                    PassphraseGenerator::new().generate()
+                )
+            })?;
 
     // Convert to sync-core's GroupSecret format
     let group_secret = GroupSecret::from_bytes(*client_secret.as_bytes());
     let group_id = group_secret.derive_group_id();
 
-    // For now, use placeholder relay node (will be replaced with actual iroh NodeId)
-    let relay_bytes = [0u8; 32]; // Placeholder - in production this would be the relay's public key
-    let relay_node_id = RelayNodeId::from_bytes(relay_bytes);
-
-    // Create invite with salt for v2 format
-    let invite = Invite::create(relay_node_id, group_id, group_secret, salt.to_vec());
-    let short_code = invite.to_short_code();
-    let qr_payload = invite.to_qr_payload();
+    // Without a relay, use a placeholder node (QR invites only)
+    let relay_node_id = match relay {
+        Some(relay) => parse_endpoint_id(relay)?,
+        None => RelayNodeId::from_bytes([0u8; 32]),
+    };
+    let relay_address = relay
+        .map(str::to_string)
+        .unwrap_or_else(|| relay_node_id.to_string());
+    let relay_key = relay_key.map(parse_noise_key).transpose()?;
 
     // Save group configuration with secret and salt for encryption
-    let group_config = GroupConfig::with_secret_and_salt(
+    let mut group_config = GroupConfig::with_secret_and_salt(
         &group_id.to_string(),
-        &[relay_node_id.to_string().as_str()],
+        &[relay_address.as_str()],
         client_secret.as_bytes(),
         &salt,
     );
-    group_config.save(data_dir).await?;
+    if let Some(key) = relay_key {
+        group_config
+            .relay_noise_keys_hex
+            .insert(relay_address, hex::encode(key));
+    }
+    save_group(data_dir, group_config).await?;
 
-    println!("Sync group created!");
-    println!();
-    println!("  Group ID: {}", &group_id.to_string()[..16]);
-    println!();
-    println!("Share with other devices:");
-    println!();
-    println!("  Short code: {}", short_code);
-    println!();
-    println!("  QR payload: {}", qr_payload);
-    println!();
-    println!("Note: Other devices need the same passphrase to join.");
-    println!("The invite expires in 10 minutes.");
+    // Create invite with salt for v2 format
+    let invite = Invite::create(relay_node_id, group_id, group_secret, salt.to_vec());
+    Ok(match relay_key {
+        Some(key) => invite.with_relay_noise_key(&relay_node_id, key),
+        None => invite,
+    })
+}
 
-    Ok(())
+/// Hand `invite` to the device entering `code`, through the relay.
+async fn offer_with_code<T: Transport>(
+    transport: T,
+    relay: &str,
+    code: &PairingCode,
+    invite: &Invite,
+) -> Result<()> {
+    let mailbox = MailboxClient::new(transport);
+    mailbox
+        .connect(relay)
+        .await
+        .context("Failed to connect to relay")?;
+    let result = mailbox.offer_invite_with_code(code, invite).await;
+    let _ = mailbox.close().await;
+    result.context("Pairing failed (wrong code entered, or timed out); start again")
+}
+
+/// Publish `invite` to the relay's mailbox and return its short code.
+async fn publish_short_code<T: Transport>(
+    transport: T,
+    relay: &str,
+    invite: &Invite,
+) -> Result<String> {
+    let mailbox = MailboxClient::new(transport);
+    mailbox
+        .connect(relay)
+        .await
+        .context("Failed to connect to relay")?;
+    let code = mailbox
+        .publish_short_code(invite)
+        .await
+        .context("Failed to publish invite to relay")?;
+    let _ = mailbox.close().await;
+    Ok(code)
 }
 
-/// Join an existing sync group using a QR payload, EndpointId, or passphrase.
-pub async fn join(data_dir: &Path, code: &str, passphrase: Option<&str>) -> Result<()> {
+/// Join an existing sync group using a QR payload, EndpointId, or short code.
+///
+/// Short codes are looked up on `relay` (the relay's EndpointId).
+pub async fn join(
+    data_dir: &Path,
+    code: &str,
+    passphrase: Option<&str>,
+    relay: Option<&str>,
+) -> Result<()> {
     // Ensure device is initialized
     let _device = DeviceConfig::load(data_dir).await?;
 
@@ -83,46 +333,12 @@ pub async fn join(data_dir: &Path, code: &str, passphrase: Option<&str>) -> Resu
 
     // Try to parse as QR payload first
     if let Ok(invite) = Invite::from_qr_payload(code) {
-        // Check if invite is expired
-        if invite.is_expired() {
-            anyhow::bail!("Invite code has expired. Request a new one.");
-        }
-
-        // If we got the invite from QR but also have a passphrase, verify it matches
-        if let Some(passphrase) = passphrase {
-            let client_secret =
-                ClientGroupSecret::from_passphrase_with_salt(passphrase, &invite.salt);
-            let derived_group_id = GroupId::from_secret(client_secret.as_bytes());
-
-            if derived_group_id != invite.group_id {
-                anyhow::bail!("Passphrase does not match the group in this invite");
-            }
-        }
-
-        // Save group configuration WITH secret and salt (the invite carries both)
         let relay_strs: Vec<String> = invite
             .relay_node_ids
             .iter()
             .map(|r| r.to_string())
             .collect();
-        let relay_refs: Vec<&str> = relay_strs.iter().map(|s| s.as_str()).collect();
-        let group_config = GroupConfig::with_secret_and_salt(
-            &invite.group_id.to_string(),
-            &relay_refs,
-            invite.group_secret.as_bytes(),
-            &invite.salt,
-        );
-        group_config.save(data_dir).await?;
-
-        println!("Joined sync group successfully!");
-        println!();
-        println!("  Group ID: {}", &invite.group_id.to_string()[..16]);
-        println!();
-        println!("Next steps:");
-        println!("  1. Push data: sync-cli push \"Hello!\"");
-        println!("  2. Pull data: sync-cli pull");
-
-        return Ok(());
+        return save_invite(data_dir, &invite, &relay_strs, passphrase).await;
     }
 
     // Check if code looks like an EndpointId (64-char hex string)
@@ -152,7 +368,7 @@ pub async fn join(data_dir: &Path, code: &str, passphrase: Option<&str>) -> Resu
             client_secret.as_bytes(),
             ENDPOINT_JOIN_SALT,
         );
-        group_config.save(data_dir).await?;
+        save_group(data_dir, group_config).await?;
 
         println!("Joined sync group successfully!");
         println!();
@@ -166,21 +382,185 @@ pub async fn join(data_dir: &Path, code: &str, passphrase: Option<&str>) -> Resu
         return Ok(());
     }
 
-    // Fall back to short code handling
-    // With short codes, we need the passphrase to reconstruct the group
-    let passphrase = match passphrase {
-        Some(p) => p.to_string(),
-        None => prompt_passphrase("Enter group passphrase: ")?,
-    };
+    // Fall back to short code lookup on the relay
+    let relay = relay.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Short code join needs the relay holding the invite:\n  \
+             sync-cli pair --join {} --relay <endpoint-id>",
+            code
+        )
+    })?;
+    let transport = IrohTransport::new()
+        .await
+        .context("Failed to create iroh transport")?;
+    join_with_short_code(data_dir, code, passphrase, relay, transport).await
+}
+
+/// Join an existing sync group with a pairing code shown by `pair --create --code`.
+pub async fn join_with_code(
+    data_dir: &Path,
+    code: &str,
+    passphrase: Option<&str>,
+    relay: &str,
+) -> Result<()> {
+    // Ensure device is initialized
+    let _device = DeviceConfig::load(data_dir).await?;
+
+    // Check if already paired
+    if GroupConfig::exists(data_dir).await {
+        anyhow::bail!(
+            "Already paired to a group. Delete {} to join a different group.",
+            data_dir.join("group.json").display()
+        );
+    }
+
+    let code = PairingCode::parse(code).context("Pairing codes look like 1234-567890")?;
+    let transport = IrohTransport::new()
+        .await
+        .context("Failed to create iroh transport")?;
+    join_with_pairing_code(data_dir, &code, passphrase, relay, transport).await
+}
 
-    // For short code joining, we would need relay info from a bootstrap server
-    // This is not yet implemented
-    let _ = (&passphrase, code); // Suppress unused warnings
-    anyhow::bail!(
-        "Short code lookup not yet implemented. Use EndpointId directly:\n  \
-         sync-cli pair --join <endpoint-id>\n\n\
-         Get the EndpointId from the server running 'sync-cli serve'"
+/// Fetch the invite from the device showing `code` and join its group.
+async fn join_with_pairing_code<T: Transport>(
+    data_dir: &Path,
+    code: &PairingCode,
+    passphrase: Option<&str>,
+    relay: &str,
+    transport: T,
+) -> Result<()> {
+    let mailbox = MailboxClient::new(transport);
+    mailbox
+        .connect(relay)
+        .await
+        .context("Failed to connect to relay")?;
+    let invite = mailbox.accept_invite_with_code(code).await;
+    let _ = mailbox.close().await;
+    let invite =
+        invite.context("Pairing failed (wrong code, or the other device stopped waiting)")?;
+
+    save_invite(data_dir, &invite, &[relay.to_string()], passphrase).await
+}
+
+/// Fetch the invite for a short code from the relay and join its group.
+async fn join_with_short_code<T: Transport>(
+    data_dir: &Path,
+    code: &str,
+    passphrase: Option<&str>,
+    relay: &str,
+    transport: T,
+) -> Result<()> {
+    let mailbox = MailboxClient::new(transport);
+    mailbox
+        .connect(relay)
+        .await
+        .context("Failed to connect to relay")?;
+    let invite = mailbox
+        .fetch_short_code(code)
+        .await
+        .context("Short code not found on relay (already used, expired, or mistyped)")?;
+    let _ = mailbox.close().await;
+
+    save_invite(data_dir, &invite, &[relay.to_string()], passphrase).await
+}
+
+/// Verify an invite and save its group configuration.
+async fn save_invite(
+    data_dir: &Path,
+    invite: &Invite,
+    relay_addresses: &[String],
+    passphrase: Option<&str>,
+) -> Result<()> {
+    // Check if invite is expired
+    if invite.is_expired() {
+        anyhow::bail!("Invite code has expired. Request a new one.");
+    }
+
+    // If we got the invite but also have a passphrase, verify it matches
+    if let Some(passphrase) = passphrase {
+        let client_secret = ClientGroupSecret::from_passphrase_with_salt(passphrase, &invite.salt);
+        let derived_group_id = GroupId::from_secret(client_secret.as_bytes());
+
+        if derived_group_id != invite.group_id {
+            anyhow::bail!("Passphrase does not match the group in this invite");
+        }
+    }
+
+    // Save group configuration WITH secret and salt (the invite carries both)
+    let relay_refs: Vec<&str> = relay_addresses.iter().map(|s| s.as_str()).collect();
+    let mut group_config = GroupConfig::with_secret_and_salt(
+        &invite.group_id.to_string(),
+        &relay_refs,
+        invite.group_secret.as_bytes(),
+        &invite.salt,
     );
+    group_config.invite_id_hex = invite.invite_id.map(hex::encode);
+    group_config.compression = invite.compression;
+    group_config.padding = invite.padding;
+    for (relay, address) in invite.relay_node_ids.iter().zip(relay_addresses) {
+        if let Some(key) = invite.relay_noise_key(relay) {
+            group_config
+                .relay_noise_keys_hex
+                .insert(address.clone(), hex::encode(key));
+        }
+    }
+    save_group(data_dir, group_config).await?;
+
+    println!("Joined sync group successfully!");
+    println!();
+    println!("  Group ID: {}", &invite.group_id.to_string()[..16]);
+    println!();
+    println!("Next steps:");
+    println!("  1. Push data: sync-cli push \"Hello!\"");
+    println!("  2. Pull data: sync-cli pull");
+
+    Ok(())
+}
+
+/// Parse a relay EndpointId (64-char hex string).
+fn parse_endpoint_id(relay: &str) -> Result<RelayNodeId> {
+    let bytes: [u8; 32] = hex::decode(relay)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Relay must be a 64-character hex EndpointId"))?;
+    Ok(RelayNodeId::from_bytes(bytes))
+}
+
+/// Parse a relay secure channel public key (64-char hex string).
+fn parse_noise_key(key: &str) -> Result<[u8; 32]> {
+    hex::decode(key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Relay key must be a 64-character hex public key"))
+}
+
+/// Prompt for a new group passphrase, generating one if the input is empty.
+fn prompt_new_passphrase() -> Result<String> {
+    let passphrase = rpassword::prompt_password(
+        "Enter passphrase for new sync group (leave empty to generate one): ",
+    )
+    .context("Failed to read passphrase")?;
+
+    let trimmed = passphrase.trim().to_string();
+    if !trimmed.is_empty() {
+        println!("Passphrase strength: {}", passphrase_strength(&trimmed));
+        return Ok(trimmed);
+    }
+
+    let generated = PassphraseGenerator::new().generate();
+    println!("Generated passphrase: {}", generated);
+    println!("Write it down: other devices need it to join.");
+    println!();
+    Ok(generated)
+}
+
+/// Save a new group configuration, sealing its secret in the keystore
+/// when a keystore passphrase is configured.
+async fn save_group(data_dir: &Path, mut group_config: GroupConfig) -> Result<()> {
+    if let Some(master) = keystore_passphrase_from_env() {
+        group_config.seal_secret(data_dir, &master).await?;
+    }
+    group_config.save(data_dir).await
 }
 
 /// Prompt for passphrase input with echo suppression.
@@ -200,6 +580,10 @@ fn prompt_passphrase(prompt: &str) -> Result<String> {
 mod tests {
     use super::*;
     use tempfile::tempdir;
+    use zerok_sync_client::MockTransport;
+    use zerok_sync_types::{Cursor, InviteAck, MailboxData, Message, Welcome};
+
+    const TEST_PASSPHRASE: &str = "maple-otter-violin-canyon-pixel";
 
     async fn init_device(dir: &Path) {
         let config = DeviceConfig::new("Test Device");
@@ -211,7 +595,7 @@ mod tests {
         let dir = tempdir().unwrap();
 
         // Should fail without device init
-        let result = create(dir.path(), Some("test-passphrase")).await;
+        let result = create(dir.path(), Some(TEST_PASSPHRASE), None, None, None).await;
         assert!(result.is_err());
     }
 
@@ -220,7 +604,9 @@ mod tests {
         let dir = tempdir().unwrap();
         init_device(dir.path()).await;
 
-        create(dir.path(), Some("test-passphrase")).await.unwrap();
+        create(dir.path(), Some(TEST_PASSPHRASE), None, None, None)
+            .await
+            .unwrap();
 
         assert!(dir.path().join("group.json").exists());
         let config = GroupConfig::load(dir.path()).await.unwrap();
@@ -233,10 +619,12 @@ mod tests {
         init_device(dir.path()).await;
 
         // First create should succeed
-        create(dir.path(), Some("test-passphrase")).await.unwrap();
+        create(dir.path(), Some(TEST_PASSPHRASE), None, None, None)
+            .await
+            .unwrap();
 
         // Second create should fail
-        let result = create(dir.path(), Some("other-passphrase")).await;
+        let result = create(dir.path(), Some("other-passphrase"), None, None, None).await;
         assert!(result.is_err());
     }
 
@@ -255,7 +643,7 @@ mod tests {
         let qr = invite.to_qr_payload();
 
         // Join with the QR payload
-        join(dir.path(), &qr, None).await.unwrap();
+        join(dir.path(), &qr, None, None).await.unwrap();
 
         assert!(dir.path().join("group.json").exists());
 
@@ -265,6 +653,55 @@ mod tests {
         assert!(config.salt_hex.is_some());
     }
 
+    #[tokio::test]
+    async fn join_with_qr_payload_pins_relay_key() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+
+        let salt = b"test-salt-00000!";
+        let client_secret = ClientGroupSecret::from_passphrase_with_salt("test", salt);
+        let group_secret = GroupSecret::from_bytes(*client_secret.as_bytes());
+        let group_id = group_secret.derive_group_id();
+        let relay = RelayNodeId::from_bytes([0xAB; 32]);
+        let invite = Invite::create(relay, group_id, group_secret, salt.to_vec())
+            .with_relay_noise_key(&relay, [7u8; 32]);
+
+        join(dir.path(), &invite.to_qr_payload(), None, None)
+            .await
+            .unwrap();
+
+        let config = GroupConfig::load(dir.path()).await.unwrap();
+        assert_eq!(
+            config.relay_noise_keys(),
+            vec![(relay.to_string().as_str(), [7u8; 32])]
+        );
+    }
+
+    #[tokio::test]
+    async fn join_with_qr_payload_keeps_payload_settings() {
+        use zerok_sync_client::{Compression, PaddingPolicy};
+
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+
+        let salt = b"test-salt-00000!";
+        let client_secret = ClientGroupSecret::from_passphrase_with_salt("test", salt);
+        let group_secret = GroupSecret::from_bytes(*client_secret.as_bytes());
+        let group_id = group_secret.derive_group_id();
+        let relay = RelayNodeId::from_bytes([0xAB; 32]);
+        let invite = Invite::create(relay, group_id, group_secret, salt.to_vec())
+            .with_compression(Compression::Lz4)
+            .with_padding(PaddingPolicy::PowerOfTwo);
+
+        join(dir.path(), &invite.to_qr_payload(), None, None)
+            .await
+            .unwrap();
+
+        let config = GroupConfig::load(dir.path()).await.unwrap();
+        assert_eq!(config.compression, Compression::Lz4);
+        assert_eq!(config.padding, PaddingPolicy::PowerOfTwo);
+    }
+
     #[tokio::test]
     async fn join_with_endpoint_id() {
         let dir = tempdir().unwrap();
@@ -275,7 +712,7 @@ mod tests {
         let passphrase = "test-passphrase";
 
         // Join with EndpointId
-        join(dir.path(), endpoint_id, Some(passphrase))
+        join(dir.path(), endpoint_id, Some(passphrase), None)
             .await
             .unwrap();
 
@@ -286,16 +723,175 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn join_with_short_code_fails() {
+    async fn join_with_short_code_requires_relay() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+
+        let result = join(dir.path(), "ABCD-EFGH-IJKL-MNOP", None, None).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("--relay"));
+    }
+
+    #[tokio::test]
+    async fn join_with_short_code_from_relay() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+
+        let salt = b"test-salt-00000!";
+        let client_secret = ClientGroupSecret::from_passphrase_with_salt("test", salt);
+        let group_secret = GroupSecret::from_bytes(*client_secret.as_bytes());
+        let group_id = group_secret.derive_group_id();
+        let relay = "aa8e9a9115685ffab95d24c40714db6fae3e046b9eb197ccc1b04cb46a014444";
+        let invite = Invite::create(
+            parse_endpoint_id(relay).unwrap(),
+            group_id,
+            group_secret,
+            salt.to_vec(),
+        )
+        .with_random_id();
+        let code = invite.to_short_code();
+
+        // Relay answers the mailbox lookup with the sealed invite
+        let transport = MockTransport::new();
+        transport.queue_response(
+            Message::MailboxData(MailboxData {
+                mailbox: Invite::short_code_mailbox(&code).unwrap(),
+                payload: Some(invite.seal_for_short_code()),
+            })
+            .to_bytes()
+            .unwrap(),
+        );
+
+        join_with_short_code(dir.path(), &code, Some("test"), relay, transport)
+            .await
+            .unwrap();
+
+        let config = GroupConfig::load(dir.path()).await.unwrap();
+        assert_eq!(config.group_id, group_id.to_string());
+        assert_eq!(config.relay_addresses, vec![relay]);
+        assert_eq!(
+            config.group_secret_bytes().unwrap(),
+            client_secret.as_bytes().to_vec()
+        );
+        assert_eq!(config.invite_id(), invite.invite_id);
+    }
+
+    fn welcome() -> Vec<u8> {
+        Message::Welcome(Welcome {
+            version: 1,
+            max_cursor: Cursor::zero(),
+            pending_count: 0,
+        })
+        .to_bytes()
+        .unwrap()
+    }
+
+    fn invite_ack(invite_id: [u8; 16]) -> Vec<u8> {
+        Message::InviteAck(InviteAck { invite_id })
+            .to_bytes()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn register_invite_with_relay() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+        let invite = create_group(dir.path(), Some(TEST_PASSPHRASE), None, None)
+            .await
+            .unwrap()
+            .with_random_id();
+
+        // Relay welcomes the member, then acks the registration
+        let transport = MockTransport::new();
+        transport.queue_response(welcome());
+        transport.queue_response(invite_ack(invite.invite_id.unwrap()));
+
+        register_invite(dir.path(), transport, &invite, 3)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn revoke_invite_rejects_mismatched_ack() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+        create(dir.path(), Some(TEST_PASSPHRASE), None, None, None)
+            .await
+            .unwrap();
+
+        let transport = MockTransport::new();
+        transport.queue_response(welcome());
+        transport.queue_response(invite_ack([2u8; 16]));
+
+        let result = revoke_invite(dir.path(), transport, [1u8; 16]).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn revoke_rejects_malformed_invite_id() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+
+        let result = revoke(dir.path(), "not-an-invite-id").await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Invite ID"));
+    }
+
+    #[tokio::test]
+    async fn create_rejects_weak_passphrase() {
         let dir = tempdir().unwrap();
         init_device(dir.path()).await;
 
-        // Short codes are not yet implemented
-        let result = join(dir.path(), "ABCD-EFGH", Some("test")).await;
+        let result = create(dir.path(), Some("password123"), None, None, None).await;
         assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Short code lookup not yet implemented"));
+        assert!(!dir.path().join("group.json").exists());
+    }
+
+    #[tokio::test]
+    async fn join_with_code_rejects_malformed_code() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+
+        let relay = "aa8e9a9115685ffab95d24c40714db6fae3e046b9eb197ccc1b04cb46a014444";
+        let result = join_with_code(dir.path(), "12-34", None, relay).await;
+        assert!(result.is_err());
+        assert!(!dir.path().join("group.json").exists());
+    }
+
+    #[tokio::test]
+    async fn join_with_pairing_code_rejects_foreign_invite() {
+        let dir = tempdir().unwrap();
+        init_device(dir.path()).await;
+        let code = PairingCode::generate();
+
+        // Relay hands over a share, then a payload not sealed for this run
+        let (_, share) = zerok_sync_core::CodeOffer::start(&code);
+        let transport = MockTransport::new();
+        for response in [
+            Message::MailboxData(MailboxData {
+                mailbox: code.joiner_mailbox(),
+                payload: Some(share),
+            }),
+            Message::MailboxAck(zerok_sync_types::MailboxAck {
+                mailbox: code.inviter_mailbox(),
+            }),
+            Message::MailboxData(MailboxData {
+                mailbox: code.joiner_mailbox(),
+                payload: Some(vec![0u8; 64]),
+            }),
+        ] {
+            transport.queue_response(response.to_bytes().unwrap());
+        }
+
+        let relay = "aa8e9a9115685ffab95d24c40714db6fae3e046b9eb197ccc1b04cb46a014444";
+        let result = join_with_pairing_code(dir.path(), &code, None, relay, transport).await;
+        assert!(result.is_err());
+        assert!(!dir.path().join("group.json").exists());
+    }
+
+    #[test]
+    fn parse_endpoint_id_rejects_invalid() {
+        assert!(parse_endpoint_id("not-hex").is_err());
+        assert!(parse_endpoint_id("abcd").is_err());
     }
 }
@@ -3,7 +3,7 @@
 use anyhow::{Context, Result};
 use std::path::Path;
 use zerok_sync_client::{
-    GroupKey, IrohTransport, MockTransport, SyncClient, SyncConfig, Transport,
+    GroupKey, IrohTransport, IrohTransportConfig, MockTransport, SyncClient, SyncConfig, Transport,
 };
 use zerok_sync_types::Cursor;
 
@@ -12,7 +12,7 @@ use crate::config::{DeviceConfig, GroupConfig};
 /// Run the pull command.
 pub async fn run(data_dir: &Path, after_cursor: Option<u64>, use_mock: bool) -> Result<()> {
     // Load configuration
-    let device = DeviceConfig::load(data_dir).await?;
+    let mut device = DeviceConfig::load(data_dir).await?;
     let mut group = GroupConfig::load(data_dir).await?;
 
     let primary_relay = group
@@ -23,23 +23,29 @@ pub async fn run(data_dir: &Path, after_cursor: Option<u64>, use_mock: bool) ->
     println!("Pulling data after cursor {}...", cursor);
 
     // Create sync client config from stored secret (F-003: no placeholder fallback)
-    let secret_bytes = group
-        .group_secret_bytes()
-        .filter(|b| b.len() == 32)
-        .ok_or_else(|| anyhow::anyhow!("Group secret not found. Run 'sync-cli pair' first."))?;
-    let bytes: [u8; 32] = secret_bytes.try_into().unwrap();
+    let secret = group.load_secret(data_dir, None).await?;
 
     // Use all relay addresses for failover, not just primary
     let relay_addrs: Vec<&str> = group.relay_addresses.iter().map(|s| s.as_str()).collect();
-    let config = SyncConfig::from_secret_bytes(&bytes, &primary_relay)
+    let config = SyncConfig::from_secret_bytes(secret.as_bytes(), &primary_relay)
         .with_device_name(&device.device_name)
-        .with_relay_addresses(&relay_addrs);
+        .with_relay_addresses(&relay_addrs)
+        .with_compression(group.compression)
+        .with_padding(group.padding)
+        .with_invite_id(group.invite_id());
+    let config = group
+        .relay_noise_keys()
+        .into_iter()
+        .fold(config, |config, (address, key)| {
+            config.with_relay_noise_key(address, key)
+        });
 
     // Create transport and client based on mode
     if use_mock {
         run_with_mock(config, &mut group, data_dir, cursor).await
     } else {
-        run_with_iroh(config, &mut group, data_dir, cursor).await
+        let transport_config = device.transport_config(data_dir).await?;
+        run_with_iroh(config, transport_config, &mut group, data_dir, cursor).await
     }
 }
 
@@ -66,6 +72,7 @@ async fn run_with_mock(
 /// Run pull with IrohTransport (real P2P).
 async fn run_with_iroh(
     config: SyncConfig,
+    transport_config: IrohTransportConfig,
     group: &mut GroupConfig,
     data_dir: &Path,
     cursor: u64,
@@ -73,7 +80,7 @@ async fn run_with_iroh(
     let primary = group.primary_relay().unwrap_or("unknown");
     println!("Connecting to peer: {}", primary);
 
-    let transport = IrohTransport::new()
+    let transport = IrohTransport::with_config(transport_config)
         .await
         .context("Failed to create iroh transport")?;
 
@@ -96,6 +103,12 @@ async fn do_pull<T: Transport>(
         .await
         .context("Failed to connect to relay")?;
 
+    // The invite is spent once the relay admits this device, which it
+    // recognizes by its endpoint key from then on
+    if group.invite_id_hex.take().is_some() {
+        group.save(data_dir).await?;
+    }
+
     match client.pull_after(Some(Cursor::new(cursor))).await {
         Ok(blobs) => {
             if blobs.is_empty() {
@@ -2,24 +2,22 @@
 
 use anyhow::{Context, Result};
 use std::path::Path;
-use zerok_sync_client::{IrohTransport, MockTransport, SyncClient, SyncConfig, Transport};
+use zerok_sync_client::{
+    IrohTransport, IrohTransportConfig, MockTransport, SyncClient, SyncConfig, Transport,
+};
 
 use crate::config::{DeviceConfig, GroupConfig};
 
 /// Run the push command.
 pub async fn run(data_dir: &Path, data: &[u8], use_mock: bool) -> Result<()> {
     // Load configuration
-    let device = DeviceConfig::load(data_dir).await?;
+    let mut device = DeviceConfig::load(data_dir).await?;
     let mut group = GroupConfig::load(data_dir).await?;
 
     println!("Pushing {} bytes...", data.len());
 
     // Create sync client config from stored secret (F-003: no placeholder fallback)
-    let secret_bytes = group
-        .group_secret_bytes()
-        .filter(|b| b.len() == 32)
-        .ok_or_else(|| anyhow::anyhow!("Group secret not found. Run 'sync-cli pair' first."))?;
-    let bytes: [u8; 32] = secret_bytes.try_into().unwrap();
+    let secret = group.load_secret(data_dir, None).await?;
     let primary_relay = group
         .primary_relay()
         .ok_or_else(|| anyhow::anyhow!("No relay addresses configured"))?
@@ -27,15 +25,25 @@ pub async fn run(data_dir: &Path, data: &[u8], use_mock: bool) -> Result<()> {
 
     // Use all relay addresses for failover, not just primary
     let relay_addrs: Vec<&str> = group.relay_addresses.iter().map(|s| s.as_str()).collect();
-    let config = SyncConfig::from_secret_bytes(&bytes, &primary_relay)
+    let config = SyncConfig::from_secret_bytes(secret.as_bytes(), &primary_relay)
         .with_device_name(&device.device_name)
-        .with_relay_addresses(&relay_addrs);
+        .with_relay_addresses(&relay_addrs)
+        .with_compression(group.compression)
+        .with_padding(group.padding)
+        .with_invite_id(group.invite_id());
+    let config = group
+        .relay_noise_keys()
+        .into_iter()
+        .fold(config, |config, (address, key)| {
+            config.with_relay_noise_key(address, key)
+        });
 
     // Create transport and client based on mode
     if use_mock {
         run_with_mock(config, &mut group, data_dir, data).await
     } else {
-        run_with_iroh(config, &mut group, data_dir, data).await
+        let transport_config = device.transport_config(data_dir).await?;
+        run_with_iroh(config, transport_config, &mut group, data_dir, data).await
     }
 }
 
@@ -62,9 +70,11 @@ async fn run_with_mock(
 /// Run push with IrohTransport (real P2P).
 ///
 /// After pushing to the primary relay, spawns fire-and-forget tasks
-/// to push the same data to secondary relays (fan-out).
+/// to push the same data to secondary relays (fan-out). Secondaries are
+/// pushed to one at a time because they share the device's endpoint key.
 async fn run_with_iroh(
     config: SyncConfig,
+    transport_config: IrohTransportConfig,
     group: &mut GroupConfig,
     data_dir: &Path,
     data: &[u8],
@@ -76,8 +86,9 @@ async fn run_with_iroh(
     let secondary_addrs: Vec<String> = group.relay_addresses.iter().skip(1).cloned().collect();
     let secret_bytes: [u8; 32] = *config.group_secret.as_bytes();
     let device_name = config.device_name.clone();
+    let (compression, padding) = (config.compression, config.padding);
 
-    let transport = IrohTransport::new()
+    let transport = IrohTransport::with_config(transport_config.clone())
         .await
         .context("Failed to create iroh transport")?;
 
@@ -95,32 +106,27 @@ async fn run_with_iroh(
             secondary_addrs.len()
         );
         let data_owned = data.to_vec();
-        let mut handles = Vec::new();
-
-        for addr in secondary_addrs {
-            let cfg =
-                SyncConfig::from_secret_bytes(&secret_bytes, &addr).with_device_name(&device_name);
-            let payload = data_owned.clone();
-            handles.push(tokio::spawn(async move {
-                let transport = match IrohTransport::new().await {
+        let fan_out = tokio::spawn(async move {
+            for addr in secondary_addrs {
+                let cfg = SyncConfig::from_secret_bytes(&secret_bytes, &addr)
+                    .with_device_name(&device_name)
+                    .with_compression(compression)
+                    .with_padding(padding);
+                let transport = match IrohTransport::with_config(transport_config.clone()).await {
                     Ok(t) => t,
-                    Err(_) => return,
+                    Err(_) => continue,
                 };
                 let client = SyncClient::new(cfg, transport);
                 if client.connect().await.is_err() {
-                    return;
+                    continue;
                 }
-                let _ = client.push(&payload).await;
-            }));
-        }
+                let _ = client.push(&data_owned).await;
+                let _ = client.disconnect().await;
+            }
+        });
 
         // Brief wait for secondaries (don't block the CLI indefinitely)
-        let _ = tokio::time::timeout(std::time::Duration::from_secs(10), async {
-            for handle in handles {
-                let _ = handle.await;
-            }
-        })
-        .await;
+        let _ = tokio::time::timeout(std::time::Duration::from_secs(10), fan_out).await;
     }
 
     Ok(())
@@ -139,6 +145,12 @@ async fn do_push<T: Transport>(
         .await
         .context("Failed to connect to relay")?;
 
+    // The invite is spent once the relay admits this device, which it
+    // recognizes by its endpoint key from then on
+    if group.invite_id_hex.take().is_some() {
+        group.save(data_dir).await?;
+    }
+
     match client.push(data).await {
         Ok((blob_id, cursor)) => {
             // Update cursor for the active relay (handles failover correctly)
@@ -243,6 +255,21 @@ mod tests {
         assert!(result.is_ok()); // Function handles the error gracefully
     }
 
+    #[tokio::test]
+    async fn push_clears_invite_after_joining() {
+        let dir = tempdir().unwrap();
+        setup_device_and_group(dir.path()).await;
+        let mut group = GroupConfig::load(dir.path()).await.unwrap();
+        group.invite_id_hex = Some(hex::encode([7u8; 16]));
+        group.save(dir.path()).await.unwrap();
+
+        run(dir.path(), b"test data", true).await.unwrap();
+
+        // Later connects must not redeem the invite again
+        let group = GroupConfig::load(dir.path()).await.unwrap();
+        assert_eq!(group.invite_id(), None);
+    }
+
     #[tokio::test]
     async fn push_fails_without_secret() {
         // F-003: no placeholder fallback — missing secret must error
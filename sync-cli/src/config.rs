@@ -4,8 +4,27 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use zerok_sync_client::{
+    Compression, GroupSecret, IrohTransport, IrohTransportConfig, Keystore, PaddingPolicy,
+};
 use zerok_sync_types::{Cursor, DeviceId};
 
+/// Environment variable holding the keystore master passphrase.
+///
+/// When set while pairing, the group secret is sealed in `keystore.bin`
+/// instead of being written to `group.json`.
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "SYNC_CLI_KEYSTORE_PASSPHRASE";
+
+/// Encrypted keystore file in the data directory.
+const KEYSTORE_FILE: &str = "keystore.bin";
+
+/// Keystore master passphrase from [`KEYSTORE_PASSPHRASE_ENV`], if set.
+pub fn keystore_passphrase_from_env() -> Option<String> {
+    std::env::var(KEYSTORE_PASSPHRASE_ENV)
+        .ok()
+        .filter(|p| !p.is_empty())
+}
+
 /// Device configuration stored locally.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceConfig {
@@ -15,6 +34,12 @@ pub struct DeviceConfig {
     pub device_name: String,
     /// When the device was initialized.
     pub created_at: u64,
+    /// Hex-encoded iroh endpoint secret key.
+    ///
+    /// Relays admit group members by endpoint ID, so the device keeps the
+    /// same key across runs. Older configs get one on first use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key_hex: Option<String>,
 }
 
 impl DeviceConfig {
@@ -28,7 +53,28 @@ impl DeviceConfig {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            secret_key_hex: Some(hex::encode(IrohTransport::generate_secret_key())),
+        }
+    }
+
+    /// Get the iroh endpoint secret key, generating and saving one if the
+    /// device predates stored keys.
+    pub async fn secret_key(&mut self, data_dir: &Path) -> Result<[u8; 32]> {
+        if let Some(h) = &self.secret_key_hex {
+            return hex::decode(h)
+                .ok()
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| anyhow::anyhow!("Invalid device secret key"));
         }
+        let secret_key = IrohTransport::generate_secret_key();
+        self.secret_key_hex = Some(hex::encode(secret_key));
+        self.save(data_dir).await?;
+        Ok(secret_key)
+    }
+
+    /// Iroh transport configuration using this device's endpoint key.
+    pub async fn transport_config(&mut self, data_dir: &Path) -> Result<IrohTransportConfig> {
+        Ok(IrohTransportConfig::default().with_secret_key(self.secret_key(data_dir).await?))
     }
 
     /// Load device configuration from a directory.
@@ -80,6 +126,21 @@ pub struct GroupConfig {
     /// Hex-encoded Argon2id salt used to derive the group secret.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub salt_hex: Option<String>,
+    /// Hex-encoded invite ID redeemed when first connecting to the relay.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_id_hex: Option<String>,
+    /// Hex-encoded secure channel public keys pinned per relay address.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub relay_noise_keys_hex: HashMap<String, String>,
+    /// Payload compression used by the group (from the invite).
+    #[serde(default)]
+    pub compression: Compression,
+    /// Payload padding used by the group (from the invite).
+    #[serde(default)]
+    pub padding: PaddingPolicy,
+    /// Whether the group secret is sealed in the encrypted keystore.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keystore: bool,
 }
 
 impl GroupConfig {
@@ -97,6 +158,11 @@ impl GroupConfig {
             cursors: HashMap::new(),
             group_secret_hex: None,
             salt_hex: None,
+            invite_id_hex: None,
+            relay_noise_keys_hex: HashMap::new(),
+            compression: Compression::None,
+            padding: PaddingPolicy::None,
+            keystore: false,
         }
     }
 
@@ -114,6 +180,11 @@ impl GroupConfig {
             cursors: HashMap::new(),
             group_secret_hex: Some(hex::encode(secret_bytes)),
             salt_hex: None,
+            invite_id_hex: None,
+            relay_noise_keys_hex: HashMap::new(),
+            compression: Compression::None,
+            padding: PaddingPolicy::None,
+            keystore: false,
         }
     }
 
@@ -135,6 +206,11 @@ impl GroupConfig {
             cursors: HashMap::new(),
             group_secret_hex: Some(hex::encode(secret_bytes)),
             salt_hex: Some(hex::encode(salt)),
+            invite_id_hex: None,
+            relay_noise_keys_hex: HashMap::new(),
+            compression: Compression::None,
+            padding: PaddingPolicy::None,
+            keystore: false,
         }
     }
 
@@ -160,12 +236,104 @@ impl GroupConfig {
             .and_then(|h| hex::decode(h).ok())
     }
 
+    /// Move the group secret into the encrypted keystore.
+    ///
+    /// Opens (or creates) `keystore.bin` under `master_passphrase`, stores the
+    /// secret there and clears it from this configuration. Call before
+    /// [`save`](Self::save).
+    pub async fn seal_secret(&mut self, data_dir: &Path, master_passphrase: &str) -> Result<()> {
+        let secret = self.inline_secret()?;
+        let path = data_dir.join(KEYSTORE_FILE);
+        let mut keystore = if path.exists() {
+            let bytes = tokio::fs::read(&path)
+                .await
+                .context("Failed to read keystore")?;
+            Keystore::open_with_passphrase(&bytes, master_passphrase)
+                .context("Failed to open keystore")?
+        } else {
+            Keystore::create_with_passphrase(master_passphrase)
+                .context("Failed to create keystore")?
+        };
+        keystore
+            .insert_group_secret(&self.group_id, &secret)
+            .context("Failed to store group secret")?;
+
+        tokio::fs::write(&path, keystore.to_bytes())
+            .await
+            .context("Failed to save keystore")?;
+        set_file_permissions_0600(&path).await?;
+
+        self.group_secret_hex = None;
+        self.keystore = true;
+        Ok(())
+    }
+
+    /// Load the group secret, unsealing it from the keystore if needed.
+    ///
+    /// The keystore master passphrase is `master_passphrase`, else
+    /// [`KEYSTORE_PASSPHRASE_ENV`], else prompted for.
+    pub async fn load_secret(
+        &self,
+        data_dir: &Path,
+        master_passphrase: Option<&str>,
+    ) -> Result<GroupSecret> {
+        if !self.keystore {
+            return self.inline_secret();
+        }
+
+        let bytes = tokio::fs::read(data_dir.join(KEYSTORE_FILE))
+            .await
+            .context("Keystore not found. Run 'sync-cli pair' again.")?;
+        let master = match master_passphrase {
+            Some(p) => p.to_string(),
+            None => match keystore_passphrase_from_env() {
+                Some(p) => p,
+                None => rpassword::prompt_password("Enter keystore passphrase: ")
+                    .context("Failed to read keystore passphrase")?,
+            },
+        };
+        let keystore =
+            Keystore::open_with_passphrase(&bytes, &master).context("Failed to open keystore")?;
+        keystore
+            .group_secret(&self.group_id)
+            .context("Failed to read keystore")?
+            .ok_or_else(|| anyhow::anyhow!("Group secret not found in keystore."))
+    }
+
+    /// The group secret stored in this configuration (F-003: no placeholder fallback).
+    fn inline_secret(&self) -> Result<GroupSecret> {
+        let bytes: [u8; 32] = self
+            .group_secret_bytes()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("Group secret not found. Run 'sync-cli pair' first."))?;
+        Ok(GroupSecret::from_raw(bytes))
+    }
+
     /// Get the salt bytes, if stored.
     #[allow(dead_code)] // Used in tests
     pub fn salt_bytes(&self) -> Option<Vec<u8>> {
         self.salt_hex.as_ref().and_then(|h| hex::decode(h).ok())
     }
 
+    /// Get the invite ID to redeem, if the group was joined with one.
+    pub fn invite_id(&self) -> Option<[u8; 16]> {
+        self.invite_id_hex
+            .as_ref()
+            .and_then(|h| hex::decode(h).ok())
+            .and_then(|b| b.try_into().ok())
+    }
+
+    /// Get the pinned secure channel public keys by relay address.
+    pub fn relay_noise_keys(&self) -> Vec<(&str, [u8; 32])> {
+        self.relay_noise_keys_hex
+            .iter()
+            .filter_map(|(address, h)| {
+                let key = hex::decode(h).ok()?.try_into().ok()?;
+                Some((address.as_str(), key))
+            })
+            .collect()
+    }
+
     /// Load group configuration from a directory.
     ///
     /// Handles backward compatibility: old `group.json` with singular
@@ -319,6 +487,40 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[tokio::test]
+    async fn device_secret_key_persists() {
+        let dir = tempdir().unwrap();
+        let mut device = DeviceConfig::new("laptop");
+        device.save(dir.path()).await.unwrap();
+        let key = device.secret_key(dir.path()).await.unwrap();
+
+        let mut loaded = DeviceConfig::load(dir.path()).await.unwrap();
+        assert_eq!(loaded.secret_key(dir.path()).await.unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn device_config_old_format_gets_secret_key() {
+        // Devices initialized before keys were stored have none on disk
+        let dir = tempdir().unwrap();
+        let old_json = serde_json::json!({
+            "device_id": "old-device",
+            "device_name": "laptop",
+            "created_at": 1000000u64,
+        });
+        let path = dir.path().join("device.json");
+        tokio::fs::write(&path, serde_json::to_string_pretty(&old_json).unwrap())
+            .await
+            .unwrap();
+
+        let mut device = DeviceConfig::load(dir.path()).await.unwrap();
+        assert!(device.secret_key_hex.is_none());
+        let key = device.secret_key(dir.path()).await.unwrap();
+
+        // The generated key is saved, so the next run is the same endpoint
+        let mut reloaded = DeviceConfig::load(dir.path()).await.unwrap();
+        assert_eq!(reloaded.secret_key(dir.path()).await.unwrap(), key);
+    }
+
     #[tokio::test]
     async fn group_config_multi_relay_roundtrip() {
         let dir = tempdir().unwrap();
@@ -400,6 +602,28 @@ mod tests {
         assert_eq!(loaded.cursor_for_relay("relay-c"), 0); // unknown relay
     }
 
+    #[tokio::test]
+    async fn group_config_keeps_payload_settings() {
+        let dir = tempdir().unwrap();
+        let mut config = GroupConfig::with_secret("group-z", &["relay"], &[1u8; 32]);
+        config.compression = Compression::Zstd;
+        config.padding = PaddingPolicy::Block(512);
+        config.save(dir.path()).await.unwrap();
+
+        let loaded = GroupConfig::load(dir.path()).await.unwrap();
+        assert_eq!(loaded.compression, Compression::Zstd);
+        assert_eq!(loaded.padding, PaddingPolicy::Block(512));
+
+        // Older group.json files predate both settings
+        let old = GroupConfig::with_secret("group-old", &["relay"], &[1u8; 32]);
+        let mut json = serde_json::to_value(&old).unwrap();
+        json.as_object_mut().unwrap().remove("compression");
+        json.as_object_mut().unwrap().remove("padding");
+        let loaded: GroupConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.compression, Compression::None);
+        assert_eq!(loaded.padding, PaddingPolicy::None);
+    }
+
     #[tokio::test]
     async fn group_config_without_salt_loads() {
         let dir = tempdir().unwrap();
@@ -412,6 +636,36 @@ mod tests {
         assert!(loaded.group_secret_bytes().is_some());
     }
 
+    #[tokio::test]
+    async fn group_secret_sealed_in_keystore() {
+        let dir = tempdir().unwrap();
+        let mut config = GroupConfig::with_secret("group-k", &["relay"], &[0x42u8; 32]);
+        config
+            .seal_secret(dir.path(), "local master")
+            .await
+            .unwrap();
+        config.save(dir.path()).await.unwrap();
+
+        // The secret no longer appears in group.json
+        let json = tokio::fs::read_to_string(dir.path().join("group.json"))
+            .await
+            .unwrap();
+        assert!(!json.contains(&hex::encode([0x42u8; 32])));
+
+        let loaded = GroupConfig::load(dir.path()).await.unwrap();
+        assert!(loaded.keystore);
+        assert!(loaded.group_secret_bytes().is_none());
+        let secret = loaded
+            .load_secret(dir.path(), Some("local master"))
+            .await
+            .unwrap();
+        assert_eq!(secret.as_bytes(), &[0x42u8; 32]);
+        assert!(loaded
+            .load_secret(dir.path(), Some("wrong master"))
+            .await
+            .is_err());
+    }
+
     #[cfg(unix)]
     #[tokio::test]
     async fn group_config_file_permissions() {
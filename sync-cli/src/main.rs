@@ -16,11 +16,11 @@
 //! # Initialize device
 //! sync-cli init --name "My Device"
 //!
-//! # Create sync group and get invite code
-//! sync-cli pair --create
+//! # Create sync group and get invite code (published to the relay)
+//! sync-cli pair --create --relay <endpoint-id>
 //!
 //! # On another device, join the group
-//! sync-cli pair --join XXXX-XXXX-XXXX-XXXX
+//! sync-cli pair --join XXXX-XXXX-XXXX-XXXX --relay <endpoint-id>
 //!
 //! # Push data
 //! sync-cli push "Hello, sync world!"
@@ -77,6 +77,28 @@ enum Commands {
         /// Passphrase for the sync group (reads from stdin if omitted)
         #[arg(long)]
         passphrase: Option<String>,
+
+        /// Relay EndpointId holding short code invites
+        #[arg(long)]
+        relay: Option<String>,
+
+        /// Pair with a short numeric code typed on both devices (needs --relay)
+        #[arg(long, requires = "relay")]
+        code: bool,
+
+        /// Relay secure channel public key to pin in the invite (printed by
+        /// sync-relay at startup)
+        #[arg(long, requires = "relay")]
+        relay_key: Option<String>,
+
+        /// Register the invite on the relay and stop admitting devices after
+        /// this many joins (needs --relay)
+        #[arg(long, requires_all = ["create", "relay"])]
+        max_uses: Option<u32>,
+
+        /// Revoke an invite registered with --max-uses (its hex invite ID)
+        #[arg(long, conflicts_with_all = ["create", "join"])]
+        revoke: Option<String>,
     },
 
     /// Push data to the sync group
@@ -131,13 +153,42 @@ async fn main() -> Result<()> {
             create,
             join,
             passphrase,
+            relay,
+            code,
+            relay_key,
+            max_uses,
+            revoke,
         } => {
-            if create {
-                pair::create(&data_dir, passphrase.as_deref()).await?;
+            if let Some(invite_id) = revoke {
+                pair::revoke(&data_dir, &invite_id).await?;
+            } else if let (true, Some(relay)) = (code, relay.as_deref()) {
+                if create {
+                    pair::create_with_code(
+                        &data_dir,
+                        passphrase.as_deref(),
+                        relay,
+                        relay_key.as_deref(),
+                        max_uses,
+                    )
+                    .await?;
+                } else if let Some(code) = join {
+                    pair::join_with_code(&data_dir, &code, passphrase.as_deref(), relay).await?;
+                } else {
+                    anyhow::bail!("Must specify --create, --join or --revoke");
+                }
+            } else if create {
+                pair::create(
+                    &data_dir,
+                    passphrase.as_deref(),
+                    relay.as_deref(),
+                    relay_key.as_deref(),
+                    max_uses,
+                )
+                .await?;
             } else if let Some(code) = join {
-                pair::join(&data_dir, &code, passphrase.as_deref()).await?;
+                pair::join(&data_dir, &code, passphrase.as_deref(), relay.as_deref()).await?;
             } else {
-                anyhow::bail!("Must specify either --create or --join");
+                anyhow::bail!("Must specify --create, --join or --revoke");
             }
         }
         Commands::Push { message, file } => {
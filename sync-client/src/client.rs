@@ -29,15 +29,24 @@
 //! let blobs = client.pull().await?;
 //! ```
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::Mutex;
-use zerok_sync_core::{ConnectionState, CursorTracker, Event};
+use tracing::Instrument;
+use zerok_sync_core::{
+    solve_pow, ChannelError, Compression, ConnectionState, CoverTrafficConfig, CursorTracker,
+    Event, HandshakeInitiator, Invite, NoiseKeypair, PaddingPolicy, PairingError, SecureChannel,
+    DEFAULT_MAX_DECOMPRESSED_SIZE, MAX_POW_DIFFICULTY, OPRF_OUTPUT_SIZE,
+};
 use zerok_sync_types::{
-    BlobId, Cursor, GroupId, Hello, Message, Pull, PullResponse, Push, PushAck,
+    BlobId, Challenge, ChallengeResponse, ContentAck, ContentData, ContentGet, ContentPut, Cursor,
+    GroupId, Handshake, Hello, InviteAck, InviteRegister, InviteRevoke, Message, Pull,
+    PullResponse, Push, PushAck,
 };
 
 use crate::crypto::{CryptoError, GroupKey, GroupSecret};
+use crate::telemetry;
 use crate::transport::{Transport, TransportError};
 
 /// Client errors.
@@ -51,6 +60,14 @@ pub enum ClientError {
     #[error("crypto error: {0}")]
     Crypto(#[from] CryptoError),
 
+    /// Secure channel error.
+    #[error("secure channel error: {0}")]
+    SecureChannel(#[from] ChannelError),
+
+    /// Invite or invite exchange error.
+    #[error("pairing error: {0}")]
+    Pairing(#[from] PairingError),
+
     /// Serialization error.
     #[error("serialization error: {0}")]
     Serialization(String),
@@ -72,6 +89,18 @@ pub enum ClientError {
     AllRelaysFailed(String),
 }
 
+/// Largest plaintext frame padding will grow a payload to.
+///
+/// Leaves headroom under the relay's 1 MB message limit for the nonce,
+/// auth tag and message envelope. Padding never truncates data.
+const MAX_PADDED_SIZE: usize = 1024 * 1024 - 4096;
+
+/// XChaCha20-Poly1305 authentication tag size.
+const AEAD_TAG_SIZE: usize = 16;
+
+/// Largest random body used for a cover push (before padding).
+const COVER_MAX_BODY: usize = 1024;
+
 /// Configuration for SyncClient.
 #[derive(Debug, Clone)]
 pub struct SyncConfig {
@@ -83,7 +112,30 @@ pub struct SyncConfig {
     /// Human-readable device name.
     pub device_name: String,
     /// Time-to-live for pushed blobs (seconds, 0 = no expiry).
+    /// Replaced by the cover traffic TTL while cover traffic is on.
     pub default_ttl: u32,
+    /// Payload compression applied before encryption.
+    /// Must match across the group (carried in the Invite).
+    pub compression: Compression,
+    /// Maximum decompressed size of a received payload (bomb limit).
+    pub max_decompressed_size: usize,
+    /// Length padding applied inside the AEAD to hide payload sizes.
+    /// Devices in a group must agree on whether padding is enabled.
+    pub padding: PaddingPolicy,
+    /// Cover traffic mode (dummy pushes on a randomized schedule).
+    /// `None` disables cover traffic (default).
+    pub cover_traffic: Option<CoverTrafficConfig>,
+    /// Run the hybrid X25519 + ML-KEM-768 handshake before HELLO and seal
+    /// all session traffic with it (protects against harvest-now-decrypt-later
+    /// if QUIC/TLS is broken). Requires a relay that supports it.
+    pub secure_channel: bool,
+    /// Pinned secure channel public keys by relay address (from the Invite).
+    /// Connections to a pinned relay always use the secure channel and fail
+    /// if the relay proves a different key.
+    pub relay_noise_keys: HashMap<String, [u8; 32]>,
+    /// Invite ID to redeem in HELLO when joining a group whose relay
+    /// tracks invites. Ignored by the relay once this device is a member.
+    pub invite_id: Option<[u8; 16]>,
 }
 
 impl SyncConfig {
@@ -98,6 +150,13 @@ impl SyncConfig {
             relay_addresses: vec![relay_address.to_string()],
             device_name: "0k-sync device".to_string(),
             default_ttl: 0,
+            compression: Compression::None,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            padding: PaddingPolicy::None,
+            cover_traffic: None,
+            secure_channel: false,
+            relay_noise_keys: HashMap::new(),
+            invite_id: None,
         };
         (config, salt)
     }
@@ -111,6 +170,39 @@ impl SyncConfig {
             relay_addresses: vec![relay_address.to_string()],
             device_name: "0k-sync device".to_string(),
             default_ttl: 0,
+            compression: Compression::None,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            padding: PaddingPolicy::None,
+            cover_traffic: None,
+            secure_channel: false,
+            relay_noise_keys: HashMap::new(),
+            invite_id: None,
+        }
+    }
+
+    /// Create a configuration from a passphrase hardened by the relay's OPRF.
+    ///
+    /// `oprf_output` comes from [`crate::oprf::evaluate_passphrase`] against
+    /// the group's relay. Every device in the group must use this constructor
+    /// (with the same relay) to derive the same secret.
+    pub fn new_with_oprf(
+        passphrase: &str,
+        salt: &[u8],
+        oprf_output: &[u8; OPRF_OUTPUT_SIZE],
+        relay_address: &str,
+    ) -> Self {
+        Self {
+            group_secret: GroupSecret::from_passphrase_with_oprf(passphrase, salt, oprf_output),
+            relay_addresses: vec![relay_address.to_string()],
+            device_name: "0k-sync device".to_string(),
+            default_ttl: 0,
+            compression: Compression::None,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            padding: PaddingPolicy::None,
+            cover_traffic: None,
+            secure_channel: false,
+            relay_noise_keys: HashMap::new(),
+            invite_id: None,
         }
     }
 
@@ -123,6 +215,13 @@ impl SyncConfig {
             relay_addresses: vec![relay_address.to_string()],
             device_name: "0k-sync device".to_string(),
             default_ttl: 0,
+            compression: Compression::None,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            padding: PaddingPolicy::None,
+            cover_traffic: None,
+            secure_channel: false,
+            relay_noise_keys: HashMap::new(),
+            invite_id: None,
         }
     }
 
@@ -143,6 +242,55 @@ impl SyncConfig {
         self
     }
 
+    /// Set the group's payload compression (from the Invite).
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the maximum decompressed size accepted for received payloads.
+    pub fn with_max_decompressed_size(mut self, max: usize) -> Self {
+        self.max_decompressed_size = max;
+        self
+    }
+
+    /// Set the payload padding policy.
+    pub fn with_padding(mut self, padding: PaddingPolicy) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Enable cover traffic mode.
+    ///
+    /// Combine with padding: unpadded dummy sizes may differ from real ones.
+    /// Real pushes then use the cover TTL instead of the default TTL.
+    pub fn with_cover_traffic(mut self, cover: CoverTrafficConfig) -> Self {
+        self.cover_traffic = Some(cover);
+        self
+    }
+
+    /// Enable or disable the hybrid post-quantum secure channel.
+    pub fn with_secure_channel(mut self, enabled: bool) -> Self {
+        self.secure_channel = enabled;
+        self
+    }
+
+    /// Pin a relay's secure channel public key (from
+    /// [`Invite::relay_noise_key`]).
+    ///
+    /// The secure channel is then required for that relay, even if
+    /// [`with_secure_channel`](Self::with_secure_channel) is off.
+    pub fn with_relay_noise_key(mut self, address: &str, key: [u8; 32]) -> Self {
+        self.relay_noise_keys.insert(address.to_string(), key);
+        self
+    }
+
+    /// Redeem an invite ID when joining (from [`Invite::invite_id`]).
+    pub fn with_invite_id(mut self, invite_id: Option<[u8; 16]>) -> Self {
+        self.invite_id = invite_id;
+        self
+    }
+
     /// Set multiple relay addresses (for multi-relay fan-out/failover).
     pub fn with_relay_addresses(mut self, addresses: &[&str]) -> Self {
         self.relay_addresses = addresses.iter().map(|s| s.to_string()).collect();
@@ -177,6 +325,21 @@ impl std::fmt::Debug for ReceivedBlob {
     }
 }
 
+/// A message the relay sent on its own, outside a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayEvent {
+    /// New blobs are available up to this cursor.
+    Notify(Cursor),
+    /// The relay ended the session (e.g. it is shutting down). The client
+    /// has already failed over to `relay`.
+    Bye {
+        /// Reason the relay gave, if any.
+        reason: Option<String>,
+        /// Relay the client is now connected to.
+        relay: String,
+    },
+}
+
 /// The main sync client.
 ///
 /// Manages connection, encryption, and sync operations.
@@ -187,6 +350,8 @@ pub struct SyncClient<T: Transport> {
     state: Arc<Mutex<ConnectionState>>,
     cursor: Arc<Mutex<CursorTracker>>,
     active_relay: Arc<Mutex<Option<String>>>,
+    noise_keypair: NoiseKeypair,
+    channel: Arc<Mutex<Option<SecureChannel>>>,
 }
 
 impl<T: Transport> SyncClient<T> {
@@ -200,6 +365,8 @@ impl<T: Transport> SyncClient<T> {
             state: Arc::new(Mutex::new(ConnectionState::new())),
             cursor: Arc::new(Mutex::new(CursorTracker::new())),
             active_relay: Arc::new(Mutex::new(None)),
+            noise_keypair: NoiseKeypair::generate(),
+            channel: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -225,7 +392,8 @@ impl<T: Transport> SyncClient<T> {
         let mut errors: Vec<(String, String)> = Vec::new();
 
         for address in &self.config.relay_addresses {
-            match self.try_connect_relay(address).await {
+            let span = tracing::info_span!("sync.connect", otel.name = "connect", relay = %address);
+            match self.try_connect_relay(address).instrument(span).await {
                 Ok(server_cursor) => {
                     // Update state machine for successful connection
                     {
@@ -276,6 +444,9 @@ impl<T: Transport> SyncClient<T> {
             .await
             .map_err(|e| ClientError::ConnectionFailed(e.to_string()))?;
 
+        // A channel never outlives the connection it was established on
+        *self.channel.lock().await = None;
+
         // Send HELLO with group identity
         let group_id = GroupId::from_secret(self.config.group_secret.as_bytes());
         let last_cursor = self.cursor.lock().await.last_cursor();
@@ -284,23 +455,106 @@ impl<T: Transport> SyncClient<T> {
             device_name: self.config.device_name.clone(),
             group_id,
             last_cursor,
+            invite_id: self.config.invite_id,
         });
         let hello_bytes = hello
             .to_bytes()
             .map_err(|e| ClientError::Serialization(e.to_string()))?;
-        self.transport.send(&hello_bytes).await?;
 
         // Receive Welcome response
-        let welcome_bytes = self.transport.recv().await?;
-        let welcome = Message::from_bytes(&welcome_bytes)
+        let pinned = self.config.relay_noise_keys.get(address).copied();
+        let welcome_bytes = if self.config.secure_channel || pinned.is_some() {
+            self.secure_handshake(&hello_bytes, pinned).await?
+        } else {
+            self.transport.send(&hello_bytes).await?;
+            self.transport.recv().await?
+        };
+        let mut welcome = Message::from_bytes(&welcome_bytes)
             .map_err(|e| ClientError::Serialization(e.to_string()))?;
 
+        // A loaded relay may ask for proof of work before it answers
+        if let Message::Challenge(challenge) = welcome {
+            let response = Message::ChallengeResponse(solve_challenge(challenge).await?)
+                .to_bytes()
+                .map_err(|e| ClientError::Serialization(e.to_string()))?;
+            welcome = Message::from_bytes(&self.exchange(&response).await?)
+                .map_err(|e| ClientError::Serialization(e.to_string()))?;
+        }
+
         match welcome {
             Message::Welcome(w) => Ok(w.max_cursor),
             _ => Err(ClientError::Protocol("expected Welcome response".into())),
         }
     }
 
+    /// Internal: run the hybrid secure channel handshake.
+    ///
+    /// The final handshake message carries `hello`; returns the relay's
+    /// (opened) reply to it. With a `pinned` key, the relay must prove that
+    /// key before HELLO is sent.
+    async fn secure_handshake(
+        &self,
+        hello: &[u8],
+        pinned: Option<[u8; 32]>,
+    ) -> Result<Vec<u8>, ClientError> {
+        let mut channel = self.channel.lock().await;
+
+        let (initiator, first) = HandshakeInitiator::start(&self.noise_keypair)?;
+        self.send_handshake(first).await?;
+
+        let reply_bytes = self.transport.recv().await?;
+        let reply = match Message::from_bytes(&reply_bytes)
+            .map_err(|e| ClientError::Serialization(e.to_string()))?
+        {
+            Message::Handshake(h) => h.payload,
+            _ => return Err(ClientError::Protocol("expected Handshake response".into())),
+        };
+
+        let (mut established, last) = initiator.finish(&reply, hello)?;
+        if let Some(expected) = pinned {
+            if established.remote_static() != Some(expected) {
+                return Err(ChannelError::Handshake(
+                    "relay key does not match the pinned key".into(),
+                )
+                .into());
+            }
+        }
+        self.send_handshake(last).await?;
+
+        let response = established.open(&self.transport.recv().await?)?;
+        *channel = Some(established);
+        Ok(response)
+    }
+
+    /// Internal: send one handshake message.
+    async fn send_handshake(&self, payload: Vec<u8>) -> Result<(), ClientError> {
+        let bytes = Message::Handshake(Handshake { payload })
+            .to_bytes()
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+        self.transport.send(&bytes).await?;
+        Ok(())
+    }
+
+    /// Internal: send one request and receive its response.
+    ///
+    /// Both are sealed when the secure channel is established. Requests are
+    /// serialized so responses (and channel nonces) stay in order.
+    async fn exchange(&self, request: &[u8]) -> Result<Vec<u8>, ClientError> {
+        let mut channel = self.channel.lock().await;
+        match channel.as_mut() {
+            Some(channel) => {
+                let sealed = channel.seal(request)?;
+                self.transport.send(&sealed).await?;
+                let response = self.transport.recv().await?;
+                Ok(channel.open(&response)?)
+            }
+            None => {
+                self.transport.send(request).await?;
+                Ok(self.transport.recv().await?)
+            }
+        }
+    }
+
     /// Check if connected.
     pub async fn is_connected(&self) -> bool {
         let state = self.state.lock().await;
@@ -402,6 +656,7 @@ impl<T: Transport> SyncClient<T> {
             *state = new_state;
         }
 
+        *self.channel.lock().await = None;
         self.transport.close().await?;
         Ok(())
     }
@@ -415,38 +670,101 @@ impl<T: Transport> SyncClient<T> {
             return Err(ClientError::NotConnected);
         }
 
+        // Compress before encrypting (ciphertext doesn't compress)
+        let encoded = self.config.compression.encode(plaintext);
+
+        // Pad after compressing, so the padding hides the compressed size
+        let padded = self.config.padding.pad(&encoded, MAX_PADDED_SIZE);
+
         // Encrypt the payload
-        let (ciphertext, nonce) = self.key.encrypt(plaintext)?;
+        let (ciphertext, nonce) = self.key.encrypt(&padded)?;
 
         // Prepend nonce to ciphertext for self-describing format
         let mut payload = Vec::with_capacity(crate::NONCE_SIZE + ciphertext.len());
         payload.extend_from_slice(&nonce);
         payload.extend_from_slice(&ciphertext);
 
-        // Create push message
-        let blob_id = BlobId::new();
-        let push = Message::Push(Push {
-            blob_id,
-            payload,
-            ttl: self.config.default_ttl,
-        });
+        self.send_push(payload, self.push_ttl()).await
+    }
 
-        // Serialize
-        let bytes = push
-            .to_bytes()
-            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+    /// Send one cover (dummy) push.
+    ///
+    /// The payload is random bytes shaped like a real push: a nonce, a body
+    /// sized by the padding policy, and an auth tag. Recipients fail to
+    /// authenticate it and drop it. Sent with the same TTL as real pushes,
+    /// so the relay cannot pick cover pushes out by TTL.
+    pub async fn push_cover(&self) -> Result<(BlobId, Cursor), ClientError> {
+        if !self.is_connected().await {
+            return Err(ClientError::NotConnected);
+        }
 
-        // Try to push, with failover on transport error
-        match self.try_push_bytes(&bytes, blob_id).await {
-            Ok(result) => Ok(result),
-            Err(ClientError::Transport(_)) => {
-                // Transport failed, try to reconnect to another relay
-                self.try_reconnect().await?;
-                // Retry the push on the new relay
-                self.try_push_bytes(&bytes, blob_id).await
+        let mut len_bytes = [0u8; 2];
+        getrandom::getrandom(&mut len_bytes)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+        let body_len = u16::from_le_bytes(len_bytes) as usize % (COVER_MAX_BODY + 1);
+        let padded_len = self
+            .config
+            .padding
+            .pad(&vec![0u8; body_len], MAX_PADDED_SIZE)
+            .len();
+
+        let mut payload = vec![0u8; crate::NONCE_SIZE + padded_len + AEAD_TAG_SIZE];
+        getrandom::getrandom(&mut payload)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+        self.send_push(payload, self.push_ttl()).await
+    }
+
+    /// Internal: relay TTL for pushes, the cover TTL while cover traffic is on.
+    fn push_ttl(&self) -> u32 {
+        match self.config.cover_traffic {
+            Some(cover) => cover.ttl,
+            None => self.config.default_ttl,
+        }
+    }
+
+    /// Internal: wrap a payload in a Push and send it, with failover.
+    ///
+    /// Runs in a `sync.push` span whose trace ID is sent with the push.
+    async fn send_push(&self, payload: Vec<u8>, ttl: u32) -> Result<(BlobId, Cursor), ClientError> {
+        let span = tracing::info_span!(
+            "sync.push",
+            otel.name = "push",
+            bytes = payload.len(),
+            trace_id = tracing::field::Empty,
+        );
+        let trace = telemetry::trace_context(&span);
+        span.record("trace_id", trace.trace_id_hex().as_str());
+
+        async move {
+            // Create push message
+            let blob_id = BlobId::new();
+            let push = Message::Push(Push {
+                blob_id,
+                payload,
+                ttl,
+                trace: Some(trace),
+            });
+
+            // Serialize
+            let bytes = push
+                .to_bytes()
+                .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+            // Try to push, with failover on transport error
+            match self.try_push_bytes(&bytes, blob_id).await {
+                Ok(result) => Ok(result),
+                Err(ClientError::Transport(_)) => {
+                    // Transport failed, try to reconnect to another relay
+                    self.try_reconnect().await?;
+                    // Retry the push on the new relay
+                    self.try_push_bytes(&bytes, blob_id).await
+                }
+                Err(e) => Err(e),
             }
-            Err(e) => Err(e),
         }
+        .instrument(span)
+        .await
     }
 
     /// Internal: attempt to send push message and receive ack.
@@ -455,9 +773,7 @@ impl<T: Transport> SyncClient<T> {
         bytes: &[u8],
         blob_id: BlobId,
     ) -> Result<(BlobId, Cursor), ClientError> {
-        self.transport.send(bytes).await?;
-
-        let response_bytes = self.transport.recv().await?;
+        let response_bytes = self.exchange(bytes).await?;
         let response = Message::from_bytes(&response_bytes)
             .map_err(|e| ClientError::Serialization(e.to_string()))?;
 
@@ -502,35 +818,47 @@ impl<T: Transport> SyncClient<T> {
             }
         };
 
-        // Create pull request
-        let pull = Message::Pull(Pull {
-            after_cursor,
-            limit: 100, // Default batch size
-        });
-
-        // Serialize
-        let bytes = pull
-            .to_bytes()
-            .map_err(|e| ClientError::Serialization(e.to_string()))?;
-
-        // Try to pull, with failover on transport error
-        match self.try_pull_bytes(&bytes).await {
-            Ok(result) => Ok(result),
-            Err(ClientError::Transport(_)) => {
-                // Transport failed, try to reconnect to another relay
-                self.try_reconnect().await?;
-                // Retry the pull on the new relay
-                self.try_pull_bytes(&bytes).await
+        let span = tracing::info_span!(
+            "sync.pull",
+            otel.name = "pull",
+            after_cursor = after_cursor.value(),
+            trace_id = tracing::field::Empty,
+        );
+        let trace = telemetry::trace_context(&span);
+        span.record("trace_id", trace.trace_id_hex().as_str());
+
+        async move {
+            // Create pull request
+            let pull = Message::Pull(Pull {
+                after_cursor,
+                limit: 100, // Default batch size
+                trace: Some(trace),
+            });
+
+            // Serialize
+            let bytes = pull
+                .to_bytes()
+                .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+            // Try to pull, with failover on transport error
+            match self.try_pull_bytes(&bytes).await {
+                Ok(result) => Ok(result),
+                Err(ClientError::Transport(_)) => {
+                    // Transport failed, try to reconnect to another relay
+                    self.try_reconnect().await?;
+                    // Retry the pull on the new relay
+                    self.try_pull_bytes(&bytes).await
+                }
+                Err(e) => Err(e),
             }
-            Err(e) => Err(e),
         }
+        .instrument(span)
+        .await
     }
 
     /// Internal: attempt to send pull request and receive/decrypt response.
     async fn try_pull_bytes(&self, bytes: &[u8]) -> Result<Vec<ReceivedBlob>, ClientError> {
-        self.transport.send(bytes).await?;
-
-        let response_bytes = self.transport.recv().await?;
+        let response_bytes = self.exchange(bytes).await?;
         let response = Message::from_bytes(&response_bytes)
             .map_err(|e| ClientError::Serialization(e.to_string()))?;
 
@@ -545,7 +873,11 @@ impl<T: Transport> SyncClient<T> {
                 for blob in blobs {
                     // Decrypt payload (nonce is prepended)
                     if blob.payload.len() < crate::NONCE_SIZE {
-                        // Skip malformed blobs
+                        tracing::warn!(
+                            "Dropping blob {} at cursor {}: shorter than a nonce",
+                            blob.blob_id,
+                            blob.cursor
+                        );
                         continue;
                     }
 
@@ -553,7 +885,29 @@ impl<T: Transport> SyncClient<T> {
                         blob.payload[..crate::NONCE_SIZE].try_into().unwrap();
                     let ciphertext = &blob.payload[crate::NONCE_SIZE..];
 
-                    match self.key.decrypt(ciphertext, &nonce) {
+                    // Cover pushes never decrypt, so this is routine
+                    let Ok(data) = self.key.decrypt(ciphertext, &nonce) else {
+                        tracing::debug!(
+                            "Dropping blob {} at cursor {}: cannot decrypt",
+                            blob.blob_id,
+                            blob.cursor
+                        );
+                        continue;
+                    };
+
+                    let decoded = self
+                        .config
+                        .padding
+                        .unpad(&data)
+                        .map_err(|e| e.to_string())
+                        .and_then(|data| {
+                            self.config
+                                .compression
+                                .decode(&data, self.config.max_decompressed_size)
+                                .map_err(|e| e.to_string())
+                        });
+
+                    match decoded {
                         Ok(plaintext) => {
                             received.push(ReceivedBlob {
                                 blob_id: blob.blob_id,
@@ -562,9 +916,16 @@ impl<T: Transport> SyncClient<T> {
                                 timestamp: blob.timestamp,
                             });
                         }
-                        Err(_) => {
-                            // Skip blobs we can't decrypt (wrong key)
-                            continue;
+                        Err(reason) => {
+                            // Decrypted but not unframed: corrupt, over the
+                            // size limit, or this device's padding and
+                            // compression settings differ from the sender's
+                            tracing::warn!(
+                                "Dropping blob {} at cursor {}: {}",
+                                blob.blob_id,
+                                blob.cursor,
+                                reason
+                            );
                         }
                     }
                 }
@@ -581,6 +942,168 @@ impl<T: Transport> SyncClient<T> {
         }
     }
 
+    /// Upload encrypted content to the relay for offline recipients.
+    ///
+    /// `content_hash` must be the BLAKE3 hash of `ciphertext` (as produced by
+    /// sync-content). The relay verifies it and holds the content until its
+    /// content TTL expires, so peers can resolve a `ContentRef` after this
+    /// device goes offline. Content must fit in a single relay message.
+    ///
+    /// On transport failure, automatically fails over to another relay and retries.
+    pub async fn upload_content(
+        &self,
+        content_hash: [u8; 32],
+        ciphertext: &[u8],
+    ) -> Result<(), ClientError> {
+        if !self.is_connected().await {
+            return Err(ClientError::NotConnected);
+        }
+
+        let put = Message::ContentPut(ContentPut {
+            content_hash,
+            data: ciphertext.to_vec(),
+            ttl: 0, // Relay content TTL
+        });
+
+        let bytes = put
+            .to_bytes()
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+        match self.try_upload_content_bytes(&bytes, content_hash).await {
+            Ok(()) => Ok(()),
+            Err(ClientError::Transport(_)) => {
+                self.try_reconnect().await?;
+                self.try_upload_content_bytes(&bytes, content_hash).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Internal: attempt to send content and receive ack.
+    async fn try_upload_content_bytes(
+        &self,
+        bytes: &[u8],
+        content_hash: [u8; 32],
+    ) -> Result<(), ClientError> {
+        let response_bytes = self.exchange(bytes).await?;
+        let response = Message::from_bytes(&response_bytes)
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+        match response {
+            Message::ContentAck(ContentAck {
+                content_hash: ack_hash,
+            }) if ack_hash == content_hash => Ok(()),
+            _ => Err(ClientError::Protocol(
+                "unexpected response to content upload".into(),
+            )),
+        }
+    }
+
+    /// Fetch relay-hosted content by hash.
+    ///
+    /// Returns the ciphertext, or `None` if the relay does not hold it.
+    /// Data that does not match `content_hash` is rejected.
+    ///
+    /// On transport failure, automatically fails over to another relay and retries.
+    pub async fn fetch_content(
+        &self,
+        content_hash: [u8; 32],
+    ) -> Result<Option<Vec<u8>>, ClientError> {
+        if !self.is_connected().await {
+            return Err(ClientError::NotConnected);
+        }
+
+        let get = Message::ContentGet(ContentGet { content_hash });
+
+        let bytes = get
+            .to_bytes()
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+        match self.try_fetch_content_bytes(&bytes, content_hash).await {
+            Ok(result) => Ok(result),
+            Err(ClientError::Transport(_)) => {
+                self.try_reconnect().await?;
+                self.try_fetch_content_bytes(&bytes, content_hash).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Internal: attempt to request content and verify the response.
+    async fn try_fetch_content_bytes(
+        &self,
+        bytes: &[u8],
+        content_hash: [u8; 32],
+    ) -> Result<Option<Vec<u8>>, ClientError> {
+        let response_bytes = self.exchange(bytes).await?;
+        let response = Message::from_bytes(&response_bytes)
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+        match response {
+            Message::ContentData(ContentData {
+                content_hash: hash,
+                data,
+            }) if hash == content_hash => match data {
+                Some(data) if blake3::hash(&data).as_bytes() != &content_hash => Err(
+                    ClientError::Protocol("relay returned content with wrong hash".into()),
+                ),
+                data => Ok(data),
+            },
+            _ => Err(ClientError::Protocol(
+                "unexpected response to content fetch".into(),
+            )),
+        }
+    }
+
+    /// Register an invite with the relay, limiting it to `max_uses` joins.
+    ///
+    /// The invite must carry an ID (see [`Invite::with_random_id`]). Once a
+    /// group has a registered invite, the relay only admits existing members
+    /// and devices redeeming a valid invite.
+    pub async fn register_invite(&self, invite: &Invite, max_uses: u32) -> Result<(), ClientError> {
+        let invite_id = invite
+            .invite_id
+            .ok_or_else(|| ClientError::Protocol("invite has no ID to register".into()))?;
+
+        let register = Message::InviteRegister(InviteRegister {
+            invite_id,
+            max_uses,
+            expires_at: invite.expires_at,
+        });
+        self.invite_request(&register, invite_id).await
+    }
+
+    /// Revoke a registered invite. Devices that already joined keep access.
+    pub async fn revoke_invite(&self, invite_id: [u8; 16]) -> Result<(), ClientError> {
+        let revoke = Message::InviteRevoke(InviteRevoke { invite_id });
+        self.invite_request(&revoke, invite_id).await
+    }
+
+    /// Internal: send an invite request and wait for its ack.
+    async fn invite_request(
+        &self,
+        request: &Message,
+        invite_id: [u8; 16],
+    ) -> Result<(), ClientError> {
+        if !self.is_connected().await {
+            return Err(ClientError::NotConnected);
+        }
+
+        let bytes = request
+            .to_bytes()
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+        let response_bytes = self.exchange(&bytes).await?;
+        let response = Message::from_bytes(&response_bytes)
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+        match response {
+            Message::InviteAck(InviteAck { invite_id: acked }) if acked == invite_id => Ok(()),
+            _ => Err(ClientError::Protocol(
+                "unexpected response to invite request".into(),
+            )),
+        }
+    }
+
     /// Get the current cursor position.
     pub async fn current_cursor(&self) -> Cursor {
         let cursor_tracker = self.cursor.lock().await;
@@ -591,12 +1114,120 @@ impl<T: Transport> SyncClient<T> {
     pub fn transport(&self) -> &T {
         &self.transport
     }
+
+    /// Wait for the next message the relay sends on its own.
+    ///
+    /// NOTIFY and BYE arrive on relay-opened streams, outside the secure
+    /// channel. On BYE the client fails over to another relay before
+    /// returning; if none accepts, returns [`ClientError::AllRelaysFailed`]
+    /// with the relay's reason. Other messages are ignored.
+    pub async fn next_event(&self) -> Result<RelayEvent, ClientError> {
+        loop {
+            let bytes = self.transport.recv_pushed().await?;
+            let message = Message::from_bytes(&bytes)
+                .map_err(|e| ClientError::Serialization(e.to_string()))?;
+            match message {
+                Message::Notify(notify) => return Ok(RelayEvent::Notify(notify.latest_cursor)),
+                Message::Bye(bye) => return self.handle_bye(bye.reason).await,
+                other => {
+                    tracing::debug!("Ignoring unexpected relay message {:?}", other);
+                }
+            }
+        }
+    }
+
+    /// Internal: fail over after the active relay said BYE.
+    async fn handle_bye(&self, reason: Option<String>) -> Result<RelayEvent, ClientError> {
+        let from = self.active_relay().await.unwrap_or_default();
+        let said = match &reason {
+            Some(reason) => format!("{}: BYE ({})", from, reason),
+            None => format!("{}: BYE", from),
+        };
+        tracing::info!("Relay ended the session, failing over ({})", said);
+
+        match self.try_reconnect().await {
+            Ok(()) => Ok(RelayEvent::Bye {
+                reason,
+                relay: self.active_relay().await.unwrap_or_default(),
+            }),
+            Err(ClientError::AllRelaysFailed(details)) if details.is_empty() => {
+                Err(ClientError::AllRelaysFailed(said))
+            }
+            Err(ClientError::AllRelaysFailed(details)) => Err(ClientError::AllRelaysFailed(
+                format!("{}; {}", said, details),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<T: Transport + 'static> SyncClient<T> {
+    /// Start sending cover pushes on the configured randomized schedule.
+    ///
+    /// Returns `None` if cover traffic is not configured. Pushes are only
+    /// sent while connected; failures are logged and retried on the next
+    /// tick. Abort the returned handle to stop.
+    pub fn spawn_cover_traffic(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let cover = self.config.cover_traffic?;
+        let client = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(cover.next_delay()).await;
+                if client.is_connected().await {
+                    if let Err(e) = client.push_cover().await {
+                        tracing::warn!("Cover push failed: {}", e);
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Answer `reply` if it is a proof-of-work challenge and return the relay's
+/// reply to the original request.
+///
+/// A loaded relay challenges the first request on a connection, including
+/// mailbox and OPRF requests made without HELLO.
+pub(crate) async fn answer_challenge<T: Transport>(
+    transport: &T,
+    reply: Message,
+) -> Result<Message, ClientError> {
+    let Message::Challenge(challenge) = reply else {
+        return Ok(reply);
+    };
+    let response = Message::ChallengeResponse(solve_challenge(challenge).await?)
+        .to_bytes()
+        .map_err(|e| ClientError::Serialization(e.to_string()))?;
+    transport.send(&response).await?;
+    Message::from_bytes(&transport.recv().await?)
+        .map_err(|e| ClientError::Serialization(e.to_string()))
+}
+
+/// Solve a relay's proof-of-work challenge on a blocking thread.
+async fn solve_challenge(challenge: Challenge) -> Result<ChallengeResponse, ClientError> {
+    if challenge.difficulty > MAX_POW_DIFFICULTY {
+        return Err(ClientError::Protocol(format!(
+            "proof-of-work difficulty {} exceeds {}",
+            challenge.difficulty, MAX_POW_DIFFICULTY
+        )));
+    }
+    tracing::debug!(
+        "Solving {}-bit proof-of-work challenge",
+        challenge.difficulty
+    );
+    let nonce = challenge.nonce;
+    let counter = tokio::task::spawn_blocking(move || solve_pow(&nonce, challenge.difficulty))
+        .await
+        .map_err(|e| ClientError::Protocol(e.to_string()))?
+        .ok_or_else(|| ClientError::Protocol("proof-of-work challenge unsolvable".into()))?;
+    Ok(ChallengeResponse { nonce, counter })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::transport::MockTransport;
+    use std::time::Duration;
     use zerok_sync_types::{GroupId, PullBlob, Welcome};
 
     fn test_config() -> SyncConfig {
@@ -685,6 +1316,35 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn connect_answers_pow_challenge() {
+        let transport = MockTransport::new();
+        let nonce = [0x42; 32];
+        transport.queue_response(
+            Message::Challenge(Challenge {
+                nonce,
+                difficulty: 8,
+            })
+            .to_bytes()
+            .unwrap(),
+        );
+        transport.queue_response(mock_welcome(3, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+
+        client.connect().await.unwrap();
+        assert!(client.is_connected().await);
+
+        let sent = transport.sent_messages();
+        assert_eq!(sent.len(), 2);
+        match Message::from_bytes(&sent[1]).unwrap() {
+            Message::ChallengeResponse(response) => {
+                assert_eq!(response.nonce, nonce);
+                assert!(zerok_sync_core::verify_pow(&nonce, 8, response.counter));
+            }
+            other => panic!("Expected ChallengeResponse, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn client_disconnects() {
         let transport = MockTransport::new();
@@ -827,6 +1487,30 @@ mod tests {
         assert!(matches!(msg, Message::Push(_)));
     }
 
+    #[tokio::test]
+    async fn requests_carry_trace_context() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        transport.queue_response(pull_response_with(Vec::new()));
+        client.pull().await.unwrap();
+        let _ = client.push(b"traced").await;
+
+        let sent = transport.sent_messages();
+        let pull_trace = match Message::from_bytes(&sent[1]).unwrap() {
+            Message::Pull(pull) => pull.trace.expect("pull carries trace context"),
+            other => panic!("expected Pull, got {:?}", other),
+        };
+        let push_trace = match Message::from_bytes(&sent[2]).unwrap() {
+            Message::Push(push) => push.trace.expect("push carries trace context"),
+            other => panic!("expected Push, got {:?}", other),
+        };
+        // Each operation starts its own trace
+        assert_ne!(pull_trace.trace_id, push_trace.trace_id);
+    }
+
     #[tokio::test]
     async fn push_payload_is_encrypted() {
         let transport = MockTransport::new();
@@ -1142,31 +1826,96 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn pull_reconnects_on_send_failure() {
+    async fn bye_fails_over_to_next_relay() {
         let transport = MockTransport::new();
         transport.queue_response(mock_welcome(0, 0));
         transport.queue_response(mock_welcome(0, 0));
 
         let config = test_config().with_relay_addresses(&["relay-a", "relay-b"]);
         let client = SyncClient::new(config, transport.clone());
-
         client.connect().await.unwrap();
-        assert_eq!(client.active_relay().await, Some("relay-a".to_string()));
 
-        // Simulate relay dying on pull's send
-        transport.fail_next_send("connection reset");
-
-        // Queue empty PullResponse for retry
-        let response = Message::PullResponse(PullResponse {
-            blobs: vec![],
-            has_more: false,
-            max_cursor: Cursor::new(0),
+        let bye = Message::Bye(zerok_sync_types::Bye {
+            reason: Some("relay shutting down".into()),
         });
-        transport.queue_response(response.to_bytes().unwrap());
-
-        let blobs = client.pull().await.unwrap();
+        transport.queue_pushed(bye.to_bytes().unwrap());
 
-        assert!(blobs.is_empty());
+        assert_eq!(
+            client.next_event().await.unwrap(),
+            RelayEvent::Bye {
+                reason: Some("relay shutting down".into()),
+                relay: "relay-b".into(),
+            }
+        );
+        assert_eq!(client.active_relay().await, Some("relay-b".to_string()));
+        assert!(client.is_connected().await);
+    }
+
+    #[tokio::test]
+    async fn bye_from_only_relay_surfaces_reason() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let bye = Message::Bye(zerok_sync_types::Bye {
+            reason: Some("relay shutting down".into()),
+        });
+        transport.queue_pushed(bye.to_bytes().unwrap());
+
+        let err = client.next_event().await.unwrap_err();
+        assert!(matches!(err, ClientError::AllRelaysFailed(_)));
+        let msg = err.to_string();
+        assert!(msg.contains("test-node: BYE (relay shutting down)"));
+        assert!(!client.is_connected().await);
+    }
+
+    #[tokio::test]
+    async fn notify_event_reports_cursor() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let notify = Message::Notify(zerok_sync_types::Notify {
+            latest_cursor: Cursor::new(7),
+            count: 1,
+            trace: None,
+        });
+        transport.queue_pushed(notify.to_bytes().unwrap());
+
+        assert_eq!(
+            client.next_event().await.unwrap(),
+            RelayEvent::Notify(Cursor::new(7))
+        );
+    }
+
+    #[tokio::test]
+    async fn pull_reconnects_on_send_failure() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        transport.queue_response(mock_welcome(0, 0));
+
+        let config = test_config().with_relay_addresses(&["relay-a", "relay-b"]);
+        let client = SyncClient::new(config, transport.clone());
+
+        client.connect().await.unwrap();
+        assert_eq!(client.active_relay().await, Some("relay-a".to_string()));
+
+        // Simulate relay dying on pull's send
+        transport.fail_next_send("connection reset");
+
+        // Queue empty PullResponse for retry
+        let response = Message::PullResponse(PullResponse {
+            blobs: vec![],
+            has_more: false,
+            max_cursor: Cursor::new(0),
+        });
+        transport.queue_response(response.to_bytes().unwrap());
+
+        let blobs = client.pull().await.unwrap();
+
+        assert!(blobs.is_empty());
         assert_eq!(
             client.active_relay().await,
             Some("relay-b".to_string()),
@@ -1250,4 +1999,618 @@ mod tests {
             result
         );
     }
+
+    // ===========================================
+    // Relay-hosted content
+    // ===========================================
+
+    #[tokio::test]
+    async fn upload_content_without_connect_fails() {
+        let client = SyncClient::new(test_config(), MockTransport::new());
+        let result = client.upload_content([0u8; 32], b"data").await;
+        assert!(matches!(result, Err(ClientError::NotConnected)));
+    }
+
+    #[tokio::test]
+    async fn upload_content_sends_content_put() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let ciphertext = b"opaque ciphertext";
+        let hash = *blake3::hash(ciphertext).as_bytes();
+        let ack = Message::ContentAck(ContentAck { content_hash: hash });
+        transport.queue_response(ack.to_bytes().unwrap());
+
+        client.upload_content(hash, ciphertext).await.unwrap();
+
+        let sent = transport.sent_messages();
+        let last = Message::from_bytes(sent.last().unwrap()).unwrap();
+        match last {
+            Message::ContentPut(put) => {
+                assert_eq!(put.content_hash, hash);
+                assert_eq!(put.data, ciphertext);
+            }
+            other => panic!("expected ContentPut, got {:?}", other),
+        }
+    }
+
+    fn test_invite() -> Invite {
+        let secret = zerok_sync_core::GroupSecret::random();
+        let group_id = secret.derive_group_id();
+        Invite::create(
+            zerok_sync_core::RelayNodeId::from_bytes([7u8; 32]),
+            group_id,
+            secret,
+            b"test-salt-00000!".to_vec(),
+        )
+    }
+
+    #[tokio::test]
+    async fn register_and_revoke_invite() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let invite = test_invite().with_random_id();
+        let invite_id = invite.invite_id.unwrap();
+
+        let ack = Message::InviteAck(InviteAck { invite_id });
+        transport.queue_response(ack.to_bytes().unwrap());
+        client.register_invite(&invite, 1).await.unwrap();
+
+        let sent = transport.sent_messages();
+        match Message::from_bytes(sent.last().unwrap()).unwrap() {
+            Message::InviteRegister(register) => {
+                assert_eq!(register.invite_id, invite_id);
+                assert_eq!(register.max_uses, 1);
+                assert_eq!(register.expires_at, invite.expires_at);
+            }
+            other => panic!("expected InviteRegister, got {:?}", other),
+        }
+
+        transport.queue_response(ack.to_bytes().unwrap());
+        client.revoke_invite(invite_id).await.unwrap();
+        let sent = transport.sent_messages();
+        assert!(matches!(
+            Message::from_bytes(sent.last().unwrap()).unwrap(),
+            Message::InviteRevoke(InviteRevoke { invite_id: id }) if id == invite_id
+        ));
+    }
+
+    #[tokio::test]
+    async fn register_invite_requires_id() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let invite = test_invite();
+        let result = client.register_invite(&invite, 1).await;
+        assert!(matches!(result, Err(ClientError::Protocol(_))));
+    }
+
+    #[tokio::test]
+    async fn fetch_content_returns_verified_data() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let ciphertext = b"opaque ciphertext".to_vec();
+        let hash = *blake3::hash(&ciphertext).as_bytes();
+        let response = Message::ContentData(ContentData {
+            content_hash: hash,
+            data: Some(ciphertext.clone()),
+        });
+        transport.queue_response(response.to_bytes().unwrap());
+
+        let fetched = client.fetch_content(hash).await.unwrap();
+        assert_eq!(fetched, Some(ciphertext));
+    }
+
+    #[tokio::test]
+    async fn fetch_content_missing_returns_none() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let hash = [0x42; 32];
+        let response = Message::ContentData(ContentData {
+            content_hash: hash,
+            data: None,
+        });
+        transport.queue_response(response.to_bytes().unwrap());
+
+        assert!(client.fetch_content(hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn fetch_content_rejects_hash_mismatch() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config(), transport.clone());
+        client.connect().await.unwrap();
+
+        let hash = *blake3::hash(b"expected").as_bytes();
+        let response = Message::ContentData(ContentData {
+            content_hash: hash,
+            data: Some(b"substituted".to_vec()),
+        });
+        transport.queue_response(response.to_bytes().unwrap());
+
+        let result = client.fetch_content(hash).await;
+        assert!(matches!(result, Err(ClientError::Protocol(_))));
+    }
+
+    // ===========================================
+    // Compression
+    // ===========================================
+
+    fn pull_response_with(payload: Vec<u8>) -> Vec<u8> {
+        Message::PullResponse(PullResponse {
+            blobs: vec![PullBlob {
+                blob_id: BlobId::new(),
+                cursor: Cursor::new(1),
+                payload,
+                timestamp: 1705000000,
+            }],
+            has_more: false,
+            max_cursor: Cursor::new(1),
+        })
+        .to_bytes()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn push_compresses_before_encrypting() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config().with_compression(Compression::Zstd);
+        let key = GroupKey::derive(&config.group_secret);
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        let plaintext = br#"{"type":"context","items":[1,1,1,1,1,1,1,1,1,1]}"#.repeat(50);
+        // PushAck won't match the random blob_id; we only inspect what was sent
+        let ack = Message::PushAck(PushAck {
+            blob_id: BlobId::new(),
+            cursor: Cursor::new(1),
+        });
+        transport.queue_response(ack.to_bytes().unwrap());
+        let _ = client.push(&plaintext).await;
+
+        let sent = transport.sent_messages();
+        let payload = match Message::from_bytes(sent.last().unwrap()).unwrap() {
+            Message::Push(push) => push.payload,
+            other => panic!("expected Push, got {:?}", other),
+        };
+        assert!(payload.len() < plaintext.len() / 5);
+
+        // The compression header is inside the ciphertext
+        let nonce: [u8; crate::NONCE_SIZE] = payload[..crate::NONCE_SIZE].try_into().unwrap();
+        let decrypted = key.decrypt(&payload[crate::NONCE_SIZE..], &nonce).unwrap();
+        assert_eq!(
+            Compression::Zstd.decode(&decrypted, 1024 * 1024).unwrap(),
+            plaintext
+        );
+
+        // Round-trips through pull on a peer with the same group setting
+        transport.queue_response(pull_response_with(payload));
+        let blobs = client.pull().await.unwrap();
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].payload, plaintext);
+    }
+
+    #[tokio::test]
+    async fn pull_accepts_baseline_payload_without_header() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config();
+        let key = GroupKey::derive(&config.group_secret);
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        // Sent by a client that predates compression; 0x01 is not a zstd flag here
+        let plaintext = [0x01, 0x02, 0x03];
+        let (ciphertext, nonce) = key.encrypt(&plaintext).unwrap();
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        transport.queue_response(pull_response_with(payload));
+        let blobs = client.pull().await.unwrap();
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].payload, plaintext);
+    }
+
+    #[tokio::test]
+    async fn pull_skips_decompression_bombs() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config()
+            .with_compression(Compression::Zstd)
+            .with_max_decompressed_size(64 * 1024);
+        let key = GroupKey::derive(&config.group_secret);
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        // 8 MB of zeros compresses to a tiny payload
+        let bomb = Compression::Zstd.encode(&vec![0u8; 8 * 1024 * 1024]);
+        let (ciphertext, nonce) = key.encrypt(&bomb).unwrap();
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        transport.queue_response(pull_response_with(payload));
+        let blobs = client.pull().await.unwrap();
+        assert!(blobs.is_empty(), "oversized payload must be skipped");
+        assert_eq!(client.current_cursor().await, Cursor::new(1));
+    }
+
+    // ===========================================
+    // Padding
+    // ===========================================
+
+    /// Push `plaintext` and return the payload the relay would see.
+    async fn pushed_payload(
+        client: &SyncClient<MockTransport>,
+        transport: &MockTransport,
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        let ack = Message::PushAck(PushAck {
+            blob_id: BlobId::new(),
+            cursor: Cursor::new(1),
+        });
+        transport.queue_response(ack.to_bytes().unwrap());
+        let _ = client.push(plaintext).await;
+
+        match Message::from_bytes(transport.sent_messages().last().unwrap()).unwrap() {
+            Message::Push(push) => push.payload,
+            other => panic!("expected Push, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn padded_pushes_hide_record_size() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config().with_padding(PaddingPolicy::PowerOfTwo);
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        let small = pushed_payload(&client, &transport, b"ack").await;
+        let large = pushed_payload(&client, &transport, &[7u8; 200]).await;
+
+        assert_eq!(small.len(), large.len());
+        assert_eq!(small.len(), crate::NONCE_SIZE + 256 + 16);
+    }
+
+    #[tokio::test]
+    async fn padded_payload_roundtrips_through_pull() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config()
+            .with_padding(PaddingPolicy::Block(512))
+            .with_compression(Compression::Lz4);
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        let plaintext = b"padded and compressed record ".repeat(10);
+        let payload = pushed_payload(&client, &transport, &plaintext).await;
+        assert_eq!((payload.len() - crate::NONCE_SIZE - 16) % 512, 0);
+
+        transport.queue_response(pull_response_with(payload));
+        let blobs = client.pull().await.unwrap();
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].payload, plaintext);
+    }
+
+    // ===========================================
+    // Cover traffic
+    // ===========================================
+
+    #[tokio::test]
+    async fn cover_push_matches_real_push_shape() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config().with_padding(PaddingPolicy::Block(2048));
+        let key = GroupKey::derive(&config.group_secret);
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        let real = pushed_payload(&client, &transport, b"real record").await;
+
+        let ack = Message::PushAck(PushAck {
+            blob_id: BlobId::new(),
+            cursor: Cursor::new(2),
+        });
+        transport.queue_response(ack.to_bytes().unwrap());
+        let _ = client.push_cover().await;
+        let cover = match Message::from_bytes(transport.sent_messages().last().unwrap()).unwrap() {
+            Message::Push(push) => push,
+            other => panic!("expected Push, got {:?}", other),
+        };
+
+        // Same size bucket and TTL as a real push
+        assert_eq!(cover.payload.len(), real.len());
+        assert_eq!(cover.ttl, 0);
+
+        // Recipients cannot decrypt it, so it is dropped
+        let nonce: [u8; crate::NONCE_SIZE] = cover.payload[..crate::NONCE_SIZE].try_into().unwrap();
+        assert!(key
+            .decrypt(&cover.payload[crate::NONCE_SIZE..], &nonce)
+            .is_err());
+        transport.queue_response(pull_response_with(cover.payload));
+        assert!(client.pull().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cover_mode_pushes_share_the_cover_ttl() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config()
+            .with_ttl(0)
+            .with_cover_traffic(CoverTrafficConfig::default().with_ttl(3600));
+        let client = SyncClient::new(config, transport.clone());
+        client.connect().await.unwrap();
+
+        let real = pushed_ttl(&client, &transport, false).await;
+        let cover = pushed_ttl(&client, &transport, true).await;
+        assert_eq!(real, 3600);
+        assert_eq!(cover, 3600);
+    }
+
+    #[tokio::test]
+    async fn pushes_use_default_ttl_without_cover_traffic() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let client = SyncClient::new(test_config().with_ttl(86400), transport.clone());
+        client.connect().await.unwrap();
+
+        assert_eq!(pushed_ttl(&client, &transport, false).await, 86400);
+    }
+
+    /// Send one real or cover push and return the TTL it carried.
+    async fn pushed_ttl(
+        client: &SyncClient<MockTransport>,
+        transport: &MockTransport,
+        cover: bool,
+    ) -> u32 {
+        let ack = Message::PushAck(PushAck {
+            blob_id: BlobId::new(),
+            cursor: Cursor::new(1),
+        });
+        transport.queue_response(ack.to_bytes().unwrap());
+        let _ = if cover {
+            client.push_cover().await
+        } else {
+            client.push(b"record").await
+        };
+        match Message::from_bytes(transport.sent_messages().last().unwrap()).unwrap() {
+            Message::Push(push) => push.ttl,
+            other => panic!("expected Push, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn spawn_cover_traffic_requires_config() {
+        let client = Arc::new(SyncClient::new(test_config(), MockTransport::new()));
+        assert!(client.spawn_cover_traffic().is_none());
+    }
+
+    #[tokio::test]
+    async fn spawn_cover_traffic_sends_periodic_pushes() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let cover = CoverTrafficConfig::new(Duration::from_millis(5), Duration::from_millis(10));
+        let config = test_config().with_cover_traffic(cover);
+        let client = Arc::new(SyncClient::new(config, transport.clone()));
+        client.connect().await.unwrap();
+        let sent_after_connect = transport.sent_messages().len();
+        for _ in 0..20 {
+            let ack = Message::PushAck(PushAck {
+                blob_id: BlobId::new(),
+                cursor: Cursor::new(1),
+            });
+            transport.queue_response(ack.to_bytes().unwrap());
+        }
+
+        let handle = client.spawn_cover_traffic().unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        let pushes = transport.sent_messages()[sent_after_connect..]
+            .iter()
+            .filter(|bytes| matches!(Message::from_bytes(bytes), Ok(Message::Push(_))))
+            .count();
+        assert!(pushes >= 2, "expected several cover pushes, got {}", pushes);
+    }
+
+    // ===========================================
+    // Secure Channel Tests
+    // ===========================================
+
+    /// Transport that plays the relay side of the secure channel.
+    #[derive(Clone)]
+    struct SecureRelayTransport {
+        state: Arc<std::sync::Mutex<SecureRelayState>>,
+    }
+
+    struct SecureRelayState {
+        key: NoiseKeypair,
+        responder: Option<zerok_sync_core::HandshakeResponder>,
+        channel: Option<SecureChannel>,
+        responses: std::collections::VecDeque<Vec<u8>>,
+        /// Raw frames as they appeared on the wire.
+        sent: Vec<Vec<u8>>,
+        /// Requests as the relay saw them after opening.
+        requests: Vec<Message>,
+    }
+
+    impl SecureRelayTransport {
+        fn new() -> Self {
+            Self {
+                state: Arc::new(std::sync::Mutex::new(SecureRelayState {
+                    key: NoiseKeypair::generate(),
+                    responder: None,
+                    channel: None,
+                    responses: Default::default(),
+                    sent: Vec::new(),
+                    requests: Vec::new(),
+                })),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for SecureRelayTransport {
+        async fn connect(&self, _address: &str) -> Result<(), TransportError> {
+            Ok(())
+        }
+
+        async fn send(&self, data: &[u8]) -> Result<(), TransportError> {
+            let mut guard = self.state.lock().unwrap();
+            let state = &mut *guard;
+            state.sent.push(data.to_vec());
+
+            let response = if let Some(channel) = state.channel.as_mut() {
+                let request = Message::from_bytes(&channel.open(data).unwrap()).unwrap();
+                let reply = match &request {
+                    Message::Push(push) => Message::PushAck(PushAck {
+                        blob_id: push.blob_id,
+                        cursor: Cursor::new(1),
+                    }),
+                    other => panic!("unexpected request {:?}", other),
+                };
+                state.requests.push(request);
+                channel.seal(&reply.to_bytes().unwrap()).unwrap()
+            } else {
+                let Ok(Message::Handshake(handshake)) = Message::from_bytes(data) else {
+                    panic!("plaintext frame before secure channel");
+                };
+                match state.responder.take() {
+                    None => {
+                        let (responder, reply) = zerok_sync_core::HandshakeResponder::respond(
+                            &state.key,
+                            &handshake.payload,
+                        )
+                        .unwrap();
+                        state.responder = Some(responder);
+                        Message::Handshake(Handshake { payload: reply })
+                            .to_bytes()
+                            .unwrap()
+                    }
+                    Some(responder) => {
+                        let (mut channel, hello) = responder.finish(&handshake.payload).unwrap();
+                        state.requests.push(Message::from_bytes(&hello).unwrap());
+                        let welcome = channel.seal(&mock_welcome(0, 0)).unwrap();
+                        state.channel = Some(channel);
+                        welcome
+                    }
+                }
+            };
+            state.responses.push_back(response);
+            Ok(())
+        }
+
+        async fn recv(&self) -> Result<Vec<u8>, TransportError> {
+            self.state
+                .lock()
+                .unwrap()
+                .responses
+                .pop_front()
+                .ok_or(TransportError::ConnectionClosed)
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn close(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn secure_channel_seals_session_traffic() {
+        let transport = SecureRelayTransport::new();
+        let config = test_config().with_secure_channel(true);
+        let expected_group_id = GroupId::from_secret(config.group_secret.as_bytes());
+        let client = SyncClient::new(config, transport.clone());
+
+        client.connect().await.unwrap();
+        let (_, cursor) = client.push(b"secret payload").await.unwrap();
+        assert_eq!(cursor, Cursor::new(1));
+
+        let state = transport.state.lock().unwrap();
+        // HELLO travelled inside the final handshake message
+        assert!(matches!(
+            &state.requests[0],
+            Message::Hello(hello) if hello.group_id == expected_group_id
+        ));
+        assert!(matches!(state.requests[1], Message::Push(_)));
+
+        // On the wire: two handshake messages, then only sealed frames
+        assert_eq!(state.sent.len(), 3);
+        assert!(matches!(
+            Message::from_bytes(&state.sent[0]),
+            Ok(Message::Handshake(_))
+        ));
+        assert!(matches!(
+            Message::from_bytes(&state.sent[1]),
+            Ok(Message::Handshake(_))
+        ));
+        assert!(Message::from_bytes(&state.sent[2]).is_err());
+    }
+
+    #[tokio::test]
+    async fn pinned_relay_key_is_verified() {
+        let transport = SecureRelayTransport::new();
+        let relay_key = *transport.state.lock().unwrap().key.public_key();
+        // Pinning alone turns the secure channel on
+        let config = test_config().with_relay_noise_key("test-node", relay_key);
+        let client = SyncClient::new(config, transport.clone());
+
+        client.connect().await.unwrap();
+        assert!(matches!(
+            transport.state.lock().unwrap().requests[0],
+            Message::Hello(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn mismatched_relay_key_fails_before_hello() {
+        let transport = SecureRelayTransport::new();
+        let config = test_config().with_relay_noise_key("test-node", [7u8; 32]);
+        let client = SyncClient::new(config, transport.clone());
+
+        let err = client.connect().await.unwrap_err();
+        assert!(err.to_string().contains("pinned key"));
+
+        // Only the first handshake message left; HELLO was never sent
+        let state = transport.state.lock().unwrap();
+        assert_eq!(state.sent.len(), 1);
+        assert!(state.requests.is_empty());
+    }
+
+    #[tokio::test]
+    async fn secure_channel_fails_against_plaintext_relay() {
+        let transport = MockTransport::new();
+        transport.queue_response(mock_welcome(0, 0));
+        let config = test_config().with_secure_channel(true);
+        let client = SyncClient::new(config, transport.clone());
+
+        let err = client.connect().await.unwrap_err();
+        assert!(err.to_string().contains("expected Handshake response"));
+
+        // HELLO was never sent in plaintext
+        let sent = transport.sent_messages();
+        assert_eq!(sent.len(), 1);
+        assert!(matches!(
+            Message::from_bytes(&sent[0]),
+            Ok(Message::Handshake(_))
+        ));
+    }
 }
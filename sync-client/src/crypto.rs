@@ -20,6 +20,7 @@ use hkdf::Hkdf;
 use sha2::Sha256;
 use thiserror::Error;
 use zeroize::{Zeroize, ZeroizeOnDrop};
+use zerok_sync_core::{check_passphrase, PassphraseError, OPRF_OUTPUT_SIZE};
 
 /// Nonce size for XChaCha20-Poly1305 (192 bits = 24 bytes).
 pub const NONCE_SIZE: usize = 24;
@@ -50,6 +51,10 @@ pub enum CryptoError {
     /// Key derivation failed.
     #[error("key derivation failed: {0}")]
     KeyDerivationFailed(String),
+
+    /// Passphrase rejected by an entropy check.
+    #[error("{0}")]
+    WeakPassphrase(#[from] PassphraseError),
 }
 
 /// Argon2id parameters for device-adaptive key derivation.
@@ -102,8 +107,22 @@ impl Argon2Params {
         self.iterations
     }
 
+    /// Get lane count.
+    pub fn parallelism(&self) -> u32 {
+        self.parallelism
+    }
+
+    /// Rebuild parameters stored alongside a salt (e.g., in a keystore file).
+    pub(crate) fn from_parts(memory_mib: u32, iterations: u32, parallelism: u32) -> Self {
+        Self {
+            memory_mib,
+            iterations,
+            parallelism,
+        }
+    }
+
     /// Convert to argon2 Params.
-    fn to_argon2_params(self) -> Result<Params, CryptoError> {
+    pub(crate) fn to_argon2_params(self) -> Result<Params, CryptoError> {
         Params::new(
             self.memory_mib * 1024, // Convert MiB to KiB
             self.iterations,
@@ -152,6 +171,19 @@ impl GroupSecret {
         (secret, salt)
     }
 
+    /// Like [`from_passphrase`](Self::from_passphrase), but rejects
+    /// passphrases with less than `min_entropy_bits` of estimated entropy.
+    ///
+    /// Use when creating a group; see `sync_core::passphrase` for the
+    /// estimate and `MIN_PASSPHRASE_ENTROPY` for a sensible minimum.
+    pub fn from_passphrase_checked(
+        passphrase: &str,
+        min_entropy_bits: f64,
+    ) -> Result<(Self, [u8; 16]), CryptoError> {
+        check_passphrase(passphrase, min_entropy_bits)?;
+        Ok(Self::from_passphrase(passphrase))
+    }
+
     /// Create a GroupSecret from a passphrase and explicit salt.
     ///
     /// Use this when the salt is known (e.g., extracted from an Invite).
@@ -178,6 +210,50 @@ impl GroupSecret {
         Self(output)
     }
 
+    /// Create a GroupSecret from a passphrase hardened by the relay's OPRF.
+    ///
+    /// `oprf_output` comes from [`crate::oprf::evaluate_passphrase`] and is
+    /// used as the Argon2id secret key, so guessing the passphrase requires
+    /// the relay's cooperation for every attempt.
+    pub fn from_passphrase_with_oprf(
+        passphrase: &str,
+        salt: &[u8],
+        oprf_output: &[u8; OPRF_OUTPUT_SIZE],
+    ) -> Self {
+        Self::from_passphrase_with_oprf_params(
+            passphrase,
+            salt,
+            oprf_output,
+            Argon2Params::default(),
+        )
+    }
+
+    /// Create an OPRF-hardened GroupSecret with custom Argon2 parameters.
+    pub fn from_passphrase_with_oprf_params(
+        passphrase: &str,
+        salt: &[u8],
+        oprf_output: &[u8; OPRF_OUTPUT_SIZE],
+        params: Argon2Params,
+    ) -> Self {
+        let argon2_params = params
+            .to_argon2_params()
+            .expect("invalid argon2 parameters");
+        let argon2 = Argon2::new_with_secret(
+            oprf_output,
+            Algorithm::Argon2id,
+            Version::V0x13,
+            argon2_params,
+        )
+        .expect("invalid argon2 secret");
+
+        let mut output = [0u8; KEY_SIZE];
+        argon2
+            .hash_password_into(passphrase.as_bytes(), salt, &mut output)
+            .expect("argon2 hash failed");
+
+        Self(output)
+    }
+
     /// Create a GroupSecret from raw bytes (pre-derived).
     pub fn from_raw(bytes: [u8; KEY_SIZE]) -> Self {
         Self(bytes)
@@ -491,6 +567,23 @@ mod tests {
         assert_ne!(secret1.as_bytes(), secret2.as_bytes());
     }
 
+    #[test]
+    fn oprf_output_changes_secret() {
+        let params = Argon2Params::for_ram_mb(1500);
+        let salt = b"0123456789abcdef";
+        let plain = GroupSecret::from_passphrase_with_params("passphrase", salt, params);
+        let hardened_a =
+            GroupSecret::from_passphrase_with_oprf_params("passphrase", salt, &[1u8; 64], params);
+        let hardened_b =
+            GroupSecret::from_passphrase_with_oprf_params("passphrase", salt, &[2u8; 64], params);
+        let again =
+            GroupSecret::from_passphrase_with_oprf_params("passphrase", salt, &[1u8; 64], params);
+
+        assert_ne!(plain.as_bytes(), hardened_a.as_bytes());
+        assert_ne!(hardened_a.as_bytes(), hardened_b.as_bytes());
+        assert_eq!(hardened_a.as_bytes(), again.as_bytes());
+    }
+
     #[test]
     fn from_passphrase_returns_salt() {
         let (secret, salt) = GroupSecret::from_passphrase("test-passphrase");
@@ -500,6 +593,17 @@ mod tests {
         assert_eq!(secret.as_bytes(), secret2.as_bytes());
     }
 
+    #[test]
+    fn from_passphrase_checked_enforces_entropy() {
+        let result = GroupSecret::from_passphrase_checked("password", 48.0);
+        assert!(matches!(result, Err(CryptoError::WeakPassphrase(_))));
+
+        let passphrase = zerok_sync_core::PassphraseGenerator::new().generate();
+        let (secret, salt) = GroupSecret::from_passphrase_checked(&passphrase, 48.0).unwrap();
+        let again = GroupSecret::from_passphrase_with_salt(&passphrase, &salt);
+        assert_eq!(secret.as_bytes(), again.as_bytes());
+    }
+
     #[test]
     fn group_secret_debug_is_redacted() {
         let secret = GroupSecret::random();
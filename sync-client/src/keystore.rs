@@ -0,0 +1,609 @@
+//! Encrypted local keystore for group secrets and device keys.
+//!
+//! Secrets that would otherwise sit on disk in plaintext (group secrets,
+//! device keys) are sealed under a local master key. The master key is
+//! either derived from a passphrase with Argon2id or supplied directly by
+//! the application (e.g., from a platform keychain).
+//!
+//! # File Format
+//!
+//! All integers are little-endian.
+//!
+//! ```text
+//! magic "0KKS" | version u8 | kdf u8
+//! [kdf = 1: salt (16) | memory_mib u32 | iterations u32 | parallelism u32]
+//! verifier: nonce (24) | tag (16)
+//! count u32
+//! entries: name_len u16 | name | sealed_len u32 | nonce (24) | ciphertext
+//! ```
+//!
+//! The verifier seals an empty message with the header as associated data,
+//! so a wrong key or tampered KDF parameters fail before any entry is
+//! touched. Each entry is sealed with its name as associated data, so
+//! entries cannot be swapped between names.
+//!
+//! # Security Notes
+//!
+//! - Entries are XChaCha20-Poly1305 with fresh 192-bit random nonces
+//! - The master key is zeroized on drop; [`Keystore::get`] returns
+//!   [`Zeroizing`] buffers
+//! - Entry names are stored in plaintext
+
+use std::collections::BTreeMap;
+
+use argon2::{Algorithm, Argon2, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::crypto::{Argon2Params, GroupSecret, KEY_SIZE, NONCE_SIZE};
+
+/// Keystore file magic.
+const MAGIC: &[u8; 4] = b"0KKS";
+
+/// Current keystore format version.
+pub const KEYSTORE_VERSION: u8 = 1;
+
+/// Argon2id salt size in keystore files.
+const SALT_SIZE: usize = 16;
+
+/// Poly1305 tag size.
+const TAG_SIZE: usize = 16;
+
+/// Upper bound on Argon2id memory accepted from a keystore file (MiB).
+///
+/// Stops a tampered file from making `open_with_passphrase` allocate
+/// arbitrary amounts of memory before the verifier is checked.
+const MAX_KDF_MEMORY_MIB: u32 = 1024;
+
+/// Upper bound on Argon2id iterations accepted from a keystore file.
+const MAX_KDF_ITERATIONS: u32 = 16;
+
+/// Associated-data prefix for entries.
+const ENTRY_AAD_PREFIX: &[u8] = b"0k-sync keystore entry v1:";
+
+/// Entry name prefix for group secrets.
+const GROUP_SECRET_PREFIX: &str = "group:";
+
+/// KDF identifier: master key supplied by the caller.
+const KDF_RAW_KEY: u8 = 0;
+
+/// KDF identifier: master key derived from a passphrase with Argon2id.
+const KDF_ARGON2ID: u8 = 1;
+
+/// Keystore errors.
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    /// Keystore bytes are truncated or malformed.
+    #[error("invalid keystore: {0}")]
+    InvalidFormat(String),
+
+    /// Keystore was written by a newer version.
+    #[error("unsupported keystore version: {0}")]
+    UnsupportedVersion(u8),
+
+    /// The passphrase or key does not open this keystore.
+    #[error("wrong keystore passphrase or key")]
+    WrongKey,
+
+    /// Opened with a passphrase but the keystore uses a raw key, or vice versa.
+    #[error("keystore expects a {expected}")]
+    KeyTypeMismatch {
+        /// What the keystore was created with.
+        expected: &'static str,
+    },
+
+    /// An entry failed authentication.
+    #[error("keystore entry corrupted: {0}")]
+    CorruptEntry(String),
+
+    /// Entry name is empty or too long.
+    #[error("invalid keystore entry name: {0:?}")]
+    InvalidName(String),
+
+    /// Master key derivation failed.
+    #[error("key derivation failed: {0}")]
+    KeyDerivationFailed(String),
+}
+
+/// Local master key. Zeroized on drop.
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct MasterKey([u8; KEY_SIZE]);
+
+impl MasterKey {
+    fn derive(passphrase: &str, salt: &[u8], params: Argon2Params) -> Result<Self, KeystoreError> {
+        let argon2_params = params
+            .to_argon2_params()
+            .map_err(|e| KeystoreError::KeyDerivationFailed(e.to_string()))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params);
+
+        let mut key = [0u8; KEY_SIZE];
+        argon2
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| KeystoreError::KeyDerivationFailed(e.to_string()))?;
+        Ok(Self(key))
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new((&self.0).into())
+    }
+
+    fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        getrandom::getrandom(&mut nonce).expect("getrandom failed");
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("aead encrypt failed");
+
+        let mut sealed = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    fn open(&self, sealed: &[u8], aad: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+        if sealed.len() < NONCE_SIZE + TAG_SIZE {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+        self.cipher()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .ok()
+            .map(Zeroizing::new)
+    }
+}
+
+/// How the master key is obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kdf {
+    RawKey,
+    Argon2id {
+        salt: [u8; SALT_SIZE],
+        params: Argon2Params,
+    },
+}
+
+impl Kdf {
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(6 + SALT_SIZE + 12);
+        header.extend_from_slice(MAGIC);
+        header.push(KEYSTORE_VERSION);
+        match self {
+            Kdf::RawKey => header.push(KDF_RAW_KEY),
+            Kdf::Argon2id { salt, params } => {
+                header.push(KDF_ARGON2ID);
+                header.extend_from_slice(salt);
+                header.extend_from_slice(&params.memory_mib().to_le_bytes());
+                header.extend_from_slice(&params.iterations().to_le_bytes());
+                header.extend_from_slice(&params.parallelism().to_le_bytes());
+            }
+        }
+        header
+    }
+}
+
+/// Encrypted store of named secrets.
+///
+/// Entries stay sealed in memory; they are only decrypted by
+/// [`get`](Self::get). Persist with [`to_bytes`](Self::to_bytes) and reload
+/// with [`open_with_passphrase`](Self::open_with_passphrase) or
+/// [`open_with_key`](Self::open_with_key).
+pub struct Keystore {
+    kdf: Kdf,
+    key: MasterKey,
+    verifier: Vec<u8>,
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl Keystore {
+    /// Create an empty keystore whose master key is derived from `passphrase`.
+    ///
+    /// Uses device-adaptive Argon2id parameters and a random salt.
+    pub fn create_with_passphrase(passphrase: &str) -> Result<Self, KeystoreError> {
+        Self::create_with_passphrase_and_params(passphrase, Argon2Params::default())
+    }
+
+    /// Create an empty passphrase keystore with explicit Argon2id parameters.
+    pub fn create_with_passphrase_and_params(
+        passphrase: &str,
+        params: Argon2Params,
+    ) -> Result<Self, KeystoreError> {
+        let mut salt = [0u8; SALT_SIZE];
+        getrandom::getrandom(&mut salt).expect("getrandom failed");
+        let key = MasterKey::derive(passphrase, &salt, params)?;
+        Ok(Self::create(Kdf::Argon2id { salt, params }, key))
+    }
+
+    /// Create an empty keystore under a caller-supplied 32-byte master key.
+    pub fn create_with_key(key: &[u8; KEY_SIZE]) -> Self {
+        Self::create(Kdf::RawKey, MasterKey(*key))
+    }
+
+    fn create(kdf: Kdf, key: MasterKey) -> Self {
+        let verifier = key.seal(&[], &kdf.header());
+        Self {
+            kdf,
+            key,
+            verifier,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Open a keystore created with a passphrase.
+    pub fn open_with_passphrase(bytes: &[u8], passphrase: &str) -> Result<Self, KeystoreError> {
+        let (kdf, rest) = parse_header(bytes)?;
+        let Kdf::Argon2id { salt, params } = kdf else {
+            return Err(KeystoreError::KeyTypeMismatch {
+                expected: "raw key",
+            });
+        };
+        let key = MasterKey::derive(passphrase, &salt, params)?;
+        Self::open(kdf, key, rest)
+    }
+
+    /// Open a keystore created with a raw master key.
+    pub fn open_with_key(bytes: &[u8], key: &[u8; KEY_SIZE]) -> Result<Self, KeystoreError> {
+        let (kdf, rest) = parse_header(bytes)?;
+        if kdf != Kdf::RawKey {
+            return Err(KeystoreError::KeyTypeMismatch {
+                expected: "passphrase",
+            });
+        }
+        Self::open(kdf, MasterKey(*key), rest)
+    }
+
+    fn open(kdf: Kdf, key: MasterKey, body: &[u8]) -> Result<Self, KeystoreError> {
+        let mut reader = Reader(body);
+        let verifier = reader.take(NONCE_SIZE + TAG_SIZE)?.to_vec();
+        if key.open(&verifier, &kdf.header()).is_none() {
+            return Err(KeystoreError::WrongKey);
+        }
+
+        let count = reader.u32()?;
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let name_len = reader.u16()? as usize;
+            let name = std::str::from_utf8(reader.take(name_len)?)
+                .map_err(|_| KeystoreError::InvalidFormat("entry name not UTF-8".into()))?
+                .to_string();
+            let sealed_len = reader.u32()? as usize;
+            let sealed = reader.take(sealed_len)?.to_vec();
+            if entries.insert(name.clone(), sealed).is_some() {
+                return Err(KeystoreError::InvalidFormat(format!(
+                    "duplicate entry {:?}",
+                    name
+                )));
+            }
+        }
+        if !reader.0.is_empty() {
+            return Err(KeystoreError::InvalidFormat("trailing bytes".into()));
+        }
+
+        Ok(Self {
+            kdf,
+            key,
+            verifier,
+            entries,
+        })
+    }
+
+    /// Store `secret` under `name`, replacing any existing entry.
+    pub fn insert(&mut self, name: &str, secret: &[u8]) -> Result<(), KeystoreError> {
+        if name.is_empty() || name.len() > u16::MAX as usize {
+            return Err(KeystoreError::InvalidName(name.to_string()));
+        }
+        let sealed = self.key.seal(secret, &entry_aad(name));
+        self.entries.insert(name.to_string(), sealed);
+        Ok(())
+    }
+
+    /// Decrypt the entry stored under `name`.
+    pub fn get(&self, name: &str) -> Result<Option<Zeroizing<Vec<u8>>>, KeystoreError> {
+        let Some(sealed) = self.entries.get(name) else {
+            return Ok(None);
+        };
+        self.key
+            .open(sealed, &entry_aad(name))
+            .map(Some)
+            .ok_or_else(|| KeystoreError::CorruptEntry(name.to_string()))
+    }
+
+    /// Remove the entry stored under `name`. Returns whether it existed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    /// Whether an entry is stored under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Entry names, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the keystore has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Store a group secret under its group ID.
+    pub fn insert_group_secret(
+        &mut self,
+        group_id: &str,
+        secret: &GroupSecret,
+    ) -> Result<(), KeystoreError> {
+        self.insert(&group_secret_name(group_id), secret.as_bytes())
+    }
+
+    /// Load the group secret stored under `group_id`.
+    pub fn group_secret(&self, group_id: &str) -> Result<Option<GroupSecret>, KeystoreError> {
+        let name = group_secret_name(group_id);
+        let Some(bytes) = self.get(&name)? else {
+            return Ok(None);
+        };
+        let raw: [u8; KEY_SIZE] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| KeystoreError::CorruptEntry(name))?;
+        Ok(Some(GroupSecret::from_raw(raw)))
+    }
+
+    /// Serialize the keystore. Entries remain encrypted.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.kdf.header();
+        out.extend_from_slice(&self.verifier);
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (name, sealed) in &self.entries {
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&(sealed.len() as u32).to_le_bytes());
+            out.extend_from_slice(sealed);
+        }
+        out
+    }
+}
+
+// Don't leak the master key in debug output
+impl std::fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keystore")
+            .field("kdf", &self.kdf)
+            .field("key", &"[REDACTED]")
+            .field("entries", &self.entries.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+fn entry_aad(name: &str) -> Vec<u8> {
+    [ENTRY_AAD_PREFIX, name.as_bytes()].concat()
+}
+
+fn group_secret_name(group_id: &str) -> String {
+    format!("{}{}", GROUP_SECRET_PREFIX, group_id)
+}
+
+/// Parse magic, version and KDF parameters; return the remaining bytes.
+fn parse_header(bytes: &[u8]) -> Result<(Kdf, &[u8]), KeystoreError> {
+    let mut reader = Reader(bytes);
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(KeystoreError::InvalidFormat("bad magic".into()));
+    }
+    let version = reader.u8()?;
+    if version != KEYSTORE_VERSION {
+        return Err(KeystoreError::UnsupportedVersion(version));
+    }
+
+    let kdf = match reader.u8()? {
+        KDF_RAW_KEY => Kdf::RawKey,
+        KDF_ARGON2ID => {
+            let salt: [u8; SALT_SIZE] = reader.take(SALT_SIZE)?.try_into().expect("length");
+            let memory_mib = reader.u32()?;
+            let iterations = reader.u32()?;
+            let parallelism = reader.u32()?;
+            if memory_mib > MAX_KDF_MEMORY_MIB || iterations > MAX_KDF_ITERATIONS {
+                return Err(KeystoreError::InvalidFormat(
+                    "KDF parameters out of range".into(),
+                ));
+            }
+            Kdf::Argon2id {
+                salt,
+                params: Argon2Params::from_parts(memory_mib, iterations, parallelism),
+            }
+        }
+        other => {
+            return Err(KeystoreError::InvalidFormat(format!(
+                "unknown KDF {}",
+                other
+            )))
+        }
+    };
+    Ok((kdf, reader.0))
+}
+
+/// Bounds-checked cursor over keystore bytes.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], KeystoreError> {
+        if self.0.len() < n {
+            return Err(KeystoreError::InvalidFormat("truncated".into()));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, KeystoreError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, KeystoreError> {
+        Ok(u16::from_le_bytes(
+            self.take(2)?.try_into().expect("length"),
+        ))
+    }
+
+    fn u32(&mut self) -> Result<u32, KeystoreError> {
+        Ok(u32::from_le_bytes(
+            self.take(4)?.try_into().expect("length"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_params() -> Argon2Params {
+        Argon2Params::for_ram_mb(1500)
+    }
+
+    #[test]
+    fn passphrase_keystore_roundtrip() {
+        let mut store =
+            Keystore::create_with_passphrase_and_params("local master", fast_params()).unwrap();
+        store.insert("device:key", &[7u8; 32]).unwrap();
+        let secret = GroupSecret::random();
+        store.insert_group_secret("group-a", &secret).unwrap();
+
+        let bytes = store.to_bytes();
+        let reopened = Keystore::open_with_passphrase(&bytes, "local master").unwrap();
+
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(
+            reopened.names().collect::<Vec<_>>(),
+            vec!["device:key", "group:group-a"]
+        );
+        assert_eq!(
+            reopened.get("device:key").unwrap().unwrap().as_slice(),
+            &[7u8; 32]
+        );
+        assert_eq!(
+            reopened
+                .group_secret("group-a")
+                .unwrap()
+                .unwrap()
+                .as_bytes(),
+            secret.as_bytes()
+        );
+        assert!(reopened.get("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn wrong_passphrase_rejected() {
+        let store =
+            Keystore::create_with_passphrase_and_params("local master", fast_params()).unwrap();
+        let err = Keystore::open_with_passphrase(&store.to_bytes(), "guess").unwrap_err();
+        assert!(matches!(err, KeystoreError::WrongKey));
+    }
+
+    #[test]
+    fn raw_key_keystore_roundtrip() {
+        let key = [3u8; KEY_SIZE];
+        let mut store = Keystore::create_with_key(&key);
+        store.insert("secret", b"value").unwrap();
+        let bytes = store.to_bytes();
+
+        let reopened = Keystore::open_with_key(&bytes, &key).unwrap();
+        assert_eq!(
+            reopened.get("secret").unwrap().unwrap().as_slice(),
+            b"value"
+        );
+
+        assert!(matches!(
+            Keystore::open_with_key(&bytes, &[4u8; KEY_SIZE]),
+            Err(KeystoreError::WrongKey)
+        ));
+        assert!(matches!(
+            Keystore::open_with_passphrase(&bytes, "anything"),
+            Err(KeystoreError::KeyTypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn entries_are_encrypted_at_rest() {
+        let mut store = Keystore::create_with_key(&[1u8; KEY_SIZE]);
+        store.insert("plain", b"find-me-if-you-can").unwrap();
+        let bytes = store.to_bytes();
+        assert!(!bytes
+            .windows(b"find-me-if-you-can".len())
+            .any(|w| w == b"find-me-if-you-can"));
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let key = [5u8; KEY_SIZE];
+        let mut store = Keystore::create_with_key(&key);
+        store.insert("a", b"secret").unwrap();
+        let mut bytes = store.to_bytes();
+
+        // Flip the last ciphertext byte of the only entry
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let reopened = Keystore::open_with_key(&bytes, &key).unwrap();
+        assert!(matches!(
+            reopened.get("a"),
+            Err(KeystoreError::CorruptEntry(_))
+        ));
+
+        // Truncation and bad versions fail cleanly
+        assert!(matches!(
+            Keystore::open_with_key(&bytes[..bytes.len() - 3], &key),
+            Err(KeystoreError::InvalidFormat(_))
+        ));
+        let mut bytes = store.to_bytes();
+        bytes[4] = 99;
+        assert!(matches!(
+            Keystore::open_with_key(&bytes, &key),
+            Err(KeystoreError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn insert_replaces_and_remove_deletes() {
+        let mut store = Keystore::create_with_key(&[9u8; KEY_SIZE]);
+        store.insert("k", b"one").unwrap();
+        store.insert("k", b"two").unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get("k").unwrap().unwrap().as_slice(), b"two");
+
+        assert!(store.remove("k"));
+        assert!(!store.remove("k"));
+        assert!(store.is_empty());
+        assert!(matches!(
+            store.insert("", b"x"),
+            Err(KeystoreError::InvalidName(_))
+        ));
+    }
+
+    #[test]
+    fn debug_redacts_master_key() {
+        let store = Keystore::create_with_key(&[0xAB; KEY_SIZE]);
+        let debug = format!("{:?}", store);
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("171"));
+    }
+}
@@ -8,8 +8,12 @@
 //!
 //! - **E2E Encryption**: XChaCha20-Poly1305 with 192-bit nonces
 //! - **Device-Adaptive Key Derivation**: Argon2id scales with available RAM
+//! - **Passphrase Hardening**: Optional relay-assisted OPRF before Argon2id
+//! - **Encrypted Keystore**: Group secrets and device keys sealed under a local master key
 //! - **Transport Abstraction**: Pluggable transport layer (iroh, mock)
 //! - **Pure State Machine**: Uses sync-core for side-effect-free logic
+//! - **Tracing**: `tracing` spans per operation; trace IDs propagated to the relay
+//!   (`otel` feature links them to OpenTelemetry spans)
 //!
 //! ## Example
 //!
@@ -31,11 +35,22 @@
 
 pub mod client;
 pub mod crypto;
+pub mod keystore;
+pub mod mailbox;
+pub mod oprf;
+mod telemetry;
 pub mod transport;
 
-pub use client::{ClientError, ReceivedBlob, SyncClient, SyncConfig};
+pub use client::{ClientError, ReceivedBlob, RelayEvent, SyncClient, SyncConfig};
 pub use crypto::{Argon2Params, CryptoError, GroupKey, GroupSecret, KEY_SIZE, NONCE_SIZE};
+pub use keystore::{Keystore, KeystoreError, KEYSTORE_VERSION};
+pub use mailbox::{MailboxClient, DEFAULT_MAILBOX_POLL_INTERVAL};
+pub use oprf::evaluate_passphrase;
 pub use transport::{
     IrohTransport, IrohTransportConfig, MockTransport, Transport, TransportError, ALPN,
     MAX_MESSAGE_SIZE,
 };
+pub use zerok_sync_core::{
+    ChannelError, Compression, CoverTrafficConfig, ExchangeInvite, PaddingPolicy, PairingCode,
+    PairingError, PassphraseGenerator, PassphraseStrength, MIN_PASSPHRASE_ENTROPY,
+};
@@ -0,0 +1,570 @@
+//! Relay mailboxes and the post-quantum invite exchange.
+//!
+//! [`MailboxClient`] talks to a relay without joining a group (no HELLO),
+//! which is what a joiner needs before it has the group secret. It drives
+//! the invite exchange from `sync_core::exchange` on both sides:
+//!
+//! - The inviter calls [`MailboxClient::offer_invite`] and waits for a joiner.
+//! - The joiner calls [`MailboxClient::accept_invite`] with the
+//!   [`ExchangeInvite`] it received out of band.
+//!
+//! Mailboxes are polled; both sides give up when the exchange invite expires.
+//!
+//! It also carries short code pairing: [`MailboxClient::publish_short_code`]
+//! leaves the invite, sealed under the code's decrypt half, in the mailbox
+//! addressed by the lookup half; [`MailboxClient::fetch_short_code`] takes
+//! it (single use) and decrypts it.
+//!
+//! Pairing codes work the same way as the exchange, but both devices type
+//! a short [`PairingCode`] instead of sharing an exchange invite:
+//! [`MailboxClient::offer_invite_with_code`] and
+//! [`MailboxClient::accept_invite_with_code`] run the CPace PAKE from
+//! `sync_core::pake` and give up after [`DEFAULT_INVITE_TTL`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let exchange = ExchangeInvite::from_qr_payload(&scanned)?;
+//! let mailbox = MailboxClient::new(IrohTransport::new().await?);
+//! mailbox.connect(&exchange.primary_relay().unwrap().to_string()).await?;
+//! let invite = mailbox.accept_invite(&exchange).await?;
+//! ```
+
+use std::time::{Duration, Instant};
+use zerok_sync_core::{
+    CodeJoin, CodeOffer, ExchangeInvite, Invite, InviteOffer, JoinRequest, PairingCode,
+    PairingError, DEFAULT_INVITE_TTL,
+};
+use zerok_sync_types::{MailboxAck, MailboxData, MailboxGet, MailboxPut, Message};
+
+use crate::client::{answer_challenge, ClientError};
+use crate::transport::Transport;
+
+/// Default delay between mailbox polls.
+pub const DEFAULT_MAILBOX_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Client for relay mailboxes (no group membership required).
+pub struct MailboxClient<T: Transport> {
+    transport: T,
+    poll_interval: Duration,
+}
+
+impl<T: Transport> MailboxClient<T> {
+    /// Create a mailbox client over the given transport.
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            poll_interval: DEFAULT_MAILBOX_POLL_INTERVAL,
+        }
+    }
+
+    /// Set the delay between mailbox polls (builder pattern).
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Connect to a relay. No HELLO is sent.
+    pub async fn connect(&self, address: &str) -> Result<(), ClientError> {
+        self.transport
+            .connect(address)
+            .await
+            .map_err(|e| ClientError::ConnectionFailed(e.to_string()))
+    }
+
+    /// Close the relay connection.
+    pub async fn close(&self) -> Result<(), ClientError> {
+        self.transport.close().await?;
+        Ok(())
+    }
+
+    /// Leave a message in a mailbox.
+    ///
+    /// `ttl` is in seconds (0 = relay maximum).
+    pub async fn put(
+        &self,
+        mailbox: [u8; 32],
+        payload: Vec<u8>,
+        ttl: u32,
+    ) -> Result<(), ClientError> {
+        let request = Message::MailboxPut(MailboxPut {
+            mailbox,
+            payload,
+            ttl,
+        });
+        match self.request(&request).await? {
+            Message::MailboxAck(MailboxAck { mailbox: acked }) if acked == mailbox => Ok(()),
+            _ => Err(ClientError::Protocol(
+                "unexpected response to mailbox put".into(),
+            )),
+        }
+    }
+
+    /// Take the oldest message from a mailbox, if any.
+    pub async fn get(&self, mailbox: [u8; 32]) -> Result<Option<Vec<u8>>, ClientError> {
+        let request = Message::MailboxGet(MailboxGet { mailbox });
+        match self.request(&request).await? {
+            Message::MailboxData(MailboxData {
+                mailbox: returned,
+                payload,
+            }) if returned == mailbox => Ok(payload),
+            _ => Err(ClientError::Protocol(
+                "unexpected response to mailbox get".into(),
+            )),
+        }
+    }
+
+    /// Join a group: fetch the invite from the inviter through the relay.
+    ///
+    /// Runs the hybrid X25519 + ML-KEM-768 exchange; the returned invite
+    /// was never visible to the relay or to the channel `exchange` was
+    /// shared over.
+    pub async fn accept_invite(&self, exchange: &ExchangeInvite) -> Result<Invite, ClientError> {
+        let ttl = exchange_ttl(exchange);
+
+        let (request, first) = JoinRequest::start(exchange)?;
+        self.put(exchange.inviter_mailbox(), first, ttl).await?;
+
+        let reply = self
+            .wait_for(exchange.joiner_mailbox(), || exchange.is_expired())
+            .await?;
+        let (pending, last) = request.confirm(&reply)?;
+        self.put(exchange.inviter_mailbox(), last, ttl).await?;
+
+        let sealed = self
+            .wait_for(exchange.joiner_mailbox(), || exchange.is_expired())
+            .await?;
+        Ok(pending.receive(&sealed)?)
+    }
+
+    /// Hand `invite` to the joiner holding `exchange`.
+    ///
+    /// Waits for the joiner's first message, so this returns once the
+    /// invite has been delivered to the relay (or the exchange expires).
+    pub async fn offer_invite(
+        &self,
+        exchange: &ExchangeInvite,
+        invite: &Invite,
+    ) -> Result<(), ClientError> {
+        let ttl = exchange_ttl(exchange);
+
+        let first = self
+            .wait_for(exchange.inviter_mailbox(), || exchange.is_expired())
+            .await?;
+        let (offer, reply) = InviteOffer::respond(exchange, &first)?;
+        self.put(exchange.joiner_mailbox(), reply, ttl).await?;
+
+        let last = self
+            .wait_for(exchange.inviter_mailbox(), || exchange.is_expired())
+            .await?;
+        let sealed = offer.deliver(&last, invite)?;
+        self.put(exchange.joiner_mailbox(), sealed, ttl).await
+    }
+
+    /// Hand `invite` to the device that types the same pairing `code`.
+    ///
+    /// Returns once the invite has been delivered to the relay. Fails with
+    /// [`PairingError::CodeMismatch`] if the other device used a different
+    /// code (or someone guessed); pairing must then restart with a new code.
+    pub async fn offer_invite_with_code(
+        &self,
+        code: &PairingCode,
+        invite: &Invite,
+    ) -> Result<(), ClientError> {
+        let deadline = Instant::now() + DEFAULT_INVITE_TTL;
+        let ttl = DEFAULT_INVITE_TTL.as_secs() as u32;
+
+        let (offer, share) = CodeOffer::start(code);
+        self.put(code.joiner_mailbox(), share, ttl).await?;
+
+        let reply = self
+            .wait_for(code.inviter_mailbox(), || Instant::now() >= deadline)
+            .await?;
+        let sealed = offer.deliver(&reply, invite)?;
+        self.put(code.joiner_mailbox(), sealed, ttl).await
+    }
+
+    /// Join a group: fetch the invite from the device showing `code`.
+    pub async fn accept_invite_with_code(&self, code: &PairingCode) -> Result<Invite, ClientError> {
+        let deadline = Instant::now() + DEFAULT_INVITE_TTL;
+        let ttl = DEFAULT_INVITE_TTL.as_secs() as u32;
+
+        let share = self
+            .wait_for(code.joiner_mailbox(), || Instant::now() >= deadline)
+            .await?;
+        let (join, reply) = CodeJoin::respond(code, &share)?;
+        self.put(code.inviter_mailbox(), reply, ttl).await?;
+
+        let sealed = self
+            .wait_for(code.joiner_mailbox(), || Instant::now() >= deadline)
+            .await?;
+        Ok(join.receive(&sealed)?)
+    }
+
+    /// Publish `invite` on the relay for short code pairing.
+    ///
+    /// Returns the short code to share. The sealed invite expires with the
+    /// invite and is deleted when the joiner fetches it.
+    pub async fn publish_short_code(&self, invite: &Invite) -> Result<String, ClientError> {
+        if invite.is_expired() {
+            return Err(PairingError::Expired.into());
+        }
+        let code = invite.to_short_code();
+        let mailbox = Invite::short_code_mailbox(&code)?;
+        self.put(mailbox, invite.seal_for_short_code(), invite_ttl(invite))
+            .await?;
+        Ok(code)
+    }
+
+    /// Fetch and decrypt the invite published under `code`.
+    ///
+    /// Fails with [`PairingError::Expired`] if there is no invite (it was
+    /// never published, already claimed, or has expired).
+    pub async fn fetch_short_code(&self, code: &str) -> Result<Invite, ClientError> {
+        let mailbox = Invite::short_code_mailbox(code)?;
+        let sealed = self.get(mailbox).await?.ok_or(PairingError::Expired)?;
+        Ok(Invite::open_short_code(code, &sealed)?)
+    }
+
+    /// Internal: poll a mailbox until a message arrives or `expired` says stop.
+    async fn wait_for(
+        &self,
+        mailbox: [u8; 32],
+        expired: impl Fn() -> bool,
+    ) -> Result<Vec<u8>, ClientError> {
+        loop {
+            if let Some(message) = self.get(mailbox).await? {
+                return Ok(message);
+            }
+            if expired() {
+                return Err(PairingError::Expired.into());
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Internal: send one request and decode its response.
+    async fn request(&self, request: &Message) -> Result<Message, ClientError> {
+        let bytes = request
+            .to_bytes()
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+        self.transport.send(&bytes).await?;
+        let response = self.transport.recv().await?;
+        let response = Message::from_bytes(&response)
+            .map_err(|e| ClientError::Serialization(e.to_string()))?;
+        answer_challenge(&self.transport, response).await
+    }
+}
+
+/// Mailbox TTL covering the rest of the exchange (at least one second).
+fn exchange_ttl(exchange: &ExchangeInvite) -> u32 {
+    exchange.remaining_secs().clamp(1, u32::MAX as u64) as u32
+}
+
+/// Mailbox TTL covering the rest of the invite's lifetime (at least one second).
+fn invite_ttl(invite: &Invite) -> u32 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    invite
+        .expires_at
+        .saturating_sub(now)
+        .clamp(1, u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{MockTransport, TransportError};
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::{Arc, Mutex};
+    use zerok_sync_core::{GroupSecret, RelayNodeId};
+
+    type Mailboxes = HashMap<[u8; 32], VecDeque<Vec<u8>>>;
+
+    /// In-memory relay answering mailbox requests.
+    #[derive(Clone, Default)]
+    struct MailboxRelay {
+        mailboxes: Arc<Mutex<Mailboxes>>,
+        responses: Arc<Mutex<VecDeque<Vec<u8>>>>,
+        /// Every payload stored, as the relay saw it.
+        puts: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl MailboxRelay {
+        /// A second connection to the same relay (shared mailboxes).
+        fn connection(&self) -> Self {
+            Self {
+                mailboxes: self.mailboxes.clone(),
+                responses: Default::default(),
+                puts: self.puts.clone(),
+            }
+        }
+    }
+
+    fn pair_of_clients(
+        relay: &MailboxRelay,
+    ) -> (MailboxClient<MailboxRelay>, MailboxClient<MailboxRelay>) {
+        let poll = Duration::from_millis(5);
+        (
+            MailboxClient::new(relay.connection()).with_poll_interval(poll),
+            MailboxClient::new(relay.connection()).with_poll_interval(poll),
+        )
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for MailboxRelay {
+        async fn connect(&self, _address: &str) -> Result<(), TransportError> {
+            Ok(())
+        }
+
+        async fn send(&self, data: &[u8]) -> Result<(), TransportError> {
+            let mut mailboxes = self.mailboxes.lock().unwrap();
+            let response = match Message::from_bytes(data).unwrap() {
+                Message::MailboxPut(put) => {
+                    self.puts.lock().unwrap().push(put.payload.clone());
+                    mailboxes
+                        .entry(put.mailbox)
+                        .or_default()
+                        .push_back(put.payload);
+                    Message::MailboxAck(MailboxAck {
+                        mailbox: put.mailbox,
+                    })
+                }
+                Message::MailboxGet(get) => Message::MailboxData(MailboxData {
+                    mailbox: get.mailbox,
+                    payload: mailboxes
+                        .get_mut(&get.mailbox)
+                        .and_then(|queue| queue.pop_front()),
+                }),
+                other => panic!("unexpected request {:?}", other),
+            };
+            self.responses
+                .lock()
+                .unwrap()
+                .push_back(response.to_bytes().unwrap());
+            Ok(())
+        }
+
+        async fn recv(&self) -> Result<Vec<u8>, TransportError> {
+            self.responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or(TransportError::ConnectionClosed)
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn close(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+    }
+
+    fn group_invite() -> Invite {
+        let secret = GroupSecret::random();
+        let group_id = secret.derive_group_id();
+        Invite::create(
+            RelayNodeId::from_bytes([7u8; 32]),
+            group_id,
+            secret,
+            b"test-salt-00000!".to_vec(),
+        )
+    }
+
+    #[tokio::test]
+    async fn invite_exchange_through_mailboxes() {
+        let relay = MailboxRelay::default();
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::from_bytes([7u8; 32])]);
+        let invite = group_invite();
+
+        let (inviter, joiner) = pair_of_clients(&relay);
+        let (offered, accepted) = tokio::join!(
+            inviter.offer_invite(&exchange, &invite),
+            joiner.accept_invite(&exchange)
+        );
+        offered.unwrap();
+        let received = accepted.unwrap();
+
+        assert_eq!(received.group_id, invite.group_id);
+        assert_eq!(received.group_secret, invite.group_secret);
+
+        // Every message was consumed; nothing left behind on the relay
+        assert!(relay
+            .mailboxes
+            .lock()
+            .unwrap()
+            .values()
+            .all(VecDeque::is_empty));
+    }
+
+    #[tokio::test]
+    async fn relay_never_sees_group_secret() {
+        let relay = MailboxRelay::default();
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::from_bytes([7u8; 32])]);
+        let invite = group_invite();
+
+        let (inviter, joiner) = pair_of_clients(&relay);
+        let (offered, accepted) = tokio::join!(
+            inviter.offer_invite(&exchange, &invite),
+            joiner.accept_invite(&exchange)
+        );
+        offered.unwrap();
+        accepted.unwrap();
+
+        let puts = relay.puts.lock().unwrap();
+        assert_eq!(puts.len(), 4);
+        let secret = invite.group_secret.as_bytes();
+        let exchange_secret = exchange.exchange_secret.as_bytes();
+        for payload in puts.iter() {
+            assert!(!payload.windows(32).any(|w| w == secret));
+            assert!(!payload.windows(32).any(|w| w == exchange_secret));
+        }
+    }
+
+    #[tokio::test]
+    async fn accept_invite_gives_up_when_exchange_expires() {
+        let exchange = ExchangeInvite::create_with_ttl(
+            vec![RelayNodeId::from_bytes([7u8; 32])],
+            Duration::from_secs(1),
+        );
+        let joiner = MailboxClient::new(MailboxRelay::default())
+            .with_poll_interval(Duration::from_millis(50));
+
+        let result = joiner.accept_invite(&exchange).await;
+        assert!(matches!(
+            result,
+            Err(ClientError::Pairing(PairingError::Expired))
+        ));
+    }
+
+    #[tokio::test]
+    async fn short_code_pairing_through_mailbox() {
+        let relay = MailboxRelay::default();
+        let invite = group_invite();
+        let (inviter, joiner) = pair_of_clients(&relay);
+
+        let code = inviter.publish_short_code(&invite).await.unwrap();
+        assert_eq!(code, invite.to_short_code());
+
+        let received = joiner.fetch_short_code(&code).await.unwrap();
+        assert_eq!(received.group_id, invite.group_id);
+        assert_eq!(received.group_secret, invite.group_secret);
+
+        // Single use: a second fetch finds nothing
+        let again = joiner.fetch_short_code(&code).await;
+        assert!(matches!(
+            again,
+            Err(ClientError::Pairing(PairingError::Expired))
+        ));
+
+        // The relay only ever saw the sealed invite
+        let puts = relay.puts.lock().unwrap();
+        let secret = invite.group_secret.as_bytes();
+        assert!(!puts[0].windows(32).any(|w| w == secret));
+    }
+
+    #[tokio::test]
+    async fn pairing_code_through_mailboxes() {
+        let relay = MailboxRelay::default();
+        let code = PairingCode::generate();
+        let invite = group_invite();
+
+        let (inviter, joiner) = pair_of_clients(&relay);
+        let (offered, accepted) = tokio::join!(
+            inviter.offer_invite_with_code(&code, &invite),
+            joiner.accept_invite_with_code(&code)
+        );
+        offered.unwrap();
+        let received = accepted.unwrap();
+        assert_eq!(received.group_id, invite.group_id);
+        assert_eq!(received.group_secret, invite.group_secret);
+
+        // Share, share + confirmation, sealed invite
+        let puts = relay.puts.lock().unwrap();
+        assert_eq!(puts.len(), 3);
+        let secret = invite.group_secret.as_bytes();
+        for payload in puts.iter() {
+            assert!(!payload.windows(32).any(|w| w == secret));
+        }
+    }
+
+    #[tokio::test]
+    async fn pairing_code_mismatch_aborts() {
+        let relay = MailboxRelay::default();
+        let (inviter, joiner) = pair_of_clients(&relay);
+        let code = PairingCode::parse("4242-000000").unwrap();
+        let typo = PairingCode::parse("4242-000001").unwrap();
+        let invite = group_invite();
+
+        let (offered, accepted) = tokio::join!(
+            inviter.offer_invite_with_code(&code, &invite),
+            tokio::time::timeout(
+                Duration::from_millis(200),
+                joiner.accept_invite_with_code(&typo)
+            )
+        );
+        assert!(matches!(
+            offered,
+            Err(ClientError::Pairing(PairingError::CodeMismatch))
+        ));
+        // The joiner never receives an invite
+        assert!(accepted.is_err());
+    }
+
+    #[tokio::test]
+    async fn put_rejects_unexpected_response() {
+        let transport = MockTransport::new();
+        transport.connect("relay").await.unwrap();
+        transport.queue_response(
+            Message::MailboxAck(MailboxAck { mailbox: [9u8; 32] })
+                .to_bytes()
+                .unwrap(),
+        );
+        let client = MailboxClient::new(transport);
+
+        let result = client.put([1u8; 32], vec![1, 2, 3], 0).await;
+        assert!(matches!(result, Err(ClientError::Protocol(_))));
+    }
+
+    #[tokio::test]
+    async fn get_answers_pow_challenge() {
+        use zerok_sync_types::{Challenge, ChallengeResponse};
+
+        let transport = MockTransport::new();
+        transport.connect("relay").await.unwrap();
+        let nonce = [0x42; 32];
+        transport.queue_response(
+            Message::Challenge(Challenge {
+                nonce,
+                difficulty: 8,
+            })
+            .to_bytes()
+            .unwrap(),
+        );
+        transport.queue_response(
+            Message::MailboxData(MailboxData {
+                mailbox: [1u8; 32],
+                payload: Some(vec![7]),
+            })
+            .to_bytes()
+            .unwrap(),
+        );
+        let client = MailboxClient::new(transport.clone());
+
+        assert_eq!(client.get([1u8; 32]).await.unwrap(), Some(vec![7]));
+
+        let sent = transport.sent_messages();
+        assert_eq!(sent.len(), 2);
+        match Message::from_bytes(&sent[1]).unwrap() {
+            Message::ChallengeResponse(ChallengeResponse { nonce: n, counter }) => {
+                assert_eq!(n, nonce);
+                assert!(zerok_sync_core::verify_pow(&nonce, 8, counter));
+            }
+            other => panic!("Expected ChallengeResponse, got {:?}", other),
+        }
+    }
+}
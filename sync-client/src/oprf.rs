@@ -0,0 +1,200 @@
+//! Relay-assisted passphrase hardening (OPRF).
+//!
+//! Optional step before deriving a group secret from a passphrase. The
+//! passphrase is blinded, evaluated by the relay under a per-group key, and
+//! unblinded locally (see `sync_core::oprf`). The output is then mixed into
+//! Argon2id by [`GroupSecret::from_passphrase_with_oprf`], so an attacker
+//! holding the salt and ciphertext can no longer brute-force the passphrase
+//! offline: every guess needs a rate-limited evaluation by the relay.
+//!
+//! All devices in a group must use the same relay (the OPRF key lives
+//! there) and must all opt in. The relay never sees the passphrase.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let transport = IrohTransport::new().await?;
+//! transport.connect(relay).await?;
+//! let output = evaluate_passphrase(&transport, passphrase, &salt).await?;
+//! let config = SyncConfig::new_with_oprf(passphrase, &salt, &output, relay);
+//! ```
+//!
+//! [`GroupSecret::from_passphrase_with_oprf`]: crate::crypto::GroupSecret::from_passphrase_with_oprf
+
+use zerok_sync_core::{oprf_key_id, OprfClient, OPRF_OUTPUT_SIZE};
+use zerok_sync_types::{Message, OprfEvaluate, OprfEvaluated};
+
+use crate::client::{answer_challenge, ClientError};
+use crate::transport::Transport;
+
+/// Evaluate the relay's OPRF on `passphrase` for the group using `salt`.
+///
+/// `transport` must already be connected to the group's relay. No HELLO is
+/// needed. Each call counts against the relay's per-group evaluation limit.
+pub async fn evaluate_passphrase<T: Transport>(
+    transport: &T,
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<[u8; OPRF_OUTPUT_SIZE], ClientError> {
+    let key_id = oprf_key_id(salt);
+    let (client, blinded) = OprfClient::blind(passphrase.as_bytes());
+
+    let request = Message::OprfEvaluate(OprfEvaluate { key_id, blinded })
+        .to_bytes()
+        .map_err(|e| ClientError::Serialization(e.to_string()))?;
+    transport.send(&request).await?;
+    let response = transport.recv().await?;
+    let response =
+        Message::from_bytes(&response).map_err(|e| ClientError::Serialization(e.to_string()))?;
+
+    match answer_challenge(transport, response).await? {
+        Message::OprfEvaluated(OprfEvaluated {
+            key_id: returned,
+            evaluated,
+        }) if returned == key_id => client
+            .finalize(&evaluated)
+            .map_err(|e| ClientError::Protocol(e.to_string())),
+        _ => Err(ClientError::Protocol(
+            "unexpected response to OPRF evaluation".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Argon2Params, GroupSecret};
+    use crate::transport::{MockTransport, TransportError};
+    use std::sync::{Arc, Mutex};
+    use zerok_sync_core::OprfServerKey;
+
+    /// In-memory relay evaluating OPRF requests with a fixed key.
+    #[derive(Clone)]
+    struct OprfRelay {
+        key: OprfServerKey,
+        response: Arc<Mutex<Option<Vec<u8>>>>,
+        /// Every blinded element the relay saw.
+        seen: Arc<Mutex<Vec<[u8; 32]>>>,
+    }
+
+    impl OprfRelay {
+        fn new() -> Self {
+            Self {
+                key: OprfServerKey::generate(),
+                response: Default::default(),
+                seen: Default::default(),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for OprfRelay {
+        async fn connect(&self, _address: &str) -> Result<(), TransportError> {
+            Ok(())
+        }
+
+        async fn send(&self, data: &[u8]) -> Result<(), TransportError> {
+            let Message::OprfEvaluate(request) = Message::from_bytes(data).unwrap() else {
+                panic!("unexpected request");
+            };
+            self.seen.lock().unwrap().push(request.blinded);
+            let response = Message::OprfEvaluated(OprfEvaluated {
+                key_id: request.key_id,
+                evaluated: self.key.evaluate(&request.blinded).unwrap(),
+            });
+            *self.response.lock().unwrap() = Some(response.to_bytes().unwrap());
+            Ok(())
+        }
+
+        async fn recv(&self) -> Result<Vec<u8>, TransportError> {
+            self.response
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or(TransportError::ConnectionClosed)
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn close(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn devices_derive_same_secret() {
+        let relay = OprfRelay::new();
+        let salt = b"0123456789abcdef";
+        let params = Argon2Params::for_ram_mb(1500);
+
+        let first = evaluate_passphrase(&relay, "shared passphrase", salt)
+            .await
+            .unwrap();
+        let second = evaluate_passphrase(&relay, "shared passphrase", salt)
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+
+        let a = GroupSecret::from_passphrase_with_oprf_params(
+            "shared passphrase",
+            salt,
+            &first,
+            params,
+        );
+        let b = GroupSecret::from_passphrase_with_oprf_params(
+            "shared passphrase",
+            salt,
+            &second,
+            params,
+        );
+        assert_eq!(a.as_bytes(), b.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn different_relay_key_gives_different_output() {
+        let salt = b"0123456789abcdef";
+        let a = evaluate_passphrase(&OprfRelay::new(), "passphrase", salt)
+            .await
+            .unwrap();
+        let b = evaluate_passphrase(&OprfRelay::new(), "passphrase", salt)
+            .await
+            .unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[tokio::test]
+    async fn relay_sees_only_blinded_elements() {
+        let relay = OprfRelay::new();
+        let salt = b"0123456789abcdef";
+        evaluate_passphrase(&relay, "passphrase", salt)
+            .await
+            .unwrap();
+        evaluate_passphrase(&relay, "passphrase", salt)
+            .await
+            .unwrap();
+
+        // Same passphrase, unlinkable requests
+        let seen = relay.seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_ne!(seen[0], seen[1]);
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatched_key_id() {
+        let transport = MockTransport::new();
+        transport.connect("relay").await.unwrap();
+        transport.queue_response(
+            Message::OprfEvaluated(OprfEvaluated {
+                key_id: [9u8; 32],
+                evaluated: [0u8; 32],
+            })
+            .to_bytes()
+            .unwrap(),
+        );
+
+        let result = evaluate_passphrase(&transport, "passphrase", b"salt").await;
+        assert!(matches!(result, Err(ClientError::Protocol(_))));
+    }
+}
@@ -0,0 +1,36 @@
+//! Trace metadata for outgoing requests.
+//!
+//! PUSH and PULL carry a [`TraceContext`] so relay spans can join the
+//! client's trace. With the `otel` feature the IDs come from the active
+//! OpenTelemetry span (set up by the application's exporter); otherwise
+//! they are random, which still lets relay logs be correlated by trace ID.
+
+use zerok_sync_types::TraceContext;
+
+/// Trace context identifying `span` to the relay.
+pub(crate) fn trace_context(span: &tracing::Span) -> TraceContext {
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = span.context();
+        let span_context = context.span().span_context().clone();
+        if span_context.is_valid() {
+            return TraceContext {
+                trace_id: span_context.trace_id().to_bytes(),
+                span_id: span_context.span_id().to_bytes(),
+            };
+        }
+    }
+    #[cfg(not(feature = "otel"))]
+    let _ = span;
+
+    let mut trace = TraceContext {
+        trace_id: [0u8; 16],
+        span_id: [0u8; 8],
+    };
+    getrandom::getrandom(&mut trace.trace_id).expect("getrandom failed");
+    getrandom::getrandom(&mut trace.span_id).expect("getrandom failed");
+    trace
+}
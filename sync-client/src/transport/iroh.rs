@@ -5,7 +5,7 @@
 
 use super::{Transport, TransportError};
 use async_trait::async_trait;
-use iroh::{endpoint::Connection, Endpoint, EndpointAddr, EndpointId};
+use iroh::{endpoint::Connection, Endpoint, EndpointAddr, EndpointId, SecretKey};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
@@ -17,12 +17,17 @@ pub const ALPN: &[u8] = b"/0k-sync/1";
 pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
 
 /// Configuration for IrohTransport.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct IrohTransportConfig {
     /// Connection timeout.
     pub connect_timeout: Duration,
     /// Send/recv operation timeout.
     pub operation_timeout: Duration,
+    /// Endpoint secret key. `None` generates a fresh identity per transport.
+    ///
+    /// Relays know a device by its endpoint ID, so a device must reuse its
+    /// key to stay a member of an invite-only group across restarts.
+    pub secret_key: Option<[u8; 32]>,
 }
 
 impl Default for IrohTransportConfig {
@@ -30,10 +35,31 @@ impl Default for IrohTransportConfig {
         Self {
             connect_timeout: Duration::from_secs(30),
             operation_timeout: Duration::from_secs(60),
+            secret_key: None,
         }
     }
 }
 
+impl IrohTransportConfig {
+    /// Use a persisted endpoint secret key (builder pattern).
+    ///
+    /// See [`IrohTransport::generate_secret_key`].
+    pub fn with_secret_key(mut self, secret_key: [u8; 32]) -> Self {
+        self.secret_key = Some(secret_key);
+        self
+    }
+}
+
+impl std::fmt::Debug for IrohTransportConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IrohTransportConfig")
+            .field("connect_timeout", &self.connect_timeout)
+            .field("operation_timeout", &self.operation_timeout)
+            .field("secret_key", &self.secret_key.map(|_| "[REDACTED]"))
+            .finish()
+    }
+}
+
 /// Active connection state.
 struct ActiveConnection {
     /// The QUIC connection.
@@ -73,7 +99,11 @@ impl IrohTransport {
 
     /// Create a new IrohTransport with custom configuration.
     pub async fn with_config(config: IrohTransportConfig) -> Result<Self, TransportError> {
-        let endpoint = Endpoint::builder().bind().await.map_err(|e| {
+        let mut builder = Endpoint::builder();
+        if let Some(secret_key) = &config.secret_key {
+            builder = builder.secret_key(SecretKey::from_bytes(secret_key));
+        }
+        let endpoint = builder.bind().await.map_err(|e| {
             TransportError::ConnectionFailed(format!("Failed to bind endpoint: {e}"))
         })?;
 
@@ -84,6 +114,14 @@ impl IrohTransport {
         })
     }
 
+    /// Generate a new endpoint secret key to persist for
+    /// [`IrohTransportConfig::with_secret_key`].
+    pub fn generate_secret_key() -> [u8; 32] {
+        let mut secret_key = [0u8; 32];
+        getrandom::getrandom(&mut secret_key).expect("getrandom failed");
+        secret_key
+    }
+
     /// Get our EndpointId for sharing with peers.
     pub fn endpoint_id(&self) -> EndpointId {
         self.endpoint.id()
@@ -181,8 +219,6 @@ impl Transport for IrohTransport {
     }
 
     async fn recv(&self) -> Result<Vec<u8>, TransportError> {
-        use iroh::endpoint::ReadExactError;
-
         let mut guard = self.connection.lock().await;
         let active = guard.as_mut().ok_or(TransportError::NotConnected)?;
 
@@ -191,35 +227,26 @@ impl Transport for IrohTransport {
             .as_mut()
             .ok_or(TransportError::ReceiveFailed("no active stream".into()))?;
 
-        // Read length prefix (4 bytes, big-endian)
-        let mut len_buf = [0u8; 4];
-        recv.read_exact(&mut len_buf).await.map_err(|e| match e {
-            ReadExactError::FinishedEarly(_) => TransportError::ConnectionClosed,
-            ReadExactError::ReadError(e) => {
-                TransportError::ReceiveFailed(format!("Failed to read length: {e}"))
-            }
-        })?;
-
-        let len = u32::from_be_bytes(len_buf) as usize;
+        read_frame(recv).await
+    }
 
-        // Validate length
-        if len > MAX_MESSAGE_SIZE {
-            return Err(TransportError::ReceiveFailed(format!(
-                "Message too large: {} > {}",
-                len, MAX_MESSAGE_SIZE
-            )));
-        }
+    async fn recv_pushed(&self) -> Result<Vec<u8>, TransportError> {
+        // Wait without holding the lock: requests share the connection
+        let conn = self
+            .connection
+            .lock()
+            .await
+            .as_ref()
+            .map(|active| active.conn.clone())
+            .ok_or(TransportError::NotConnected)?;
 
-        // Read payload
-        let mut data = vec![0u8; len];
-        recv.read_exact(&mut data).await.map_err(|e| match e {
-            ReadExactError::FinishedEarly(_) => TransportError::ConnectionClosed,
-            ReadExactError::ReadError(e) => {
-                TransportError::ReceiveFailed(format!("Failed to read data: {e}"))
-            }
-        })?;
+        // The relay opens one unidirectional stream per NOTIFY/BYE
+        let mut recv = conn
+            .accept_uni()
+            .await
+            .map_err(|_| TransportError::ConnectionClosed)?;
 
-        Ok(data)
+        read_frame(&mut recv).await
     }
 
     fn is_connected(&self) -> bool {
@@ -238,6 +265,40 @@ impl Transport for IrohTransport {
     }
 }
 
+/// Read one length-prefixed message (4 bytes, big-endian) from a stream.
+async fn read_frame(recv: &mut iroh::endpoint::RecvStream) -> Result<Vec<u8>, TransportError> {
+    use iroh::endpoint::ReadExactError;
+
+    let mut len_buf = [0u8; 4];
+    recv.read_exact(&mut len_buf).await.map_err(|e| match e {
+        ReadExactError::FinishedEarly(_) => TransportError::ConnectionClosed,
+        ReadExactError::ReadError(e) => {
+            TransportError::ReceiveFailed(format!("Failed to read length: {e}"))
+        }
+    })?;
+
+    let len = u32::from_be_bytes(len_buf) as usize;
+
+    // Validate length
+    if len > MAX_MESSAGE_SIZE {
+        return Err(TransportError::ReceiveFailed(format!(
+            "Message too large: {} > {}",
+            len, MAX_MESSAGE_SIZE
+        )));
+    }
+
+    // Read payload
+    let mut data = vec![0u8; len];
+    recv.read_exact(&mut data).await.map_err(|e| match e {
+        ReadExactError::FinishedEarly(_) => TransportError::ConnectionClosed,
+        ReadExactError::ReadError(e) => {
+            TransportError::ReceiveFailed(format!("Failed to read data: {e}"))
+        }
+    })?;
+
+    Ok(data)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -339,6 +400,21 @@ mod tests {
         assert_ne!(transport1.endpoint_id(), transport2.endpoint_id());
     }
 
+    #[tokio::test]
+    async fn persisted_secret_key_keeps_endpoint_id() {
+        let secret_key = IrohTransport::generate_secret_key();
+        let config = IrohTransportConfig::default().with_secret_key(secret_key);
+
+        // A new transport (e.g. after a restart) is the same device
+        let first = IrohTransport::with_config(config.clone()).await.unwrap();
+        let first_id = first.endpoint_id();
+        drop(first);
+        let second = IrohTransport::with_config(config.clone()).await.unwrap();
+        assert_eq!(second.endpoint_id(), first_id);
+
+        assert!(!format!("{:?}", config).contains(&format!("{:?}", secret_key)));
+    }
+
     // ===========================================
     // Integration Tests (Two Endpoints)
     // ===========================================
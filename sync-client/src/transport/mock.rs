@@ -21,6 +21,7 @@ struct MockTransportInner {
     connected_address: Option<String>,
     sent_messages: Vec<Vec<u8>>,
     receive_queue: VecDeque<Vec<u8>>,
+    pushed_queue: VecDeque<Vec<u8>>,
     connect_failures: VecDeque<String>,
     fail_next_send: Option<String>,
     fail_next_recv: Option<String>,
@@ -38,6 +39,12 @@ impl MockTransport {
         inner.receive_queue.push_back(data);
     }
 
+    /// Queue a relay-initiated message (NOTIFY, BYE) for `recv_pushed()`.
+    pub fn queue_pushed(&self, data: Vec<u8>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pushed_queue.push_back(data);
+    }
+
     /// Get all messages that were sent.
     pub fn sent_messages(&self) -> Vec<Vec<u8>> {
         let inner = self.inner.lock().unwrap();
@@ -148,6 +155,19 @@ impl Transport for MockTransport {
             .ok_or(TransportError::ConnectionClosed)
     }
 
+    async fn recv_pushed(&self) -> Result<Vec<u8>, TransportError> {
+        let mut inner = self.inner.lock().unwrap();
+
+        if !inner.connected {
+            return Err(TransportError::NotConnected);
+        }
+
+        inner
+            .pushed_queue
+            .pop_front()
+            .ok_or(TransportError::ConnectionClosed)
+    }
+
     fn is_connected(&self) -> bool {
         let inner = self.inner.lock().unwrap();
         inner.connected
@@ -211,6 +231,22 @@ mod tests {
         assert_eq!(r2, b"response 2");
     }
 
+    #[tokio::test]
+    async fn mock_transport_keeps_pushed_messages_separate() {
+        let transport = MockTransport::new();
+        transport.connect("node").await.unwrap();
+
+        transport.queue_pushed(b"notify".to_vec());
+        transport.queue_response(b"response".to_vec());
+
+        assert_eq!(transport.recv_pushed().await.unwrap(), b"notify");
+        assert_eq!(transport.recv().await.unwrap(), b"response");
+        assert!(matches!(
+            transport.recv_pushed().await,
+            Err(TransportError::ConnectionClosed)
+        ));
+    }
+
     #[tokio::test]
     async fn mock_transport_recv_empty_returns_closed() {
         let transport = MockTransport::new();
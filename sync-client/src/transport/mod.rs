@@ -9,6 +9,7 @@
 //! - `connect()` establishes a connection
 //! - `send()` transmits encrypted envelope bytes
 //! - `recv()` receives envelope bytes
+//! - `recv_pushed()` receives messages the relay sends on its own (NOTIFY, BYE)
 //! - `close()` gracefully terminates
 //!
 //! # Example
@@ -78,6 +79,14 @@ pub trait Transport: Send + Sync {
     /// Blocks until data is available or connection closes.
     async fn recv(&self) -> Result<Vec<u8>, TransportError>;
 
+    /// Receive a message the relay pushed outside a request (NOTIFY, BYE).
+    ///
+    /// Blocks until one arrives or the connection closes. The default never
+    /// returns, for transports without relay-initiated streams.
+    async fn recv_pushed(&self) -> Result<Vec<u8>, TransportError> {
+        std::future::pending().await
+    }
+
     /// Check if currently connected.
     fn is_connected(&self) -> bool;
 
@@ -0,0 +1,303 @@
+//! Chunked content encryption for range reads.
+//!
+//! Large content is split into fixed-size plaintext chunks. Each chunk is
+//! encrypted separately and stored as its own blob, and a manifest blob lists
+//! the chunk hashes in order. The `ContentRef` points at the manifest, so a
+//! range read fetches the manifest plus only the chunks covering the range.
+//!
+//! ## Manifest Layout
+//!
+//! ```text
+//! "0KCM" (4) | version (1) | chunk_size u32 | content_size u64 | chunk_count u32
+//!   | chunk_hash (32) × chunk_count | tag (16)
+//! ```
+//!
+//! Integers are big-endian. The header and hash list are not secret (the
+//! store sees every chunk anyway) but are authenticated: the tag is an
+//! XChaCha20-Poly1305 tag over an empty message with the header as AAD.
+//!
+//! ## Nonces
+//!
+//! The `ContentRef` nonce is a base nonce. Chunk `i` uses the base nonce with
+//! its last 8 bytes XORed with `i + 1`; the manifest uses index `u64::MAX`.
+//! Index 0 (the base nonce itself) is left to single-blob encryption, so a
+//! convergent file never reuses a nonce between its chunked and unchunked
+//! forms. A chunk moved to a different position fails authentication.
+
+use std::ops::Range;
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::encrypt::{CONTENT_KEY_SIZE, HASH_SIZE, NONCE_SIZE};
+use crate::error::ContentError;
+
+/// Default plaintext chunk size (1 MiB).
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Magic prefix identifying a chunk manifest blob.
+const MANIFEST_MAGIC: &[u8; 4] = b"0KCM";
+
+/// Current manifest format version.
+const MANIFEST_VERSION: u8 = 1;
+
+/// Fixed header size: magic + version + chunk_size + content_size + chunk_count.
+const HEADER_SIZE: usize = 4 + 1 + 4 + 8 + 4;
+
+/// Poly1305 tag size.
+const TAG_SIZE: usize = 16;
+
+/// Nonce index reserved for the manifest tag.
+const MANIFEST_NONCE_INDEX: u64 = u64::MAX;
+
+/// Derive the nonce for nonce index `index` from the content's base nonce.
+///
+/// Chunk `i` uses index `i + 1`; see [`ChunkManifest::chunk_nonce_index`].
+pub fn chunk_nonce(base: &[u8; NONCE_SIZE], index: u64) -> [u8; NONCE_SIZE] {
+    let mut nonce = *base;
+    for (byte, idx) in nonce[NONCE_SIZE - 8..].iter_mut().zip(index.to_be_bytes()) {
+        *byte ^= idx;
+    }
+    nonce
+}
+
+/// Ordered list of chunk hashes for one piece of chunked content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChunkManifest {
+    /// Plaintext bytes per chunk (the last chunk may be shorter).
+    pub chunk_size: u32,
+    /// Total plaintext size.
+    pub content_size: u64,
+    /// BLAKE3 hash of each chunk's ciphertext, in order.
+    pub chunk_hashes: Vec<[u8; HASH_SIZE]>,
+}
+
+impl ChunkManifest {
+    /// Check whether a stored blob carries the manifest magic prefix.
+    ///
+    /// A plain single-blob ciphertext can start with the magic by chance, so
+    /// callers must fall back to single-blob decryption if [`Self::open`] fails.
+    pub fn is_manifest(blob: &[u8]) -> bool {
+        blob.len() >= HEADER_SIZE + TAG_SIZE && blob.starts_with(MANIFEST_MAGIC)
+    }
+
+    /// Serialize and authenticate the manifest.
+    pub fn seal(
+        &self,
+        content_key: &[u8; CONTENT_KEY_SIZE],
+        base_nonce: &[u8; NONCE_SIZE],
+    ) -> Result<Vec<u8>, ContentError> {
+        let mut blob = self.header_bytes();
+        let tag = manifest_cipher(content_key)
+            .map_err(ContentError::EncryptionFailed)?
+            .encrypt(
+                XNonce::from_slice(&chunk_nonce(base_nonce, MANIFEST_NONCE_INDEX)),
+                Payload {
+                    msg: &[],
+                    aad: &blob,
+                },
+            )
+            .map_err(|e| ContentError::EncryptionFailed(e.to_string()))?;
+        blob.extend_from_slice(&tag);
+        Ok(blob)
+    }
+
+    /// Parse a manifest blob without verifying its tag.
+    ///
+    /// Never trust the result on its own — use [`Self::open`], which also
+    /// checks the tag under the caller's content key.
+    pub fn parse(blob: &[u8]) -> Result<Self, ContentError> {
+        if !Self::is_manifest(blob) {
+            return Err(ContentError::InvalidManifest("missing magic".into()));
+        }
+        if blob[4] != MANIFEST_VERSION {
+            return Err(ContentError::InvalidManifest(format!(
+                "unsupported version {}",
+                blob[4]
+            )));
+        }
+
+        let chunk_size = u32::from_be_bytes(blob[5..9].try_into().expect("4 bytes"));
+        let content_size = u64::from_be_bytes(blob[9..17].try_into().expect("8 bytes"));
+        let chunk_count = u32::from_be_bytes(blob[17..21].try_into().expect("4 bytes")) as usize;
+
+        if chunk_size == 0 {
+            return Err(ContentError::InvalidManifest("zero chunk size".into()));
+        }
+        if content_size.div_ceil(chunk_size as u64) != chunk_count as u64 {
+            return Err(ContentError::InvalidManifest(
+                "chunk count does not match content size".into(),
+            ));
+        }
+        let expected_len = chunk_count
+            .checked_mul(HASH_SIZE)
+            .and_then(|n| n.checked_add(HEADER_SIZE + TAG_SIZE));
+        if expected_len != Some(blob.len()) {
+            return Err(ContentError::InvalidManifest("length mismatch".into()));
+        }
+
+        let chunk_hashes = blob[HEADER_SIZE..blob.len() - TAG_SIZE]
+            .chunks_exact(HASH_SIZE)
+            .map(|h| h.try_into().expect("32 bytes"))
+            .collect();
+
+        Ok(Self {
+            chunk_size,
+            content_size,
+            chunk_hashes,
+        })
+    }
+
+    /// Parse a manifest blob and verify its tag.
+    pub fn open(
+        blob: &[u8],
+        content_key: &[u8; CONTENT_KEY_SIZE],
+        base_nonce: &[u8; NONCE_SIZE],
+    ) -> Result<Self, ContentError> {
+        let manifest = Self::parse(blob)?;
+        let (header, tag) = blob.split_at(blob.len() - TAG_SIZE);
+        manifest_cipher(content_key)
+            .map_err(|_| ContentError::DecryptionFailed)?
+            .decrypt(
+                XNonce::from_slice(&chunk_nonce(base_nonce, MANIFEST_NONCE_INDEX)),
+                Payload {
+                    msg: tag,
+                    aad: header,
+                },
+            )
+            .map_err(|_| ContentError::DecryptionFailed)?;
+        Ok(manifest)
+    }
+
+    /// Indexes of the chunks covering `len` bytes starting at `offset`.
+    ///
+    /// Callers must check the range against `content_size` first.
+    pub fn covering_chunks(&self, offset: u64, len: u64) -> Range<usize> {
+        if len == 0 {
+            return 0..0;
+        }
+        let chunk_size = self.chunk_size as u64;
+        let first = offset / chunk_size;
+        let last = (offset + len - 1) / chunk_size;
+        first as usize..last as usize + 1
+    }
+
+    /// Nonce index for chunk `index` (0 is reserved for single blobs).
+    pub fn chunk_nonce_index(index: usize) -> u64 {
+        index as u64 + 1
+    }
+
+    /// Plaintext offset where chunk `index` starts.
+    pub fn chunk_start(&self, index: usize) -> u64 {
+        index as u64 * self.chunk_size as u64
+    }
+
+    /// Expected plaintext length of chunk `index`.
+    pub fn chunk_len(&self, index: usize) -> usize {
+        let start = self.chunk_start(index);
+        (self.content_size - start).min(self.chunk_size as u64) as usize
+    }
+
+    fn header_bytes(&self) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(HEADER_SIZE + self.chunk_hashes.len() * HASH_SIZE + TAG_SIZE);
+        out.extend_from_slice(MANIFEST_MAGIC);
+        out.push(MANIFEST_VERSION);
+        out.extend_from_slice(&self.chunk_size.to_be_bytes());
+        out.extend_from_slice(&self.content_size.to_be_bytes());
+        out.extend_from_slice(&(self.chunk_hashes.len() as u32).to_be_bytes());
+        for hash in &self.chunk_hashes {
+            out.extend_from_slice(hash);
+        }
+        out
+    }
+}
+
+fn manifest_cipher(content_key: &[u8; CONTENT_KEY_SIZE]) -> Result<XChaCha20Poly1305, String> {
+    XChaCha20Poly1305::new_from_slice(content_key).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_manifest() -> ChunkManifest {
+        ChunkManifest {
+            chunk_size: 100,
+            content_size: 250,
+            chunk_hashes: vec![[1u8; 32], [2u8; 32], [3u8; 32]],
+        }
+    }
+
+    #[test]
+    fn chunk_nonce_unique_per_index() {
+        let base = [0x42; NONCE_SIZE];
+        assert_eq!(chunk_nonce(&base, 0), base);
+        assert_ne!(chunk_nonce(&base, 1), chunk_nonce(&base, 2));
+        assert_ne!(
+            chunk_nonce(&base, 0),
+            chunk_nonce(&base, MANIFEST_NONCE_INDEX)
+        );
+        // Prefix is untouched
+        assert_eq!(chunk_nonce(&base, 7)[..16], base[..16]);
+    }
+
+    #[test]
+    fn manifest_seal_open_roundtrip() {
+        let key = [0x11; 32];
+        let nonce = [0x22; NONCE_SIZE];
+        let manifest = sample_manifest();
+
+        let blob = manifest.seal(&key, &nonce).unwrap();
+        assert!(ChunkManifest::is_manifest(&blob));
+
+        let opened = ChunkManifest::open(&blob, &key, &nonce).unwrap();
+        assert_eq!(opened, manifest);
+    }
+
+    #[test]
+    fn manifest_open_wrong_key_fails() {
+        let blob = sample_manifest().seal(&[0x11; 32], &[0x22; 24]).unwrap();
+        let result = ChunkManifest::open(&blob, &[0x33; 32], &[0x22; 24]);
+        assert!(matches!(result, Err(ContentError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn manifest_tampered_hash_list_fails() {
+        let key = [0x11; 32];
+        let nonce = [0x22; NONCE_SIZE];
+        let mut blob = sample_manifest().seal(&key, &nonce).unwrap();
+        blob[HEADER_SIZE] ^= 0xFF;
+
+        // Structure still parses, authentication does not
+        assert!(ChunkManifest::parse(&blob).is_ok());
+        assert!(matches!(
+            ChunkManifest::open(&blob, &key, &nonce),
+            Err(ContentError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn manifest_parse_rejects_inconsistent_count() {
+        let mut manifest = sample_manifest();
+        manifest.chunk_hashes.pop();
+        let blob = manifest.seal(&[0x11; 32], &[0x22; 24]).unwrap();
+        assert!(matches!(
+            ChunkManifest::parse(&blob),
+            Err(ContentError::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn covering_chunks_boundaries() {
+        let manifest = sample_manifest();
+        assert_eq!(manifest.covering_chunks(0, 1), 0..1);
+        assert_eq!(manifest.covering_chunks(99, 2), 0..2);
+        assert_eq!(manifest.covering_chunks(100, 100), 1..2);
+        assert_eq!(manifest.covering_chunks(150, 100), 1..3);
+        assert_eq!(manifest.covering_chunks(10, 0), 0..0);
+        assert_eq!(manifest.chunk_len(2), 50);
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides:
 //! - Content key derivation (HKDF from GroupSecret + blob_id)
 //! - XChaCha20-Poly1305 encryption with random nonces
+//! - Optional convergent (deterministic) encryption for in-group dedup
 //! - BLAKE3 hashing of ciphertext for content addressing
 
 use chacha20poly1305::{
@@ -11,6 +12,7 @@ use chacha20poly1305::{
 };
 use hkdf::Hkdf;
 use sha2::Sha256;
+use zeroize::Zeroize;
 
 use crate::error::ContentError;
 
@@ -26,6 +28,9 @@ pub const HASH_SIZE: usize = 32;
 /// Maximum content size for encryption (100 MB).
 pub const MAX_CONTENT_SIZE: usize = 100 * 1024 * 1024;
 
+/// HKDF salt for convergent (deterministic) content encryption.
+const CONVERGENT_SALT: &[u8] = b"0k-sync-convergent-v1";
+
 /// Derive a content-specific encryption key from group secret and blob ID.
 ///
 /// Uses HKDF-SHA256 with domain separation:
@@ -61,6 +66,85 @@ pub type EncryptedContent = (Vec<u8>, [u8; NONCE_SIZE], [u8; HASH_SIZE]);
 pub fn encrypt_content(
     content_key: &[u8; CONTENT_KEY_SIZE],
     plaintext: &[u8],
+) -> Result<EncryptedContent, ContentError> {
+    // Generate random nonce (192 bits safe for random generation)
+    let mut nonce = [0u8; NONCE_SIZE];
+    getrandom::getrandom(&mut nonce).map_err(|e| ContentError::EncryptionFailed(e.to_string()))?;
+
+    seal(content_key, nonce, plaintext)
+}
+
+/// Derive the synthetic nonce for convergent encryption.
+///
+/// The nonce is a BLAKE3 keyed hash of the plaintext, truncated to 192 bits.
+/// The MAC key is derived from the group secret (salt `"0k-sync-convergent-v1"`),
+/// so identical plaintext yields the same nonce only within one sync group.
+pub fn derive_convergent_nonce(group_secret: &[u8; 32], plaintext: &[u8]) -> [u8; NONCE_SIZE] {
+    let hkdf = Hkdf::<Sha256>::new(Some(CONVERGENT_SALT), group_secret);
+    let mut mac_key = [0u8; 32];
+    hkdf.expand(b"convergent-mac", &mut mac_key)
+        .expect("HKDF expand should not fail with valid lengths");
+
+    let mac = blake3::keyed_hash(&mac_key, plaintext);
+    mac_key.zeroize();
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce.copy_from_slice(&mac.as_bytes()[..NONCE_SIZE]);
+    nonce
+}
+
+/// Derive the content key for convergent encryption from its nonce.
+///
+/// Uses HKDF-SHA256 with salt `"0k-sync-convergent-v1"` and info
+/// `nonce || "convergent-encryption"`. Recipients only need the group secret
+/// and the nonce from the `ContentRef` — no `blob_id` is involved.
+pub fn derive_convergent_key(
+    group_secret: &[u8; 32],
+    nonce: &[u8; NONCE_SIZE],
+) -> [u8; CONTENT_KEY_SIZE] {
+    let hkdf = Hkdf::<Sha256>::new(Some(CONVERGENT_SALT), group_secret);
+
+    let mut info = Vec::with_capacity(NONCE_SIZE + 21);
+    info.extend_from_slice(nonce);
+    info.extend_from_slice(b"convergent-encryption");
+
+    let mut content_key = [0u8; CONTENT_KEY_SIZE];
+    hkdf.expand(&info, &mut content_key)
+        .expect("HKDF expand should not fail with valid lengths");
+
+    content_key
+}
+
+/// Encrypt content deterministically within a sync group.
+///
+/// Same group secret + same plaintext always yields the same ciphertext and
+/// therefore the same BLAKE3 content address, so duplicate content dedups in
+/// the blob store. The relay still only sees opaque ciphertext.
+///
+/// # Tradeoffs
+///
+/// - Any group member can confirm whether a stored blob matches a guessed
+///   plaintext (confirmation-of-file). Outsiders cannot.
+/// - Equal ciphertext hashes reveal to the store that two uploads are the
+///   same content, even though the content itself stays hidden.
+/// - Low-entropy content (e.g. short form values) can be brute-forced by
+///   group members. Use [`encrypt_content`] for such data.
+pub fn encrypt_content_convergent(
+    group_secret: &[u8; 32],
+    plaintext: &[u8],
+) -> Result<EncryptedContent, ContentError> {
+    let nonce = derive_convergent_nonce(group_secret, plaintext);
+    let mut content_key = derive_convergent_key(group_secret, &nonce);
+    let result = seal(&content_key, nonce, plaintext);
+    content_key.zeroize();
+    result
+}
+
+/// Encrypt with an explicit nonce and hash the ciphertext.
+pub(crate) fn seal(
+    content_key: &[u8; CONTENT_KEY_SIZE],
+    nonce: [u8; NONCE_SIZE],
+    plaintext: &[u8],
 ) -> Result<EncryptedContent, ContentError> {
     // F-017: Reject content exceeding maximum size
     if plaintext.len() > MAX_CONTENT_SIZE {
@@ -71,10 +155,6 @@ pub fn encrypt_content(
         )));
     }
 
-    // Generate random nonce (192 bits safe for random generation)
-    let mut nonce = [0u8; NONCE_SIZE];
-    getrandom::getrandom(&mut nonce).map_err(|e| ContentError::EncryptionFailed(e.to_string()))?;
-
     // Encrypt with XChaCha20-Poly1305
     let cipher = XChaCha20Poly1305::new_from_slice(content_key)
         .map_err(|e| ContentError::EncryptionFailed(e.to_string()))?;
@@ -230,6 +310,53 @@ mod tests {
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn convergent_encryption_is_deterministic() {
+        let group_secret = [0x66; 32];
+        let plaintext = b"Same photo from two phones";
+
+        let (ct1, nonce1, hash1) = encrypt_content_convergent(&group_secret, plaintext).unwrap();
+        let (ct2, nonce2, hash2) = encrypt_content_convergent(&group_secret, plaintext).unwrap();
+
+        assert_eq!(ct1, ct2);
+        assert_eq!(nonce1, nonce2);
+        assert_eq!(hash1, hash2);
+    }
+
+    #[test]
+    fn convergent_encryption_differs_across_groups() {
+        let plaintext = b"Same photo from two phones";
+
+        let (_, nonce_a, hash_a) = encrypt_content_convergent(&[0xAA; 32], plaintext).unwrap();
+        let (_, nonce_b, hash_b) = encrypt_content_convergent(&[0xBB; 32], plaintext).unwrap();
+
+        assert_ne!(nonce_a, nonce_b);
+        assert_ne!(hash_a, hash_b);
+    }
+
+    #[test]
+    fn convergent_encryption_differs_for_different_content() {
+        let group_secret = [0x77; 32];
+
+        let (_, _, hash_a) = encrypt_content_convergent(&group_secret, b"content a").unwrap();
+        let (_, _, hash_b) = encrypt_content_convergent(&group_secret, b"content b").unwrap();
+
+        assert_ne!(hash_a, hash_b);
+    }
+
+    #[test]
+    fn convergent_decrypt_with_key_from_nonce() {
+        let group_secret = [0x88; 32];
+        let plaintext = b"Deduplicated content";
+
+        let (ciphertext, nonce, _hash) =
+            encrypt_content_convergent(&group_secret, plaintext).unwrap();
+        let content_key = derive_convergent_key(&group_secret, &nonce);
+        let decrypted = decrypt_content(&content_key, &nonce, &ciphertext).unwrap();
+
+        assert_eq!(decrypted, plaintext);
+    }
+
     #[test]
     fn encrypt_rejects_oversized_content() {
         // F-017: Content exceeding MAX_CONTENT_SIZE must be rejected.
@@ -33,4 +33,23 @@ pub enum ContentError {
         /// Actual hash (hex-encoded).
         actual: String,
     },
+
+    /// Requested range lies outside the content.
+    #[error("range out of bounds: offset {offset} + len {len} exceeds size {size}")]
+    RangeOutOfBounds {
+        /// Requested start offset.
+        offset: u64,
+        /// Requested length.
+        len: u64,
+        /// Plaintext content size.
+        size: u64,
+    },
+
+    /// Chunk manifest is malformed.
+    #[error("invalid chunk manifest: {0}")]
+    InvalidManifest(String),
+
+    /// Decrypted content could not be decompressed (corrupt or over its size).
+    #[error("decompression failed: {0}")]
+    Decompression(String),
 }
@@ -0,0 +1,585 @@
+//! Reference tracking and garbage collection for stored content.
+//!
+//! A [`BlobStore`] only knows hashes; it has no idea whether any sync message
+//! still points at a piece of content. [`ReferenceTracker`] records which
+//! blob IDs (sync messages carrying a `ContentRef`) reference which content
+//! hashes, and [`ReferenceTracker::collect_garbage`] evicts content that is
+//! no longer needed.
+//!
+//! ## GC Pass
+//!
+//! 1. Release references whose `expires_at` has passed (message TTL elapsed)
+//! 2. Evict unreferenced content older than the grace period
+//! 3. If tracked bytes still exceed the disk budget, evict the remaining
+//!    unreferenced content oldest-first until under budget
+//!
+//! Referenced content is never evicted. The grace period protects content
+//! that was just added but whose `ContentRef` has not been pushed yet.
+//! Eviction goes through [`ContentTransfer::remove`], so a chunk manifest
+//! only takes its chunks with it if it opens under the group's key.
+//!
+//! ## Persistence
+//!
+//! The tracker lives in memory. Keep it next to a persistent store with
+//! [`ReferenceTracker::save`] and [`ReferenceTracker::load`]; a tracker
+//! that starts empty over a non-empty store would never collect the
+//! content stored before the restart.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sync_types::ContentRef;
+
+use crate::error::ContentError;
+use crate::store::BlobStore;
+use crate::ContentTransfer;
+
+/// Default grace period before unreferenced content is collected (1 hour).
+pub const DEFAULT_GC_GRACE_PERIOD_SECS: u64 = 3600;
+
+/// Format version written at the start of a saved tracker.
+const TRACKER_FORMAT_VERSION: u8 = 1;
+
+/// Configuration for a garbage collection pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcConfig {
+    /// Disk budget in bytes of ciphertext. `None` means unlimited.
+    pub max_bytes: Option<u64>,
+    /// Seconds unreferenced content is kept before it becomes collectable.
+    pub grace_period_secs: u64,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: None,
+            grace_period_secs: DEFAULT_GC_GRACE_PERIOD_SECS,
+        }
+    }
+}
+
+impl GcConfig {
+    /// Create a config with no disk budget and the default grace period.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the disk budget in bytes.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the grace period in seconds.
+    pub fn with_grace_period(mut self, secs: u64) -> Self {
+        self.grace_period_secs = secs;
+        self
+    }
+}
+
+/// Result of a garbage collection pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Number of references released because they expired.
+    pub expired_references: usize,
+    /// Content hashes evicted from the store.
+    pub evicted: Vec<[u8; 32]>,
+    /// Ciphertext bytes freed.
+    pub bytes_freed: u64,
+    /// Ciphertext bytes still tracked after the pass.
+    pub bytes_retained: u64,
+    /// True if referenced content alone exceeds the disk budget.
+    pub over_budget: bool,
+}
+
+/// Tracked metadata for one stored piece of content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ContentEntry {
+    /// Blob ID the content was added under (its key, in random mode).
+    blob_id: Vec<u8>,
+    content_ref: ContentRef,
+    added_at: u64,
+    ref_count: usize,
+}
+
+/// A sync message's claim on one or more content hashes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Reference {
+    hashes: Vec<[u8; 32]>,
+    expires_at: Option<u64>,
+}
+
+/// Tracks which blob IDs reference which content hashes.
+///
+/// Timestamps are Unix seconds and are passed in by the caller, so the
+/// tracker holds no clock and can be driven deterministically in tests.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReferenceTracker {
+    contents: HashMap<[u8; 32], ContentEntry>,
+    references: HashMap<Vec<u8>, Reference>,
+}
+
+impl ReferenceTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a tracker saved with [`save`](Self::save).
+    ///
+    /// A missing file gives an empty tracker (first run).
+    pub fn load(path: &Path) -> Result<Self, ContentError> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(ContentError::StoreError(e.to_string())),
+        };
+        match bytes.split_first() {
+            Some((&TRACKER_FORMAT_VERSION, body)) => rmp_serde::from_slice(body)
+                .map_err(|e| ContentError::StoreError(format!("reference table: {e}"))),
+            _ => Err(ContentError::StoreError(
+                "reference table: unsupported format".into(),
+            )),
+        }
+    }
+
+    /// Save the tracker to `path`, replacing any earlier save.
+    ///
+    /// Writes a sibling temporary file and renames it over `path`, so a
+    /// crash leaves either the old table or the new one.
+    pub fn save(&self, path: &Path) -> Result<(), ContentError> {
+        let mut bytes = vec![TRACKER_FORMAT_VERSION];
+        bytes.extend(
+            rmp_serde::to_vec(self)
+                .map_err(|e| ContentError::StoreError(format!("reference table: {e}")))?,
+        );
+        let staging = path.with_extension("tmp");
+        std::fs::write(&staging, &bytes)
+            .and_then(|()| std::fs::rename(&staging, path))
+            .map_err(|e| ContentError::StoreError(e.to_string()))
+    }
+
+    /// Register stored content without referencing it.
+    ///
+    /// Call this right after `ContentTransfer::add()`, with the same
+    /// `blob_id`. The content is collectable once the grace period elapses
+    /// unless a reference is added.
+    pub fn track(&mut self, blob_id: &[u8], content_ref: &ContentRef, now: u64) {
+        self.contents
+            .entry(content_ref.content_hash)
+            .or_insert_with(|| ContentEntry {
+                blob_id: blob_id.to_vec(),
+                content_ref: content_ref.clone(),
+                added_at: now,
+                ref_count: 0,
+            });
+    }
+
+    /// Record that `blob_id` references the given content.
+    ///
+    /// A blob may reference several content hashes. Adding the same hash
+    /// twice for one blob is a no-op. If `expires_at` is given, it replaces
+    /// any earlier expiry for this blob.
+    pub fn add_reference(
+        &mut self,
+        blob_id: &[u8],
+        content_ref: &ContentRef,
+        expires_at: Option<u64>,
+        now: u64,
+    ) {
+        self.track(blob_id, content_ref, now);
+
+        let reference = self
+            .references
+            .entry(blob_id.to_vec())
+            .or_insert(Reference {
+                hashes: Vec::new(),
+                expires_at: None,
+            });
+        if expires_at.is_some() {
+            reference.expires_at = expires_at;
+        }
+        if reference.hashes.contains(&content_ref.content_hash) {
+            return;
+        }
+        reference.hashes.push(content_ref.content_hash);
+
+        if let Some(entry) = self.contents.get_mut(&content_ref.content_hash) {
+            entry.ref_count += 1;
+        }
+    }
+
+    /// Release all references held by `blob_id`.
+    ///
+    /// Returns `true` if the blob held any references.
+    pub fn release(&mut self, blob_id: &[u8]) -> bool {
+        let Some(reference) = self.references.remove(blob_id) else {
+            return false;
+        };
+        for hash in &reference.hashes {
+            if let Some(entry) = self.contents.get_mut(hash) {
+                entry.ref_count = entry.ref_count.saturating_sub(1);
+            }
+        }
+        true
+    }
+
+    /// Number of blobs referencing a content hash.
+    pub fn ref_count(&self, hash: &[u8; 32]) -> usize {
+        self.contents.get(hash).map_or(0, |entry| entry.ref_count)
+    }
+
+    /// Check if any blob references a content hash.
+    pub fn is_referenced(&self, hash: &[u8; 32]) -> bool {
+        self.ref_count(hash) > 0
+    }
+
+    /// Total ciphertext bytes of all tracked content.
+    pub fn tracked_bytes(&self) -> u64 {
+        self.contents
+            .values()
+            .map(|entry| entry.content_ref.encrypted_size)
+            .sum()
+    }
+
+    /// Number of tracked content hashes.
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    /// Check if no content is tracked.
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    /// Run a garbage collection pass against `transfer`'s store.
+    ///
+    /// Evicted content is removed from both the store and the tracker.
+    /// Evicting a chunk manifest also removes its chunks (see
+    /// [`ContentTransfer::remove`]).
+    /// Content already missing from the store is dropped from tracking.
+    pub async fn collect_garbage<S: BlobStore>(
+        &mut self,
+        transfer: &ContentTransfer<S>,
+        config: &GcConfig,
+        now: u64,
+    ) -> Result<GcReport, ContentError> {
+        let mut report = GcReport::default();
+
+        // Phase 1: release expired references
+        let expired: Vec<Vec<u8>> = self
+            .references
+            .iter()
+            .filter(|(_, r)| r.expires_at.is_some_and(|t| t <= now))
+            .map(|(blob_id, _)| blob_id.clone())
+            .collect();
+        for blob_id in &expired {
+            self.release(blob_id);
+        }
+        report.expired_references = expired.len();
+
+        // Unreferenced content, oldest first
+        let mut candidates: Vec<([u8; 32], u64)> = self
+            .contents
+            .iter()
+            .filter(|(_, entry)| entry.ref_count == 0)
+            .map(|(hash, entry)| (*hash, entry.added_at))
+            .collect();
+        candidates.sort_by_key(|&(_, added_at)| added_at);
+
+        let mut retained = self.tracked_bytes();
+        for (hash, added_at) in candidates {
+            // Phase 2: past grace period. Phase 3: over budget.
+            let past_grace = added_at.saturating_add(config.grace_period_secs) <= now;
+            let over_budget = config.max_bytes.is_some_and(|max| retained > max);
+            if !past_grace && !over_budget {
+                continue;
+            }
+
+            if let Some(entry) = self.contents.remove(&hash) {
+                transfer.remove(&entry.blob_id, &entry.content_ref).await?;
+                retained -= entry.content_ref.encrypted_size;
+                report.bytes_freed += entry.content_ref.encrypted_size;
+            }
+            report.evicted.push(hash);
+        }
+
+        report.bytes_retained = retained;
+        report.over_budget = config.max_bytes.is_some_and(|max| retained > max);
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    async fn stored_ref(store: &MemoryStore, data: &[u8]) -> ContentRef {
+        let hash = store.put(data).await.unwrap();
+        ContentRef {
+            content_hash: hash,
+            encryption_nonce: [0u8; 24],
+            content_size: data.len().saturating_sub(16) as u64,
+            encrypted_size: data.len() as u64,
+            mode: Default::default(),
+            compressed: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn add_and_release_reference() {
+        let store = MemoryStore::new();
+        let content = stored_ref(&store, b"ciphertext-a").await;
+        let mut tracker = ReferenceTracker::new();
+
+        tracker.add_reference(b"blob-1", &content, None, 100);
+        tracker.add_reference(b"blob-2", &content, None, 100);
+        assert_eq!(tracker.ref_count(&content.content_hash), 2);
+
+        assert!(tracker.release(b"blob-1"));
+        assert_eq!(tracker.ref_count(&content.content_hash), 1);
+        assert!(!tracker.release(b"blob-1"));
+    }
+
+    #[tokio::test]
+    async fn duplicate_reference_counted_once() {
+        let store = MemoryStore::new();
+        let content = stored_ref(&store, b"ciphertext-a").await;
+        let mut tracker = ReferenceTracker::new();
+
+        tracker.add_reference(b"blob-1", &content, None, 100);
+        tracker.add_reference(b"blob-1", &content, None, 100);
+        assert_eq!(tracker.ref_count(&content.content_hash), 1);
+
+        tracker.release(b"blob-1");
+        assert!(!tracker.is_referenced(&content.content_hash));
+    }
+
+    #[tokio::test]
+    async fn gc_keeps_referenced_content() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let content = stored_ref(&store, b"ciphertext-a").await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.add_reference(b"blob-1", &content, None, 100);
+
+        let report = tracker
+            .collect_garbage(&transfer, &GcConfig::new().with_grace_period(0), 10_000)
+            .await
+            .unwrap();
+
+        assert!(report.evicted.is_empty());
+        assert!(store.contains(&content.content_hash).await);
+    }
+
+    #[tokio::test]
+    async fn gc_evicts_unreferenced_after_grace_period() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let content = stored_ref(&store, b"ciphertext-a").await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.track(b"blob-content", &content, 100);
+
+        let config = GcConfig::new().with_grace_period(60);
+
+        // Within grace period: kept
+        let report = tracker
+            .collect_garbage(&transfer, &config, 150)
+            .await
+            .unwrap();
+        assert!(report.evicted.is_empty());
+        assert!(store.contains(&content.content_hash).await);
+
+        // Past grace period: evicted
+        let report = tracker
+            .collect_garbage(&transfer, &config, 160)
+            .await
+            .unwrap();
+        assert_eq!(report.evicted, vec![content.content_hash]);
+        assert_eq!(report.bytes_freed, content.encrypted_size);
+        assert!(!store.contains(&content.content_hash).await);
+        assert!(tracker.is_empty());
+    }
+
+    #[tokio::test]
+    async fn gc_releases_expired_references() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let content = stored_ref(&store, b"ciphertext-a").await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.add_reference(b"blob-1", &content, Some(500), 100);
+
+        let config = GcConfig::new().with_grace_period(0);
+
+        let report = tracker
+            .collect_garbage(&transfer, &config, 499)
+            .await
+            .unwrap();
+        assert_eq!(report.expired_references, 0);
+        assert!(store.contains(&content.content_hash).await);
+
+        let report = tracker
+            .collect_garbage(&transfer, &config, 500)
+            .await
+            .unwrap();
+        assert_eq!(report.expired_references, 1);
+        assert_eq!(report.evicted.len(), 1);
+        assert!(!store.contains(&content.content_hash).await);
+    }
+
+    #[tokio::test]
+    async fn gc_enforces_budget_oldest_first() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let old = stored_ref(&store, &[1u8; 100]).await;
+        let new = stored_ref(&store, &[2u8; 100]).await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.track(b"blob-old", &old, 100);
+        tracker.track(b"blob-new", &new, 200);
+
+        // Both within grace period, but budget only fits one
+        let config = GcConfig::new().with_grace_period(3600).with_max_bytes(150);
+        let report = tracker
+            .collect_garbage(&transfer, &config, 300)
+            .await
+            .unwrap();
+
+        assert_eq!(report.evicted, vec![old.content_hash]);
+        assert_eq!(report.bytes_retained, 100);
+        assert!(!report.over_budget);
+        assert!(store.contains(&new.content_hash).await);
+    }
+
+    #[tokio::test]
+    async fn gc_reports_over_budget_when_all_referenced() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let content = stored_ref(&store, &[3u8; 100]).await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.add_reference(b"blob-1", &content, None, 100);
+
+        let config = GcConfig::new().with_max_bytes(50);
+        let report = tracker
+            .collect_garbage(&transfer, &config, 200)
+            .await
+            .unwrap();
+
+        assert!(report.evicted.is_empty());
+        assert!(report.over_budget);
+        assert!(store.contains(&content.content_hash).await);
+    }
+
+    #[tokio::test]
+    async fn gc_tolerates_content_missing_from_store() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let content = stored_ref(&store, b"ciphertext-a").await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.track(b"blob-content", &content, 0);
+        store.clear();
+
+        let config = GcConfig::new().with_grace_period(0);
+        let report = tracker
+            .collect_garbage(&transfer, &config, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(report.evicted.len(), 1);
+        assert!(tracker.is_empty());
+    }
+
+    #[tokio::test]
+    async fn saved_tracker_reloads_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("references.bin");
+        assert!(ReferenceTracker::load(&path).unwrap().is_empty());
+
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0u8; 32]);
+        let referenced = stored_ref(&store, b"ciphertext-a").await;
+        let orphan = stored_ref(&store, b"ciphertext-b").await;
+        let mut tracker = ReferenceTracker::new();
+        tracker.add_reference(b"blob-1", &referenced, Some(500), 100);
+        tracker.track(b"blob-orphan", &orphan, 100);
+        tracker.save(&path).unwrap();
+
+        // After a restart the reloaded tracker still knows both hashes
+        let mut reloaded = ReferenceTracker::load(&path).unwrap();
+        assert_eq!(reloaded.len(), 2);
+        assert_eq!(reloaded.ref_count(&referenced.content_hash), 1);
+        assert_eq!(reloaded.tracked_bytes(), tracker.tracked_bytes());
+
+        let config = GcConfig::new().with_grace_period(60);
+        let report = reloaded
+            .collect_garbage(&transfer, &config, 200)
+            .await
+            .unwrap();
+        assert_eq!(report.evicted, vec![orphan.content_hash]);
+        let report = reloaded
+            .collect_garbage(&transfer, &config, 500)
+            .await
+            .unwrap();
+        assert_eq!(report.expired_references, 1);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn load_rejects_unknown_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("references.bin");
+        std::fs::write(&path, [0xff, 0x00]).unwrap();
+        assert!(matches!(
+            ReferenceTracker::load(&path),
+            Err(ContentError::StoreError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn gc_evicts_chunks_with_manifest() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0x01; 32]).with_chunk_size(10);
+        let content = transfer.add_chunked(b"blob", &[7u8; 25]).await.unwrap();
+        assert_eq!(store.len(), 4);
+
+        let mut tracker = ReferenceTracker::new();
+        tracker.track(b"blob", &content, 0);
+        let config = GcConfig::new().with_grace_period(0);
+        let report = tracker
+            .collect_garbage(&transfer, &config, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(report.bytes_freed, content.encrypted_size);
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn gc_ignores_tampered_manifest() {
+        let store = MemoryStore::new();
+        let victim = stored_ref(&store, b"someone-elses-ciphertext").await;
+        let transfer = ContentTransfer::new(store.clone(), [0x02; 32]).with_chunk_size(10);
+        let content = transfer.add_chunked(b"blob", &[7u8; 15]).await.unwrap();
+
+        // Rewrite the stored manifest to list the victim as a chunk
+        let blob = store.get(&content.content_hash).await.unwrap();
+        let mut manifest = crate::chunk::ChunkManifest::parse(&blob).unwrap();
+        manifest.chunk_hashes[0] = victim.content_hash;
+        let forged = manifest
+            .seal(&[0u8; 32], &content.encryption_nonce)
+            .unwrap();
+        store.tamper(&content.content_hash, forged);
+
+        let mut tracker = ReferenceTracker::new();
+        tracker.track(b"blob", &content, 0);
+        let config = GcConfig::new().with_grace_period(0);
+        tracker
+            .collect_garbage(&transfer, &config, 10)
+            .await
+            .unwrap();
+
+        assert!(!store.contains(&content.content_hash).await);
+        assert!(store.contains(&victim.content_hash).await);
+    }
+}
@@ -22,6 +22,47 @@
 //! 4. Store ciphertext in blob store using hash as key
 //! 5. Return ContentRef with hash, nonce, and sizes
 //!
+//! ## Chunked Content and Range Reads
+//!
+//! [`ContentTransfer::add_chunked`] splits content into independently
+//! encrypted chunks plus a manifest, so [`ContentTransfer::get_range`] can
+//! fetch and decrypt only the chunks covering a byte range.
+//!
+//! ## Compression (opt-in)
+//!
+//! With [`ContentTransfer::with_compression`], `add()` compresses plaintext
+//! before encrypting it, and `get()` decompresses with the `ContentRef`'s
+//! `content_size` as a hard limit. Chunked content is never compressed, so
+//! ranges map directly onto chunks. Each `ContentRef` records whether its
+//! content was compressed, so readers decode it regardless of their own
+//! setting.
+//!
+//! ## Convergent Encryption (opt-in)
+//!
+//! With [`EncryptionMode::Convergent`], the nonce is a group-keyed hash of
+//! the plaintext and the key is derived from that nonce, so identical content
+//! added twice within a group produces identical ciphertext and is stored
+//! once. See [`encrypt_content_convergent`] for the tradeoffs. The mode is
+//! recorded in each `ContentRef`, so readers decrypt content added in either
+//! mode regardless of their own setting.
+//!
+//! ## Garbage Collection
+//!
+//! Content stores only grow unless something tracks which `ContentRef`s are
+//! still live. [`ReferenceTracker`] records blob → content hash references
+//! and [`ReferenceTracker::collect_garbage`] evicts unreferenced or expired
+//! content under an optional disk budget ([`GcConfig`]). The tracker is
+//! saved and reloaded alongside a persistent store so it survives restarts.
+//!
+//! ## Relay Fallback
+//!
+//! Relays configured with `content_enabled` can hold ciphertext by hash, so
+//! a `ContentRef` stays resolvable after the device that added it goes
+//! offline (`SyncClient::upload_content` / `fetch_content`). Each relay
+//! message is capped at 1 MB, so upload chunked content with a smaller chunk
+//! size (e.g. `with_chunk_size(512 * 1024)`) and push the manifest and each
+//! chunk individually.
+//!
 //! ## Example
 //!
 //! ```rust,ignore
@@ -48,17 +89,28 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+mod chunk;
 mod encrypt;
 mod error;
+mod gc;
 mod store;
 
+pub use chunk::DEFAULT_CHUNK_SIZE;
 pub use encrypt::{
-    decrypt_content, derive_content_key, encrypt_content, EncryptedContent, CONTENT_KEY_SIZE,
-    HASH_SIZE, NONCE_SIZE,
+    decrypt_content, derive_content_key, derive_convergent_key, derive_convergent_nonce,
+    encrypt_content, encrypt_content_convergent, EncryptedContent, CONTENT_KEY_SIZE, HASH_SIZE,
+    NONCE_SIZE,
 };
 pub use error::ContentError;
+pub use gc::{GcConfig, GcReport, ReferenceTracker, DEFAULT_GC_GRACE_PERIOD_SECS};
 pub use store::{BlobStore, MemoryStore};
+pub use sync_types::EncryptionMode;
 
+use std::ops::Range;
+
+use chunk::{chunk_nonce, ChunkManifest};
+use encrypt::{seal, MAX_CONTENT_SIZE};
+use sync_core::{compress, Compression};
 use sync_types::ContentRef;
 use zeroize::Zeroize;
 
@@ -69,6 +121,9 @@ use zeroize::Zeroize;
 pub struct ContentTransfer<S: BlobStore> {
     store: S,
     group_secret: [u8; 32],
+    mode: EncryptionMode,
+    chunk_size: usize,
+    compression: Compression,
 }
 
 impl<S: BlobStore> ContentTransfer<S> {
@@ -82,9 +137,44 @@ impl<S: BlobStore> ContentTransfer<S> {
         Self {
             store,
             group_secret,
+            mode: EncryptionMode::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            compression: Compression::None,
         }
     }
 
+    /// Set the plaintext chunk size used by `add_chunked()` (builder pattern).
+    ///
+    /// Clamped to `1..=MAX_CONTENT_SIZE`.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_CONTENT_SIZE);
+        self
+    }
+
+    /// Set the encryption mode used by `add()` and `add_chunked()` (builder pattern).
+    ///
+    /// Reads follow the mode recorded in each `ContentRef`.
+    pub fn with_mode(mut self, mode: EncryptionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Get the encryption mode.
+    pub fn mode(&self) -> EncryptionMode {
+        self.mode
+    }
+
+    /// Compress content before encryption in `add()` (builder pattern).
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Get the compression setting.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
     /// Add plaintext content to the store.
     ///
     /// This encrypts the content, stores it, and returns a `ContentRef`
@@ -92,36 +182,37 @@ impl<S: BlobStore> ContentTransfer<S> {
     ///
     /// # Arguments
     ///
-    /// * `blob_id` - Unique identifier for this content (used in key derivation)
+    /// * `blob_id` - Unique identifier for this content (used in key derivation,
+    ///   ignored in [`EncryptionMode::Convergent`])
     /// * `plaintext` - The content to encrypt and store
     ///
     /// # Returns
     ///
     /// A `ContentRef` containing the hash, nonce, and sizes needed to retrieve
-    /// and decrypt the content.
+    /// and decrypt the content. `content_size` is always the uncompressed size.
     pub async fn add(&self, blob_id: &[u8], plaintext: &[u8]) -> Result<ContentRef, ContentError> {
-        // Derive content-specific key
-        let content_key = derive_content_key(&self.group_secret, blob_id);
+        // Compress before encrypting (ciphertext doesn't compress)
+        let encoded = self.compression.encode(plaintext);
 
         // Encrypt and hash
-        let (ciphertext, nonce, hash) = encrypt_content(&content_key, plaintext)?;
+        let (ciphertext, nonce, hash) = match self.mode {
+            EncryptionMode::Random => {
+                let content_key = derive_content_key(&self.group_secret, blob_id);
+                encrypt_content(&content_key, &encoded)?
+            }
+            EncryptionMode::Convergent => encrypt_content_convergent(&self.group_secret, &encoded)?,
+        };
 
         // Store ciphertext
-        let stored_hash = self.store.put(&ciphertext).await?;
-
-        // Verify hash matches (should always be true)
-        if hash != stored_hash {
-            return Err(ContentError::HashMismatch {
-                expected: hex::encode(hash),
-                actual: hex::encode(stored_hash),
-            });
-        }
+        self.put_verified(&ciphertext, hash).await?;
 
         Ok(ContentRef {
             content_hash: hash,
             encryption_nonce: nonce,
             content_size: plaintext.len() as u64,
             encrypted_size: ciphertext.len() as u64,
+            mode: self.mode,
+            compressed: self.compression.is_enabled(),
         })
     }
 
@@ -146,21 +237,120 @@ impl<S: BlobStore> ContentTransfer<S> {
         blob_id: &[u8],
         content_ref: &ContentRef,
     ) -> Result<Vec<u8>, ContentError> {
-        // Get ciphertext from store
-        let ciphertext = self.store.get(&content_ref.content_hash).await?;
+        let blob = self.fetch_verified(&content_ref.content_hash).await?;
+        let nonce = &content_ref.encryption_nonce;
+        let content_key = self.content_key(content_ref.mode, blob_id, nonce);
+
+        if let Some(manifest) = open_manifest(&blob, &content_key, nonce) {
+            check_manifest_size(&manifest, content_ref)?;
+            let all = manifest.covering_chunks(0, manifest.content_size);
+            return self.read_chunks(&manifest, all, &content_key, nonce).await;
+        }
 
-        // Verify hash (detect corruption)
-        let actual_hash = *blake3::hash(&ciphertext).as_bytes();
-        if actual_hash != content_ref.content_hash {
-            return Err(ContentError::HashMismatch {
-                expected: hex::encode(content_ref.content_hash),
-                actual: hex::encode(actual_hash),
-            });
+        let decrypted = decrypt_content(&content_key, nonce, &blob)?;
+        decode(decrypted, content_ref)
+    }
+
+    /// Add plaintext content as independently encrypted chunks.
+    ///
+    /// Each chunk of `chunk_size` bytes is encrypted and stored as its own
+    /// blob; the returned `ContentRef` points at a manifest listing them.
+    /// This enables [`get_range()`](Self::get_range) to fetch only the chunks
+    /// it needs. `encrypted_size` is the total of all stored blobs.
+    ///
+    /// The F-017 size limit applies per chunk, not to the whole content.
+    /// Chunks are never compressed, regardless of the compression setting.
+    pub async fn add_chunked(
+        &self,
+        blob_id: &[u8],
+        plaintext: &[u8],
+    ) -> Result<ContentRef, ContentError> {
+        let base_nonce = match self.mode {
+            EncryptionMode::Random => {
+                let mut nonce = [0u8; NONCE_SIZE];
+                getrandom::getrandom(&mut nonce)
+                    .map_err(|e| ContentError::EncryptionFailed(e.to_string()))?;
+                nonce
+            }
+            EncryptionMode::Convergent => derive_convergent_nonce(&self.group_secret, plaintext),
+        };
+        let content_key = self.content_key(self.mode, blob_id, &base_nonce);
+
+        let mut chunk_hashes = Vec::with_capacity(plaintext.len().div_ceil(self.chunk_size));
+        let mut encrypted_size = 0u64;
+        for (index, chunk) in plaintext.chunks(self.chunk_size).enumerate() {
+            let nonce = chunk_nonce(&base_nonce, ChunkManifest::chunk_nonce_index(index));
+            let (ciphertext, _, hash) = seal(&content_key, nonce, chunk)?;
+            self.put_verified(&ciphertext, hash).await?;
+            encrypted_size += ciphertext.len() as u64;
+            chunk_hashes.push(hash);
         }
 
-        // Derive content key and decrypt
-        let content_key = derive_content_key(&self.group_secret, blob_id);
-        decrypt_content(&content_key, &content_ref.encryption_nonce, &ciphertext)
+        let manifest = ChunkManifest {
+            chunk_size: self.chunk_size as u32,
+            content_size: plaintext.len() as u64,
+            chunk_hashes,
+        };
+        let blob = manifest.seal(&content_key, &base_nonce)?;
+        let hash = *blake3::hash(&blob).as_bytes();
+        self.put_verified(&blob, hash).await?;
+
+        Ok(ContentRef {
+            content_hash: hash,
+            encryption_nonce: base_nonce,
+            content_size: plaintext.len() as u64,
+            encrypted_size: encrypted_size + blob.len() as u64,
+            mode: self.mode,
+            compressed: false,
+        })
+    }
+
+    /// Retrieve and decrypt `len` bytes starting at `offset`.
+    ///
+    /// For content added with `add_chunked()`, only the manifest and the
+    /// chunks covering the range are fetched from the store. Content added
+    /// with `add()` is a single blob and is fetched and decrypted in full.
+    ///
+    /// # Errors
+    ///
+    /// - `RangeOutOfBounds` if `offset + len` exceeds the content size
+    /// - Otherwise the same errors as [`get()`](Self::get)
+    pub async fn get_range(
+        &self,
+        blob_id: &[u8],
+        content_ref: &ContentRef,
+        offset: u64,
+        len: u64,
+    ) -> Result<Vec<u8>, ContentError> {
+        let out_of_bounds = |size| ContentError::RangeOutOfBounds { offset, len, size };
+        let end = offset
+            .checked_add(len)
+            .filter(|&end| end <= content_ref.content_size)
+            .ok_or_else(|| out_of_bounds(content_ref.content_size))?;
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let blob = self.fetch_verified(&content_ref.content_hash).await?;
+        let nonce = &content_ref.encryption_nonce;
+        let content_key = self.content_key(content_ref.mode, blob_id, nonce);
+
+        if let Some(manifest) = open_manifest(&blob, &content_key, nonce) {
+            check_manifest_size(&manifest, content_ref)?;
+            let chunks = manifest.covering_chunks(offset, len);
+            let start = (offset - manifest.chunk_start(chunks.start)) as usize;
+            let data = self
+                .read_chunks(&manifest, chunks, &content_key, nonce)
+                .await?;
+            return Ok(data[start..start + len as usize].to_vec());
+        }
+
+        let decrypted = decrypt_content(&content_key, nonce, &blob)?;
+        let plaintext = decode(decrypted, content_ref)?;
+        plaintext
+            .get(offset as usize..end as usize)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| out_of_bounds(plaintext.len() as u64))
     }
 
     /// Check if content exists in the store.
@@ -170,15 +360,142 @@ impl<S: BlobStore> ContentTransfer<S> {
 
     /// Remove content from the store.
     ///
+    /// Chunked content is removed together with all of its chunks. Chunks
+    /// are only removed if the stored manifest opens under this
+    /// `ContentRef`'s key (same `blob_id` as for `get()`); any other blob
+    /// is removed on its own, so a forged manifest cannot name someone
+    /// else's content for deletion.
     /// Returns `Ok(true)` if removed, `Ok(false)` if not found.
-    pub async fn remove(&self, content_ref: &ContentRef) -> Result<bool, ContentError> {
-        self.store.remove(&content_ref.content_hash).await
+    pub async fn remove(
+        &self,
+        blob_id: &[u8],
+        content_ref: &ContentRef,
+    ) -> Result<bool, ContentError> {
+        let hash = &content_ref.content_hash;
+        if let Ok(blob) = self.fetch_verified(hash).await {
+            let nonce = &content_ref.encryption_nonce;
+            let content_key = self.content_key(content_ref.mode, blob_id, nonce);
+            if let Some(manifest) = open_manifest(&blob, &content_key, nonce) {
+                for chunk_hash in &manifest.chunk_hashes {
+                    self.store.remove(chunk_hash).await?;
+                }
+            }
+        }
+        self.store.remove(hash).await
     }
 
     /// Get a reference to the underlying store.
     pub fn store(&self) -> &S {
         &self.store
     }
+
+    /// Derive the content key for `mode`.
+    fn content_key(
+        &self,
+        mode: EncryptionMode,
+        blob_id: &[u8],
+        nonce: &[u8; NONCE_SIZE],
+    ) -> [u8; CONTENT_KEY_SIZE] {
+        match mode {
+            EncryptionMode::Random => derive_content_key(&self.group_secret, blob_id),
+            EncryptionMode::Convergent => derive_convergent_key(&self.group_secret, nonce),
+        }
+    }
+
+    /// Store ciphertext and check the store agrees on its hash.
+    ///
+    /// Convergent content that is already present is not written again.
+    async fn put_verified(&self, ciphertext: &[u8], hash: [u8; 32]) -> Result<(), ContentError> {
+        let stored_hash =
+            if self.mode == EncryptionMode::Convergent && self.store.contains(&hash).await {
+                hash
+            } else {
+                self.store.put(ciphertext).await?
+            };
+
+        // Verify hash matches (should always be true)
+        if hash != stored_hash {
+            return Err(ContentError::HashMismatch {
+                expected: hex::encode(hash),
+                actual: hex::encode(stored_hash),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetch a blob and verify its hash (detect corruption).
+    async fn fetch_verified(&self, hash: &[u8; 32]) -> Result<Vec<u8>, ContentError> {
+        let blob = self.store.get(hash).await?;
+        let actual_hash = *blake3::hash(&blob).as_bytes();
+        if actual_hash != *hash {
+            return Err(ContentError::HashMismatch {
+                expected: hex::encode(hash),
+                actual: hex::encode(actual_hash),
+            });
+        }
+        Ok(blob)
+    }
+
+    /// Fetch, verify and decrypt a run of chunks, concatenated in order.
+    async fn read_chunks(
+        &self,
+        manifest: &ChunkManifest,
+        chunks: Range<usize>,
+        content_key: &[u8; CONTENT_KEY_SIZE],
+        base_nonce: &[u8; NONCE_SIZE],
+    ) -> Result<Vec<u8>, ContentError> {
+        let mut out = Vec::new();
+        for index in chunks {
+            let ciphertext = self.fetch_verified(&manifest.chunk_hashes[index]).await?;
+            let nonce = chunk_nonce(base_nonce, ChunkManifest::chunk_nonce_index(index));
+            let plaintext = decrypt_content(content_key, &nonce, &ciphertext)?;
+            if plaintext.len() != manifest.chunk_len(index) {
+                return Err(ContentError::InvalidManifest(format!(
+                    "chunk {index} has unexpected length"
+                )));
+            }
+            out.extend_from_slice(&plaintext);
+        }
+        Ok(out)
+    }
+}
+
+/// Undo `add()`'s compression, bounded by the advertised content size.
+///
+/// Follows the `ContentRef` rather than the reader's setting, so content
+/// added by a peer with a different compression setting still decodes.
+fn decode(decrypted: Vec<u8>, content_ref: &ContentRef) -> Result<Vec<u8>, ContentError> {
+    if !content_ref.compressed {
+        return Ok(decrypted);
+    }
+    let limit = (content_ref.content_size as usize).min(MAX_CONTENT_SIZE);
+    compress::decompress(&decrypted, limit).map_err(|e| ContentError::Decompression(e.to_string()))
+}
+
+/// Check an opened manifest describes as much content as its `ContentRef`.
+fn check_manifest_size(
+    manifest: &ChunkManifest,
+    content_ref: &ContentRef,
+) -> Result<(), ContentError> {
+    if manifest.content_size != content_ref.content_size {
+        return Err(ContentError::InvalidManifest(format!(
+            "content size {} does not match ContentRef ({})",
+            manifest.content_size, content_ref.content_size
+        )));
+    }
+    Ok(())
+}
+
+/// Open `blob` as a chunk manifest, or `None` if it is a single-blob ciphertext.
+fn open_manifest(
+    blob: &[u8],
+    content_key: &[u8; CONTENT_KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+) -> Option<ChunkManifest> {
+    if !ChunkManifest::is_manifest(blob) {
+        return None;
+    }
+    ChunkManifest::open(blob, content_key, nonce).ok()
 }
 
 /// XC-001: Zeroize group_secret on drop to prevent key material lingering in freed memory.
@@ -277,7 +594,7 @@ mod tests {
 
         assert!(transfer.contains(&content_ref).await);
 
-        let removed = transfer.remove(&content_ref).await.unwrap();
+        let removed = transfer.remove(blob_id, &content_ref).await.unwrap();
         assert!(removed);
 
         assert!(!transfer.contains(&content_ref).await);
@@ -332,4 +649,369 @@ mod tests {
 
         assert_eq!(content_ref.content_hash, expected_hash);
     }
+
+    #[tokio::test]
+    async fn convergent_mode_dedups_identical_content() {
+        let store = MemoryStore::new();
+        let transfer =
+            ContentTransfer::new(store, [0x55; 32]).with_mode(EncryptionMode::Convergent);
+
+        let plaintext = b"Same photo from two phones";
+        let ref_a = transfer.add(b"phone-a-blob", plaintext).await.unwrap();
+        let ref_b = transfer.add(b"phone-b-blob", plaintext).await.unwrap();
+
+        assert_eq!(ref_a, ref_b);
+        assert_eq!(transfer.store().len(), 1);
+
+        // blob_id is not part of the key in convergent mode
+        let retrieved = transfer.get(b"any-blob-id", &ref_a).await.unwrap();
+        assert_eq!(retrieved, plaintext);
+    }
+
+    #[tokio::test]
+    async fn random_mode_does_not_dedup() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x66; 32]);
+        assert_eq!(transfer.mode(), EncryptionMode::Random);
+
+        let plaintext = b"Same photo from two phones";
+        let ref_a = transfer.add(b"phone-a-blob", plaintext).await.unwrap();
+        let ref_b = transfer.add(b"phone-b-blob", plaintext).await.unwrap();
+
+        assert_ne!(ref_a.content_hash, ref_b.content_hash);
+        assert_eq!(transfer.store().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn convergent_mode_isolated_between_groups() {
+        let store = MemoryStore::new();
+        let group_a =
+            ContentTransfer::new(store.clone(), [0x01; 32]).with_mode(EncryptionMode::Convergent);
+        let group_b = ContentTransfer::new(store, [0x02; 32]).with_mode(EncryptionMode::Convergent);
+
+        let plaintext = b"Shared file";
+        let ref_a = group_a.add(b"blob", plaintext).await.unwrap();
+        let ref_b = group_b.add(b"blob", plaintext).await.unwrap();
+
+        assert_ne!(ref_a.content_hash, ref_b.content_hash);
+        assert!(matches!(
+            group_b.get(b"blob", &ref_a).await,
+            Err(ContentError::DecryptionFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reads_follow_the_mode_in_the_content_ref() {
+        let store = MemoryStore::new();
+        let random = ContentTransfer::new(store.clone(), [0x03; 32]);
+        let convergent =
+            ContentTransfer::new(store, [0x03; 32]).with_mode(EncryptionMode::Convergent);
+
+        let plaintext = b"Shared across devices with different modes";
+        let random_ref = random.add(b"blob-r", plaintext).await.unwrap();
+        let convergent_ref = convergent.add(b"blob-c", plaintext).await.unwrap();
+        assert_eq!(random_ref.mode, EncryptionMode::Random);
+        assert_eq!(convergent_ref.mode, EncryptionMode::Convergent);
+
+        // Each side reads content added in the other mode
+        assert_eq!(
+            convergent.get(b"blob-r", &random_ref).await.unwrap(),
+            plaintext
+        );
+        assert_eq!(
+            random.get(b"blob-c", &convergent_ref).await.unwrap(),
+            plaintext
+        );
+
+        let chunked = convergent.add_chunked(b"blob-k", plaintext).await.unwrap();
+        assert_eq!(
+            random.get_range(b"blob-k", &chunked, 7, 6).await.unwrap(),
+            &plaintext[7..13]
+        );
+    }
+
+    #[tokio::test]
+    async fn chunked_content_roundtrip() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x77; 32]).with_chunk_size(1000);
+
+        let blob_id = b"checkpoint";
+        let plaintext: Vec<u8> = (0..4500).map(|i| (i % 251) as u8).collect();
+
+        let content_ref = transfer.add_chunked(blob_id, &plaintext).await.unwrap();
+        assert_eq!(content_ref.content_size, 4500);
+        // 5 chunks + manifest
+        assert_eq!(transfer.store().len(), 6);
+
+        let retrieved = transfer.get(blob_id, &content_ref).await.unwrap();
+        assert_eq!(retrieved, plaintext);
+    }
+
+    #[tokio::test]
+    async fn get_range_fetches_only_covering_chunks() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x78; 32]).with_chunk_size(1000);
+
+        let blob_id = b"video";
+        let plaintext: Vec<u8> = (0..4500).map(|i| (i % 251) as u8).collect();
+        let content_ref = transfer.add_chunked(blob_id, &plaintext).await.unwrap();
+
+        // Drop every chunk except the ones covering bytes 1500..2500
+        let blob = transfer
+            .store()
+            .get(&content_ref.content_hash)
+            .await
+            .unwrap();
+        let manifest = ChunkManifest::parse(&blob).unwrap();
+        for (index, hash) in manifest.chunk_hashes.iter().enumerate() {
+            if index != 1 && index != 2 {
+                transfer.store().remove(hash).await.unwrap();
+            }
+        }
+
+        let range = transfer
+            .get_range(blob_id, &content_ref, 1500, 1000)
+            .await
+            .unwrap();
+        assert_eq!(range, &plaintext[1500..2500]);
+
+        // Full read needs the missing chunks
+        assert!(matches!(
+            transfer.get(blob_id, &content_ref).await,
+            Err(ContentError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_range_edges() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x79; 32]).with_chunk_size(100);
+
+        let blob_id = b"edges";
+        let plaintext: Vec<u8> = (0..250).map(|i| i as u8).collect();
+        let content_ref = transfer.add_chunked(blob_id, &plaintext).await.unwrap();
+
+        let tail = transfer
+            .get_range(blob_id, &content_ref, 200, 50)
+            .await
+            .unwrap();
+        assert_eq!(tail, &plaintext[200..]);
+
+        let empty = transfer
+            .get_range(blob_id, &content_ref, 250, 0)
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+
+        assert!(matches!(
+            transfer.get_range(blob_id, &content_ref, 200, 51).await,
+            Err(ContentError::RangeOutOfBounds { size: 250, .. })
+        ));
+        assert!(matches!(
+            transfer.get_range(blob_id, &content_ref, u64::MAX, 2).await,
+            Err(ContentError::RangeOutOfBounds { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_range_on_single_blob_content() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x7A; 32]);
+
+        let blob_id = b"single";
+        let plaintext = b"The quick brown fox";
+        let content_ref = transfer.add(blob_id, plaintext).await.unwrap();
+
+        let range = transfer
+            .get_range(blob_id, &content_ref, 4, 5)
+            .await
+            .unwrap();
+        assert_eq!(range, b"quick");
+    }
+
+    #[tokio::test]
+    async fn chunked_wrong_blob_id_fails() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x7B; 32]).with_chunk_size(10);
+
+        let content_ref = transfer
+            .add_chunked(b"right", b"chunked secret content")
+            .await
+            .unwrap();
+
+        // Manifest fails authentication, single-blob fallback fails too
+        assert!(matches!(
+            transfer.get_range(b"wrong", &content_ref, 0, 5).await,
+            Err(ContentError::DecryptionFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn remove_chunked_removes_all_chunks() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x7C; 32]).with_chunk_size(10);
+
+        let content_ref = transfer.add_chunked(b"blob", &[0x42; 35]).await.unwrap();
+        assert_eq!(transfer.store().len(), 5);
+
+        assert!(transfer.remove(b"blob", &content_ref).await.unwrap());
+        assert!(transfer.store().is_empty());
+    }
+
+    #[tokio::test]
+    async fn remove_ignores_tampered_manifest() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0x7E; 32]).with_chunk_size(10);
+        let victim = transfer.add(b"victim", b"unrelated content").await.unwrap();
+        let content_ref = transfer.add_chunked(b"blob", &[0x42; 15]).await.unwrap();
+
+        // Rewrite the stored manifest to list the victim as a chunk
+        let blob = store.get(&content_ref.content_hash).await.unwrap();
+        let mut manifest = ChunkManifest::parse(&blob).unwrap();
+        manifest.chunk_hashes[0] = victim.content_hash;
+        let forged = manifest
+            .seal(&[0u8; 32], &content_ref.encryption_nonce)
+            .unwrap();
+        store.tamper(&content_ref.content_hash, forged);
+
+        assert!(transfer.remove(b"blob", &content_ref).await.unwrap());
+        assert!(transfer.contains(&victim).await);
+    }
+
+    #[tokio::test]
+    async fn remove_ignores_manifest_sealed_under_another_key() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store.clone(), [0x7E; 32]).with_chunk_size(10);
+        let victim = transfer.add(b"victim", b"unrelated content").await.unwrap();
+
+        // A well-formed manifest, stored under its true hash, listing the
+        // victim, but sealed under a key this transfer does not derive
+        let manifest = ChunkManifest {
+            chunk_size: 10,
+            content_size: 5,
+            chunk_hashes: vec![victim.content_hash],
+        };
+        let nonce = [0x01; NONCE_SIZE];
+        let blob = manifest.seal(&[0x99; 32], &nonce).unwrap();
+        let hash = store.put(&blob).await.unwrap();
+        let forged = ContentRef {
+            content_hash: hash,
+            encryption_nonce: nonce,
+            content_size: 5,
+            encrypted_size: blob.len() as u64,
+            mode: EncryptionMode::Random,
+            compressed: false,
+        };
+
+        assert!(transfer.remove(b"blob", &forged).await.unwrap());
+        assert!(!store.contains(&hash).await);
+        assert!(transfer.contains(&victim).await);
+    }
+
+    #[tokio::test]
+    async fn chunked_get_checks_content_size() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x7F; 32]).with_chunk_size(10);
+        let mut content_ref = transfer.add_chunked(b"blob", &[0x42; 25]).await.unwrap();
+
+        content_ref.content_size = 20;
+        assert!(matches!(
+            transfer.get(b"blob", &content_ref).await,
+            Err(ContentError::InvalidManifest(_))
+        ));
+        assert!(matches!(
+            transfer.get_range(b"blob", &content_ref, 0, 5).await,
+            Err(ContentError::InvalidManifest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn convergent_chunked_dedups() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x7D; 32])
+            .with_mode(EncryptionMode::Convergent)
+            .with_chunk_size(10);
+
+        let plaintext = [0x24; 35];
+        let ref_a = transfer.add_chunked(b"a", &plaintext).await.unwrap();
+        let ref_b = transfer.add_chunked(b"b", &plaintext).await.unwrap();
+
+        assert_eq!(ref_a, ref_b);
+        assert_eq!(transfer.store().len(), 5);
+        let range = transfer.get_range(b"b", &ref_a, 8, 4).await.unwrap();
+        assert_eq!(range, &plaintext[8..12]);
+    }
+
+    #[tokio::test]
+    async fn compressed_content_roundtrip() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x81; 32]).with_compression(Compression::Zstd);
+
+        let plaintext = br#"{"note":"hello","tags":["a","b","c"]}"#.repeat(100);
+        let content_ref = transfer.add(b"doc", &plaintext).await.unwrap();
+
+        // Sizes: content_size is uncompressed, encrypted_size reflects compression
+        assert_eq!(content_ref.content_size, plaintext.len() as u64);
+        assert!(content_ref.encrypted_size * 5 < content_ref.content_size);
+
+        let retrieved = transfer.get(b"doc", &content_ref).await.unwrap();
+        assert_eq!(retrieved, plaintext);
+        let range = transfer
+            .get_range(b"doc", &content_ref, 10, 20)
+            .await
+            .unwrap();
+        assert_eq!(range, &plaintext[10..30]);
+    }
+
+    #[tokio::test]
+    async fn readers_follow_the_refs_compression() {
+        let secret = [0x84; 32];
+        let plaintext = br#"{"note":"hello","tags":["a","b","c"]}"#.repeat(100);
+
+        // Zstd writer, uncompressed reader
+        let writer =
+            ContentTransfer::new(MemoryStore::new(), secret).with_compression(Compression::Zstd);
+        let content_ref = writer.add(b"doc", &plaintext).await.unwrap();
+        assert!(content_ref.compressed);
+        let reader = ContentTransfer::new(writer.store().clone(), secret);
+        assert_eq!(reader.get(b"doc", &content_ref).await.unwrap(), plaintext);
+
+        // Uncompressed writer, LZ4 reader
+        let writer = ContentTransfer::new(MemoryStore::new(), secret);
+        let content_ref = writer.add(b"doc", &plaintext).await.unwrap();
+        assert!(!content_ref.compressed);
+        let reader =
+            ContentTransfer::new(writer.store().clone(), secret).with_compression(Compression::Lz4);
+        assert_eq!(reader.get(b"doc", &content_ref).await.unwrap(), plaintext);
+    }
+
+    #[tokio::test]
+    async fn compressed_content_limited_by_content_size() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x82; 32]).with_compression(Compression::Lz4);
+
+        let plaintext = vec![0u8; 64 * 1024];
+        let mut content_ref = transfer.add(b"zeros", &plaintext).await.unwrap();
+
+        // A ref understating the size must not let more data through
+        content_ref.content_size = 1024;
+        let result = transfer.get(b"zeros", &content_ref).await;
+        assert!(matches!(result, Err(ContentError::Decompression(_))));
+    }
+
+    #[tokio::test]
+    async fn convergent_compressed_still_dedups() {
+        let store = MemoryStore::new();
+        let transfer = ContentTransfer::new(store, [0x83; 32])
+            .with_mode(EncryptionMode::Convergent)
+            .with_compression(Compression::Zstd);
+
+        let plaintext = b"same compressible content ".repeat(40);
+        let ref_a = transfer.add(b"a", &plaintext).await.unwrap();
+        let ref_b = transfer.add(b"b", &plaintext).await.unwrap();
+
+        assert_eq!(ref_a, ref_b);
+        assert_eq!(transfer.store().len(), 1);
+        assert_eq!(transfer.get(b"b", &ref_a).await.unwrap(), plaintext);
+    }
 }
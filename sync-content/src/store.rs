@@ -65,6 +65,12 @@ impl MemoryStore {
     pub fn clear(&self) {
         self.blobs.lock().unwrap().clear();
     }
+
+    /// Overwrite the bytes stored under `hash` (simulates a tampered store).
+    #[cfg(test)]
+    pub(crate) fn tamper(&self, hash: &[u8; 32], bytes: Vec<u8>) {
+        self.blobs.lock().unwrap().insert(*hash, bytes);
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,513 @@
+//! Hybrid post-quantum secure channel between client and relay.
+//!
+//! iroh encrypts every connection with QUIC/TLS, but its X25519 key exchange
+//! falls to a future quantum computer, so recorded sessions could be
+//! decrypted later ("harvest now, decrypt later"). This module runs a second
+//! handshake inside the iroh stream that combines X25519 with ML-KEM-768:
+//! session keys stay secret unless *both* are broken.
+//!
+//! Handshake (`Noise_XXpsk3_25519_ChaChaPoly_BLAKE2s`):
+//!
+//! ```text
+//! → e                  payload: ML-KEM-768 encapsulation key
+//! ← e, ee, s, es       payload: ML-KEM-768 ciphertext
+//! → s, se, psk         payload: first application message (HELLO)
+//! ```
+//!
+//! The ML-KEM shared secret fills the Noise PSK slot, so it is mixed into
+//! the transport keys together with the X25519 results.
+//!
+//! The relay's static key is stable across restarts, so clients can pin the
+//! [`SecureChannel::remote_static`] key they learn from an invite.
+//!
+//! Only request/response traffic is sealed. NOTIFY and BYE, which the relay
+//! pushes on its own streams, still travel under QUIC/TLS alone.
+//!
+//! Sealed frame format (after the handshake):
+//!
+//! ```text
+//! [nonce: u64 LE][chunk 0][chunk 1]...
+//! ```
+//!
+//! Each chunk is one Noise transport message (at most 65535 bytes) encrypted
+//! under `nonce + i`. The first chunk's plaintext starts with the total
+//! length (u32 LE) so a frame cut at a chunk boundary is rejected.
+
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use ml_kem::kem::{Decapsulate, Encapsulate};
+use ml_kem::{EncodedSizeUser, KemCore, MlKem768};
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+/// Noise protocol name used by the handshake.
+pub const NOISE_PARAMS: &str = "Noise_XXpsk3_25519_ChaChaPoly_BLAKE2s";
+
+/// Bound into the handshake transcript; changing it breaks compatibility.
+const PROLOGUE: &[u8] = b"0k-sync hybrid channel v1";
+
+/// PSK slot that carries the ML-KEM shared secret.
+const KEM_PSK_LOCATION: usize = 3;
+
+/// Largest Noise message (protocol limit).
+const NOISE_MAX_MESSAGE: usize = 65535;
+
+/// ChaChaPoly authentication tag size.
+const NOISE_TAG_SIZE: usize = 16;
+
+/// Largest plaintext carried by one chunk.
+const MAX_CHUNK_PLAINTEXT: usize = NOISE_MAX_MESSAGE - NOISE_TAG_SIZE;
+
+const NONCE_SIZE: usize = 8;
+const LENGTH_SIZE: usize = 4;
+
+type EncapsulationKey = <MlKem768 as KemCore>::EncapsulationKey;
+type DecapsulationKey = <MlKem768 as KemCore>::DecapsulationKey;
+
+/// Error type for secure channel operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelError {
+    /// The handshake failed (malformed message, failed authentication, or
+    /// mismatched ML-KEM secret).
+    Handshake(String),
+    /// A sealed frame failed authentication.
+    Decrypt,
+    /// A sealed frame is malformed or truncated.
+    Malformed,
+    /// A sealed frame reused a nonce that was already consumed.
+    Replay {
+        /// Nonce carried by the frame.
+        nonce: u64,
+        /// Lowest nonce still acceptable.
+        expected: u64,
+    },
+    /// The nonce space is exhausted; a new handshake is required.
+    NonceExhausted,
+}
+
+impl std::fmt::Display for ChannelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelError::Handshake(msg) => write!(f, "secure channel handshake failed: {}", msg),
+            ChannelError::Decrypt => write!(f, "sealed frame failed authentication"),
+            ChannelError::Malformed => write!(f, "sealed frame is malformed"),
+            ChannelError::Replay { nonce, expected } => {
+                write!(f, "sealed frame replayed (nonce {} < {})", nonce, expected)
+            }
+            ChannelError::NonceExhausted => write!(f, "secure channel nonce space exhausted"),
+        }
+    }
+}
+
+impl std::error::Error for ChannelError {}
+
+fn handshake_error(e: impl std::fmt::Display) -> ChannelError {
+    ChannelError::Handshake(e.to_string())
+}
+
+fn noise_builder(prologue: &[u8]) -> snow::Builder<'_> {
+    snow::Builder::new(NOISE_PARAMS.parse().expect("valid Noise parameters")).prologue(prologue)
+}
+
+/// Static X25519 identity used in the handshake.
+#[derive(Clone)]
+pub struct NoiseKeypair {
+    private: Zeroizing<Vec<u8>>,
+    public: [u8; 32],
+}
+
+impl NoiseKeypair {
+    /// Generate a new random keypair.
+    pub fn generate() -> Self {
+        let keypair = noise_builder(PROLOGUE)
+            .generate_keypair()
+            .expect("keypair generation failed");
+        let mut public = [0u8; 32];
+        public.copy_from_slice(&keypair.public);
+        Self {
+            private: Zeroizing::new(keypair.private),
+            public,
+        }
+    }
+
+    /// Rebuild a keypair from a stored private key.
+    pub fn from_private_bytes(private: [u8; 32]) -> Self {
+        let public = MontgomeryPoint::mul_base_clamped(private).to_bytes();
+        Self {
+            private: Zeroizing::new(private.to_vec()),
+            public,
+        }
+    }
+
+    /// The private key, for persisting the keypair.
+    pub fn private_bytes(&self) -> Zeroizing<[u8; 32]> {
+        let mut private = Zeroizing::new([0u8; 32]);
+        private.copy_from_slice(&self.private);
+        private
+    }
+
+    /// The public key (sent to the peer during the handshake).
+    pub fn public_key(&self) -> &[u8; 32] {
+        &self.public
+    }
+}
+
+// Intentionally opaque debug to avoid logging the private key
+impl std::fmt::Debug for NoiseKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NoiseKeypair")
+            .field("public", &self.public)
+            .field("private", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Client side of the handshake, waiting for the relay's reply.
+pub struct HandshakeInitiator {
+    noise: snow::HandshakeState,
+    kem_key: DecapsulationKey,
+}
+
+impl HandshakeInitiator {
+    /// Start a handshake, returning the state and the first message.
+    pub fn start(local: &NoiseKeypair) -> Result<(Self, Vec<u8>), ChannelError> {
+        Self::start_with_prologue(local, PROLOGUE)
+    }
+
+    /// Start a handshake bound to a caller-supplied prologue.
+    ///
+    /// Both sides must use the same prologue or the handshake fails.
+    pub(crate) fn start_with_prologue(
+        local: &NoiseKeypair,
+        prologue: &[u8],
+    ) -> Result<(Self, Vec<u8>), ChannelError> {
+        let mut noise = noise_builder(prologue)
+            .local_private_key(&local.private[..])
+            .build_initiator()
+            .map_err(handshake_error)?;
+        let (kem_key, encapsulation_key) = MlKem768::generate(&mut OsRng);
+        let message = write_handshake(&mut noise, &encapsulation_key.as_bytes())?;
+        Ok((Self { noise, kem_key }, message))
+    }
+
+    /// Process the relay's reply and produce the final message, which
+    /// carries `payload` encrypted under the hybrid keys.
+    pub fn finish(
+        mut self,
+        reply: &[u8],
+        payload: &[u8],
+    ) -> Result<(SecureChannel, Vec<u8>), ChannelError> {
+        let ciphertext_bytes = read_handshake(&mut self.noise, reply)?;
+        let ciphertext = ml_kem::Ciphertext::<MlKem768>::try_from(ciphertext_bytes.as_slice())
+            .map_err(|_| ChannelError::Handshake("malformed ML-KEM ciphertext".into()))?;
+        let shared = self
+            .kem_key
+            .decapsulate(&ciphertext)
+            .map_err(|_| ChannelError::Handshake("ML-KEM decapsulation failed".into()))?;
+        self.noise
+            .set_psk(KEM_PSK_LOCATION, &shared[..])
+            .map_err(handshake_error)?;
+
+        let message = write_handshake(&mut self.noise, payload)?;
+        Ok((SecureChannel::new(self.noise)?, message))
+    }
+}
+
+/// Relay side of the handshake, waiting for the client's final message.
+pub struct HandshakeResponder {
+    noise: snow::HandshakeState,
+}
+
+impl HandshakeResponder {
+    /// Process a client's first message, returning the state and the reply.
+    pub fn respond(local: &NoiseKeypair, message: &[u8]) -> Result<(Self, Vec<u8>), ChannelError> {
+        Self::respond_with_prologue(local, PROLOGUE, message)
+    }
+
+    /// Respond to a handshake bound to a caller-supplied prologue.
+    pub(crate) fn respond_with_prologue(
+        local: &NoiseKeypair,
+        prologue: &[u8],
+        message: &[u8],
+    ) -> Result<(Self, Vec<u8>), ChannelError> {
+        let mut noise = noise_builder(prologue)
+            .local_private_key(&local.private[..])
+            .build_responder()
+            .map_err(handshake_error)?;
+
+        let key_bytes = read_handshake(&mut noise, message)?;
+        let encoded = ml_kem::Encoded::<EncapsulationKey>::try_from(key_bytes.as_slice())
+            .map_err(|_| ChannelError::Handshake("malformed ML-KEM encapsulation key".into()))?;
+        let (ciphertext, shared) = EncapsulationKey::from_bytes(&encoded)
+            .encapsulate(&mut OsRng)
+            .map_err(|_| ChannelError::Handshake("ML-KEM encapsulation failed".into()))?;
+        noise
+            .set_psk(KEM_PSK_LOCATION, &shared[..])
+            .map_err(handshake_error)?;
+
+        let reply = write_handshake(&mut noise, &ciphertext)?;
+        Ok((Self { noise }, reply))
+    }
+
+    /// Process the client's final message, returning the channel and the
+    /// payload it carried.
+    pub fn finish(mut self, message: &[u8]) -> Result<(SecureChannel, Vec<u8>), ChannelError> {
+        let payload = read_handshake(&mut self.noise, message)?;
+        Ok((SecureChannel::new(self.noise)?, payload))
+    }
+}
+
+fn write_handshake(
+    noise: &mut snow::HandshakeState,
+    payload: &[u8],
+) -> Result<Vec<u8>, ChannelError> {
+    let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+    let len = noise
+        .write_message(payload, &mut buf)
+        .map_err(handshake_error)?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+fn read_handshake(
+    noise: &mut snow::HandshakeState,
+    message: &[u8],
+) -> Result<Vec<u8>, ChannelError> {
+    let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+    let len = noise
+        .read_message(message, &mut buf)
+        .map_err(handshake_error)?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// An established channel that seals and opens application frames.
+pub struct SecureChannel {
+    transport: snow::StatelessTransportState,
+    send_nonce: u64,
+    recv_nonce: u64,
+}
+
+impl SecureChannel {
+    fn new(noise: snow::HandshakeState) -> Result<Self, ChannelError> {
+        Ok(Self {
+            transport: noise
+                .into_stateless_transport_mode()
+                .map_err(handshake_error)?,
+            send_nonce: 0,
+            recv_nonce: 0,
+        })
+    }
+
+    /// The peer's static public key, as proven during the handshake.
+    pub fn remote_static(&self) -> Option<[u8; 32]> {
+        self.transport
+            .get_remote_static()
+            .and_then(|key| key.try_into().ok())
+    }
+
+    /// Encrypt one application frame.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, ChannelError> {
+        let declared = u32::try_from(plaintext.len()).map_err(|_| ChannelError::Malformed)?;
+        let mut body = Vec::with_capacity(LENGTH_SIZE + plaintext.len());
+        body.extend_from_slice(&declared.to_le_bytes());
+        body.extend_from_slice(plaintext);
+
+        let nonce = self.send_nonce;
+        let chunks = body.len().div_ceil(MAX_CHUNK_PLAINTEXT) as u64;
+        let next_nonce = nonce
+            .checked_add(chunks)
+            .ok_or(ChannelError::NonceExhausted)?;
+
+        let mut frame =
+            Vec::with_capacity(NONCE_SIZE + body.len() + chunks as usize * NOISE_TAG_SIZE);
+        frame.extend_from_slice(&nonce.to_le_bytes());
+        let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+        for (i, chunk) in body.chunks(MAX_CHUNK_PLAINTEXT).enumerate() {
+            let len = self
+                .transport
+                .write_message(nonce + i as u64, chunk, &mut buf)
+                .map_err(|_| ChannelError::NonceExhausted)?;
+            frame.extend_from_slice(&buf[..len]);
+        }
+
+        self.send_nonce = next_nonce;
+        Ok(frame)
+    }
+
+    /// Decrypt one application frame produced by the peer's [`seal`](Self::seal).
+    pub fn open(&mut self, frame: &[u8]) -> Result<Vec<u8>, ChannelError> {
+        let (nonce_bytes, body) = frame
+            .split_first_chunk::<NONCE_SIZE>()
+            .ok_or(ChannelError::Malformed)?;
+        let nonce = u64::from_le_bytes(*nonce_bytes);
+        if nonce < self.recv_nonce {
+            return Err(ChannelError::Replay {
+                nonce,
+                expected: self.recv_nonce,
+            });
+        }
+        if body.is_empty() {
+            return Err(ChannelError::Malformed);
+        }
+
+        let mut plaintext = Vec::with_capacity(body.len());
+        let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+        let mut next_nonce = nonce;
+        for chunk in body.chunks(NOISE_MAX_MESSAGE) {
+            let len = self
+                .transport
+                .read_message(next_nonce, chunk, &mut buf)
+                .map_err(|_| ChannelError::Decrypt)?;
+            plaintext.extend_from_slice(&buf[..len]);
+            next_nonce = next_nonce
+                .checked_add(1)
+                .ok_or(ChannelError::NonceExhausted)?;
+        }
+
+        let (len_bytes, data) = plaintext
+            .split_first_chunk::<LENGTH_SIZE>()
+            .ok_or(ChannelError::Malformed)?;
+        if u32::from_le_bytes(*len_bytes) as usize != data.len() {
+            return Err(ChannelError::Malformed);
+        }
+
+        self.recv_nonce = next_nonce;
+        Ok(data.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn establish() -> (SecureChannel, SecureChannel, NoiseKeypair, NoiseKeypair) {
+        let client_key = NoiseKeypair::generate();
+        let relay_key = NoiseKeypair::generate();
+
+        let (initiator, first) = HandshakeInitiator::start(&client_key).unwrap();
+        let (responder, reply) = HandshakeResponder::respond(&relay_key, &first).unwrap();
+        let (client, last) = initiator.finish(&reply, b"hello").unwrap();
+        let (relay, payload) = responder.finish(&last).unwrap();
+        assert_eq!(payload, b"hello");
+
+        (client, relay, client_key, relay_key)
+    }
+
+    #[test]
+    fn handshake_establishes_channel() {
+        let (mut client, mut relay, client_key, relay_key) = establish();
+
+        assert_eq!(client.remote_static(), Some(*relay_key.public_key()));
+        assert_eq!(relay.remote_static(), Some(*client_key.public_key()));
+
+        let frame = client.seal(b"push").unwrap();
+        assert_eq!(relay.open(&frame).unwrap(), b"push");
+        let frame = relay.seal(b"ack").unwrap();
+        assert_eq!(client.open(&frame).unwrap(), b"ack");
+    }
+
+    #[test]
+    fn keypair_restores_from_private_bytes() {
+        let key = NoiseKeypair::generate();
+        let restored = NoiseKeypair::from_private_bytes(*key.private_bytes());
+        assert_eq!(restored.public_key(), key.public_key());
+
+        // The restored key completes a handshake under the same identity
+        let (initiator, first) = HandshakeInitiator::start(&NoiseKeypair::generate()).unwrap();
+        let (responder, reply) = HandshakeResponder::respond(&restored, &first).unwrap();
+        let (client, last) = initiator.finish(&reply, b"hello").unwrap();
+        responder.finish(&last).unwrap();
+        assert_eq!(client.remote_static(), Some(*key.public_key()));
+    }
+
+    #[test]
+    fn first_message_carries_kem_key() {
+        let (_, first) = HandshakeInitiator::start(&NoiseKeypair::generate()).unwrap();
+        // Ephemeral X25519 key + ML-KEM-768 encapsulation key (1184 bytes) +
+        // tag (PSK handshakes mix `e` into the key, so the payload is sealed)
+        assert_eq!(first.len(), 32 + 1184 + NOISE_TAG_SIZE);
+    }
+
+    #[test]
+    fn large_frames_span_chunks() {
+        let (mut client, mut relay, _, _) = establish();
+        let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+
+        let frame = client.seal(&data).unwrap();
+        assert!(frame.len() > 3 * NOISE_MAX_MESSAGE);
+        assert_eq!(relay.open(&frame).unwrap(), data);
+
+        // Nonces advanced per chunk on both sides
+        let frame = client.seal(b"next").unwrap();
+        assert_eq!(relay.open(&frame).unwrap(), b"next");
+    }
+
+    #[test]
+    fn empty_frame_roundtrips() {
+        let (mut client, mut relay, _, _) = establish();
+        let frame = client.seal(b"").unwrap();
+        assert_eq!(relay.open(&frame).unwrap(), b"");
+    }
+
+    #[test]
+    fn tampered_frame_rejected() {
+        let (mut client, mut relay, _, _) = establish();
+        let mut frame = client.seal(b"secret").unwrap();
+        let last = frame.len() - 1;
+        frame[last] ^= 0x01;
+        assert_eq!(relay.open(&frame), Err(ChannelError::Decrypt));
+    }
+
+    #[test]
+    fn replayed_frame_rejected() {
+        let (mut client, mut relay, _, _) = establish();
+        let frame = client.seal(b"once").unwrap();
+        relay.open(&frame).unwrap();
+        assert_eq!(
+            relay.open(&frame),
+            Err(ChannelError::Replay {
+                nonce: 0,
+                expected: 1
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_frame_rejected() {
+        let (mut client, mut relay, _, _) = establish();
+        let frame = client.seal(&vec![7u8; 100_000]).unwrap();
+        // Drop the final chunk: remaining chunks still authenticate
+        let cut = NONCE_SIZE + NOISE_MAX_MESSAGE;
+        assert_eq!(relay.open(&frame[..cut]), Err(ChannelError::Malformed));
+        assert_eq!(relay.open(&[1, 2, 3]), Err(ChannelError::Malformed));
+    }
+
+    #[test]
+    fn kem_secret_is_mixed_into_keys() {
+        let client_key = NoiseKeypair::generate();
+        let relay_key = NoiseKeypair::generate();
+
+        let (initiator, first) = HandshakeInitiator::start(&client_key).unwrap();
+        let (mut responder, reply) = HandshakeResponder::respond(&relay_key, &first).unwrap();
+        // X25519 alone agrees, but a different KEM secret must fail the handshake
+        responder
+            .noise
+            .set_psk(KEM_PSK_LOCATION, &[0u8; 32])
+            .unwrap();
+
+        let (_, last) = initiator.finish(&reply, b"hello").unwrap();
+        assert!(matches!(
+            responder.finish(&last),
+            Err(ChannelError::Handshake(_))
+        ));
+    }
+
+    #[test]
+    fn garbage_handshake_rejected() {
+        let relay_key = NoiseKeypair::generate();
+        assert!(HandshakeResponder::respond(&relay_key, b"not noise").is_err());
+
+        let (initiator, _) = HandshakeInitiator::start(&NoiseKeypair::generate()).unwrap();
+        assert!(initiator.finish(&[0u8; 200], b"hello").is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! Payload compression for 0k-Sync.
+//!
+//! Compression must happen before encryption (ciphertext doesn't compress),
+//! so it is applied to plaintext by sync-client and sync-content. This module
+//! provides:
+//! - A per-group [`Compression`] setting (carried in invites)
+//! - A one-byte payload header recording how each payload was encoded
+//! - Decompression with a hard output limit (decompression-bomb protection)
+//!
+//! Framed payload format:
+//!
+//! ```text
+//! [flag: u8][body]
+//!   0x00 = raw body
+//!   0x01 = zstd frame
+//!   0x02 = LZ4 block (4-byte LE uncompressed size prefix)
+//! ```
+//!
+//! The flag is per payload: a group configured for zstd still sends
+//! incompressible payloads raw rather than paying for a larger frame.
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+/// Default maximum decompressed payload size (16 MB).
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// zstd compression level (library default; fast with good ratios on JSON).
+const ZSTD_LEVEL: i32 = 3;
+
+const FLAG_RAW: u8 = 0x00;
+const FLAG_ZSTD: u8 = 0x01;
+const FLAG_LZ4: u8 = 0x02;
+
+/// Size of the LZ4 uncompressed-size prefix.
+const LZ4_SIZE_PREFIX: usize = 4;
+
+/// Error type for compression operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressionError {
+    /// The payload is empty (missing header byte).
+    MissingHeader,
+    /// The header flag is not a known algorithm.
+    UnknownFlag(u8),
+    /// Decompressed output would exceed the configured limit.
+    TooLarge {
+        /// Maximum allowed decompressed size.
+        limit: usize,
+    },
+    /// The compressed body is malformed.
+    Corrupt(String),
+}
+
+impl std::fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompressionError::MissingHeader => write!(f, "payload is missing compression header"),
+            CompressionError::UnknownFlag(flag) => {
+                write!(f, "unknown compression flag: {:#04x}", flag)
+            }
+            CompressionError::TooLarge { limit } => {
+                write!(f, "decompressed payload exceeds limit of {} bytes", limit)
+            }
+            CompressionError::Corrupt(msg) => write!(f, "corrupt compressed payload: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {}
+
+/// Compression setting for a sync group.
+///
+/// All devices in a group must agree: with `None`, payloads carry no header
+/// (the original format); with any other setting, every payload is framed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// No compression and no header (default, wire-compatible).
+    #[default]
+    None,
+    /// zstd: best ratio, good speed.
+    Zstd,
+    /// LZ4: fastest, lower ratio.
+    Lz4,
+}
+
+impl Compression {
+    /// Whether payloads in this group carry a compression header.
+    pub fn is_enabled(self) -> bool {
+        self != Compression::None
+    }
+
+    /// Encode a payload for this group.
+    ///
+    /// Returns `data` unchanged when compression is `None`; otherwise a
+    /// framed payload, falling back to a raw body if compression doesn't
+    /// make the payload smaller.
+    pub fn encode(self, data: &[u8]) -> Vec<u8> {
+        if !self.is_enabled() {
+            return data.to_vec();
+        }
+        compress(self, data)
+    }
+
+    /// Decode a payload produced by [`encode`](Self::encode) for this group.
+    ///
+    /// Output larger than `max_size` is rejected without being fully
+    /// materialized.
+    pub fn decode(self, payload: &[u8], max_size: usize) -> Result<Vec<u8>, CompressionError> {
+        if !self.is_enabled() {
+            return Ok(payload.to_vec());
+        }
+        decompress(payload, max_size)
+    }
+}
+
+/// Compress `data` and frame it with a one-byte header.
+///
+/// Falls back to a raw body if the compressed form isn't smaller.
+pub fn compress(algorithm: Compression, data: &[u8]) -> Vec<u8> {
+    let compressed = match algorithm {
+        Compression::None => None,
+        // In-memory encoding only fails on allocation failure
+        Compression::Zstd => zstd::stream::encode_all(data, ZSTD_LEVEL)
+            .ok()
+            .map(|body| (FLAG_ZSTD, body)),
+        Compression::Lz4 => Some((FLAG_LZ4, lz4_flex::block::compress_prepend_size(data))),
+    };
+
+    let (flag, body) = match compressed {
+        Some((flag, body)) if body.len() < data.len() => (flag, body),
+        _ => (FLAG_RAW, data.to_vec()),
+    };
+
+    let mut out = Vec::with_capacity(1 + body.len());
+    out.push(flag);
+    out.extend_from_slice(&body);
+    out
+}
+
+/// Decompress a framed payload, rejecting output larger than `max_size`.
+pub fn decompress(payload: &[u8], max_size: usize) -> Result<Vec<u8>, CompressionError> {
+    let (&flag, body) = payload
+        .split_first()
+        .ok_or(CompressionError::MissingHeader)?;
+
+    let out = match flag {
+        FLAG_RAW => body.to_vec(),
+        FLAG_ZSTD => {
+            let decoder = zstd::stream::read::Decoder::new(body)
+                .map_err(|e| CompressionError::Corrupt(e.to_string()))?;
+            // Read at most one byte past the limit so bombs stop early
+            let mut out = Vec::new();
+            decoder
+                .take(max_size as u64 + 1)
+                .read_to_end(&mut out)
+                .map_err(|e| CompressionError::Corrupt(e.to_string()))?;
+            out
+        }
+        FLAG_LZ4 => {
+            let size_bytes: [u8; LZ4_SIZE_PREFIX] = body
+                .get(..LZ4_SIZE_PREFIX)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| CompressionError::Corrupt("truncated LZ4 header".into()))?;
+            let size = u32::from_le_bytes(size_bytes) as usize;
+            // Check the declared size before allocating for it
+            if size > max_size {
+                return Err(CompressionError::TooLarge { limit: max_size });
+            }
+            lz4_flex::block::decompress(&body[LZ4_SIZE_PREFIX..], size)
+                .map_err(|e| CompressionError::Corrupt(e.to_string()))?
+        }
+        other => return Err(CompressionError::UnknownFlag(other)),
+    };
+
+    if out.len() > max_size {
+        return Err(CompressionError::TooLarge { limit: max_size });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_like(n: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        for i in 0..n {
+            data.extend_from_slice(
+                format!(r#"{{"id":{},"field":"value","nested":{{"ok":true}}}},"#, i).as_bytes(),
+            );
+        }
+        data
+    }
+
+    #[test]
+    fn zstd_roundtrip_shrinks_json() {
+        let data = json_like(200);
+        let framed = compress(Compression::Zstd, &data);
+        assert_eq!(framed[0], FLAG_ZSTD);
+        assert!(framed.len() * 5 < data.len(), "JSON should compress 5x+");
+        assert_eq!(
+            decompress(&framed, DEFAULT_MAX_DECOMPRESSED_SIZE).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn lz4_roundtrip_shrinks_json() {
+        let data = json_like(200);
+        let framed = compress(Compression::Lz4, &data);
+        assert_eq!(framed[0], FLAG_LZ4);
+        assert!(framed.len() < data.len());
+        assert_eq!(
+            decompress(&framed, DEFAULT_MAX_DECOMPRESSED_SIZE).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn incompressible_data_sent_raw() {
+        let mut data = vec![0u8; 256];
+        getrandom::getrandom(&mut data).unwrap();
+
+        for algorithm in [Compression::Zstd, Compression::Lz4] {
+            let framed = compress(algorithm, &data);
+            assert_eq!(framed[0], FLAG_RAW);
+            assert_eq!(framed.len(), data.len() + 1);
+            assert_eq!(decompress(&framed, 1024).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn none_is_passthrough_without_header() {
+        let data = b"legacy payload".to_vec();
+        assert_eq!(Compression::None.encode(&data), data);
+        assert_eq!(Compression::None.decode(&data, 0).unwrap(), data);
+    }
+
+    #[test]
+    fn none_decodes_baseline_payload_that_looks_framed() {
+        // Pre-compression payloads have no header, so a leading flag byte is data
+        let data = vec![FLAG_ZSTD, 0x28, 0xB5, 0x2F, 0xFD];
+        assert_eq!(Compression::None.decode(&data, 0).unwrap(), data);
+    }
+
+    #[test]
+    fn zstd_bomb_rejected() {
+        // 64 MB of zeros compresses to a few KB
+        let bomb = compress(Compression::Zstd, &vec![0u8; 64 * 1024 * 1024]);
+        assert!(bomb.len() < 64 * 1024);
+
+        let result = decompress(&bomb, 1024 * 1024);
+        assert_eq!(
+            result,
+            Err(CompressionError::TooLarge { limit: 1024 * 1024 })
+        );
+    }
+
+    #[test]
+    fn lz4_bomb_rejected_by_declared_size() {
+        let bomb = compress(Compression::Lz4, &vec![0u8; 4 * 1024 * 1024]);
+        let result = decompress(&bomb, 1024 * 1024);
+        assert_eq!(
+            result,
+            Err(CompressionError::TooLarge { limit: 1024 * 1024 })
+        );
+    }
+
+    #[test]
+    fn malformed_payloads_rejected() {
+        assert_eq!(decompress(&[], 1024), Err(CompressionError::MissingHeader));
+        assert_eq!(
+            decompress(&[0x7F, 1, 2, 3], 1024),
+            Err(CompressionError::UnknownFlag(0x7F))
+        );
+        assert!(matches!(
+            decompress(&[FLAG_LZ4, 1], 1024),
+            Err(CompressionError::Corrupt(_))
+        ));
+        assert!(matches!(
+            decompress(&[FLAG_ZSTD, 0xDE, 0xAD, 0xBE, 0xEF], 1024),
+            Err(CompressionError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn compression_serializes_lowercase() {
+        assert_eq!(
+            serde_json::to_string(&Compression::Zstd).unwrap(),
+            r#""zstd""#
+        );
+        let parsed: Compression = serde_json::from_str(r#""lz4""#).unwrap();
+        assert_eq!(parsed, Compression::Lz4);
+    }
+}
@@ -0,0 +1,122 @@
+//! Cover traffic scheduling for 0k-Sync.
+//!
+//! The relay timestamps every blob it stores, which reveals when a device is
+//! active. In cover traffic mode, sync-client sends dummy pushes on a
+//! randomized schedule so real activity blends into a steady background.
+//!
+//! Dummy payloads are random bytes shaped like real ones (nonce, padded
+//! body, auth tag). The relay cannot tell them from ciphertext; recipients
+//! fail to authenticate them and drop them like any undecryptable blob.
+//!
+//! Dummies expire after a short relay TTL so they don't pile up against the
+//! group's storage quota. While cover traffic is on, real pushes use the
+//! same TTL, so the relay cannot pick dummies out by TTL either.
+//!
+//! This module is pure: it only decides *when* the next dummy is due.
+
+use std::time::Duration;
+
+use crate::padding::random_below;
+
+/// Default minimum delay between cover pushes (30 seconds).
+pub const DEFAULT_COVER_MIN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default maximum delay between cover pushes (5 minutes).
+pub const DEFAULT_COVER_MAX_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Default relay TTL for pushes while cover traffic is on (1 day).
+pub const DEFAULT_COVER_TTL: u32 = 86400;
+
+/// Cover traffic configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoverTrafficConfig {
+    /// Minimum delay between cover pushes.
+    pub min_interval: Duration,
+    /// Maximum delay between cover pushes.
+    pub max_interval: Duration,
+    /// Relay TTL in seconds for every push, real or cover, while cover
+    /// traffic is on. Devices offline for longer miss real pushes too.
+    pub ttl: u32,
+}
+
+impl Default for CoverTrafficConfig {
+    fn default() -> Self {
+        Self {
+            min_interval: DEFAULT_COVER_MIN_INTERVAL,
+            max_interval: DEFAULT_COVER_MAX_INTERVAL,
+            ttl: DEFAULT_COVER_TTL,
+        }
+    }
+}
+
+impl CoverTrafficConfig {
+    /// Create a config sending one cover push every `min..=max` (uniform).
+    ///
+    /// If `max < min`, the bounds are swapped.
+    pub fn new(min_interval: Duration, max_interval: Duration) -> Self {
+        Self {
+            min_interval: min_interval.min(max_interval),
+            max_interval: max_interval.max(min_interval),
+            ttl: DEFAULT_COVER_TTL,
+        }
+    }
+
+    /// Set the relay TTL for pushes while cover traffic is on (builder pattern).
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Randomized delay until the next cover push.
+    pub fn next_delay(&self) -> Duration {
+        let min_ms = self.min_interval.as_millis() as u64;
+        let max_ms = (self.max_interval.as_millis() as u64).max(min_ms);
+        let spread = random_below(max_ms - min_ms + 1) as u64;
+        Duration::from_millis(min_ms + spread)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_interval_is_30s_to_5m() {
+        let config = CoverTrafficConfig::default();
+        assert_eq!(config.min_interval, Duration::from_secs(30));
+        assert_eq!(config.max_interval, Duration::from_secs(300));
+        assert_eq!(config.ttl, DEFAULT_COVER_TTL);
+    }
+
+    #[test]
+    fn next_delay_within_bounds_and_varies() {
+        let config = CoverTrafficConfig::new(Duration::from_secs(1), Duration::from_secs(10));
+        let delays: Vec<Duration> = (0..50).map(|_| config.next_delay()).collect();
+
+        assert!(delays
+            .iter()
+            .all(|d| *d >= Duration::from_secs(1) && *d <= Duration::from_secs(10)));
+        let min = delays.iter().min().unwrap();
+        let max = delays.iter().max().unwrap();
+        assert!(max > min, "delays should be randomized");
+    }
+
+    #[test]
+    fn fixed_interval_when_bounds_equal() {
+        let config = CoverTrafficConfig::new(Duration::from_secs(5), Duration::from_secs(5));
+        assert_eq!(config.next_delay(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn swapped_bounds_are_normalized() {
+        let config = CoverTrafficConfig::new(Duration::from_secs(10), Duration::from_secs(1));
+        assert_eq!(config.min_interval, Duration::from_secs(1));
+        assert_eq!(config.max_interval, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn ttl_builder() {
+        let config = CoverTrafficConfig::default().with_ttl(600);
+        assert_eq!(config.ttl, 600);
+    }
+}
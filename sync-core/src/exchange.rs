@@ -0,0 +1,392 @@
+//! Post-quantum invite exchange for 0k-Sync.
+//!
+//! A plain [`Invite`] embeds the group secret, so anyone who records the
+//! channel it was shared over (chat log, screenshot, shoulder-surfed QR)
+//! holds the key forever. An [`ExchangeInvite`] carries no group key
+//! material: only relay addresses and a one-time exchange secret. The
+//! joiner and inviter then run the hybrid X25519 + ML-KEM-768 handshake
+//! (see [`crate::channel`]) through relay mailboxes, and the full invite is
+//! delivered sealed under the resulting keys.
+//!
+//! The flow (each arrow is one relay mailbox message):
+//!
+//! ```text
+//! joiner → inviter    handshake message 1 (e, ML-KEM encapsulation key)
+//! inviter → joiner    handshake message 2 (e, ee, s, es, ML-KEM ciphertext)
+//! joiner → inviter    handshake message 3 (s, se, psk)
+//! inviter → joiner    sealed invite
+//! ```
+//!
+//! The exchange secret is bound into the handshake prologue, so a relay (or
+//! anyone else) without the exchange invite cannot complete or
+//! man-in-the-middle the handshake. It never reaches the relay: mailbox
+//! addresses are one-way hashes of it.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::channel::{HandshakeInitiator, HandshakeResponder, NoiseKeypair, SecureChannel};
+use crate::pairing::{Invite, PairingError, RelayNodeId, DEFAULT_INVITE_TTL};
+
+/// Exchange invite format version.
+pub const EXCHANGE_INVITE_VERSION: u32 = 1;
+
+/// Handshake prologue prefix; the exchange secret is appended.
+const PROLOGUE_PREFIX: &[u8] = b"0k-sync invite exchange v1";
+
+/// Domain separator for mailbox address derivation.
+const MAILBOX_DOMAIN: &[u8] = b"0k-sync invite mailbox v1";
+
+const MAILBOX_TO_INVITER: u8 = 0x01;
+const MAILBOX_TO_JOINER: u8 = 0x02;
+
+/// One-time secret shared out of band in an [`ExchangeInvite`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct ExchangeSecret([u8; 32]);
+
+impl ExchangeSecret {
+    /// Create a new random exchange secret.
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).expect("getrandom failed");
+        Self(bytes)
+    }
+
+    /// Get the raw bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+// Intentionally opaque debug to avoid logging secrets
+impl std::fmt::Debug for ExchangeSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExchangeSecret([REDACTED])")
+    }
+}
+
+/// An invite that is safe to share over untrusted channels.
+///
+/// Contains no group key material; the joiner uses it to fetch the real
+/// [`Invite`] from the inviter through the relay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeInvite {
+    /// Exchange invite format version.
+    pub version: u32,
+    /// The iroh NodeIds of relays hosting the exchange mailboxes.
+    pub relay_node_ids: Vec<RelayNodeId>,
+    /// One-time secret authenticating the exchange.
+    pub exchange_secret: ExchangeSecret,
+    /// Unix timestamp when the exchange invite expires.
+    pub expires_at: u64,
+}
+
+impl ExchangeInvite {
+    /// Create a new exchange invite (default TTL 10 minutes).
+    pub fn create(relay_node_ids: Vec<RelayNodeId>) -> Self {
+        Self::create_with_ttl(relay_node_ids, DEFAULT_INVITE_TTL)
+    }
+
+    /// Create a new exchange invite with a custom TTL.
+    pub fn create_with_ttl(relay_node_ids: Vec<RelayNodeId>, ttl: Duration) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Self {
+            version: EXCHANGE_INVITE_VERSION,
+            relay_node_ids,
+            exchange_secret: ExchangeSecret::random(),
+            expires_at: now + ttl.as_secs(),
+        }
+    }
+
+    /// Check if the exchange invite has expired.
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        now >= self.expires_at
+    }
+
+    /// Seconds until the exchange invite expires (0 if already expired).
+    pub fn remaining_secs(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.expires_at.saturating_sub(now)
+    }
+
+    /// Get the primary relay node ID (first in the list).
+    pub fn primary_relay(&self) -> Option<&RelayNodeId> {
+        self.relay_node_ids.first()
+    }
+
+    /// Encode the exchange invite as a base64 JSON payload for QR codes.
+    pub fn to_qr_payload(&self) -> String {
+        let json = serde_json::to_string(self).expect("exchange invite serialization failed");
+        URL_SAFE_NO_PAD.encode(json.as_bytes())
+    }
+
+    /// Decode an exchange invite from a base64 JSON payload.
+    pub fn from_qr_payload(payload: &str) -> Result<Self, PairingError> {
+        let json_bytes = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|e| PairingError::InvalidPayload(format!("base64 decode: {}", e)))?;
+
+        let invite: Self = serde_json::from_slice(&json_bytes)
+            .map_err(|e| PairingError::InvalidPayload(format!("json parse: {}", e)))?;
+
+        if invite.version != EXCHANGE_INVITE_VERSION {
+            return Err(PairingError::UnsupportedVersion(invite.version));
+        }
+
+        Ok(invite)
+    }
+
+    /// Relay mailbox the inviter reads (joiner's handshake messages).
+    pub fn inviter_mailbox(&self) -> [u8; 32] {
+        self.mailbox(MAILBOX_TO_INVITER)
+    }
+
+    /// Relay mailbox the joiner reads (inviter's replies and the invite).
+    pub fn joiner_mailbox(&self) -> [u8; 32] {
+        self.mailbox(MAILBOX_TO_JOINER)
+    }
+
+    fn mailbox(&self, direction: u8) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(MAILBOX_DOMAIN);
+        hasher.update(self.exchange_secret.as_bytes());
+        hasher.update([direction]);
+        hasher.finalize().into()
+    }
+
+    fn prologue(&self) -> Vec<u8> {
+        let mut prologue = PROLOGUE_PREFIX.to_vec();
+        prologue.extend_from_slice(self.exchange_secret.as_bytes());
+        prologue
+    }
+}
+
+/// Joiner side: first handshake message sent, awaiting the inviter's reply.
+pub struct JoinRequest {
+    initiator: HandshakeInitiator,
+}
+
+impl JoinRequest {
+    /// Start the exchange, returning the state and the message to post to
+    /// the inviter's mailbox.
+    pub fn start(exchange: &ExchangeInvite) -> Result<(Self, Vec<u8>), PairingError> {
+        if exchange.is_expired() {
+            return Err(PairingError::Expired);
+        }
+        let (initiator, message) = HandshakeInitiator::start_with_prologue(
+            &NoiseKeypair::generate(),
+            &exchange.prologue(),
+        )
+        .map_err(PairingError::KeyExchange)?;
+        Ok((Self { initiator }, message))
+    }
+
+    /// Process the inviter's reply, returning the state and the final
+    /// handshake message to post to the inviter's mailbox.
+    pub fn confirm(self, reply: &[u8]) -> Result<(PendingJoin, Vec<u8>), PairingError> {
+        let (channel, message) = self
+            .initiator
+            .finish(reply, &[])
+            .map_err(PairingError::KeyExchange)?;
+        Ok((PendingJoin { channel }, message))
+    }
+}
+
+/// Joiner side: handshake complete, awaiting the sealed invite.
+pub struct PendingJoin {
+    channel: SecureChannel,
+}
+
+impl PendingJoin {
+    /// Open the sealed invite delivered by the inviter.
+    pub fn receive(mut self, sealed: &[u8]) -> Result<Invite, PairingError> {
+        let payload = self
+            .channel
+            .open(sealed)
+            .map_err(PairingError::KeyExchange)?;
+        let payload = String::from_utf8(payload)
+            .map_err(|e| PairingError::InvalidPayload(format!("utf-8: {}", e)))?;
+        Invite::from_qr_payload(&payload)
+    }
+}
+
+/// Inviter side: reply sent, awaiting the joiner's final handshake message.
+pub struct InviteOffer {
+    responder: HandshakeResponder,
+}
+
+impl InviteOffer {
+    /// Process the joiner's first message, returning the state and the
+    /// reply to post to the joiner's mailbox.
+    pub fn respond(
+        exchange: &ExchangeInvite,
+        message: &[u8],
+    ) -> Result<(Self, Vec<u8>), PairingError> {
+        if exchange.is_expired() {
+            return Err(PairingError::Expired);
+        }
+        let (responder, reply) = HandshakeResponder::respond_with_prologue(
+            &NoiseKeypair::generate(),
+            &exchange.prologue(),
+            message,
+        )
+        .map_err(PairingError::KeyExchange)?;
+        Ok((Self { responder }, reply))
+    }
+
+    /// Complete the handshake and seal `invite` for the joiner.
+    pub fn deliver(self, message: &[u8], invite: &Invite) -> Result<Vec<u8>, PairingError> {
+        let (mut channel, _) = self
+            .responder
+            .finish(message)
+            .map_err(PairingError::KeyExchange)?;
+        channel
+            .seal(invite.to_qr_payload().as_bytes())
+            .map_err(PairingError::KeyExchange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pairing::GroupSecret;
+    use crate::{ChannelError, Compression};
+
+    fn group_invite() -> Invite {
+        let secret = GroupSecret::random();
+        let group_id = secret.derive_group_id();
+        Invite::create(
+            RelayNodeId::random(),
+            group_id,
+            secret,
+            b"salt-0123456789!".to_vec(),
+        )
+        .with_compression(Compression::Zstd)
+    }
+
+    fn run_exchange(
+        joiner_view: &ExchangeInvite,
+        inviter_view: &ExchangeInvite,
+        invite: &Invite,
+    ) -> Result<Invite, PairingError> {
+        let (request, first) = JoinRequest::start(joiner_view)?;
+        let (offer, reply) = InviteOffer::respond(inviter_view, &first)?;
+        let (pending, last) = request.confirm(&reply)?;
+        let sealed = offer.deliver(&last, invite)?;
+        pending.receive(&sealed)
+    }
+
+    #[test]
+    fn exchange_delivers_invite() {
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::random()]);
+        let invite = group_invite();
+
+        let received = run_exchange(&exchange, &exchange, &invite).unwrap();
+
+        assert_eq!(received.group_id, invite.group_id);
+        assert_eq!(received.group_secret, invite.group_secret);
+        assert_eq!(received.salt, invite.salt);
+        assert_eq!(received.compression, Compression::Zstd);
+    }
+
+    #[test]
+    fn exchange_invite_carries_no_group_secret() {
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::random()]);
+        let json =
+            String::from_utf8(URL_SAFE_NO_PAD.decode(exchange.to_qr_payload()).unwrap()).unwrap();
+        assert!(!json.contains("group_secret"));
+        assert!(!json.contains("group_id"));
+    }
+
+    #[test]
+    fn qr_payload_roundtrip() {
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::random(), RelayNodeId::random()]);
+        let decoded = ExchangeInvite::from_qr_payload(&exchange.to_qr_payload()).unwrap();
+        assert_eq!(decoded.relay_node_ids, exchange.relay_node_ids);
+        assert_eq!(decoded.exchange_secret, exchange.exchange_secret);
+        assert_eq!(decoded.inviter_mailbox(), exchange.inviter_mailbox());
+    }
+
+    #[test]
+    fn group_invite_is_not_an_exchange_invite() {
+        let payload = group_invite().to_qr_payload();
+        assert!(matches!(
+            ExchangeInvite::from_qr_payload(&payload),
+            Err(PairingError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn mailboxes_are_distinct_and_hide_the_secret() {
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::random()]);
+        assert_ne!(exchange.inviter_mailbox(), exchange.joiner_mailbox());
+        assert_ne!(
+            &exchange.inviter_mailbox(),
+            exchange.exchange_secret.as_bytes()
+        );
+        assert_ne!(
+            &exchange.joiner_mailbox(),
+            exchange.exchange_secret.as_bytes()
+        );
+    }
+
+    #[test]
+    fn wrong_exchange_secret_fails() {
+        let relays = vec![RelayNodeId::random()];
+        let exchange = ExchangeInvite::create(relays.clone());
+        let impostor = ExchangeInvite::create(relays);
+
+        let result = run_exchange(&exchange, &impostor, &group_invite());
+        assert!(matches!(
+            result,
+            Err(PairingError::KeyExchange(ChannelError::Handshake(_)))
+        ));
+    }
+
+    #[test]
+    fn expired_exchange_rejected() {
+        let exchange = ExchangeInvite::create_with_ttl(vec![RelayNodeId::random()], Duration::ZERO);
+        assert!(exchange.is_expired());
+        assert_eq!(exchange.remaining_secs(), 0);
+        assert!(matches!(
+            JoinRequest::start(&exchange),
+            Err(PairingError::Expired)
+        ));
+    }
+
+    #[test]
+    fn tampered_invite_rejected() {
+        let exchange = ExchangeInvite::create(vec![RelayNodeId::random()]);
+        let (request, first) = JoinRequest::start(&exchange).unwrap();
+        let (offer, reply) = InviteOffer::respond(&exchange, &first).unwrap();
+        let (pending, last) = request.confirm(&reply).unwrap();
+        let mut sealed = offer.deliver(&last, &group_invite()).unwrap();
+        let end = sealed.len() - 1;
+        sealed[end] ^= 0x01;
+
+        assert_eq!(
+            pending.receive(&sealed).unwrap_err(),
+            PairingError::KeyExchange(ChannelError::Decrypt)
+        );
+    }
+
+    #[test]
+    fn exchange_secret_debug_is_redacted() {
+        let secret = ExchangeSecret::random();
+        assert_eq!(format!("{:?}", secret), "ExchangeSecret([REDACTED])");
+    }
+}
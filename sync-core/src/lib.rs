@@ -20,11 +20,40 @@
 #![warn(clippy::all)]
 
 pub mod buffer;
+pub mod channel;
+pub mod compress;
+pub mod cover;
 pub mod cursor;
+pub mod exchange;
+pub mod oprf;
+pub mod padding;
 pub mod pairing;
+pub mod pake;
+pub mod passphrase;
+pub mod pow;
 pub mod state;
 
 pub use buffer::{BufferError, MessageBuffer, QueuedMessage};
+pub use channel::{
+    ChannelError, HandshakeInitiator, HandshakeResponder, NoiseKeypair, SecureChannel,
+};
+pub use compress::{Compression, CompressionError, DEFAULT_MAX_DECOMPRESSED_SIZE};
+pub use cover::{
+    CoverTrafficConfig, DEFAULT_COVER_MAX_INTERVAL, DEFAULT_COVER_MIN_INTERVAL, DEFAULT_COVER_TTL,
+};
 pub use cursor::CursorTracker;
+pub use exchange::{
+    ExchangeInvite, ExchangeSecret, InviteOffer, JoinRequest, PendingJoin, EXCHANGE_INVITE_VERSION,
+};
+pub use oprf::{
+    oprf_key_id, OprfClient, OprfError, OprfServerKey, OPRF_ELEMENT_SIZE, OPRF_OUTPUT_SIZE,
+};
+pub use padding::{PaddingError, PaddingPolicy};
 pub use pairing::{GroupSecret, Invite, PairingError, RelayNodeId, DEFAULT_INVITE_TTL};
+pub use pake::{CodeJoin, CodeOffer, PairingCode};
+pub use passphrase::{
+    check_passphrase, estimate_entropy, passphrase_strength, PassphraseError, PassphraseGenerator,
+    PassphraseStrength, DEFAULT_PASSPHRASE_ENTROPY, MIN_PASSPHRASE_ENTROPY,
+};
+pub use pow::{pow_nonce, solve_pow, verify_pow, MAX_POW_DIFFICULTY, POW_NONCE_SIZE};
 pub use state::{Action, ConnectionState, Event, ReceivedMessage, SyncEvent};
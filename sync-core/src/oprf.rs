@@ -0,0 +1,319 @@
+//! Oblivious PRF for relay-assisted passphrase hardening.
+//!
+//! A passphrase-derived group secret can be brute-forced offline by anyone
+//! who sees the salt and some ciphertext. With the OPRF step enabled, the
+//! passphrase is first mixed with a key held by the relay, so every guess
+//! costs one (rate-limited) round trip to the relay. The relay learns
+//! nothing about the passphrase or the output.
+//!
+//! This is the 2HashDH construction from RFC 9497 (OPRF mode) over
+//! ristretto255 with SHA-512:
+//!
+//! ```text
+//! client:  blinded   = r * HashToGroup(passphrase)
+//! relay:   evaluated = k * blinded
+//! client:  output    = SHA-512(passphrase || r^-1 * evaluated || "Finalize")
+//! ```
+//!
+//! The relay keeps one OPRF key per group, addressed by [`oprf_key_id`]
+//! (derived from the group salt, since the group ID is not known before
+//! the secret is derived).
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Size of an encoded group element (blinded or evaluated).
+pub const OPRF_ELEMENT_SIZE: usize = 32;
+
+/// Size of the OPRF output.
+pub const OPRF_OUTPUT_SIZE: usize = 64;
+
+/// RFC 9497 context string for ristretto255-SHA512 in OPRF mode.
+const CONTEXT_STRING: &[u8] = b"OPRFV1-\x00-ristretto255-SHA512";
+
+/// Domain separator for relay key IDs.
+const KEY_ID_DOMAIN: &[u8] = b"0k-sync oprf key v1";
+
+/// Error type for OPRF operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OprfError {
+    /// A group element failed to decode or was the identity.
+    InvalidElement,
+    /// A server key was zero or not canonically encoded.
+    InvalidKey,
+}
+
+impl std::fmt::Display for OprfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OprfError::InvalidElement => write!(f, "invalid OPRF group element"),
+            OprfError::InvalidKey => write!(f, "invalid OPRF key"),
+        }
+    }
+}
+
+impl std::error::Error for OprfError {}
+
+/// Relay key ID for the group using `salt`.
+pub fn oprf_key_id(salt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_ID_DOMAIN);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Client side of one OPRF evaluation.
+///
+/// Holds the blinding scalar between [`blind`](Self::blind) and
+/// [`finalize`](Self::finalize).
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct OprfClient {
+    input: Vec<u8>,
+    blind: Scalar,
+}
+
+impl OprfClient {
+    /// Blind `input` for evaluation by the relay.
+    ///
+    /// Returns the client state and the blinded element to send.
+    pub fn blind(input: &[u8]) -> (Self, [u8; OPRF_ELEMENT_SIZE]) {
+        let blind = random_scalar();
+        let blinded = (hash_to_group(input) * blind).compress().to_bytes();
+        let client = Self {
+            input: input.to_vec(),
+            blind,
+        };
+        (client, blinded)
+    }
+
+    /// Unblind the relay's response and derive the OPRF output.
+    pub fn finalize(
+        self,
+        evaluated: &[u8; OPRF_ELEMENT_SIZE],
+    ) -> Result<[u8; OPRF_OUTPUT_SIZE], OprfError> {
+        let evaluated = decode_element(evaluated)?;
+        let unblinded = (evaluated * self.blind.invert()).compress();
+
+        let mut hasher = Sha512::new();
+        hasher.update((self.input.len() as u16).to_be_bytes());
+        hasher.update(&self.input);
+        hasher.update((OPRF_ELEMENT_SIZE as u16).to_be_bytes());
+        hasher.update(unblinded.as_bytes());
+        hasher.update(b"Finalize");
+        Ok(hasher.finalize().into())
+    }
+}
+
+impl std::fmt::Debug for OprfClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OprfClient([REDACTED])")
+    }
+}
+
+/// Relay-held OPRF key for one group.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct OprfServerKey(Scalar);
+
+impl OprfServerKey {
+    /// Generate a fresh random key.
+    pub fn generate() -> Self {
+        Self(random_scalar())
+    }
+
+    /// Load a key from its canonical encoding.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, OprfError> {
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(*bytes))
+            .filter(|k| *k != Scalar::ZERO)
+            .map(Self)
+            .ok_or(OprfError::InvalidKey)
+    }
+
+    /// Canonical encoding of the key (for storage).
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Evaluate a blinded element from a client.
+    pub fn evaluate(
+        &self,
+        blinded: &[u8; OPRF_ELEMENT_SIZE],
+    ) -> Result<[u8; OPRF_ELEMENT_SIZE], OprfError> {
+        let blinded = decode_element(blinded)?;
+        Ok((blinded * self.0).compress().to_bytes())
+    }
+}
+
+impl std::fmt::Debug for OprfServerKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OprfServerKey([REDACTED])")
+    }
+}
+
+/// Decode a group element, rejecting the identity.
+fn decode_element(bytes: &[u8; OPRF_ELEMENT_SIZE]) -> Result<RistrettoPoint, OprfError> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .filter(|p| *p != RistrettoPoint::identity())
+        .ok_or(OprfError::InvalidElement)
+}
+
+/// Uniformly random non-zero scalar.
+pub(crate) fn random_scalar() -> Scalar {
+    loop {
+        let mut bytes = [0u8; 64];
+        getrandom::getrandom(&mut bytes).expect("getrandom failed");
+        let scalar = Scalar::from_bytes_mod_order_wide(&bytes);
+        bytes.zeroize();
+        if scalar != Scalar::ZERO {
+            return scalar;
+        }
+    }
+}
+
+/// RFC 9497 HashToGroup: expand_message_xmd (SHA-512) into 64 uniform bytes,
+/// then the ristretto255 one-way map.
+fn hash_to_group(input: &[u8]) -> RistrettoPoint {
+    const BLOCK_SIZE: usize = 128;
+    let dst_len = (b"HashToGroup-".len() + CONTEXT_STRING.len()) as u8;
+    let dst_prime = |hasher: &mut Sha512| {
+        hasher.update(b"HashToGroup-");
+        hasher.update(CONTEXT_STRING);
+        hasher.update([dst_len]);
+    };
+
+    let mut b0 = Sha512::new();
+    b0.update([0u8; BLOCK_SIZE]);
+    b0.update(input);
+    b0.update((OPRF_OUTPUT_SIZE as u16).to_be_bytes());
+    b0.update([0u8]);
+    dst_prime(&mut b0);
+    let b0 = b0.finalize();
+
+    let mut b1 = Sha512::new();
+    b1.update(b0);
+    b1.update([1u8]);
+    dst_prime(&mut b1);
+    let uniform: [u8; 64] = b1.finalize().into();
+
+    RistrettoPoint::from_uniform_bytes(&uniform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(key: &OprfServerKey, input: &[u8]) -> [u8; OPRF_OUTPUT_SIZE] {
+        let (client, blinded) = OprfClient::blind(input);
+        let evaluated = key.evaluate(&blinded).unwrap();
+        client.finalize(&evaluated).unwrap()
+    }
+
+    #[test]
+    fn output_is_deterministic_per_key() {
+        let key = OprfServerKey::generate();
+        assert_eq!(
+            evaluate(&key, b"correct horse"),
+            evaluate(&key, b"correct horse")
+        );
+        assert_ne!(
+            evaluate(&key, b"correct horse"),
+            evaluate(&key, b"battery staple")
+        );
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn matches_rfc9497_test_vector() {
+        // RFC 9497 Appendix A.1.1 (ristretto255-SHA512, OPRF mode), vector 1.
+        // The output does not depend on the blind, so a random one is fine.
+        let sk: [u8; 32] = hex("5ebcea5ee37023ccb9fc2d2019f9d7737be85591ae8652ffa9ef0f4d37063b0e")
+            .try_into()
+            .unwrap();
+        let key = OprfServerKey::from_bytes(&sk).unwrap();
+        assert_eq!(
+            evaluate(&key, &[0x00]).to_vec(),
+            hex(concat!(
+                "527759c3d9366f277d8c6020418d96bb393ba2afb20ff90df23fb7708264e2f3",
+                "ab9135e3bd69955851de4b1f9fe8a0973396719b7912ba9ee8aa7d0b5e24bcf6"
+            ))
+        );
+    }
+
+    #[test]
+    fn output_depends_on_key() {
+        let a = OprfServerKey::generate();
+        let b = OprfServerKey::generate();
+        assert_ne!(evaluate(&a, b"passphrase"), evaluate(&b, b"passphrase"));
+    }
+
+    #[test]
+    fn blinded_element_hides_input() {
+        // Fresh blinding per evaluation: the relay can't link repeated inputs
+        let (_, first) = OprfClient::blind(b"passphrase");
+        let (_, second) = OprfClient::blind(b"passphrase");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn key_roundtrips_through_bytes() {
+        let key = OprfServerKey::generate();
+        let restored = OprfServerKey::from_bytes(&key.to_bytes()).unwrap();
+        assert_eq!(evaluate(&key, b"input"), evaluate(&restored, b"input"));
+    }
+
+    #[test]
+    fn invalid_keys_rejected() {
+        assert_eq!(
+            OprfServerKey::from_bytes(&[0u8; 32]).unwrap_err(),
+            OprfError::InvalidKey
+        );
+        assert_eq!(
+            OprfServerKey::from_bytes(&[0xFF; 32]).unwrap_err(),
+            OprfError::InvalidKey
+        );
+    }
+
+    #[test]
+    fn invalid_elements_rejected() {
+        let key = OprfServerKey::generate();
+        // Identity element
+        assert_eq!(
+            key.evaluate(&[0u8; 32]).unwrap_err(),
+            OprfError::InvalidElement
+        );
+        // Not a valid encoding
+        assert_eq!(
+            key.evaluate(&[0xFF; 32]).unwrap_err(),
+            OprfError::InvalidElement
+        );
+
+        let (client, _) = OprfClient::blind(b"input");
+        assert_eq!(
+            client.finalize(&[0u8; 32]).unwrap_err(),
+            OprfError::InvalidElement
+        );
+    }
+
+    #[test]
+    fn key_id_is_per_salt() {
+        assert_eq!(oprf_key_id(b"salt-a"), oprf_key_id(b"salt-a"));
+        assert_ne!(oprf_key_id(b"salt-a"), oprf_key_id(b"salt-b"));
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let key = OprfServerKey::generate();
+        assert_eq!(format!("{:?}", key), "OprfServerKey([REDACTED])");
+        let (client, _) = OprfClient::blind(b"passphrase");
+        assert!(!format!("{:?}", client).contains("passphrase"));
+    }
+}
@@ -0,0 +1,254 @@
+//! Length padding for 0k-Sync payloads.
+//!
+//! The relay sees the size of every blob it stores. Without padding, the
+//! exact ciphertext length reveals what kind of record each push is. Padding
+//! is applied to plaintext *before* encryption (so the padding itself is
+//! authenticated and hidden) and stripped after decryption.
+//!
+//! Padded frame format:
+//!
+//! ```text
+//! [length: u32 LE][data][zero padding]
+//! ```
+//!
+//! Every non-`None` policy produces the same frame, so receivers only need
+//! to know that padding is enabled, not which policy the sender chose.
+
+use serde::{Deserialize, Serialize};
+
+/// Size of the length prefix in a padded frame.
+pub const PADDING_HEADER_SIZE: usize = 4;
+
+/// Smallest bucket used by [`PaddingPolicy::PowerOfTwo`].
+///
+/// Keeps small records (acks, presence, tiny edits) indistinguishable.
+pub const MIN_POWER_OF_TWO_BUCKET: usize = 256;
+
+/// Error type for padding operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaddingError {
+    /// The frame is shorter than the length header.
+    Truncated,
+    /// The declared length exceeds the frame.
+    LengthOutOfRange {
+        /// Length declared in the header.
+        declared: usize,
+        /// Bytes available after the header.
+        available: usize,
+    },
+}
+
+impl std::fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaddingError::Truncated => write!(f, "padded frame is truncated"),
+            PaddingError::LengthOutOfRange {
+                declared,
+                available,
+            } => write!(
+                f,
+                "padded length {} exceeds frame ({} bytes)",
+                declared, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaddingError {}
+
+/// How payloads are padded before encryption.
+///
+/// All devices in a group must agree on whether padding is enabled (any
+/// policy other than `None`); the policy itself may differ per device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaddingPolicy {
+    /// No padding and no frame (default, wire-compatible).
+    #[default]
+    None,
+    /// Round the frame up to the next power of two (minimum 256 bytes).
+    ///
+    /// Leaks only the size bucket, at up to 2x size overhead.
+    PowerOfTwo,
+    /// Round the frame up to a multiple of the given block size.
+    Block(u32),
+    /// Append a uniformly random amount of padding, up to `max` bytes.
+    Random {
+        /// Maximum padding in bytes.
+        max: u32,
+    },
+}
+
+impl PaddingPolicy {
+    /// Whether payloads carry a padding frame.
+    pub fn is_enabled(self) -> bool {
+        self != PaddingPolicy::None
+    }
+
+    /// Pad `data` according to this policy.
+    ///
+    /// The padded frame never exceeds `max_size` unless the unpadded frame
+    /// already does; data is never truncated. Returns `data` unchanged for
+    /// `None`.
+    pub fn pad(self, data: &[u8], max_size: usize) -> Vec<u8> {
+        if !self.is_enabled() {
+            return data.to_vec();
+        }
+
+        let frame_len = PADDING_HEADER_SIZE + data.len();
+        let target = self.target_len(frame_len).min(max_size).max(frame_len);
+
+        let mut out = Vec::with_capacity(target);
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+        out.resize(target, 0);
+        out
+    }
+
+    /// Strip padding from a frame produced by [`pad`](Self::pad).
+    pub fn unpad(self, frame: &[u8]) -> Result<Vec<u8>, PaddingError> {
+        if !self.is_enabled() {
+            return Ok(frame.to_vec());
+        }
+
+        let (header, rest) = frame
+            .split_first_chunk::<PADDING_HEADER_SIZE>()
+            .ok_or(PaddingError::Truncated)?;
+        let declared = u32::from_le_bytes(*header) as usize;
+        rest.get(..declared)
+            .map(<[u8]>::to_vec)
+            .ok_or(PaddingError::LengthOutOfRange {
+                declared,
+                available: rest.len(),
+            })
+    }
+
+    /// Padded size for a frame of `frame_len` bytes (before the size cap).
+    fn target_len(self, frame_len: usize) -> usize {
+        match self {
+            PaddingPolicy::None => frame_len,
+            PaddingPolicy::PowerOfTwo => frame_len
+                .max(MIN_POWER_OF_TWO_BUCKET)
+                .checked_next_power_of_two()
+                .unwrap_or(frame_len),
+            PaddingPolicy::Block(block) => {
+                let block = (block as usize).max(1);
+                frame_len.div_ceil(block).saturating_mul(block)
+            }
+            PaddingPolicy::Random { max } => frame_len.saturating_add(random_below(max as u64 + 1)),
+        }
+    }
+}
+
+/// Uniform random value in `0..bound` (bound > 0).
+pub(crate) fn random_below(bound: u64) -> usize {
+    // Rejection sampling avoids modulo bias
+    let zone = u64::MAX - (u64::MAX % bound);
+    loop {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes).expect("getrandom failed");
+        let value = u64::from_le_bytes(bytes);
+        if value < zone {
+            return (value % bound) as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_CAP: usize = usize::MAX;
+
+    #[test]
+    fn none_is_passthrough() {
+        let data = b"exact length".to_vec();
+        assert_eq!(PaddingPolicy::None.pad(&data, NO_CAP), data);
+        assert_eq!(PaddingPolicy::None.unpad(&data).unwrap(), data);
+    }
+
+    #[test]
+    fn power_of_two_buckets() {
+        let policy = PaddingPolicy::PowerOfTwo;
+        assert_eq!(policy.pad(b"", NO_CAP).len(), 256);
+        assert_eq!(policy.pad(&[1; 200], NO_CAP).len(), 256);
+        assert_eq!(policy.pad(&[1; 253], NO_CAP).len(), 512);
+        assert_eq!(policy.pad(&[1; 1000], NO_CAP).len(), 1024);
+        assert_eq!(policy.pad(&[1; 1021], NO_CAP).len(), 2048);
+    }
+
+    #[test]
+    fn same_bucket_hides_record_type() {
+        let policy = PaddingPolicy::PowerOfTwo;
+        let ack = policy.pad(br#"{"t":"ack"}"#, NO_CAP);
+        let edit = policy.pad(br#"{"t":"edit","text":"a longer record body"}"#, NO_CAP);
+        assert_eq!(ack.len(), edit.len());
+    }
+
+    #[test]
+    fn block_rounds_to_multiple() {
+        let policy = PaddingPolicy::Block(1024);
+        assert_eq!(policy.pad(&[1; 10], NO_CAP).len(), 1024);
+        assert_eq!(policy.pad(&[1; 1020], NO_CAP).len(), 1024);
+        assert_eq!(policy.pad(&[1; 1021], NO_CAP).len(), 2048);
+
+        // Zero block size is treated as unpadded framing
+        assert_eq!(PaddingPolicy::Block(0).pad(&[1; 10], NO_CAP).len(), 14);
+    }
+
+    #[test]
+    fn random_padding_within_bounds() {
+        let policy = PaddingPolicy::Random { max: 64 };
+        for _ in 0..100 {
+            let len = policy.pad(&[1; 100], NO_CAP).len();
+            assert!((104..=168).contains(&len), "len {} out of range", len);
+        }
+    }
+
+    #[test]
+    fn roundtrip_all_policies() {
+        let data = b"record payload".to_vec();
+        for policy in [
+            PaddingPolicy::PowerOfTwo,
+            PaddingPolicy::Block(100),
+            PaddingPolicy::Random { max: 32 },
+        ] {
+            let padded = policy.pad(&data, NO_CAP);
+            // Any enabled policy can strip any other's padding
+            assert_eq!(PaddingPolicy::PowerOfTwo.unpad(&padded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn cap_limits_padding_but_never_truncates() {
+        let policy = PaddingPolicy::PowerOfTwo;
+        assert_eq!(policy.pad(&[1; 600], 800).len(), 800);
+        // Unpadded frame already over the cap: framed but not padded
+        let padded = policy.pad(&[1; 900], 800);
+        assert_eq!(padded.len(), 904);
+        assert_eq!(policy.unpad(&padded).unwrap(), vec![1; 900]);
+    }
+
+    #[test]
+    fn malformed_frames_rejected() {
+        let policy = PaddingPolicy::PowerOfTwo;
+        assert_eq!(policy.unpad(&[1, 2]), Err(PaddingError::Truncated));
+        assert_eq!(
+            policy.unpad(&[10, 0, 0, 0, 1, 2]),
+            Err(PaddingError::LengthOutOfRange {
+                declared: 10,
+                available: 2
+            })
+        );
+    }
+
+    #[test]
+    fn policy_serializes_snake_case() {
+        assert_eq!(
+            serde_json::to_string(&PaddingPolicy::PowerOfTwo).unwrap(),
+            r#""power_of_two""#
+        );
+        let parsed: PaddingPolicy = serde_json::from_str(r#"{"block":4096}"#).unwrap();
+        assert_eq!(parsed, PaddingPolicy::Block(4096));
+    }
+}
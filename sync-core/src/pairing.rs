@@ -3,7 +3,8 @@
 //! This module provides:
 //! - Invite creation with relay NodeId, GroupId, and GroupSecret
 //! - QR code payload encoding/decoding (base64 JSON)
-//! - Short code format (XXXX-XXXX-XXXX-XXXX)
+//! - Short code format (XXXX-XXXX-XXXX-XXXX) with a sealed invite for
+//!   the relay mailbox
 //! - Time-limited invites with expiration
 //!
 //! The invite flow:
@@ -12,15 +13,42 @@
 //! 3. Device B scans/enters the invite
 //! 4. Both devices now share the GroupSecret for E2E encryption
 
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 use zerok_sync_types::GroupId;
 
+use crate::channel::ChannelError;
+use crate::compress::Compression;
+use crate::padding::PaddingPolicy;
+
 /// Default invite TTL (10 minutes).
 pub const DEFAULT_INVITE_TTL: Duration = Duration::from_secs(600);
 
+/// Domain separator for short code mailbox addresses.
+const SHORT_CODE_MAILBOX_DOMAIN: &[u8] = b"0k-sync short code mailbox v1";
+
+/// Associated data (and key salt prefix) for sealed short code invites.
+const SHORT_CODE_SEAL_DOMAIN: &[u8] = b"0k-sync short code invite v1";
+
+/// Argon2id memory for the short code key (OWASP minimum, 19 MiB).
+///
+/// The decrypt half carries only 40 bits, so the key derivation is
+/// deliberately slow. Fixed (not device-adaptive) so every device derives
+/// the same key.
+const SHORT_CODE_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+
+/// Argon2id iterations for the short code key.
+const SHORT_CODE_ARGON2_ITERATIONS: u32 = 2;
+
+/// XChaCha20-Poly1305 nonce size for sealed short code invites.
+const SHORT_CODE_NONCE_SIZE: usize = 24;
+
 /// Error type for pairing operations.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PairingError {
@@ -32,6 +60,12 @@ pub enum PairingError {
     Expired,
     /// Version mismatch.
     UnsupportedVersion(u32),
+    /// The post-quantum invite key exchange failed.
+    KeyExchange(ChannelError),
+    /// A sealed short code invite failed to decrypt (wrong code or tampered).
+    DecryptionFailed,
+    /// The other device used a different pairing code.
+    CodeMismatch,
 }
 
 impl std::fmt::Display for PairingError {
@@ -41,6 +75,9 @@ impl std::fmt::Display for PairingError {
             PairingError::InvalidShortCode(msg) => write!(f, "invalid short code: {}", msg),
             PairingError::Expired => write!(f, "invite has expired"),
             PairingError::UnsupportedVersion(v) => write!(f, "unsupported invite version: {}", v),
+            PairingError::KeyExchange(e) => write!(f, "invite key exchange failed: {}", e),
+            PairingError::DecryptionFailed => write!(f, "short code invite decryption failed"),
+            PairingError::CodeMismatch => write!(f, "pairing code mismatch"),
         }
     }
 }
@@ -143,6 +180,21 @@ pub struct Invite {
     pub created_at: u64,
     /// Unix timestamp when the invite expires.
     pub expires_at: u64,
+    /// Payload compression used by the group.
+    /// Absent in older invites, which predate compression (`None`).
+    #[serde(default)]
+    pub compression: Compression,
+    /// Payload padding used by the group (`None` in older invites).
+    #[serde(default)]
+    pub padding: PaddingPolicy,
+    /// Random ID the relay tracks for use limits and revocation.
+    /// `None` for untracked invites (valid until `expires_at`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_id: Option<[u8; 16]>,
+    /// Secure channel public keys of the relays, by position in
+    /// `relay_node_ids` (`None` where not pinned). Empty in older invites.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relay_noise_keys: Vec<Option<[u8; 32]>>,
 }
 
 impl Invite {
@@ -201,6 +253,10 @@ impl Invite {
             salt,
             created_at: now,
             expires_at: now + ttl.as_secs(),
+            compression: Compression::None,
+            padding: PaddingPolicy::None,
+            invite_id: None,
+            relay_noise_keys: Vec::new(),
         }
     }
 
@@ -225,7 +281,53 @@ impl Invite {
             salt,
             created_at: now,
             expires_at: now + ttl.as_secs(),
+            compression: Compression::None,
+            padding: PaddingPolicy::None,
+            invite_id: None,
+            relay_noise_keys: Vec::new(),
+        }
+    }
+
+    /// Set the group's payload compression (builder pattern).
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the group's payload padding policy (builder pattern).
+    pub fn with_padding(mut self, padding: PaddingPolicy) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Give the invite a random ID (builder pattern).
+    ///
+    /// Register the ID with the relay to limit how many devices may join
+    /// with this invite and to be able to revoke it.
+    pub fn with_random_id(mut self) -> Self {
+        let mut id = [0u8; 16];
+        getrandom::getrandom(&mut id).expect("getrandom failed");
+        self.invite_id = Some(id);
+        self
+    }
+
+    /// Pin a relay's secure channel public key (builder pattern).
+    ///
+    /// Joining devices verify the relay's handshake key against it. Ignored
+    /// if `relay` is not one of the invite's relays.
+    pub fn with_relay_noise_key(mut self, relay: &RelayNodeId, key: [u8; 32]) -> Self {
+        if let Some(index) = self.relay_node_ids.iter().position(|id| id == relay) {
+            self.relay_noise_keys
+                .resize(self.relay_node_ids.len(), None);
+            self.relay_noise_keys[index] = Some(key);
         }
+        self
+    }
+
+    /// Get the pinned secure channel public key of a relay, if any.
+    pub fn relay_noise_key(&self, relay: &RelayNodeId) -> Option<[u8; 32]> {
+        let index = self.relay_node_ids.iter().position(|id| id == relay)?;
+        self.relay_noise_keys.get(index).copied().flatten()
     }
 
     /// Check if the invite has expired.
@@ -319,6 +421,103 @@ impl Invite {
 
         Ok((lookup, decrypt))
     }
+
+    /// Relay mailbox address for a short code.
+    ///
+    /// Hash of the lookup half only; the relay never learns the decrypt half.
+    pub fn short_code_mailbox(code: &str) -> Result<[u8; 32], PairingError> {
+        let (lookup, _) = Self::split_short_code(code)?;
+        let mut hasher = Sha256::new();
+        hasher.update(SHORT_CODE_MAILBOX_DOMAIN);
+        hasher.update(lookup.as_bytes());
+        Ok(hasher.finalize().into())
+    }
+
+    /// Encrypt this invite for the relay mailbox of [`to_short_code`](Self::to_short_code).
+    ///
+    /// Format: `[nonce: 24][XChaCha20-Poly1305(qr_payload)]`, keyed by
+    /// Argon2id over the decrypt half.
+    pub fn seal_for_short_code(&self) -> Vec<u8> {
+        let (lookup, decrypt) =
+            Self::split_short_code(&self.to_short_code()).expect("short code is well-formed");
+        let cipher = short_code_cipher(&lookup, &decrypt);
+
+        let mut nonce = [0u8; SHORT_CODE_NONCE_SIZE];
+        getrandom::getrandom(&mut nonce).expect("getrandom failed");
+        let payload = self.to_qr_payload();
+        let ciphertext = cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: payload.as_bytes(),
+                    aad: SHORT_CODE_SEAL_DOMAIN,
+                },
+            )
+            .expect("short code encryption failed");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    /// Decrypt an invite fetched from the relay mailbox for `code`.
+    ///
+    /// Rejects invites that don't match the code or have expired.
+    pub fn open_short_code(code: &str, sealed: &[u8]) -> Result<Self, PairingError> {
+        let (lookup, decrypt) = Self::split_short_code(code)?;
+        if sealed.len() < SHORT_CODE_NONCE_SIZE {
+            return Err(PairingError::DecryptionFailed);
+        }
+        let (nonce, ciphertext) = sealed.split_at(SHORT_CODE_NONCE_SIZE);
+
+        let plaintext = short_code_cipher(&lookup, &decrypt)
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: SHORT_CODE_SEAL_DOMAIN,
+                },
+            )
+            .map_err(|_| PairingError::DecryptionFailed)?;
+        let payload = String::from_utf8(plaintext)
+            .map_err(|e| PairingError::InvalidPayload(format!("utf-8: {}", e)))?;
+        let invite = Self::from_qr_payload(&payload)?;
+
+        let (invite_lookup, invite_decrypt) = Self::split_short_code(&invite.to_short_code())?;
+        if invite_lookup != lookup || invite_decrypt != decrypt {
+            return Err(PairingError::InvalidPayload(
+                "invite does not match short code".into(),
+            ));
+        }
+        if invite.is_expired() {
+            return Err(PairingError::Expired);
+        }
+
+        Ok(invite)
+    }
+}
+
+/// Cipher for a sealed short code invite (Argon2id over the decrypt half).
+fn short_code_cipher(lookup: &str, decrypt: &str) -> XChaCha20Poly1305 {
+    let params = Params::new(
+        SHORT_CODE_ARGON2_MEMORY_KIB,
+        SHORT_CODE_ARGON2_ITERATIONS,
+        1,
+        Some(32),
+    )
+    .expect("valid argon2 parameters");
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut salt = SHORT_CODE_SEAL_DOMAIN.to_vec();
+    salt.extend_from_slice(lookup.as_bytes());
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(decrypt.as_bytes(), &salt, &mut key)
+        .expect("argon2 hash failed");
+
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    key.zeroize();
+    cipher
 }
 
 /// Custom deserializer that accepts either a single RelayNodeId or a Vec<RelayNodeId>.
@@ -696,4 +895,234 @@ mod tests {
         // Same short code regardless of relay count
         assert_eq!(invite_v2.to_short_code(), invite_v3.to_short_code());
     }
+
+    #[test]
+    fn invite_carries_group_compression() {
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        )
+        .with_compression(Compression::Zstd);
+
+        let decoded = Invite::from_qr_payload(&invite.to_qr_payload()).unwrap();
+        assert_eq!(decoded.compression, Compression::Zstd);
+    }
+
+    #[test]
+    fn invite_carries_group_padding() {
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        )
+        .with_padding(PaddingPolicy::Block(4096));
+
+        let decoded = Invite::from_qr_payload(&invite.to_qr_payload()).unwrap();
+        assert_eq!(decoded.padding, PaddingPolicy::Block(4096));
+    }
+
+    #[test]
+    fn invite_without_group_settings_defaults_to_none() {
+        // Invites from before compression existed have no field
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        let mut json: serde_json::Value = serde_json::to_value(&invite).unwrap();
+        json.as_object_mut().unwrap().remove("compression");
+        json.as_object_mut().unwrap().remove("padding");
+        let payload = URL_SAFE_NO_PAD.encode(json.to_string());
+
+        let decoded = Invite::from_qr_payload(&payload).unwrap();
+        assert_eq!(decoded.compression, Compression::None);
+        assert_eq!(decoded.padding, PaddingPolicy::None);
+    }
+
+    #[test]
+    fn sealed_short_code_roundtrip() {
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        let code = invite.to_short_code();
+        let sealed = invite.seal_for_short_code();
+
+        let opened = Invite::open_short_code(&code, &sealed).unwrap();
+        assert_eq!(opened.group_id, invite.group_id);
+        assert_eq!(opened.group_secret, invite.group_secret);
+        assert_eq!(opened.salt, invite.salt);
+
+        // Dashes are optional when entering the code
+        let undashed: String = code.chars().filter(|c| *c != '-').collect();
+        assert!(Invite::open_short_code(&undashed, &sealed).is_ok());
+    }
+
+    #[test]
+    fn sealed_short_code_hides_secret() {
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        let sealed = invite.seal_for_short_code();
+        let secret = invite.group_secret.as_bytes();
+        let (_, decrypt) = Invite::split_short_code(&invite.to_short_code()).unwrap();
+        assert!(!sealed.windows(32).any(|w| w == secret));
+        assert!(!sealed.windows(8).any(|w| w == decrypt.as_bytes()));
+    }
+
+    #[test]
+    fn sealed_short_code_rejects_wrong_code() {
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        let other = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        let sealed = invite.seal_for_short_code();
+
+        assert_eq!(
+            Invite::open_short_code(&other.to_short_code(), &sealed).unwrap_err(),
+            PairingError::DecryptionFailed
+        );
+        assert_eq!(
+            Invite::open_short_code(&invite.to_short_code(), &sealed[..10]).unwrap_err(),
+            PairingError::DecryptionFailed
+        );
+    }
+
+    #[test]
+    fn sealed_short_code_rejects_expired_invite() {
+        let invite = Invite::create_with_ttl(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+            Duration::from_secs(0),
+        );
+        let sealed = invite.seal_for_short_code();
+        assert_eq!(
+            Invite::open_short_code(&invite.to_short_code(), &sealed).unwrap_err(),
+            PairingError::Expired
+        );
+    }
+
+    #[test]
+    fn short_code_mailbox_depends_only_on_lookup() {
+        let a = Invite::short_code_mailbox("ABCD-EFGH-IJKL-MNOP").unwrap();
+        let b = Invite::short_code_mailbox("ABCDEFGH-QRST-UVWX").unwrap();
+        let c = Invite::short_code_mailbox("ABCD-EFGZ-IJKL-MNOP").unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(Invite::short_code_mailbox("ABCD").is_err());
+    }
+
+    #[test]
+    fn invite_id_roundtrips_and_is_optional() {
+        let invite = Invite::create(
+            test_relay_node_id(),
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        assert!(invite.invite_id.is_none());
+
+        let tracked = invite.clone().with_random_id();
+        let id = tracked.invite_id.unwrap();
+        let decoded = Invite::from_qr_payload(&tracked.to_qr_payload()).unwrap();
+        assert_eq!(decoded.invite_id, Some(id));
+
+        // Fresh ID each time
+        assert_ne!(invite.with_random_id().invite_id, Some(id));
+    }
+
+    #[test]
+    fn relay_noise_keys_roundtrip_and_are_optional() {
+        let relay_a = RelayNodeId::from_bytes([0xAA; 32]);
+        let relay_b = RelayNodeId::from_bytes([0xBB; 32]);
+        let invite = Invite::create_multi_relay(
+            vec![relay_a, relay_b],
+            GroupId::random(),
+            GroupSecret::random(),
+            test_salt(),
+        );
+        assert_eq!(invite.relay_noise_key(&relay_a), None);
+
+        let pinned = invite
+            .with_relay_noise_key(&relay_b, [7u8; 32])
+            .with_relay_noise_key(&RelayNodeId::from_bytes([0xCC; 32]), [9u8; 32]);
+        let decoded = Invite::from_qr_payload(&pinned.to_qr_payload()).unwrap();
+        assert_eq!(decoded.relay_noise_key(&relay_a), None);
+        assert_eq!(decoded.relay_noise_key(&relay_b), Some([7u8; 32]));
+        assert_eq!(decoded.relay_noise_keys.len(), 2);
+    }
+
+    /// Invite as decoded by devices from before tracked invites and pinned
+    /// relay keys, which ignore fields they don't know.
+    #[derive(Deserialize)]
+    struct BaselineInvite {
+        version: u32,
+        relay_node_ids: Vec<RelayNodeId>,
+        group_id: GroupId,
+        group_secret: GroupSecret,
+        salt: Vec<u8>,
+        expires_at: u64,
+    }
+
+    #[test]
+    fn baseline_invite_decodes_without_optional_fields() {
+        let relay = test_relay_node_id();
+        let group_id = GroupId::random();
+        let secret = GroupSecret::random();
+
+        // Payload as encoded before invite IDs and relay keys existed
+        let baseline_json = serde_json::json!({
+            "version": 2,
+            "relay_node_ids": [relay],
+            "group_id": group_id,
+            "group_secret": secret,
+            "salt": test_salt(),
+            "created_at": 1000000u64,
+            "expires_at": 2000000u64,
+        });
+        let payload = URL_SAFE_NO_PAD.encode(baseline_json.to_string());
+
+        let decoded = Invite::from_qr_payload(&payload).unwrap();
+        assert_eq!(decoded.relay_node_ids, vec![relay]);
+        assert_eq!(decoded.group_id, group_id);
+        assert_eq!(decoded.invite_id, None);
+        assert_eq!(decoded.relay_noise_key(&relay), None);
+    }
+
+    #[test]
+    fn new_invite_decodes_as_baseline() {
+        let relay = test_relay_node_id();
+        let invite = Invite::create(relay, GroupId::random(), GroupSecret::random(), test_salt())
+            .with_random_id()
+            .with_relay_noise_key(&relay, [7u8; 32]);
+
+        // Older devices can still join with a tracked, pinned invite
+        let json = URL_SAFE_NO_PAD.decode(invite.to_qr_payload()).unwrap();
+        let baseline: BaselineInvite = serde_json::from_slice(&json).unwrap();
+        assert_eq!(baseline.version, invite.version);
+        assert_eq!(baseline.relay_node_ids, vec![relay]);
+        assert_eq!(baseline.group_id, invite.group_id);
+        assert_eq!(baseline.group_secret, invite.group_secret);
+        assert_eq!(baseline.salt, invite.salt);
+        assert_eq!(baseline.expires_at, invite.expires_at);
+    }
 }
@@ -0,0 +1,473 @@
+//! Pairing from a short numeric code (CPace PAKE).
+//!
+//! Both devices type the same short [`PairingCode`] (e.g. `4096-271828`).
+//! The code is never sent anywhere: the devices run CPace over ristretto255
+//! through relay mailboxes, and the inviter seals the full [`Invite`] under
+//! the resulting key.
+//!
+//! The code has two parts:
+//!
+//! - The *nameplate* (4 digits) only addresses the mailboxes. The relay can
+//!   learn it.
+//! - The *password* (6 digits) derives the CPace generator. An attacker,
+//!   including the relay, gets one online guess per pairing attempt: the
+//!   inviter aborts as soon as a confirmation fails, and nothing observed
+//!   allows an offline search.
+//!
+//! The flow (each arrow is one relay mailbox message):
+//!
+//! ```text
+//! inviter → joiner    Y_a = y_a * G(password)
+//! joiner → inviter    Y_b = y_b * G(password), key confirmation
+//! inviter → joiner    sealed invite
+//! ```
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::oprf::random_scalar;
+use crate::pairing::{Invite, PairingError};
+
+/// Digits in the public nameplate.
+const NAMEPLATE_DIGITS: usize = 4;
+
+/// Digits in the secret password.
+const PASSWORD_DIGITS: usize = 6;
+
+/// Domain separator for the CPace generator.
+const GENERATOR_DOMAIN: &[u8] = b"0k-sync cpace generator v1";
+
+/// Domain separator for the session key.
+const SESSION_KEY_DOMAIN: &[u8] = b"0k-sync cpace session key v1";
+
+/// Domain separator for mailbox address derivation.
+const MAILBOX_DOMAIN: &[u8] = b"0k-sync pairing code mailbox v1";
+
+/// Associated data for the joiner's key confirmation.
+const CONFIRM_AAD: &[u8] = b"0k-sync cpace joiner confirm v1";
+
+/// Associated data for the sealed invite.
+const INVITE_AAD: &[u8] = b"0k-sync cpace invite v1";
+
+const MAILBOX_TO_INVITER: u8 = 0x01;
+const MAILBOX_TO_JOINER: u8 = 0x02;
+
+const SHARE_SIZE: usize = 32;
+const NONCE_SIZE: usize = 24;
+const TAG_SIZE: usize = 16;
+
+/// A short code typed on both devices to pair them.
+///
+/// Formatted as `NNNN-NNNNNN`: nameplate, then password.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct PairingCode {
+    nameplate: String,
+    password: String,
+}
+
+impl PairingCode {
+    /// Generate a random pairing code.
+    pub fn generate() -> Self {
+        Self {
+            nameplate: random_digits(NAMEPLATE_DIGITS),
+            password: random_digits(PASSWORD_DIGITS),
+        }
+    }
+
+    /// Parse a pairing code as typed by the user.
+    ///
+    /// Spaces and dashes are ignored.
+    pub fn parse(code: &str) -> Result<Self, PairingError> {
+        let digits: String = code
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .collect();
+
+        if digits.len() != NAMEPLATE_DIGITS + PASSWORD_DIGITS
+            || !digits.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(PairingError::InvalidShortCode(format!(
+                "expected {} digits",
+                NAMEPLATE_DIGITS + PASSWORD_DIGITS
+            )));
+        }
+
+        let (nameplate, password) = digits.split_at(NAMEPLATE_DIGITS);
+        Ok(Self {
+            nameplate: nameplate.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Relay mailbox the inviter reads (joiner's share and confirmation).
+    pub fn inviter_mailbox(&self) -> [u8; 32] {
+        self.mailbox(MAILBOX_TO_INVITER)
+    }
+
+    /// Relay mailbox the joiner reads (inviter's share and the invite).
+    pub fn joiner_mailbox(&self) -> [u8; 32] {
+        self.mailbox(MAILBOX_TO_JOINER)
+    }
+
+    fn mailbox(&self, direction: u8) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(MAILBOX_DOMAIN);
+        hasher.update(self.nameplate.as_bytes());
+        hasher.update([direction]);
+        hasher.finalize().into()
+    }
+
+    /// CPace generator for this code's password.
+    fn generator(&self) -> RistrettoPoint {
+        let mut hasher = Sha512::new();
+        hasher.update(GENERATOR_DOMAIN);
+        for part in [&self.nameplate, &self.password] {
+            hasher.update((part.len() as u16).to_be_bytes());
+            hasher.update(part.as_bytes());
+        }
+        let uniform: [u8; 64] = hasher.finalize().into();
+        RistrettoPoint::from_uniform_bytes(&uniform)
+    }
+}
+
+impl std::fmt::Display for PairingCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.nameplate, self.password)
+    }
+}
+
+// Intentionally opaque debug to avoid logging secrets
+impl std::fmt::Debug for PairingCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PairingCode({}-[REDACTED])", self.nameplate)
+    }
+}
+
+/// Inviter side: share sent, awaiting the joiner's share and confirmation.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct CodeOffer {
+    code: PairingCode,
+    secret: Scalar,
+    share: [u8; SHARE_SIZE],
+}
+
+impl CodeOffer {
+    /// Start pairing, returning the state and the share to post to the
+    /// joiner's mailbox.
+    pub fn start(code: &PairingCode) -> (Self, Vec<u8>) {
+        let secret = random_scalar();
+        let share = (code.generator() * secret).compress().to_bytes();
+        let offer = Self {
+            code: code.clone(),
+            secret,
+            share,
+        };
+        (offer, share.to_vec())
+    }
+
+    /// Check the joiner's confirmation and seal `invite` for it.
+    ///
+    /// Fails with [`PairingError::CodeMismatch`] if the joiner used a
+    /// different code. Pairing must then restart with a fresh code.
+    pub fn deliver(self, message: &[u8], invite: &Invite) -> Result<Vec<u8>, PairingError> {
+        if message.len() != SHARE_SIZE + TAG_SIZE {
+            return Err(PairingError::InvalidPayload(
+                "malformed pairing confirmation".into(),
+            ));
+        }
+        let (joiner_share, confirmation) = message.split_at(SHARE_SIZE);
+        let joiner_share: [u8; SHARE_SIZE] = joiner_share.try_into().expect("split at share size");
+
+        let keys = SessionKeys::derive(
+            &self.code,
+            &self.secret,
+            &joiner_share,
+            &self.share,
+            &joiner_share,
+        )?;
+        keys.confirm_cipher()
+            .decrypt(
+                XNonce::from_slice(&[0u8; NONCE_SIZE]),
+                Payload {
+                    msg: confirmation,
+                    aad: CONFIRM_AAD,
+                },
+            )
+            .map_err(|_| PairingError::CodeMismatch)?;
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        getrandom::getrandom(&mut nonce).expect("getrandom failed");
+        let ciphertext = keys
+            .invite_cipher()
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: invite.to_qr_payload().as_bytes(),
+                    aad: INVITE_AAD,
+                },
+            )
+            .expect("invite encryption failed");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+}
+
+/// Joiner side: share and confirmation sent, awaiting the sealed invite.
+pub struct CodeJoin {
+    keys: SessionKeys,
+}
+
+impl CodeJoin {
+    /// Process the inviter's share, returning the state and the message
+    /// (share and key confirmation) to post to the inviter's mailbox.
+    pub fn respond(
+        code: &PairingCode,
+        inviter_share: &[u8],
+    ) -> Result<(Self, Vec<u8>), PairingError> {
+        let inviter_share: [u8; SHARE_SIZE] = inviter_share
+            .try_into()
+            .map_err(|_| PairingError::InvalidPayload("malformed pairing share".into()))?;
+
+        let mut secret = random_scalar();
+        let share = (code.generator() * secret).compress().to_bytes();
+        let keys = SessionKeys::derive(code, &secret, &inviter_share, &inviter_share, &share)?;
+        secret.zeroize();
+
+        let confirmation = keys
+            .confirm_cipher()
+            .encrypt(
+                XNonce::from_slice(&[0u8; NONCE_SIZE]),
+                Payload {
+                    msg: &[],
+                    aad: CONFIRM_AAD,
+                },
+            )
+            .expect("confirmation encryption failed");
+
+        let mut message = share.to_vec();
+        message.extend_from_slice(&confirmation);
+        Ok((Self { keys }, message))
+    }
+
+    /// Open the sealed invite delivered by the inviter.
+    pub fn receive(self, sealed: &[u8]) -> Result<Invite, PairingError> {
+        if sealed.len() < NONCE_SIZE {
+            return Err(PairingError::CodeMismatch);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+
+        let payload = self
+            .keys
+            .invite_cipher()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: INVITE_AAD,
+                },
+            )
+            .map_err(|_| PairingError::CodeMismatch)?;
+        let payload = String::from_utf8(payload)
+            .map_err(|e| PairingError::InvalidPayload(format!("utf-8: {}", e)))?;
+
+        let invite = Invite::from_qr_payload(&payload)?;
+        if invite.is_expired() {
+            return Err(PairingError::Expired);
+        }
+        Ok(invite)
+    }
+}
+
+/// Keys derived from one CPace run.
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct SessionKeys {
+    confirm: [u8; 32],
+    invite: [u8; 32],
+}
+
+impl SessionKeys {
+    /// Derive the session keys from our secret and the peer's share.
+    ///
+    /// Both shares are bound into the key in inviter-then-joiner order.
+    fn derive(
+        code: &PairingCode,
+        secret: &Scalar,
+        peer_share: &[u8; SHARE_SIZE],
+        inviter_share: &[u8; SHARE_SIZE],
+        joiner_share: &[u8; SHARE_SIZE],
+    ) -> Result<Self, PairingError> {
+        let point = CompressedRistretto(*peer_share)
+            .decompress()
+            .filter(|p| *p != RistrettoPoint::identity())
+            .ok_or_else(|| PairingError::InvalidPayload("invalid pairing share".into()))?;
+        let shared = (point * secret).compress();
+
+        let mut hasher = Sha512::new();
+        hasher.update(SESSION_KEY_DOMAIN);
+        hasher.update(code.nameplate.as_bytes());
+        hasher.update(inviter_share);
+        hasher.update(joiner_share);
+        hasher.update(shared.as_bytes());
+        let mut output: [u8; 64] = hasher.finalize().into();
+
+        let mut keys = Self {
+            confirm: [0u8; 32],
+            invite: [0u8; 32],
+        };
+        keys.confirm.copy_from_slice(&output[..32]);
+        keys.invite.copy_from_slice(&output[32..]);
+        output.zeroize();
+        Ok(keys)
+    }
+
+    fn confirm_cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new((&self.confirm).into())
+    }
+
+    fn invite_cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new((&self.invite).into())
+    }
+}
+
+/// Uniformly random decimal digits.
+fn random_digits(count: usize) -> String {
+    (0..count)
+        .map(|_| loop {
+            // Rejection sampling avoids modulo bias
+            let mut byte = [0u8; 1];
+            getrandom::getrandom(&mut byte).expect("getrandom failed");
+            if byte[0] < 250 {
+                break char::from(b'0' + byte[0] % 10);
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pairing::{GroupSecret, RelayNodeId};
+
+    fn group_invite() -> Invite {
+        let secret = GroupSecret::random();
+        let group_id = secret.derive_group_id();
+        Invite::create(
+            RelayNodeId::random(),
+            group_id,
+            secret,
+            b"salt-0123456789!".to_vec(),
+        )
+    }
+
+    fn run_pairing(
+        inviter_code: &PairingCode,
+        joiner_code: &PairingCode,
+        invite: &Invite,
+    ) -> Result<Invite, PairingError> {
+        let (offer, share) = CodeOffer::start(inviter_code);
+        let (join, reply) = CodeJoin::respond(joiner_code, &share)?;
+        let sealed = offer.deliver(&reply, invite)?;
+        join.receive(&sealed)
+    }
+
+    #[test]
+    fn pairing_delivers_invite() {
+        let code = PairingCode::generate();
+        let invite = group_invite();
+
+        let received = run_pairing(&code, &code, &invite).unwrap();
+
+        assert_eq!(received.group_id, invite.group_id);
+        assert_eq!(received.group_secret, invite.group_secret);
+        assert_eq!(received.salt, invite.salt);
+    }
+
+    #[test]
+    fn wrong_code_rejected_by_inviter() {
+        let code = PairingCode::parse("1234-567890").unwrap();
+        let typo = PairingCode::parse("1234-567891").unwrap();
+
+        let result = run_pairing(&code, &typo, &group_invite());
+        assert_eq!(result.unwrap_err(), PairingError::CodeMismatch);
+    }
+
+    #[test]
+    fn code_parse_and_display_roundtrip() {
+        let code = PairingCode::generate();
+        let text = code.to_string();
+        assert_eq!(text.len(), 11);
+        assert_eq!(PairingCode::parse(&text).unwrap(), code);
+        assert_eq!(
+            PairingCode::parse(" 1234 567890 ").unwrap(),
+            PairingCode::parse("1234-567890").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_codes_rejected() {
+        for code in ["", "1234-56789", "1234-5678901", "abcd-efghij"] {
+            assert!(matches!(
+                PairingCode::parse(code),
+                Err(PairingError::InvalidShortCode(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn mailboxes_depend_only_on_nameplate() {
+        let a = PairingCode::parse("1234-000000").unwrap();
+        let b = PairingCode::parse("1234-999999").unwrap();
+        assert_eq!(a.inviter_mailbox(), b.inviter_mailbox());
+        assert_ne!(a.inviter_mailbox(), a.joiner_mailbox());
+    }
+
+    #[test]
+    fn shares_hide_the_password() {
+        // Fresh secret per run: the same code gives unlinkable shares
+        let code = PairingCode::generate();
+        let (_, first) = CodeOffer::start(&code);
+        let (_, second) = CodeOffer::start(&code);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn invalid_share_rejected() {
+        let code = PairingCode::generate();
+        assert!(matches!(
+            CodeJoin::respond(&code, &[0u8; SHARE_SIZE]),
+            Err(PairingError::InvalidPayload(_))
+        ));
+        assert!(matches!(
+            CodeJoin::respond(&code, &[1u8; 5]),
+            Err(PairingError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn tampered_invite_rejected() {
+        let code = PairingCode::generate();
+        let (offer, share) = CodeOffer::start(&code);
+        let (join, reply) = CodeJoin::respond(&code, &share).unwrap();
+        let mut sealed = offer.deliver(&reply, &group_invite()).unwrap();
+        let end = sealed.len() - 1;
+        sealed[end] ^= 0x01;
+
+        assert_eq!(
+            join.receive(&sealed).unwrap_err(),
+            PairingError::CodeMismatch
+        );
+    }
+
+    #[test]
+    fn debug_redacts_password() {
+        let code = PairingCode::parse("1234-567890").unwrap();
+        assert_eq!(format!("{:?}", code), "PairingCode(1234-[REDACTED])");
+    }
+}
@@ -0,0 +1,355 @@
+//! Passphrase generation and strength estimation.
+//!
+//! Group secrets are derived from a passphrase with Argon2id, so the
+//! passphrase's entropy bounds how hard an offline guess is for anyone
+//! holding the salt and some ciphertext. This module:
+//!
+//! - generates diceware-style passphrases from an embedded 2048-word list
+//!   (11 bits per word) with [`PassphraseGenerator`];
+//! - estimates the entropy of user-supplied passphrases with
+//!   [`estimate_entropy`];
+//! - checks a passphrase against a minimum with [`check_passphrase`].
+//!
+//! The estimate is deliberately conservative: words from the list count as
+//! 11 bits, sequences and repeats as 1 bit per character, and a handful of
+//! well-known passwords as nothing.
+
+use std::sync::OnceLock;
+
+/// Embedded wordlist, one lowercase word per line.
+const WORDLIST: &str = include_str!("wordlist.txt");
+
+/// Number of words in the embedded wordlist.
+pub const WORDLIST_SIZE: usize = 2048;
+
+/// Entropy contributed by one generated word.
+pub const BITS_PER_WORD: f64 = 11.0;
+
+/// Default entropy target for generated passphrases (6 words).
+pub const DEFAULT_PASSPHRASE_ENTROPY: f64 = 66.0;
+
+/// Recommended minimum entropy for a group passphrase.
+pub const MIN_PASSPHRASE_ENTROPY: f64 = 48.0;
+
+/// Passwords that are guessed first regardless of length or character mix.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password",
+    "passphrase",
+    "passw0rd",
+    "qwerty",
+    "qwertyuiop",
+    "letmein",
+    "iloveyou",
+    "welcome",
+    "admin",
+    "administrator",
+    "secret",
+    "trustno1",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "changeme",
+    "correcthorsebatterystaple",
+];
+
+/// Error type for passphrase checks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PassphraseError {
+    /// The passphrase's estimated entropy is below the required minimum.
+    TooWeak {
+        /// Estimated entropy in bits.
+        estimated_bits: f64,
+        /// Required entropy in bits.
+        required_bits: f64,
+    },
+}
+
+impl std::fmt::Display for PassphraseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PassphraseError::TooWeak {
+                estimated_bits,
+                required_bits,
+            } => write!(
+                f,
+                "passphrase too weak: ~{:.0} bits of entropy, need {:.0}",
+                estimated_bits, required_bits
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PassphraseError {}
+
+/// Coarse strength rating for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PassphraseStrength {
+    /// Under 28 bits: guessable in seconds even with Argon2id.
+    VeryWeak,
+    /// Below [`MIN_PASSPHRASE_ENTROPY`].
+    Weak,
+    /// At least [`MIN_PASSPHRASE_ENTROPY`].
+    Strong,
+    /// 80 bits or more.
+    VeryStrong,
+}
+
+impl PassphraseStrength {
+    /// Rate an entropy estimate.
+    pub fn from_entropy(bits: f64) -> Self {
+        if bits < 28.0 {
+            Self::VeryWeak
+        } else if bits < MIN_PASSPHRASE_ENTROPY {
+            Self::Weak
+        } else if bits < 80.0 {
+            Self::Strong
+        } else {
+            Self::VeryStrong
+        }
+    }
+}
+
+impl std::fmt::Display for PassphraseStrength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VeryWeak => write!(f, "very weak"),
+            Self::Weak => write!(f, "weak"),
+            Self::Strong => write!(f, "strong"),
+            Self::VeryStrong => write!(f, "very strong"),
+        }
+    }
+}
+
+/// Generator for diceware-style passphrases.
+#[derive(Debug, Clone)]
+pub struct PassphraseGenerator {
+    entropy_bits: f64,
+    separator: String,
+}
+
+impl Default for PassphraseGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PassphraseGenerator {
+    /// Create a generator targeting [`DEFAULT_PASSPHRASE_ENTROPY`],
+    /// separating words with `-`.
+    pub fn new() -> Self {
+        Self {
+            entropy_bits: DEFAULT_PASSPHRASE_ENTROPY,
+            separator: "-".to_string(),
+        }
+    }
+
+    /// Set the minimum entropy of generated passphrases (builder pattern).
+    ///
+    /// Rounded up to a whole number of words; at least one word.
+    pub fn with_entropy_bits(mut self, bits: f64) -> Self {
+        self.entropy_bits = bits;
+        self
+    }
+
+    /// Set the separator placed between words (builder pattern).
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Number of words in generated passphrases.
+    pub fn word_count(&self) -> usize {
+        ((self.entropy_bits / BITS_PER_WORD).ceil() as usize).max(1)
+    }
+
+    /// Actual entropy of generated passphrases.
+    pub fn entropy_bits(&self) -> f64 {
+        self.word_count() as f64 * BITS_PER_WORD
+    }
+
+    /// Generate a passphrase.
+    pub fn generate(&self) -> String {
+        let words = wordlist();
+        (0..self.word_count())
+            .map(|_| {
+                let mut bytes = [0u8; 2];
+                getrandom::getrandom(&mut bytes).expect("getrandom failed");
+                // 2048 words: the low 11 bits index without bias
+                words[u16::from_le_bytes(bytes) as usize % WORDLIST_SIZE]
+            })
+            .collect::<Vec<_>>()
+            .join(&self.separator)
+    }
+}
+
+/// Estimate the entropy of a passphrase in bits.
+///
+/// The passphrase is split into tokens at non-alphanumeric characters.
+/// Tokens from the wordlist count as one word each; other tokens are scored
+/// per character from the character classes they use.
+pub fn estimate_entropy(passphrase: &str) -> f64 {
+    let normalized: String = passphrase
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if normalized.is_empty() || COMMON_PASSWORDS.contains(&normalized.as_str()) {
+        return 0.0;
+    }
+
+    let words = wordlist();
+    passphrase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let lower = token.to_lowercase();
+            if COMMON_PASSWORDS.contains(&lower.as_str()) {
+                1.0
+            } else if words.binary_search(&lower.as_str()).is_ok() {
+                // Capitalized or upper-case words add about a bit
+                let cased = if token == lower { 0.0 } else { 1.0 };
+                BITS_PER_WORD + cased
+            } else {
+                token_entropy(token)
+            }
+        })
+        .sum()
+}
+
+/// Rate a passphrase for display.
+pub fn passphrase_strength(passphrase: &str) -> PassphraseStrength {
+    PassphraseStrength::from_entropy(estimate_entropy(passphrase))
+}
+
+/// Check that a passphrase meets `min_bits` of estimated entropy.
+///
+/// Returns the estimate on success.
+pub fn check_passphrase(passphrase: &str, min_bits: f64) -> Result<f64, PassphraseError> {
+    let estimated_bits = estimate_entropy(passphrase);
+    if estimated_bits < min_bits {
+        return Err(PassphraseError::TooWeak {
+            estimated_bits,
+            required_bits: min_bits,
+        });
+    }
+    Ok(estimated_bits)
+}
+
+/// The embedded wordlist (sorted).
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| WORDLIST.lines().collect())
+}
+
+/// Per-character entropy of a token not found in the wordlist.
+fn token_entropy(token: &str) -> f64 {
+    let mut pool = 0u32;
+    if token.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if token.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if token.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if !token.is_ascii() {
+        pool += 100;
+    }
+    let per_char = f64::from(pool.max(2)).log2();
+
+    let mut bits = 0.0;
+    let mut previous: Option<char> = None;
+    for c in token.chars() {
+        // Repeats and runs like "aaa", "abc" or "321" are nearly free
+        let predictable = previous.is_some_and(|p| {
+            let step = c as i64 - p as i64;
+            step.abs() <= 1
+        });
+        bits += if predictable { 1.0 } else { per_char };
+        previous = Some(c);
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wordlist_is_sorted_and_unique() {
+        let words = wordlist();
+        assert_eq!(words.len(), WORDLIST_SIZE);
+        assert!(words.windows(2).all(|w| w[0] < w[1]));
+        assert!(words
+            .iter()
+            .all(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_lowercase())));
+    }
+
+    #[test]
+    fn generator_meets_entropy_target() {
+        let generator = PassphraseGenerator::new();
+        assert_eq!(generator.word_count(), 6);
+        assert_eq!(generator.entropy_bits(), 66.0);
+
+        let passphrase = generator.generate();
+        assert_eq!(passphrase.split('-').count(), 6);
+        assert!(estimate_entropy(&passphrase) >= generator.entropy_bits());
+
+        let generator = PassphraseGenerator::new()
+            .with_entropy_bits(100.0)
+            .with_separator(" ");
+        assert_eq!(generator.word_count(), 10);
+        assert_eq!(generator.generate().split(' ').count(), 10);
+    }
+
+    #[test]
+    fn generated_passphrases_differ() {
+        let generator = PassphraseGenerator::new();
+        assert_ne!(generator.generate(), generator.generate());
+    }
+
+    #[test]
+    fn weak_passphrases_score_low() {
+        assert_eq!(estimate_entropy(""), 0.0);
+        assert_eq!(estimate_entropy("password"), 0.0);
+        assert_eq!(estimate_entropy("correct-horse-battery-staple"), 0.0);
+        assert!(estimate_entropy("aaaaaaaaaaaa") < 20.0);
+        assert!(estimate_entropy("abcdefghijkl") < 20.0);
+        assert_eq!(passphrase_strength("qwerty"), PassphraseStrength::VeryWeak);
+    }
+
+    #[test]
+    fn words_score_per_word() {
+        assert_eq!(estimate_entropy("maple-otter-violin"), 33.0);
+        assert_eq!(estimate_entropy("Maple otter violin"), 34.0);
+        assert_eq!(
+            passphrase_strength("maple otter violin canyon pixel"),
+            PassphraseStrength::Strong
+        );
+    }
+
+    #[test]
+    fn random_characters_score_per_class() {
+        // 12 characters from a 62-character pool, no runs
+        let bits = estimate_entropy("q7Rw2mXk9Lp4");
+        assert!((bits - 12.0 * 62f64.log2()).abs() < 1e-9);
+        assert_eq!(
+            PassphraseStrength::from_entropy(bits),
+            PassphraseStrength::Strong
+        );
+    }
+
+    #[test]
+    fn check_enforces_minimum() {
+        assert!(check_passphrase("maple-otter-violin-canyon-pixel", 48.0).is_ok());
+        assert_eq!(
+            check_passphrase("maple-otter", 48.0).unwrap_err(),
+            PassphraseError::TooWeak {
+                estimated_bits: 22.0,
+                required_bits: 48.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Hashcash-style proof of work for relay admission.
+//!
+//! Endpoint IDs cost nothing to mint, so per-identity rate limits alone
+//! cannot stop a flood of fresh identities. A loaded relay may answer HELLO
+//! with a challenge: a random nonce and a difficulty in bits. The client
+//! searches for a counter such that
+//! `SHA-256("0k-sync-pow-v1" || nonce || counter)` (counter big-endian)
+//! starts with `difficulty` zero bits, and repeats HELLO with it. Expected
+//! work doubles with each bit; checking a solution costs one hash.
+
+use sha2::{Digest, Sha256};
+
+/// Size of a challenge nonce in bytes.
+pub const POW_NONCE_SIZE: usize = 32;
+
+/// Highest difficulty a relay may ask for (about 4 billion hashes).
+pub const MAX_POW_DIFFICULTY: u8 = 32;
+
+/// Domain separation for the challenge hash.
+const POW_DOMAIN: &[u8] = b"0k-sync-pow-v1";
+
+/// Generate a random challenge nonce.
+pub fn pow_nonce() -> [u8; POW_NONCE_SIZE] {
+    let mut nonce = [0u8; POW_NONCE_SIZE];
+    getrandom::getrandom(&mut nonce).expect("getrandom failed");
+    nonce
+}
+
+/// Check that `counter` solves the challenge.
+pub fn verify_pow(nonce: &[u8; POW_NONCE_SIZE], difficulty: u8, counter: u64) -> bool {
+    difficulty <= MAX_POW_DIFFICULTY && leading_zero_bits(&digest(nonce, counter)) >= difficulty
+}
+
+/// Find a counter that solves the challenge.
+///
+/// Returns `None` if `difficulty` exceeds [`MAX_POW_DIFFICULTY`]. This is
+/// CPU-bound; async callers should run it on a blocking thread.
+pub fn solve_pow(nonce: &[u8; POW_NONCE_SIZE], difficulty: u8) -> Option<u64> {
+    if difficulty > MAX_POW_DIFFICULTY {
+        return None;
+    }
+    (0..=u64::MAX).find(|&counter| leading_zero_bits(&digest(nonce, counter)) >= difficulty)
+}
+
+fn digest(nonce: &[u8; POW_NONCE_SIZE], counter: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(POW_DOMAIN);
+    hasher.update(nonce);
+    hasher.update(counter.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u8 {
+    let mut bits = 0u8;
+    for byte in hash {
+        if *byte != 0 {
+            return bits + byte.leading_zeros() as u8;
+        }
+        bits = bits.saturating_add(8);
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solution_verifies_at_its_difficulty() {
+        let nonce = pow_nonce();
+        let counter = solve_pow(&nonce, 12).unwrap();
+        assert!(verify_pow(&nonce, 12, counter));
+        assert!(leading_zero_bits(&digest(&nonce, counter)) >= 12);
+    }
+
+    #[test]
+    fn solution_is_bound_to_nonce() {
+        let nonce = [0u8; POW_NONCE_SIZE];
+        let counter = solve_pow(&nonce, 16).unwrap();
+        assert!(!verify_pow(&[1u8; POW_NONCE_SIZE], 16, counter));
+    }
+
+    #[test]
+    fn zero_difficulty_accepts_anything() {
+        let nonce = pow_nonce();
+        assert_eq!(solve_pow(&nonce, 0), Some(0));
+        assert!(verify_pow(&nonce, 0, 12345));
+    }
+
+    #[test]
+    fn difficulty_above_max_rejected() {
+        let nonce = pow_nonce();
+        assert_eq!(solve_pow(&nonce, MAX_POW_DIFFICULTY + 1), None);
+        assert!(!verify_pow(&nonce, MAX_POW_DIFFICULTY + 1, 0));
+    }
+
+    #[test]
+    fn counts_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0xff, 0]), 0);
+        assert_eq!(leading_zero_bits(&[0x00, 0x10]), 11);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+}
@@ -0,0 +1,479 @@
+//! Database backups, periodic snapshots and restore.
+//!
+//! The relay database holds the only copy of undelivered ciphertext.
+//! Snapshots use SQLite's `VACUUM INTO`, which copies from a single read
+//! transaction, so they are consistent and can be taken while the relay
+//! serves traffic. Restore verifies a backup (`PRAGMA integrity_check`,
+//! relay tables, schema version) before it replaces the database, and must
+//! run while the relay is stopped.
+
+use crate::config::BackupConfig;
+use crate::error::StorageError;
+use crate::storage::{migrations, SqliteStorage, SCHEMA_VERSION};
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::interval;
+
+/// File name prefix for periodic snapshots (`relay-<unix millis>.db`).
+pub const SNAPSHOT_PREFIX: &str = "relay-";
+
+/// Tables every relay database has; a backup without them is rejected.
+const REQUIRED_TABLES: &[&str] = &["group_cursors", "blobs", "deliveries"];
+
+/// Backup and restore errors.
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    /// Refusing to overwrite an existing file.
+    #[error("{0} already exists")]
+    Exists(PathBuf),
+    /// Source file is missing.
+    #[error("{0} does not exist")]
+    NotFound(PathBuf),
+    /// `PRAGMA integrity_check` reported problems.
+    #[error("integrity check failed: {0}")]
+    Corrupt(String),
+    /// The file is a SQLite database but not a relay database.
+    #[error("not a relay database: missing table {0}")]
+    NotRelayDatabase(&'static str),
+    /// The backup was written by a newer relay.
+    #[error("schema version {found} is newer than supported version {supported}")]
+    UnsupportedSchema {
+        /// Version recorded in the backup.
+        found: i64,
+        /// Newest version this relay understands.
+        supported: i64,
+    },
+    /// Storage layer error while taking a snapshot.
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    /// Database error while verifying a backup.
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    /// Filesystem error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A backup written or restored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupReport {
+    /// Database file written.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub bytes: u64,
+    /// Schema version recorded in the file.
+    pub schema_version: i64,
+}
+
+/// Write an online snapshot of `storage` to `dest` and verify it.
+///
+/// # Errors
+///
+/// Fails if `dest` exists, the snapshot cannot be written, or the written
+/// file does not verify.
+pub async fn backup(storage: &SqliteStorage, dest: &Path) -> Result<BackupReport, BackupError> {
+    if dest.exists() {
+        return Err(BackupError::Exists(dest.to_path_buf()));
+    }
+    storage.snapshot(dest).await?;
+    written(dest).await
+}
+
+/// Write an online snapshot of the database file at `database` to `dest`
+/// and verify it.
+///
+/// Unlike [`backup`], this needs no [`SqliteStorage`]: the database is
+/// opened read-only and never migrated, so it is safe to point at a
+/// running relay's database (or one written by a different relay version).
+///
+/// # Errors
+///
+/// Fails if `database` is missing, `dest` exists, the snapshot cannot be
+/// written, or the written file does not verify.
+pub async fn backup_file(database: &Path, dest: &Path) -> Result<BackupReport, BackupError> {
+    if !database.is_file() {
+        return Err(BackupError::NotFound(database.to_path_buf()));
+    }
+    if dest.exists() {
+        return Err(BackupError::Exists(dest.to_path_buf()));
+    }
+    let options = SqliteConnectOptions::new()
+        .filename(database)
+        .read_only(true)
+        .busy_timeout(Duration::from_secs(5));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+
+    // VACUUM INTO reads in one transaction and works on read-only handles
+    let result = sqlx::query("VACUUM INTO ?1")
+        .bind(dest.to_string_lossy().as_ref())
+        .execute(&pool)
+        .await;
+    pool.close().await;
+    result?;
+
+    written(dest).await
+}
+
+/// Verify a freshly written backup at `dest` and describe it.
+async fn written(dest: &Path) -> Result<BackupReport, BackupError> {
+    let schema_version = verify(dest).await?;
+    Ok(BackupReport {
+        path: dest.to_path_buf(),
+        bytes: std::fs::metadata(dest)?.len(),
+        schema_version,
+    })
+}
+
+/// Check that `path` is an intact relay database this relay can open.
+///
+/// Returns the schema version recorded in the file. The file is opened
+/// read-only and never modified.
+///
+/// # Errors
+///
+/// Fails if the file is missing, corrupt, not a relay database, or from a
+/// newer schema.
+pub async fn verify(path: &Path) -> Result<i64, BackupError> {
+    if !path.is_file() {
+        return Err(BackupError::NotFound(path.to_path_buf()));
+    }
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .immutable(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+
+    let result = async {
+        let problems: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(&pool)
+            .await?;
+        if problems != ["ok"] {
+            return Err(BackupError::Corrupt(problems.join("; ")));
+        }
+
+        for table in REQUIRED_TABLES {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            )
+            .bind(table)
+            .fetch_one(&pool)
+            .await?;
+            if !exists {
+                return Err(BackupError::NotRelayDatabase(table));
+            }
+        }
+
+        let version = migrations::current_version(&pool).await?;
+        if version > SCHEMA_VERSION {
+            return Err(BackupError::UnsupportedSchema {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        Ok(version)
+    }
+    .await;
+
+    pool.close().await;
+    result
+}
+
+/// Replace `database` with a verified copy of `backup`.
+///
+/// The relay must be stopped. The copy is written next to `database` and
+/// renamed over it, and the old database's WAL files are removed so they
+/// are not replayed into the restored data. Without `force`, an existing
+/// `database` is never overwritten.
+///
+/// # Errors
+///
+/// Fails if the backup does not verify, `database` exists and `force` is
+/// not set, or the copy cannot be written.
+pub async fn restore(
+    backup: &Path,
+    database: &Path,
+    force: bool,
+) -> Result<BackupReport, BackupError> {
+    let schema_version = verify(backup).await?;
+    if database.exists() && !force {
+        return Err(BackupError::Exists(database.to_path_buf()));
+    }
+
+    let staging = sibling(database, ".restore");
+    let bytes = std::fs::copy(backup, &staging)?;
+    for suffix in ["-wal", "-shm"] {
+        match std::fs::remove_file(sibling(database, suffix)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    std::fs::rename(&staging, database)?;
+
+    Ok(BackupReport {
+        path: database.to_path_buf(),
+        bytes,
+        schema_version,
+    })
+}
+
+/// Spawn the periodic snapshot task, if `backup.snapshot_dir` is set.
+///
+/// Returns a handle that can be used to abort the task.
+pub fn spawn_snapshot_task(
+    storage: Arc<SqliteStorage>,
+    config: BackupConfig,
+) -> Option<tokio::task::JoinHandle<()>> {
+    let dir = config.snapshot_dir?;
+    Some(tokio::spawn(async move {
+        tracing::info!(
+            "Snapshot task started (dir: {:?}, interval: {}s, keep: {})",
+            dir,
+            config.snapshot_interval_secs,
+            config.snapshot_keep
+        );
+
+        let mut timer = interval(Duration::from_secs(config.snapshot_interval_secs));
+        // The first tick completes immediately; wait a full interval
+        timer.tick().await;
+
+        loop {
+            timer.tick().await;
+            match run_snapshot(&storage, &dir, config.snapshot_keep).await {
+                Ok(report) => tracing::info!(
+                    "Snapshot written to {:?} ({} bytes)",
+                    report.path,
+                    report.bytes
+                ),
+                Err(e) => tracing::error!("Snapshot error: {}", e),
+            }
+        }
+    }))
+}
+
+/// Write one snapshot into `dir` and delete all but the newest `keep`.
+///
+/// # Errors
+///
+/// Fails if the directory cannot be created or the snapshot fails.
+/// Pruning errors are logged, not returned.
+pub async fn run_snapshot(
+    storage: &SqliteStorage,
+    dir: &Path,
+    keep: usize,
+) -> Result<BackupReport, BackupError> {
+    std::fs::create_dir_all(dir)?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let report = backup(storage, &dir.join(format!("{SNAPSHOT_PREFIX}{millis}.db"))).await?;
+
+    if let Err(e) = prune_snapshots(dir, keep) {
+        tracing::warn!("Failed to prune old snapshots in {:?}: {}", dir, e);
+    }
+    Ok(report)
+}
+
+/// Delete the oldest snapshots in `dir`, keeping the newest `keep`.
+///
+/// Returns the number of snapshots deleted. Other files are left alone.
+fn prune_snapshots(dir: &Path, keep: usize) -> std::io::Result<usize> {
+    let mut snapshots: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(".db"))
+        })
+        .collect();
+    // Millisecond timestamps have the same width, so name order is age order
+    snapshots.sort();
+
+    let excess = snapshots.len().saturating_sub(keep);
+    for path in &snapshots[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+/// `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{BlobStorage, StoreBlobRequest};
+    use sync_types::{BlobId, DeviceId, GroupId};
+
+    async fn store(storage: &SqliteStorage, group_id: GroupId) {
+        storage
+            .store_blob(StoreBlobRequest {
+                blob_id: BlobId::new(),
+                group_id,
+                sender_id: DeviceId::random(),
+                payload: b"ciphertext".to_vec(),
+                timestamp: 0,
+                ttl_secs: 3600,
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn backup_and_restore_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = dir.path().join("relay.db");
+        let group = GroupId::random();
+
+        let storage = SqliteStorage::new(&database).await.unwrap();
+        store(&storage, group).await;
+
+        // Online: the relay's pool stays open while the backup is taken
+        let backup_path = dir.path().join("backup.db");
+        let report = backup(&storage, &backup_path).await.unwrap();
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert!(report.bytes > 0);
+        assert!(matches!(
+            backup(&storage, &backup_path).await,
+            Err(BackupError::Exists(_))
+        ));
+
+        store(&storage, group).await;
+        drop(storage);
+
+        assert!(matches!(
+            restore(&backup_path, &database, false).await,
+            Err(BackupError::Exists(_))
+        ));
+        restore(&backup_path, &database, true).await.unwrap();
+
+        let restored = SqliteStorage::new(&database).await.unwrap();
+        assert_eq!(restored.get_group_storage(&group).await.unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn backup_file_does_not_migrate_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = dir.path().join("relay.db");
+        let dest = dir.path().join("backup.db");
+
+        // An unversioned relay database, held open by a writer
+        let options = SqliteConnectOptions::new()
+            .filename(&database)
+            .create_if_missing(true)
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+        let live = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+        for table in REQUIRED_TABLES {
+            sqlx::query(&format!("CREATE TABLE {table} (id INTEGER)"))
+                .execute(&live)
+                .await
+                .unwrap();
+        }
+
+        let report = backup_file(&database, &dest).await.unwrap();
+        assert_eq!(report.schema_version, 0);
+        assert_eq!(migrations::current_version(&live).await.unwrap(), 0);
+        assert!(matches!(
+            backup_file(&database, &dest).await,
+            Err(BackupError::Exists(_))
+        ));
+        assert!(matches!(
+            backup_file(&dir.path().join("missing.db"), &dir.path().join("other.db")).await,
+            Err(BackupError::NotFound(_))
+        ));
+        live.close().await;
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_bad_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = dir.path().join("relay.db");
+
+        let garbage = dir.path().join("garbage.db");
+        std::fs::write(&garbage, b"not a database").unwrap();
+        assert!(restore(&garbage, &database, false).await.is_err());
+
+        // A valid SQLite file that isn't a relay database
+        let other = dir.path().join("other.db");
+        let options = SqliteConnectOptions::new()
+            .filename(&other)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE notes (body TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        assert!(matches!(
+            restore(&other, &database, false).await,
+            Err(BackupError::NotRelayDatabase(_))
+        ));
+
+        // A relay database from a newer schema
+        let storage = SqliteStorage::new(&dir.path().join("newer.db"))
+            .await
+            .unwrap();
+        let newer = dir.path().join("newer-backup.db");
+        backup(&storage, &newer).await.unwrap();
+        let options = SqliteConnectOptions::new().filename(&newer);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?1, 'future')")
+            .bind(SCHEMA_VERSION + 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        assert!(matches!(
+            restore(&newer, &database, false).await,
+            Err(BackupError::UnsupportedSchema { .. })
+        ));
+
+        assert!(!database.exists());
+    }
+
+    #[tokio::test]
+    async fn snapshots_are_pruned_to_keep() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = SqliteStorage::new(&dir.path().join("relay.db"))
+            .await
+            .unwrap();
+        let snapshots = dir.path().join("snapshots");
+        std::fs::create_dir_all(&snapshots).unwrap();
+        std::fs::write(snapshots.join("notes.txt"), b"keep me").unwrap();
+
+        for _ in 0..3 {
+            run_snapshot(&storage, &snapshots, 2).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+
+        let mut names: Vec<String> = std::fs::read_dir(&snapshots)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 3, "two snapshots plus the unrelated file");
+        assert_eq!(names[0], "notes.txt");
+    }
+}
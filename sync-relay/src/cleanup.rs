@@ -1,9 +1,11 @@
 //! Background cleanup task for expired blobs.
 //!
-//! Runs periodically to delete blobs that have exceeded their TTL.
+//! Runs periodically to delete blobs, relay-hosted content, mailbox
+//! messages and invites that have exceeded their TTL.
 
 use crate::config::CleanupConfig;
 use crate::storage::{BlobStorage, SqliteStorage};
+use serde::Serialize;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
@@ -28,21 +30,82 @@ pub fn spawn_cleanup_task(
 
         loop {
             timer.tick().await;
+            run_cleanup(&storage).await;
+        }
+    })
+}
+
+/// What one cleanup pass deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CleanupReport {
+    /// Expired blobs deleted.
+    pub blobs: u64,
+    /// Expired content blobs deleted.
+    pub content: u64,
+    /// Expired mailbox messages deleted.
+    pub mailbox: u64,
+    /// Expired invites deleted.
+    pub invites: u64,
+}
+
+/// Run one cleanup pass now.
+///
+/// Errors are logged and counted as nothing deleted, so one failing table
+/// doesn't stop the others being cleaned.
+pub async fn run_cleanup(storage: &SqliteStorage) -> CleanupReport {
+    let mut report = CleanupReport::default();
 
-            match storage.cleanup_expired().await {
-                Ok(deleted) => {
-                    if deleted > 0 {
-                        tracing::info!("Cleanup: deleted {} expired blobs", deleted);
-                    } else {
-                        tracing::debug!("Cleanup: no expired blobs");
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Cleanup error: {}", e);
-                }
+    match storage.cleanup_expired().await {
+        Ok(deleted) => {
+            if deleted > 0 {
+                tracing::info!("Cleanup: deleted {} expired blobs", deleted);
+            } else {
+                tracing::debug!("Cleanup: no expired blobs");
             }
+            report.blobs = deleted;
         }
-    })
+        Err(e) => {
+            tracing::error!("Cleanup error: {}", e);
+        }
+    }
+
+    match storage.cleanup_expired_content().await {
+        Ok(deleted) => {
+            if deleted > 0 {
+                tracing::info!("Cleanup: deleted {} expired content blobs", deleted);
+            }
+            report.content = deleted;
+        }
+        Err(e) => {
+            tracing::error!("Content cleanup error: {}", e);
+        }
+    }
+
+    match storage.cleanup_expired_mailbox().await {
+        Ok(deleted) => {
+            if deleted > 0 {
+                tracing::info!("Cleanup: deleted {} expired mailbox messages", deleted);
+            }
+            report.mailbox = deleted;
+        }
+        Err(e) => {
+            tracing::error!("Mailbox cleanup error: {}", e);
+        }
+    }
+
+    match storage.cleanup_expired_invites().await {
+        Ok(deleted) => {
+            if deleted > 0 {
+                tracing::info!("Cleanup: deleted {} expired invites", deleted);
+            }
+            report.invites = deleted;
+        }
+        Err(e) => {
+            tracing::error!("Invite cleanup error: {}", e);
+        }
+    }
+
+    report
 }
 
 #[cfg(test)]
@@ -86,6 +149,48 @@ mod tests {
         assert_eq!(deleted, 1);
     }
 
+    #[tokio::test]
+    async fn cleanup_task_removes_expired_content() {
+        let storage = Arc::new(SqliteStorage::in_memory().await.unwrap());
+        let group_id = GroupId::random();
+        storage
+            .store_content(&group_id, &[0x01; 32], b"expired", 0)
+            .await
+            .unwrap();
+        storage
+            .store_content(&group_id, &[0x02; 32], b"fresh", 3600)
+            .await
+            .unwrap();
+
+        // First interval tick fires immediately
+        let handle = spawn_cleanup_task(storage.clone(), test_cleanup_config(3600));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        assert_eq!(
+            storage.get_group_content_storage(&group_id).await.unwrap(),
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn cleanup_task_removes_expired_invites() {
+        let storage = Arc::new(SqliteStorage::in_memory().await.unwrap());
+        let group_id = GroupId::random();
+        storage
+            .register_invite(&group_id, &[0x01; 16], 1, 0)
+            .await
+            .unwrap();
+        storage
+            .register_invite(&group_id, &[0x02; 16], 1, i64::MAX)
+            .await
+            .unwrap();
+
+        assert_eq!(storage.cleanup_expired_invites().await.unwrap(), 1);
+        // The group stays invite-only after its invites expire
+        assert!(storage.is_group_invite_only(&group_id).await.unwrap());
+    }
+
     #[tokio::test]
     async fn cleanup_task_disabled() {
         let storage = Arc::new(SqliteStorage::in_memory().await.unwrap());
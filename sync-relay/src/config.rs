@@ -4,6 +4,7 @@
 
 use serde::Deserialize;
 use std::path::PathBuf;
+use sync_core::MAX_POW_DIFFICULTY;
 
 /// Root configuration for sync-relay.
 #[derive(Debug, Clone, Deserialize)]
@@ -18,6 +19,15 @@ pub struct Config {
     pub http: HttpConfig,
     /// Cleanup task configuration.
     pub cleanup: CleanupConfig,
+    /// Admin API configuration (optional section, disabled by default).
+    #[serde(default)]
+    pub admin: AdminConfig,
+    /// Trace export configuration (optional section, disabled by default).
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    /// Periodic database snapshots (optional section, disabled by default).
+    #[serde(default)]
+    pub backup: BackupConfig,
 }
 
 /// Server configuration.
@@ -28,6 +38,28 @@ pub struct ServerConfig {
     pub bind_address: String,
     /// Secret key path for iroh endpoint (optional, generates if missing).
     pub secret_key_path: Option<PathBuf>,
+    /// Secure channel static key file (optional, generates if missing).
+    /// Defaults to `secret_key_path` with a `.noise` extension. With neither
+    /// set the key changes on every restart and clients cannot pin it.
+    pub noise_key_path: Option<PathBuf>,
+    /// Reject clients that skip the hybrid secure channel handshake
+    /// (default: false, plaintext HELLO still accepted).
+    #[serde(default)]
+    pub require_secure_channel: bool,
+    /// Seconds to let in-flight requests finish on shutdown (default: 20).
+    #[serde(default = "default_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+}
+
+impl ServerConfig {
+    /// File the secure channel static key is kept in, if any.
+    pub fn noise_key_file(&self) -> Option<PathBuf> {
+        self.noise_key_path.clone().or_else(|| {
+            self.secret_key_path
+                .as_ref()
+                .map(|path| path.with_extension("noise"))
+        })
+    }
 }
 
 /// Storage configuration.
@@ -45,6 +77,18 @@ pub struct StorageConfig {
     /// Default TTL for blobs in seconds (default: 7 days).
     #[serde(default = "default_ttl")]
     pub default_ttl: u64,
+    /// Accept content blobs (ContentPut) for offline recipients (default: false).
+    #[serde(default)]
+    pub content_enabled: bool,
+    /// Maximum total content storage per group in bytes (default: 500MB).
+    #[serde(default = "default_max_group_content_storage")]
+    pub max_group_content_storage: usize,
+    /// Default and maximum TTL for content blobs in seconds (default: 7 days).
+    #[serde(default = "default_ttl")]
+    pub content_ttl: u64,
+    /// Maximum TTL for mailbox messages in seconds (default: 10 minutes).
+    #[serde(default = "default_mailbox_ttl")]
+    pub mailbox_ttl: u64,
 }
 
 /// Rate limiting configuration.
@@ -72,6 +116,64 @@ pub struct LimitsConfig {
     /// Global requests per second across all clients (default: 1000).
     #[serde(default = "default_global_rps")]
     pub global_requests_per_second: u32,
+    /// OPRF evaluations per group key per hour (default: 30).
+    /// Caps online guessing against OPRF-hardened passphrases.
+    #[serde(default = "default_oprf_evaluations_per_hour")]
+    pub oprf_evaluations_per_hour: u32,
+    /// Messages a device may send back to back before the per-minute rate
+    /// applies (default: `messages_per_minute`).
+    #[serde(default)]
+    pub message_burst: Option<u32>,
+    /// Requests the whole relay may take back to back
+    /// (default: `global_requests_per_second`).
+    #[serde(default)]
+    pub global_burst: Option<u32>,
+    /// PUSH and PULL requests per group per minute, across all its devices
+    /// (default: 1000). A group policy's `messages_per_minute` overrides it.
+    #[serde(default = "default_group_messages_per_minute")]
+    pub group_messages_per_minute: u32,
+    /// Group requests allowed back to back
+    /// (default: `group_messages_per_minute`).
+    #[serde(default)]
+    pub group_message_burst: Option<u32>,
+    /// Ciphertext bytes per device per second, pushed plus pulled
+    /// (default: 1MB).
+    #[serde(default = "default_device_bytes_per_second")]
+    pub device_bytes_per_second: u32,
+    /// Ciphertext bytes per group per second, pushed plus pulled
+    /// (default: 8MB).
+    #[serde(default = "default_group_bytes_per_second")]
+    pub group_bytes_per_second: u32,
+    /// Seconds of byte allowance a device or group may use at once
+    /// (default: 10). A single request is never charged more than this.
+    #[serde(default = "default_bytes_burst_secs")]
+    pub bytes_burst_secs: u32,
+    /// Proof-of-work bits required in HELLO on an idle relay (default: 0).
+    #[serde(default)]
+    pub pow_min_difficulty: u8,
+    /// Proof-of-work bits required at `max_concurrent_sessions`
+    /// (default: 0, disabled). Scales linearly from `pow_min_difficulty`.
+    #[serde(default)]
+    pub pow_max_difficulty: u8,
+}
+
+impl LimitsConfig {
+    /// Byte burst for a byte rate, or `None` if it overflows `u32`.
+    pub fn bytes_burst(&self, bytes_per_second: u32) -> Option<u32> {
+        bytes_per_second.checked_mul(self.bytes_burst_secs)
+    }
+
+    /// Proof-of-work difficulty for a new session with `sessions` active
+    /// (0 = no challenge).
+    pub fn pow_difficulty(&self, sessions: usize) -> u8 {
+        let (min, max) = (self.pow_min_difficulty, self.pow_max_difficulty);
+        if max <= min {
+            return max;
+        }
+        let capacity = self.max_concurrent_sessions.max(1);
+        let extra = usize::from(max - min) * sessions.min(capacity) / capacity;
+        min + extra as u8
+    }
 }
 
 /// HTTP endpoints configuration.
@@ -85,6 +187,107 @@ pub struct HttpConfig {
     pub metrics_enabled: bool,
 }
 
+/// Admin API configuration.
+///
+/// The admin API runs on its own listener so it can be bound to loopback
+/// or a management network while `/health` and `/metrics` stay public.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminConfig {
+    /// Enable the admin API (default: false).
+    #[serde(default)]
+    pub enabled: bool,
+    /// Bind address for the admin listener (default: 127.0.0.1:8081).
+    #[serde(default = "default_admin_bind")]
+    pub bind_address: String,
+    /// Bearer token required on every admin request.
+    /// Must be at least [`MIN_ADMIN_TOKEN_LEN`] characters when enabled.
+    pub token: Option<String>,
+}
+
+/// Minimum admin token length.
+pub const MIN_ADMIN_TOKEN_LEN: usize = 32;
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: default_admin_bind(),
+            token: None,
+        }
+    }
+}
+
+impl AdminConfig {
+    /// The configured token, if the admin API is enabled and the token is
+    /// long enough.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API is enabled without a usable token.
+    pub fn validated_token(&self) -> Result<Option<&str>, ConfigError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        match self.token.as_deref() {
+            Some(token) if token.len() >= MIN_ADMIN_TOKEN_LEN => Ok(Some(token)),
+            _ => Err(ConfigError::Invalid(format!(
+                "admin.token must be at least {} characters when admin.enabled = true",
+                MIN_ADMIN_TOKEN_LEN
+            ))),
+        }
+    }
+}
+
+/// Trace export configuration.
+///
+/// Spans are exported over OTLP/HTTP when `otlp_endpoint` is set and the
+/// relay was built with the `otel` feature. Spans carry message types,
+/// sizes and trace IDs — never payload contents.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP collector base URL, e.g. `http://127.0.0.1:4318`
+    /// (default: unset, no export).
+    pub otlp_endpoint: Option<String>,
+    /// `service.name` reported to the collector (default: sync-relay).
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: default_service_name(),
+        }
+    }
+}
+
+/// Periodic snapshot configuration.
+///
+/// Snapshots are consistent copies of the database written while the relay
+/// runs; restore one with `sync-relay restore <path>`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupConfig {
+    /// Directory to write snapshots to (default: unset, no snapshots).
+    pub snapshot_dir: Option<PathBuf>,
+    /// Seconds between snapshots (default: 86400 = 1 day).
+    #[serde(default = "default_snapshot_interval")]
+    pub snapshot_interval_secs: u64,
+    /// Snapshots to keep; older ones are deleted (default: 7).
+    #[serde(default = "default_snapshot_keep")]
+    pub snapshot_keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_dir: None,
+            snapshot_interval_secs: default_snapshot_interval(),
+            snapshot_keep: default_snapshot_keep(),
+        }
+    }
+}
+
 /// Cleanup task configuration.
 #[derive(Debug, Clone, Deserialize)]
 pub struct CleanupConfig {
@@ -101,6 +304,10 @@ fn default_bind_address() -> String {
     "0.0.0.0:4433".to_string()
 }
 
+fn default_drain_timeout_secs() -> u64 {
+    20
+}
+
 fn default_database_path() -> PathBuf {
     PathBuf::from("relay.db")
 }
@@ -117,6 +324,14 @@ fn default_ttl() -> u64 {
     7 * 24 * 60 * 60 // 7 days in seconds
 }
 
+fn default_max_group_content_storage() -> usize {
+    500 * 1024 * 1024 // 500MB
+}
+
+fn default_mailbox_ttl() -> u64 {
+    600 // 10 minutes (invite lifetime)
+}
+
 fn default_connections_per_ip() -> usize {
     10
 }
@@ -145,6 +360,26 @@ fn default_global_rps() -> u32 {
     1000
 }
 
+fn default_oprf_evaluations_per_hour() -> u32 {
+    30
+}
+
+fn default_group_messages_per_minute() -> u32 {
+    1000
+}
+
+fn default_device_bytes_per_second() -> u32 {
+    1024 * 1024 // 1MB
+}
+
+fn default_group_bytes_per_second() -> u32 {
+    8 * 1024 * 1024 // 8MB
+}
+
+fn default_bytes_burst_secs() -> u32 {
+    10
+}
+
 fn default_http_bind() -> String {
     "0.0.0.0:8080".to_string()
 }
@@ -153,6 +388,14 @@ fn default_metrics_enabled() -> bool {
     true
 }
 
+fn default_admin_bind() -> String {
+    "127.0.0.1:8081".to_string()
+}
+
+fn default_service_name() -> String {
+    "sync-relay".to_string()
+}
+
 fn default_cleanup_interval() -> u64 {
     3600 // 1 hour
 }
@@ -161,18 +404,33 @@ fn default_cleanup_enabled() -> bool {
     true
 }
 
+fn default_snapshot_interval() -> u64 {
+    24 * 60 * 60 // 1 day
+}
+
+fn default_snapshot_keep() -> usize {
+    7
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             server: ServerConfig {
                 bind_address: default_bind_address(),
                 secret_key_path: None,
+                noise_key_path: None,
+                require_secure_channel: false,
+                drain_timeout_secs: default_drain_timeout_secs(),
             },
             storage: StorageConfig {
                 database: default_database_path(),
                 max_blob_size: default_max_blob_size(),
                 max_group_storage: default_max_group_storage(),
                 default_ttl: default_ttl(),
+                content_enabled: false,
+                max_group_content_storage: default_max_group_content_storage(),
+                content_ttl: default_ttl(),
+                mailbox_ttl: default_mailbox_ttl(),
             },
             limits: LimitsConfig {
                 connections_per_ip: default_connections_per_ip(),
@@ -182,6 +440,16 @@ impl Default for Config {
                 max_device_name_len: default_max_device_name_len(),
                 max_pull_limit: default_max_pull_limit(),
                 global_requests_per_second: default_global_rps(),
+                oprf_evaluations_per_hour: default_oprf_evaluations_per_hour(),
+                message_burst: None,
+                global_burst: None,
+                group_messages_per_minute: default_group_messages_per_minute(),
+                group_message_burst: None,
+                device_bytes_per_second: default_device_bytes_per_second(),
+                group_bytes_per_second: default_group_bytes_per_second(),
+                bytes_burst_secs: default_bytes_burst_secs(),
+                pow_min_difficulty: 0,
+                pow_max_difficulty: 0,
             },
             http: HttpConfig {
                 bind_address: default_http_bind(),
@@ -191,6 +459,9 @@ impl Default for Config {
                 interval_secs: default_cleanup_interval(),
                 enabled: default_cleanup_enabled(),
             },
+            admin: AdminConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            backup: BackupConfig::default(),
         }
     }
 }
@@ -212,6 +483,173 @@ impl Config {
             source: e,
         })
     }
+
+    /// Check values that would otherwise fail once the relay is running.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] for zero rate limits, a zero pull
+    /// limit, out-of-range proof-of-work difficulties or an enabled admin
+    /// API without a usable token.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let limits = &self.limits;
+        for (key, value) in [
+            (
+                "limits.connections_per_ip",
+                limits.connections_per_ip as u64,
+            ),
+            (
+                "limits.messages_per_minute",
+                limits.messages_per_minute.into(),
+            ),
+            ("limits.max_pull_limit", limits.max_pull_limit.into()),
+            (
+                "limits.global_requests_per_second",
+                limits.global_requests_per_second.into(),
+            ),
+            (
+                "limits.oprf_evaluations_per_hour",
+                limits.oprf_evaluations_per_hour.into(),
+            ),
+            (
+                "limits.group_messages_per_minute",
+                limits.group_messages_per_minute.into(),
+            ),
+            (
+                "limits.device_bytes_per_second",
+                limits.device_bytes_per_second.into(),
+            ),
+            (
+                "limits.group_bytes_per_second",
+                limits.group_bytes_per_second.into(),
+            ),
+            ("limits.bytes_burst_secs", limits.bytes_burst_secs.into()),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::Invalid(format!("{key} must be > 0")));
+            }
+        }
+        for (key, burst) in [
+            ("limits.message_burst", limits.message_burst),
+            ("limits.global_burst", limits.global_burst),
+            ("limits.group_message_burst", limits.group_message_burst),
+        ] {
+            if burst == Some(0) {
+                return Err(ConfigError::Invalid(format!("{key} must be > 0")));
+            }
+        }
+        for (key, rate) in [
+            (
+                "limits.device_bytes_per_second",
+                limits.device_bytes_per_second,
+            ),
+            (
+                "limits.group_bytes_per_second",
+                limits.group_bytes_per_second,
+            ),
+        ] {
+            if limits.bytes_burst(rate).is_none() {
+                return Err(ConfigError::Invalid(format!(
+                    "{key} x limits.bytes_burst_secs must fit in 32 bits"
+                )));
+            }
+        }
+        if limits.pow_max_difficulty > MAX_POW_DIFFICULTY
+            || limits.pow_min_difficulty > limits.pow_max_difficulty
+        {
+            return Err(ConfigError::Invalid(format!(
+                "limits.pow_min_difficulty <= limits.pow_max_difficulty <= {MAX_POW_DIFFICULTY} required"
+            )));
+        }
+        self.admin.validated_token()?;
+        if self.backup.snapshot_dir.is_some()
+            && (self.backup.snapshot_interval_secs == 0 || self.backup.snapshot_keep == 0)
+        {
+            return Err(ConfigError::Invalid(
+                "backup.snapshot_interval_secs and backup.snapshot_keep must be > 0".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Settings that differ between `self` and `new`.
+    ///
+    /// Every field must be listed here; anything bound at startup (listeners,
+    /// database, keys, background tasks, exporters) is marked as requiring a
+    /// restart.
+    pub fn changes(&self, new: &Config) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+        macro_rules! diff {
+            ($reloadable:expr, $($section:ident.$field:ident),+ $(,)?) => {
+                $(
+                    if self.$section.$field != new.$section.$field {
+                        changes.push(ConfigChange {
+                            key: concat!(stringify!($section), ".", stringify!($field)),
+                            reloadable: $reloadable,
+                        });
+                    }
+                )+
+            };
+        }
+        diff!(
+            false,
+            server.bind_address,
+            server.secret_key_path,
+            server.noise_key_path,
+            storage.database,
+            http.bind_address,
+            http.metrics_enabled,
+            cleanup.interval_secs,
+            cleanup.enabled,
+            admin.enabled,
+            admin.bind_address,
+            admin.token,
+            telemetry.otlp_endpoint,
+            telemetry.service_name,
+            backup.snapshot_dir,
+            backup.snapshot_interval_secs,
+            backup.snapshot_keep,
+        );
+        diff!(
+            true,
+            server.require_secure_channel,
+            server.drain_timeout_secs,
+            storage.max_blob_size,
+            storage.max_group_storage,
+            storage.default_ttl,
+            storage.content_enabled,
+            storage.max_group_content_storage,
+            storage.content_ttl,
+            storage.mailbox_ttl,
+            limits.connections_per_ip,
+            limits.messages_per_minute,
+            limits.hello_timeout_secs,
+            limits.max_concurrent_sessions,
+            limits.max_device_name_len,
+            limits.max_pull_limit,
+            limits.global_requests_per_second,
+            limits.oprf_evaluations_per_hour,
+            limits.message_burst,
+            limits.global_burst,
+            limits.group_messages_per_minute,
+            limits.group_message_burst,
+            limits.device_bytes_per_second,
+            limits.group_bytes_per_second,
+            limits.bytes_burst_secs,
+            limits.pow_min_difficulty,
+            limits.pow_max_difficulty,
+        );
+        changes
+    }
+}
+
+/// One setting that differs between two configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigChange {
+    /// Dotted TOML key, e.g. `limits.messages_per_minute`.
+    pub key: &'static str,
+    /// Whether a running relay can apply the change without a restart.
+    pub reloadable: bool,
 }
 
 /// Configuration error types.
@@ -233,6 +671,12 @@ pub enum ConfigError {
         /// Underlying TOML parse error.
         source: toml::de::Error,
     },
+    /// Configuration values are inconsistent.
+    #[error("invalid config: {0}")]
+    Invalid(String),
+    /// Reloaded configuration changes settings that are bound at startup.
+    #[error("changes require a restart: {}", .0.join(", "))]
+    RestartRequired(Vec<&'static str>),
 }
 
 #[cfg(test)]
@@ -276,6 +720,24 @@ interval_secs = 1800
         assert_eq!(config.cleanup.interval_secs, 1800);
     }
 
+    #[test]
+    fn noise_key_file_defaults_next_to_secret_key() {
+        let mut server = Config::default().server;
+        assert_eq!(server.noise_key_file(), None);
+
+        server.secret_key_path = Some(PathBuf::from("/etc/sync-relay/secret.key"));
+        assert_eq!(
+            server.noise_key_file(),
+            Some(PathBuf::from("/etc/sync-relay/secret.noise"))
+        );
+
+        server.noise_key_path = Some(PathBuf::from("/data/noise.key"));
+        assert_eq!(
+            server.noise_key_file(),
+            Some(PathBuf::from("/data/noise.key"))
+        );
+    }
+
     #[test]
     fn hello_timeout_has_default() {
         // F-006: HELLO timeout must have a reasonable default
@@ -306,6 +768,7 @@ hello_timeout_secs = 30
         assert_eq!(config.limits.max_device_name_len, 256);
         assert_eq!(config.limits.max_pull_limit, 1000);
         assert_eq!(config.limits.global_requests_per_second, 1000);
+        assert_eq!(config.limits.oprf_evaluations_per_hour, 30);
     }
 
     #[test]
@@ -322,4 +785,152 @@ hello_timeout_secs = 30
         assert_eq!(config.storage.max_blob_size, 1024 * 1024);
         assert_eq!(config.storage.default_ttl, 7 * 24 * 60 * 60);
     }
+
+    #[test]
+    fn content_hosting_disabled_by_default() {
+        let config = Config::default();
+        assert!(!config.storage.content_enabled);
+        assert_eq!(config.storage.max_group_content_storage, 500 * 1024 * 1024);
+        assert_eq!(config.storage.content_ttl, 7 * 24 * 60 * 60);
+    }
+
+    #[test]
+    fn content_hosting_configurable_from_toml() {
+        let toml = r#"
+[server]
+[storage]
+content_enabled = true
+max_group_content_storage = 1048576
+content_ttl = 86400
+[limits]
+[http]
+[cleanup]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.storage.content_enabled);
+        assert_eq!(config.storage.max_group_content_storage, 1048576);
+        assert_eq!(config.storage.content_ttl, 86400);
+    }
+
+    #[test]
+    fn mailbox_ttl_defaults_to_invite_lifetime() {
+        assert_eq!(Config::default().storage.mailbox_ttl, 600);
+
+        let toml = r#"
+[server]
+[storage]
+mailbox_ttl = 120
+[limits]
+[http]
+[cleanup]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.storage.mailbox_ttl, 120);
+    }
+
+    #[test]
+    fn secure_channel_optional_by_default() {
+        assert!(!Config::default().server.require_secure_channel);
+
+        let toml = r#"
+[server]
+require_secure_channel = true
+[storage]
+[limits]
+[http]
+[cleanup]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.server.require_secure_channel);
+    }
+
+    #[test]
+    fn admin_api_disabled_by_default() {
+        let config = Config::default();
+        assert!(!config.admin.enabled);
+        assert_eq!(config.admin.bind_address, "127.0.0.1:8081");
+        assert_eq!(config.admin.validated_token().unwrap(), None);
+    }
+
+    #[test]
+    fn admin_api_requires_long_token() {
+        let toml = r#"
+[server]
+[storage]
+[limits]
+[http]
+[cleanup]
+[admin]
+enabled = true
+token = "short"
+"#;
+        let mut config: Config = toml::from_str(toml).unwrap();
+        assert!(matches!(
+            config.admin.validated_token(),
+            Err(ConfigError::Invalid(_))
+        ));
+
+        config.admin.token = Some("x".repeat(MIN_ADMIN_TOKEN_LEN));
+        assert!(config.admin.validated_token().unwrap().is_some());
+    }
+
+    #[test]
+    fn validate_rejects_zero_limits() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.limits.messages_per_minute = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("limits.messages_per_minute"));
+    }
+
+    #[test]
+    fn pow_difficulty_scales_with_sessions() {
+        let mut limits = Config::default().limits;
+        assert_eq!(limits.pow_difficulty(limits.max_concurrent_sessions), 0);
+
+        limits.max_concurrent_sessions = 100;
+        limits.pow_min_difficulty = 8;
+        limits.pow_max_difficulty = 20;
+        assert_eq!(limits.pow_difficulty(0), 8);
+        assert_eq!(limits.pow_difficulty(50), 14);
+        assert_eq!(limits.pow_difficulty(100), 20);
+        assert_eq!(limits.pow_difficulty(500), 20);
+
+        let mut config = Config::default();
+        config.limits.pow_max_difficulty = MAX_POW_DIFFICULTY + 1;
+        assert!(config.validate().is_err());
+        config.limits.pow_max_difficulty = 4;
+        config.limits.pow_min_difficulty = 8;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn changes_separate_reloadable_from_restart_required() {
+        let old = Config::default();
+        assert!(old.changes(&old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.limits.messages_per_minute = 50;
+        new.storage.max_blob_size = 4096;
+        new.storage.database = PathBuf::from("/elsewhere/relay.db");
+        new.server.bind_address = "127.0.0.1:5000".into();
+
+        let changes = old.changes(&new);
+        let restart: Vec<_> = changes
+            .iter()
+            .filter(|c| !c.reloadable)
+            .map(|c| c.key)
+            .collect();
+        let reload: Vec<_> = changes
+            .iter()
+            .filter(|c| c.reloadable)
+            .map(|c| c.key)
+            .collect();
+        assert_eq!(restart, ["server.bind_address", "storage.database"]);
+        assert_eq!(
+            reload,
+            ["storage.max_blob_size", "limits.messages_per_minute"]
+        );
+    }
 }
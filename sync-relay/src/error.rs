@@ -65,6 +65,15 @@ pub enum StorageError {
     #[error("migration error: {0}")]
     Migration(String),
 
+    /// Database was written by a newer relay.
+    #[error("database schema version {found} is newer than supported version {supported}")]
+    SchemaTooNew {
+        /// Version recorded in the database.
+        found: i64,
+        /// Newest version this build understands.
+        supported: i64,
+    },
+
     /// Database path error.
     #[error("invalid database path: {path}")]
     InvalidPath {
@@ -151,6 +160,68 @@ pub enum ProtocolError {
         /// Reason for rate limiting.
         reason: String,
     },
+
+    /// Relay is not configured to host content blobs.
+    #[error("content hosting is disabled on this relay")]
+    ContentHostingDisabled,
+
+    /// Secure channel handshake or frame failure.
+    #[error("secure channel error: {0}")]
+    SecureChannel(String),
+
+    /// Relay requires the secure channel handshake before HELLO.
+    #[error("secure channel required before HELLO")]
+    SecureChannelRequired,
+
+    /// Mailbox already holds the maximum number of messages.
+    #[error("mailbox is full (limit: {limit} messages)")]
+    MailboxFull {
+        /// Maximum unexpired messages per mailbox.
+        limit: usize,
+    },
+
+    /// Relay is draining for shutdown and accepts no new requests.
+    #[error("relay shutting down")]
+    ShuttingDown,
+
+    /// Device is not a member of an invite-only group and presented no
+    /// usable invite.
+    #[error("invite rejected: {reason}")]
+    InviteRejected {
+        /// Why the invite was not accepted.
+        reason: String,
+    },
+
+    /// HELLO did not solve this connection's proof-of-work challenge.
+    #[error("proof of work missing or invalid")]
+    ProofOfWorkInvalid,
+}
+
+impl ProtocolError {
+    /// Short, stable label for metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProtocolError::InvalidMessage { .. } => "invalid_message",
+            ProtocolError::Internal(_) => "internal",
+            ProtocolError::BlobTooLarge { .. } => "blob_too_large",
+            ProtocolError::QuotaExceeded { .. } => "quota_exceeded",
+            ProtocolError::Deserialization(_) => "deserialization",
+            ProtocolError::Serialization(_) => "serialization",
+            ProtocolError::UnexpectedMessage { .. } => "unexpected_message",
+            ProtocolError::NotAuthenticated => "not_authenticated",
+            ProtocolError::Connection(_) => "connection",
+            ProtocolError::Stream(_) => "stream",
+            ProtocolError::VersionMismatch { .. } => "version_mismatch",
+            ProtocolError::RateLimited { .. } => "rate_limited",
+            ProtocolError::ContentHostingDisabled => "content_hosting_disabled",
+            ProtocolError::SecureChannel(_) => "secure_channel",
+            ProtocolError::SecureChannelRequired => "secure_channel_required",
+            ProtocolError::MailboxFull { .. } => "mailbox_full",
+            ProtocolError::ShuttingDown => "shutting_down",
+            ProtocolError::InviteRejected { .. } => "invite_rejected",
+            ProtocolError::ProofOfWorkInvalid => "proof_of_work_invalid",
+        }
+    }
 }
 
 /// Result type alias for relay operations.
@@ -0,0 +1,574 @@
+//! Token-authenticated admin API.
+//!
+//! Served on its own listener (`[admin] bind_address`), never on the public
+//! HTTP port. Every request must carry `Authorization: Bearer <token>`.
+//!
+//! Groups are identified by the hex encoding of their opaque 32-byte ID.
+//! Responses contain counts and sizes only, never payloads.
+//!
+//! | Method | Path | Action |
+//! |--------|------|--------|
+//! | GET | `/admin/groups` | List groups with storage and session counts |
+//! | DELETE | `/admin/groups/:group_id` | Purge a group's stored data |
+//! | GET | `/admin/groups/:group_id/policy` | Show a group's policy overrides |
+//! | PUT | `/admin/groups/:group_id/policy` | Replace a group's policy overrides |
+//! | PUT | `/admin/groups/:group_id/quota` | Set or clear only the storage quota override |
+//! | GET | `/admin/rate-limits` | Rate limiter configuration and state |
+//! | POST | `/admin/cleanup` | Run a cleanup pass now |
+//! | POST | `/admin/reload` | Re-read `relay.toml` and apply reloadable settings |
+
+use crate::cleanup::{run_cleanup, CleanupReport};
+use crate::config::ConfigError;
+use crate::server::SyncRelay;
+use crate::storage::{BlobStorage, GroupPolicy};
+use axum::extract::{Path, Request, State};
+use axum::http::{header::AUTHORIZATION, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use sync_types::GroupId;
+
+/// One group in the `/admin/groups` listing.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupInfo {
+    /// Hex-encoded group ID.
+    pub group_id: String,
+    /// Number of stored blobs.
+    pub blob_count: u64,
+    /// Bytes of stored blob payloads.
+    pub storage_bytes: u64,
+    /// Bytes of relay-hosted content.
+    pub content_bytes: u64,
+    /// Storage quota override, if set.
+    pub quota_bytes: Option<u64>,
+    /// Quota actually enforced (override or global default).
+    pub effective_quota_bytes: u64,
+    /// Devices currently connected.
+    pub sessions: usize,
+}
+
+/// Response for `DELETE /admin/groups/:group_id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeResponse {
+    /// Hex-encoded group ID.
+    pub group_id: String,
+    /// Blobs deleted.
+    pub blobs: u64,
+    /// Content blobs deleted.
+    pub content: u64,
+    /// Invites deleted.
+    pub invites: u64,
+    /// Member records deleted.
+    pub members: u64,
+}
+
+/// Body for `PUT /admin/groups/:group_id/quota`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUpdate {
+    /// New storage quota in bytes; `null` restores the global default.
+    pub max_storage_bytes: Option<u64>,
+}
+
+/// Response for `GET /admin/rate-limits`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitsInfo {
+    /// Connection attempts allowed per endpoint per minute.
+    pub connections_per_minute: usize,
+    /// Messages allowed per device per minute.
+    pub messages_per_minute: u32,
+    /// Requests per second allowed across all clients.
+    pub global_requests_per_second: u32,
+    /// OPRF evaluations allowed per key per hour.
+    pub oprf_evaluations_per_hour: u32,
+    /// PUSH/PULL allowed per group per minute (without a policy override).
+    pub group_messages_per_minute: u32,
+    /// Ciphertext bytes allowed per device per second.
+    pub device_bytes_per_second: u32,
+    /// Ciphertext bytes allowed per group per second.
+    pub group_bytes_per_second: u32,
+    /// Proof-of-work bits a new session must solve at current load.
+    pub pow_difficulty: u8,
+    /// Proof-of-work challenges sent since startup.
+    pub pow_challenges: u64,
+    /// Endpoints currently tracked by the connection limiter.
+    pub tracked_connections: usize,
+    /// Devices currently tracked by the message limiter.
+    pub tracked_devices: usize,
+    /// OPRF keys currently tracked.
+    pub tracked_oprf_keys: usize,
+    /// Groups currently tracked by the group limiters.
+    pub tracked_groups: usize,
+    /// Total rate limit rejections since startup.
+    pub rate_limit_hits: u64,
+    /// Rejections since startup, by limiter.
+    pub rate_limit_hits_by_limiter: BTreeMap<&'static str, u64>,
+}
+
+/// Response for `POST /admin/reload`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadResponse {
+    /// Config keys whose new values were applied.
+    pub changed: Vec<&'static str>,
+}
+
+/// Build the admin router. Every route requires `token`.
+pub fn build_admin_router(relay: Arc<SyncRelay>, token: &str) -> Router {
+    let token_hash = Arc::new(blake3::hash(token.as_bytes()));
+
+    Router::new()
+        .route("/admin/groups", get(list_groups))
+        .route("/admin/groups/:group_id", delete(purge_group))
+        .route(
+            "/admin/groups/:group_id/policy",
+            get(get_policy).put(set_policy),
+        )
+        .route("/admin/groups/:group_id/quota", put(set_quota))
+        .route("/admin/rate-limits", get(rate_limits))
+        .route("/admin/cleanup", post(cleanup))
+        .route("/admin/reload", post(reload))
+        .layer(middleware::from_fn_with_state(token_hash, require_token))
+        .layer(Extension(relay))
+}
+
+/// Reject requests without the admin bearer token.
+async fn require_token(
+    State(token_hash): State<Arc<blake3::Hash>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    // blake3::Hash equality is constant-time
+    match provided {
+        Some(token) if blake3::hash(token.as_bytes()) == *token_hash => next.run(request).await,
+        _ => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+/// Admin handler error: status plus a plain-text reason.
+type AdminError = (StatusCode, String);
+
+fn internal(e: impl std::fmt::Display) -> AdminError {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn parse_group_id(hex_id: &str) -> Result<GroupId, AdminError> {
+    hex::decode(hex_id)
+        .ok()
+        .and_then(|bytes| GroupId::from_bytes(&bytes))
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                "group_id must be 64 hex characters".to_string(),
+            )
+        })
+}
+
+async fn list_groups(
+    Extension(relay): Extension<Arc<SyncRelay>>,
+) -> Result<Json<Vec<GroupInfo>>, AdminError> {
+    let default_quota = relay.config().storage.max_group_storage as u64;
+    let mut groups: BTreeMap<String, GroupInfo> = BTreeMap::new();
+
+    for stats in relay.storage().list_groups().await.map_err(internal)? {
+        let group_id = hex::encode(stats.group_id.as_bytes());
+        groups.insert(
+            group_id.clone(),
+            GroupInfo {
+                group_id,
+                blob_count: stats.blob_count,
+                storage_bytes: stats.storage_bytes,
+                content_bytes: stats.content_bytes,
+                quota_bytes: stats.quota_bytes,
+                effective_quota_bytes: stats.quota_bytes.unwrap_or(default_quota),
+                sessions: relay.session_count(&stats.group_id).await,
+            },
+        );
+    }
+
+    // Groups with live sessions but nothing stored yet
+    for group in relay.active_groups() {
+        let group_id = hex::encode(group.as_bytes());
+        if !groups.contains_key(&group_id) {
+            groups.insert(
+                group_id.clone(),
+                GroupInfo {
+                    group_id,
+                    blob_count: 0,
+                    storage_bytes: 0,
+                    content_bytes: 0,
+                    quota_bytes: None,
+                    effective_quota_bytes: default_quota,
+                    sessions: relay.session_count(&group).await,
+                },
+            );
+        }
+    }
+
+    Ok(Json(groups.into_values().collect()))
+}
+
+async fn purge_group(
+    Extension(relay): Extension<Arc<SyncRelay>>,
+    Path(group_id): Path<String>,
+) -> Result<Json<PurgeResponse>, AdminError> {
+    let group = parse_group_id(&group_id)?;
+    let report = relay
+        .storage()
+        .purge_group(&group)
+        .await
+        .map_err(internal)?;
+    tracing::warn!("Admin purged group {:?}: {:?}", group, report);
+
+    Ok(Json(PurgeResponse {
+        group_id,
+        blobs: report.blobs,
+        content: report.content,
+        invites: report.invites,
+        members: report.members,
+    }))
+}
+
+async fn get_policy(
+    Extension(relay): Extension<Arc<SyncRelay>>,
+    Path(group_id): Path<String>,
+) -> Result<Json<GroupPolicy>, AdminError> {
+    let group = parse_group_id(&group_id)?;
+    let policy = relay
+        .storage()
+        .get_group_policy(&group)
+        .await
+        .map_err(internal)?;
+    Ok(Json(policy))
+}
+
+async fn set_policy(
+    Extension(relay): Extension<Arc<SyncRelay>>,
+    Path(group_id): Path<String>,
+    Json(policy): Json<GroupPolicy>,
+) -> Result<Json<GroupPolicy>, AdminError> {
+    let group = parse_group_id(&group_id)?;
+    policy
+        .validate()
+        .map_err(|reason| (StatusCode::BAD_REQUEST, reason))?;
+    relay
+        .storage()
+        .set_group_policy(&group, &policy)
+        .await
+        .map_err(internal)?;
+    tracing::info!("Admin set policy for {:?}: {:?}", group, policy);
+
+    Ok(Json(policy))
+}
+
+async fn set_quota(
+    Extension(relay): Extension<Arc<SyncRelay>>,
+    Path(group_id): Path<String>,
+    Json(update): Json<QuotaUpdate>,
+) -> Result<Json<QuotaUpdate>, AdminError> {
+    let group = parse_group_id(&group_id)?;
+    let storage = relay.storage();
+    let mut policy = storage.get_group_policy(&group).await.map_err(internal)?;
+    policy.max_storage_bytes = update.max_storage_bytes;
+    storage
+        .set_group_policy(&group, &policy)
+        .await
+        .map_err(internal)?;
+    tracing::info!(
+        "Admin set quota for {:?}: {:?}",
+        group,
+        update.max_storage_bytes
+    );
+
+    Ok(Json(update))
+}
+
+async fn rate_limits(Extension(relay): Extension<Arc<SyncRelay>>) -> Json<RateLimitsInfo> {
+    let config = &relay.config().limits;
+    let limits = relay.rate_limits();
+
+    Json(RateLimitsInfo {
+        connections_per_minute: config.connections_per_ip,
+        messages_per_minute: config.messages_per_minute,
+        global_requests_per_second: config.global_requests_per_second,
+        oprf_evaluations_per_hour: config.oprf_evaluations_per_hour,
+        group_messages_per_minute: config.group_messages_per_minute,
+        device_bytes_per_second: config.device_bytes_per_second,
+        group_bytes_per_second: config.group_bytes_per_second,
+        pow_difficulty: config.pow_difficulty(relay.total_sessions()),
+        pow_challenges: relay.metrics().pow_challenges_total.load(Ordering::Relaxed),
+        tracked_connections: limits.connection_keys_count(),
+        tracked_devices: limits.message_keys_count(),
+        tracked_oprf_keys: limits.oprf_keys_count(),
+        tracked_groups: limits.group_keys_count(),
+        rate_limit_hits: relay.metrics().rate_limit_hits.load(Ordering::Relaxed),
+        rate_limit_hits_by_limiter: relay
+            .metrics()
+            .rate_limit_hits_by_limiter
+            .snapshot()
+            .into_iter()
+            .collect(),
+    })
+}
+
+async fn cleanup(Extension(relay): Extension<Arc<SyncRelay>>) -> Json<CleanupReport> {
+    let report = run_cleanup(relay.storage()).await;
+    relay.rate_limits().shrink();
+    Json(report)
+}
+
+async fn reload(
+    Extension(relay): Extension<Arc<SyncRelay>>,
+) -> Result<Json<ReloadResponse>, AdminError> {
+    match relay.reload_from_file() {
+        Ok(changed) => {
+            tracing::info!("Admin reloaded config: {:?}", changed);
+            Ok(Json(ReloadResponse { changed }))
+        }
+        Err(e) => {
+            let status = match e {
+                ConfigError::RestartRequired(_) => StatusCode::CONFLICT,
+                ConfigError::ReadError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+                ConfigError::ParseError { .. } | ConfigError::Invalid(_) => {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+            };
+            tracing::warn!("Admin config reload rejected: {}", e);
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::storage::{SqliteStorage, StoreBlobRequest};
+    use axum::body::Body;
+    use axum::http::Request;
+    use sync_types::{BlobId, DeviceId};
+    use tower::util::ServiceExt;
+
+    const TOKEN: &str = "test-admin-token-0123456789abcdef";
+
+    async fn test_relay() -> Arc<SyncRelay> {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        Arc::new(SyncRelay::new(Config::default(), storage))
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        (status, json)
+    }
+
+    async fn store(relay: &SyncRelay, group_id: GroupId, payload: &[u8]) {
+        relay
+            .storage()
+            .store_blob(StoreBlobRequest {
+                blob_id: BlobId::new(),
+                group_id,
+                sender_id: DeviceId::random(),
+                payload: payload.to_vec(),
+                timestamp: 0,
+                ttl_secs: 3600,
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn admin_requires_token() {
+        let app = build_admin_router(test_relay().await, TOKEN);
+
+        for auth in [None, Some("Bearer wrong-token"), Some(TOKEN)] {
+            let mut request = Request::builder().uri("/admin/groups");
+            if let Some(auth) = auth {
+                request = request.header(AUTHORIZATION, auth);
+            }
+            let response = app
+                .clone()
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[tokio::test]
+    async fn list_quota_and_purge_group() {
+        let relay = test_relay().await;
+        let app = build_admin_router(relay.clone(), TOKEN);
+        let group = GroupId::random();
+        store(&relay, group, b"ciphertext").await;
+        store(&relay, group, b"more").await;
+        let hex_id = hex::encode(group.as_bytes());
+
+        let (status, groups) = send(&app, "GET", "/admin/groups", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(groups[0]["group_id"], hex_id);
+        assert_eq!(groups[0]["blob_count"], 2);
+        assert_eq!(groups[0]["storage_bytes"], 14);
+        assert_eq!(groups[0]["quota_bytes"], serde_json::Value::Null);
+        assert_eq!(groups[0]["sessions"], 0);
+
+        let uri = format!("/admin/groups/{}/quota", hex_id);
+        let (status, _) = send(&app, "PUT", &uri, Some(r#"{"max_storage_bytes":1024}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            relay
+                .storage()
+                .get_group_policy(&group)
+                .await
+                .unwrap()
+                .max_storage_bytes,
+            Some(1024)
+        );
+        let (_, groups) = send(&app, "GET", "/admin/groups", None).await;
+        assert_eq!(groups[0]["effective_quota_bytes"], 1024);
+
+        let (status, purged) =
+            send(&app, "DELETE", &format!("/admin/groups/{}", hex_id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(purged["blobs"], 2);
+        assert_eq!(relay.storage().get_group_storage(&group).await.unwrap(), 0);
+        assert!(relay
+            .storage()
+            .get_group_policy(&group)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let (status, _) = send(&app, "DELETE", "/admin/groups/not-hex", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn set_and_validate_group_policy() {
+        let relay = test_relay().await;
+        let app = build_admin_router(relay.clone(), TOKEN);
+        let group = GroupId::random();
+        let uri = format!("/admin/groups/{}/policy", hex::encode(group.as_bytes()));
+
+        let (status, policy) = send(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(policy["max_blob_size"], serde_json::Value::Null);
+
+        let body = r#"{"max_blob_size":4096,"max_ttl_secs":3600,"messages_per_minute":600}"#;
+        let (status, _) = send(&app, "PUT", &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let stored = relay.storage().get_group_policy(&group).await.unwrap();
+        assert_eq!(stored.max_blob_size, Some(4096));
+        assert_eq!(stored.messages_per_minute, Some(600));
+
+        // The quota endpoint only touches the storage quota
+        let quota_uri = format!("/admin/groups/{}/quota", hex::encode(group.as_bytes()));
+        send(
+            &app,
+            "PUT",
+            &quota_uri,
+            Some(r#"{"max_storage_bytes":1024}"#),
+        )
+        .await;
+        let (_, policy) = send(&app, "GET", &uri, None).await;
+        assert_eq!(policy["max_storage_bytes"], 1024);
+        assert_eq!(policy["max_ttl_secs"], 3600);
+
+        let (status, _) = send(
+            &app,
+            "PUT",
+            &uri,
+            Some(r#"{"min_ttl_secs":60,"max_ttl_secs":10}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&app, "PUT", &uri, Some(r#"{"messages_per_minute":0}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn rate_limits_and_cleanup() {
+        let relay = test_relay().await;
+        let app = build_admin_router(relay.clone(), TOKEN);
+        relay.rate_limits().check_message(&[1u8; 32]).unwrap();
+        relay
+            .metrics()
+            .record_rate_limit(&crate::limits::RateLimitError::GroupLimitExceeded);
+
+        let (status, limits) = send(&app, "GET", "/admin/rate-limits", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(limits["messages_per_minute"], 100);
+        assert_eq!(limits["tracked_devices"], 1);
+        assert_eq!(limits["rate_limit_hits"], 1);
+        assert_eq!(limits["rate_limit_hits_by_limiter"]["group"], 1);
+
+        let (status, report) = send(&app, "POST", "/admin/cleanup", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["blobs"], 0);
+    }
+
+    #[tokio::test]
+    async fn reload_applies_limits_and_rejects_restart_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relay.toml");
+        let write = |extra: &str| {
+            let toml = format!(
+                "[server]\n[storage]\n[limits]\nmessages_per_minute = 5\n{extra}[http]\n[cleanup]\n"
+            );
+            std::fs::write(&path, toml).unwrap();
+        };
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let relay = Arc::new(SyncRelay::new(Config::default(), storage).with_config_path(&path));
+        let app = build_admin_router(relay.clone(), TOKEN);
+
+        write("");
+        let (status, body) = send(&app, "POST", "/admin/reload", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["changed"][0], "limits.messages_per_minute");
+        assert_eq!(relay.config().limits.messages_per_minute, 5);
+        let (_, limits) = send(&app, "GET", "/admin/rate-limits", None).await;
+        assert_eq!(limits["messages_per_minute"], 5);
+
+        // Invalid values are rejected and the running config is kept
+        write("global_requests_per_second = 0\n");
+        let (status, _) = send(&app, "POST", "/admin/reload", None).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // Bind address and database path need a restart
+        std::fs::write(
+            &path,
+            "[server]\nbind_address = \"127.0.0.1:1\"\n[storage]\ndatabase = \"other.db\"\n\
+             [limits]\nmessages_per_minute = 7\n[http]\n[cleanup]\n",
+        )
+        .unwrap();
+        let (status, _) = send(&app, "POST", "/admin/reload", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(relay.config().limits.messages_per_minute, 5);
+        assert_eq!(relay.config().server.bind_address, "0.0.0.0:4433");
+    }
+}
@@ -3,6 +3,7 @@
 use crate::server::SyncRelay;
 use axum::{Extension, Json};
 use serde::Serialize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -17,7 +18,7 @@ pub fn init_start_time() {
 /// Health status response.
 #[derive(Debug, Clone, Serialize)]
 pub struct HealthStatus {
-    /// Overall status.
+    /// Overall status (`ok`, or `draining` during shutdown).
     pub status: String,
     /// Server version.
     pub version: String,
@@ -42,12 +43,18 @@ pub async fn health_handler(Extension(relay): Extension<Arc<SyncRelay>>) -> Json
         .map(|start| start.elapsed().as_secs())
         .unwrap_or(0);
 
-    let total_blobs = relay.storage().get_total_blobs().await.unwrap_or(0);
-    let storage_bytes = relay.storage().get_total_storage_bytes().await.unwrap_or(0);
-    let groups_with_data = relay.storage().get_total_groups_with_data().await.unwrap_or(0);
+    let storage = relay.storage().metrics();
+    let total_blobs = storage.blobs.load(Ordering::Relaxed);
+    let storage_bytes = storage.bytes.load(Ordering::Relaxed);
+    let groups_with_data = storage.groups.load(Ordering::Relaxed);
 
     Json(HealthStatus {
-        status: "ok".to_string(),
+        status: if relay.is_draining() {
+            "draining"
+        } else {
+            "ok"
+        }
+        .to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         connections: relay.total_sessions(),
         groups: relay.total_groups(),
@@ -8,13 +8,15 @@ use std::sync::Arc;
 /// Prometheus metrics handler.
 ///
 /// Returns metrics in Prometheus text format.
-/// Includes both gauges (current state) and counters (monotonic since startup).
+/// Includes gauges (current state), counters (monotonic since startup) and
+/// latency/size histograms. Nothing here queries the database.
 pub async fn metrics_handler(Extension(relay): Extension<Arc<SyncRelay>>) -> impl IntoResponse {
     let m = relay.metrics();
 
     // Gauges — current state
     let connections = relay.total_sessions();
     let groups = relay.total_groups();
+    let pow_difficulty = relay.config().limits.pow_difficulty(connections);
 
     // Counters — monotonic since startup
     let pushes = m.pushes_total.load(Ordering::Relaxed);
@@ -23,15 +25,16 @@ pub async fn metrics_handler(Extension(relay): Extension<Arc<SyncRelay>>) -> imp
     let bytes_rx = m.bytes_received.load(Ordering::Relaxed);
     let bytes_tx = m.bytes_sent.load(Ordering::Relaxed);
     let blobs = m.blobs_stored.load(Ordering::Relaxed);
-    let rate_limits = m.rate_limit_hits.load(Ordering::Relaxed);
     let errors = m.errors_total.load(Ordering::Relaxed);
+    let pow_challenges = m.pow_challenges_total.load(Ordering::Relaxed);
 
-    // Storage stats (async queries — best effort)
-    let total_blobs_stored = relay.storage().get_total_blobs().await.unwrap_or(0);
-    let storage_bytes = relay.storage().get_total_storage_bytes().await.unwrap_or(0);
-    let groups_with_data = relay.storage().get_total_groups_with_data().await.unwrap_or(0);
+    // Storage gauges (maintained incrementally by the storage layer)
+    let storage = relay.storage().metrics();
+    let total_blobs_stored = storage.blobs.load(Ordering::Relaxed);
+    let storage_bytes = storage.bytes.load(Ordering::Relaxed);
+    let groups_with_data = storage.groups.load(Ordering::Relaxed);
 
-    let body = format!(
+    let mut body = format!(
         r#"# HELP sync_relay_connections_active Number of active connections
 # TYPE sync_relay_connections_active gauge
 sync_relay_connections_active {connections}
@@ -40,6 +43,10 @@ sync_relay_connections_active {connections}
 # TYPE sync_relay_groups_active gauge
 sync_relay_groups_active {groups}
 
+# HELP sync_relay_pow_difficulty Proof-of-work bits a new session must solve
+# TYPE sync_relay_pow_difficulty gauge
+sync_relay_pow_difficulty {pow_difficulty}
+
 # HELP sync_relay_info Server information
 # TYPE sync_relay_info gauge
 sync_relay_info{{version="{version}"}} 1
@@ -68,14 +75,14 @@ sync_relay_bytes_sent_total {bytes_tx}
 # TYPE sync_relay_blobs_stored_total counter
 sync_relay_blobs_stored_total {blobs}
 
-# HELP sync_relay_rate_limit_hits_total Total rate limit rejections
-# TYPE sync_relay_rate_limit_hits_total counter
-sync_relay_rate_limit_hits_total {rate_limits}
-
 # HELP sync_relay_errors_total Total protocol errors
 # TYPE sync_relay_errors_total counter
 sync_relay_errors_total {errors}
 
+# HELP sync_relay_pow_challenges_total Proof-of-work challenges sent
+# TYPE sync_relay_pow_challenges_total counter
+sync_relay_pow_challenges_total {pow_challenges}
+
 # HELP sync_relay_storage_blobs Number of blobs currently in database
 # TYPE sync_relay_storage_blobs gauge
 sync_relay_storage_blobs {total_blobs_stored}
@@ -87,10 +94,58 @@ sync_relay_storage_bytes {storage_bytes}
 # HELP sync_relay_storage_groups Number of groups with stored data
 # TYPE sync_relay_storage_groups gauge
 sync_relay_storage_groups {groups_with_data}
+
 "#,
         version = env!("CARGO_PKG_VERSION"),
     );
 
+    // Labeled counters and histograms
+    m.messages_total.render(
+        &mut body,
+        "sync_relay_messages_total",
+        "Messages received by type",
+        "type",
+    );
+    m.rate_limit_hits_by_limiter.render(
+        &mut body,
+        "sync_relay_rate_limit_hits_total",
+        "Rate limit rejections by limiter",
+        "limiter",
+    );
+    m.errors_by_kind.render(
+        &mut body,
+        "sync_relay_errors_by_kind_total",
+        "Protocol errors by kind",
+        "kind",
+    );
+    m.request_duration.render(
+        &mut body,
+        "sync_relay_request_duration_seconds",
+        "Request handling latency by message type",
+        "type",
+    );
+    m.notify_fanout_duration.render(
+        &mut body,
+        "sync_relay_notify_fanout_duration_seconds",
+        "Time until NOTIFY reached all online devices",
+    );
+    m.push_payload_bytes.render(
+        &mut body,
+        "sync_relay_push_payload_bytes",
+        "Push payload size",
+    );
+    m.pull_response_bytes.render(
+        &mut body,
+        "sync_relay_pull_response_bytes",
+        "Pull response payload size",
+    );
+    storage.query_duration.render(
+        &mut body,
+        "sync_relay_storage_query_duration_seconds",
+        "Storage query latency by operation",
+        "op",
+    );
+
     (
         [(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
         body,
@@ -1,7 +1,9 @@
 //! HTTP endpoints for sync-relay.
 //!
-//! Provides health checks, metrics, and discovery endpoints.
+//! Provides health checks, metrics, and discovery endpoints, plus the
+//! token-authenticated admin API served on a separate listener.
 
+pub mod admin;
 pub mod health;
 mod metrics;
 
@@ -9,6 +11,7 @@ use crate::server::SyncRelay;
 use axum::{routing::get, Extension, Router};
 use std::sync::Arc;
 
+pub use admin::build_admin_router;
 pub use health::HealthStatus;
 
 /// Build the HTTP router with all endpoints.
@@ -34,12 +34,15 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod backup;
 pub mod cleanup;
 pub mod config;
 pub mod error;
 pub mod http;
 pub mod limits;
+pub mod metrics;
 pub mod protocol;
 pub mod server;
 pub mod session;
 pub mod storage;
+pub mod telemetry;
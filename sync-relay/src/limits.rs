@@ -8,8 +8,13 @@
 //! identify clients by IP address. Instead, we rate limit by:
 //! - **EndpointId** (32-byte public key) for connection attempts
 //! - **DeviceId** (32-byte identifier) for message operations
+//! - **OPRF key ID** (32-byte identifier) for OPRF evaluations
+//! - **GroupId** (32-byte identifier) for group-wide PUSH/PULL, so one busy
+//!   group cannot use up the global limit for everyone else
 //!
-//! Both use the governor crate's keyed rate limiters backed by DashMap.
+//! All use the governor crate's keyed rate limiters backed by DashMap.
+//! Alongside request counts, devices and groups have byte-rate buckets
+//! charged with ciphertext bytes pushed and pulled.
 
 use crate::config::LimitsConfig;
 use governor::clock::DefaultClock;
@@ -30,6 +35,24 @@ type KeyedLimiter<K> = RateLimiter<
 /// Type alias for a direct (non-keyed) rate limiter.
 type DirectLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
 
+/// Per-group limiters, keyed by GroupId, each at its group's policy rate.
+type GroupLimiters = dashmap::DashMap<[u8; 32], GroupLimiter>;
+
+/// Limiter for one group, remembering the rate it was built with.
+struct GroupLimiter {
+    per_minute: NonZeroU32,
+    limiter: Arc<DirectLimiter>,
+}
+
+impl GroupLimiter {
+    fn new(per_minute: NonZeroU32) -> Self {
+        Self {
+            per_minute,
+            limiter: Arc::new(RateLimiter::direct(Quota::per_minute(per_minute))),
+        }
+    }
+}
+
 /// Rate limiters for the relay server.
 #[derive(Clone)]
 pub struct RateLimits {
@@ -48,6 +71,36 @@ pub struct RateLimits {
     ///
     /// Prevents aggregate overload even if individual clients are within limits.
     global_limiter: Arc<DirectLimiter>,
+
+    /// Limits OPRF evaluations per group key, across all connections.
+    ///
+    /// Configured via `limits.oprf_evaluations_per_hour`. Each evaluation is
+    /// one passphrase guess, so this bounds online brute force.
+    oprf_limiter: Arc<KeyedLimiter<[u8; 32]>>,
+
+    /// Limits PUSH/PULL per GroupId.
+    ///
+    /// Configured via `limits.group_messages_per_minute`.
+    group_limiter: Arc<KeyedLimiter<[u8; 32]>>,
+
+    /// Limits PUSH/PULL per GroupId for groups whose policy sets
+    /// `messages_per_minute`. Rates differ per group, so each group gets
+    /// its own direct limiter; it is rebuilt when the policy rate changes.
+    group_limiters: Arc<GroupLimiters>,
+
+    /// Limits ciphertext bytes per DeviceId (one cell per byte).
+    ///
+    /// Configured via `limits.device_bytes_per_second`.
+    device_bytes_limiter: Arc<KeyedLimiter<[u8; 32]>>,
+
+    /// Limits ciphertext bytes per GroupId (one cell per byte).
+    ///
+    /// Configured via `limits.group_bytes_per_second`.
+    group_bytes_limiter: Arc<KeyedLimiter<[u8; 32]>>,
+
+    /// Byte bursts: the most a single request is charged.
+    device_bytes_burst: NonZeroU32,
+    group_bytes_burst: NonZeroU32,
 }
 
 impl std::fmt::Debug for RateLimits {
@@ -56,6 +109,11 @@ impl std::fmt::Debug for RateLimits {
             .field("connection_limiter", &"KeyedLimiter<[u8;32]>")
             .field("message_limiter", &"KeyedLimiter<[u8;32]>")
             .field("global_limiter", &"DirectLimiter")
+            .field("oprf_limiter", &"KeyedLimiter<[u8;32]>")
+            .field("group_limiter", &"KeyedLimiter<[u8;32]>")
+            .field("group_limiters", &self.group_limiters.len())
+            .field("device_bytes_limiter", &"KeyedLimiter<[u8;32]>")
+            .field("group_bytes_limiter", &"KeyedLimiter<[u8;32]>")
             .finish()
     }
 }
@@ -80,18 +138,97 @@ impl RateLimits {
         // Message rate: allow `messages_per_minute` per minute
         let messages_per_minute =
             NonZeroU32::new(config.messages_per_minute).expect("messages_per_minute must be > 0");
-        let message_quota = Quota::per_minute(messages_per_minute);
+        let message_quota =
+            with_burst(Quota::per_minute(messages_per_minute), config.message_burst);
 
         // Global rate: allow `global_requests_per_second` per second across all clients
         let global_rps = NonZeroU32::new(config.global_requests_per_second)
             .expect("global_requests_per_second must be > 0");
-        let global_quota = Quota::per_second(global_rps);
+        let global_quota = with_burst(Quota::per_second(global_rps), config.global_burst);
+
+        // OPRF rate: allow `oprf_evaluations_per_hour` per hour for each key
+        let oprf_per_hour = NonZeroU32::new(config.oprf_evaluations_per_hour)
+            .expect("oprf_evaluations_per_hour must be > 0");
+        let oprf_quota = Quota::per_hour(oprf_per_hour);
+
+        // Group rate: allow `group_messages_per_minute` per minute for each group
+        let group_per_minute = NonZeroU32::new(config.group_messages_per_minute)
+            .expect("group_messages_per_minute must be > 0");
+        let group_quota = with_burst(
+            Quota::per_minute(group_per_minute),
+            config.group_message_burst,
+        );
+
+        // Byte rates: refill per second, bursting up to `bytes_burst_secs` worth
+        let device_bytes_burst = bytes_burst(config, config.device_bytes_per_second);
+        let group_bytes_burst = bytes_burst(config, config.group_bytes_per_second);
+        let device_bytes_quota = Quota::per_second(
+            NonZeroU32::new(config.device_bytes_per_second)
+                .expect("device_bytes_per_second must be > 0"),
+        )
+        .allow_burst(device_bytes_burst);
+        let group_bytes_quota = Quota::per_second(
+            NonZeroU32::new(config.group_bytes_per_second)
+                .expect("group_bytes_per_second must be > 0"),
+        )
+        .allow_burst(group_bytes_burst);
 
         Self {
             connection_limiter: Arc::new(RateLimiter::keyed(connection_quota)),
             message_limiter: Arc::new(RateLimiter::keyed(message_quota)),
             global_limiter: Arc::new(RateLimiter::direct(global_quota)),
+            oprf_limiter: Arc::new(RateLimiter::keyed(oprf_quota)),
+            group_limiter: Arc::new(RateLimiter::keyed(group_quota)),
+            group_limiters: Arc::default(),
+            device_bytes_limiter: Arc::new(RateLimiter::keyed(device_bytes_quota)),
+            group_bytes_limiter: Arc::new(RateLimiter::keyed(group_bytes_quota)),
+            device_bytes_burst,
+            group_bytes_burst,
+        }
+    }
+
+    /// Rebuild the limiters for a reloaded configuration.
+    ///
+    /// Limiters whose settings are unchanged between `old` and `new` are
+    /// shared with `self`, so their per-key state survives the reload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new configured values are zero.
+    pub fn reconfigure(&self, old: &LimitsConfig, new: &LimitsConfig) -> Self {
+        let mut limits = Self::new(new);
+        if old.connections_per_ip == new.connections_per_ip {
+            limits.connection_limiter = self.connection_limiter.clone();
+        }
+        if (old.messages_per_minute, old.message_burst)
+            == (new.messages_per_minute, new.message_burst)
+        {
+            limits.message_limiter = self.message_limiter.clone();
         }
+        if (old.global_requests_per_second, old.global_burst)
+            == (new.global_requests_per_second, new.global_burst)
+        {
+            limits.global_limiter = self.global_limiter.clone();
+        }
+        if (old.group_messages_per_minute, old.group_message_burst)
+            == (new.group_messages_per_minute, new.group_message_burst)
+        {
+            limits.group_limiter = self.group_limiter.clone();
+        }
+        if old.bytes_burst_secs == new.bytes_burst_secs {
+            if old.device_bytes_per_second == new.device_bytes_per_second {
+                limits.device_bytes_limiter = self.device_bytes_limiter.clone();
+            }
+            if old.group_bytes_per_second == new.group_bytes_per_second {
+                limits.group_bytes_limiter = self.group_bytes_limiter.clone();
+            }
+        }
+        if old.oprf_evaluations_per_hour == new.oprf_evaluations_per_hour {
+            limits.oprf_limiter = self.oprf_limiter.clone();
+        }
+        // Group rates come from the database, not the config file
+        limits.group_limiters = self.group_limiters.clone();
+        limits
     }
 
     /// Check if a connection attempt is allowed.
@@ -134,6 +271,92 @@ impl RateLimits {
             .map_err(|_| RateLimitError::GlobalLimitExceeded)
     }
 
+    /// Check if an OPRF evaluation under `key_id` is allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - The 32-byte OPRF key ID being evaluated
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if allowed, `Err` with reason if rate limited.
+    pub fn check_oprf(&self, key_id: &[u8; 32]) -> Result<(), RateLimitError> {
+        self.oprf_limiter
+            .check_key(key_id)
+            .map_err(|_| RateLimitError::OprfLimitExceeded)
+    }
+
+    /// Check if a PUSH or PULL in a group is allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The 32-byte GroupId the request targets
+    /// * `policy_rate` - The group policy's per-minute rate, if it sets one;
+    ///   otherwise `limits.group_messages_per_minute` applies
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if allowed, `Err` with reason if rate limited.
+    pub fn check_group(
+        &self,
+        group_id: &[u8; 32],
+        policy_rate: Option<NonZeroU32>,
+    ) -> Result<(), RateLimitError> {
+        let Some(per_minute) = policy_rate else {
+            return self
+                .group_limiter
+                .check_key(group_id)
+                .map_err(|_| RateLimitError::GroupLimitExceeded);
+        };
+        let limiter = {
+            let mut entry = self
+                .group_limiters
+                .entry(*group_id)
+                .or_insert_with(|| GroupLimiter::new(per_minute));
+            if entry.per_minute != per_minute {
+                *entry = GroupLimiter::new(per_minute);
+            }
+            entry.limiter.clone()
+        };
+        limiter
+            .check()
+            .map_err(|_| RateLimitError::GroupLimitExceeded)
+    }
+
+    /// Charge `bytes` of ciphertext to a device and its group.
+    ///
+    /// A request is charged at most the configured burst, so one large
+    /// blob drains the bucket instead of being refused outright.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if allowed, `Err` with the exhausted limiter otherwise.
+    pub fn check_bytes(
+        &self,
+        device_id: &[u8; 32],
+        group_id: &[u8; 32],
+        bytes: usize,
+    ) -> Result<(), RateLimitError> {
+        let Some(bytes) = NonZeroU32::new(bytes.min(u32::MAX as usize) as u32) else {
+            return Ok(());
+        };
+        if !matches!(
+            self.device_bytes_limiter
+                .check_key_n(device_id, bytes.min(self.device_bytes_burst)),
+            Ok(Ok(()))
+        ) {
+            return Err(RateLimitError::DeviceBandwidthExceeded);
+        }
+        if !matches!(
+            self.group_bytes_limiter
+                .check_key_n(group_id, bytes.min(self.group_bytes_burst)),
+            Ok(Ok(()))
+        ) {
+            return Err(RateLimitError::GroupBandwidthExceeded);
+        }
+        Ok(())
+    }
+
     /// Get the number of tracked connection keys (for metrics).
     pub fn connection_keys_count(&self) -> usize {
         self.connection_limiter.len()
@@ -144,6 +367,16 @@ impl RateLimits {
         self.message_limiter.len()
     }
 
+    /// Get the number of tracked OPRF keys (for metrics).
+    pub fn oprf_keys_count(&self) -> usize {
+        self.oprf_limiter.len()
+    }
+
+    /// Get the number of tracked group keys (for metrics).
+    pub fn group_keys_count(&self) -> usize {
+        self.group_limiter.len() + self.group_limiters.len()
+    }
+
     /// Evict stale entries from the keyed rate limiter DashMaps (F-015).
     ///
     /// Over time, disconnected clients leave entries in the DashMap.
@@ -152,9 +385,33 @@ impl RateLimits {
     pub fn shrink(&self) {
         self.connection_limiter.retain_recent();
         self.message_limiter.retain_recent();
+        self.oprf_limiter.retain_recent();
+        self.group_limiter.retain_recent();
+        self.device_bytes_limiter.retain_recent();
+        self.group_bytes_limiter.retain_recent();
     }
 }
 
+/// Apply a configured burst to `quota` (unset keeps the quota's default).
+fn with_burst(quota: Quota, burst: Option<u32>) -> Quota {
+    match burst.and_then(NonZeroU32::new) {
+        Some(burst) => quota.allow_burst(burst),
+        None => quota,
+    }
+}
+
+/// Byte burst for a per-second byte rate.
+///
+/// # Panics
+///
+/// Panics if the burst is zero or overflows `u32`.
+fn bytes_burst(config: &LimitsConfig, bytes_per_second: u32) -> NonZeroU32 {
+    config
+        .bytes_burst(bytes_per_second)
+        .and_then(NonZeroU32::new)
+        .expect("byte rate x bytes_burst_secs must be in 1..=u32::MAX")
+}
+
 /// Rate limit error types.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RateLimitError {
@@ -164,6 +421,29 @@ pub enum RateLimitError {
     MessageLimitExceeded,
     /// Global request rate exceeded across all clients (F-014).
     GlobalLimitExceeded,
+    /// Too many OPRF evaluations for this key.
+    OprfLimitExceeded,
+    /// Too many requests in this group.
+    GroupLimitExceeded,
+    /// Too many bytes from or to this device.
+    DeviceBandwidthExceeded,
+    /// Too many bytes from or to this group.
+    GroupBandwidthExceeded,
+}
+
+impl RateLimitError {
+    /// Short limiter name, used as a metric label.
+    pub fn limiter(&self) -> &'static str {
+        match self {
+            Self::ConnectionLimitExceeded => "connection",
+            Self::MessageLimitExceeded => "message",
+            Self::GlobalLimitExceeded => "global",
+            Self::OprfLimitExceeded => "oprf",
+            Self::GroupLimitExceeded => "group",
+            Self::DeviceBandwidthExceeded => "device_bytes",
+            Self::GroupBandwidthExceeded => "group_bytes",
+        }
+    }
 }
 
 impl std::fmt::Display for RateLimitError {
@@ -178,6 +458,18 @@ impl std::fmt::Display for RateLimitError {
             Self::GlobalLimitExceeded => {
                 write!(f, "global rate limit exceeded")
             }
+            Self::OprfLimitExceeded => {
+                write!(f, "OPRF evaluation rate limit exceeded")
+            }
+            Self::GroupLimitExceeded => {
+                write!(f, "group rate limit exceeded")
+            }
+            Self::DeviceBandwidthExceeded => {
+                write!(f, "device bandwidth limit exceeded")
+            }
+            Self::GroupBandwidthExceeded => {
+                write!(f, "group bandwidth limit exceeded")
+            }
         }
     }
 }
@@ -197,6 +489,8 @@ mod tests {
             max_device_name_len: 256,
             max_pull_limit: 1000,
             global_requests_per_second: 1000,
+            oprf_evaluations_per_hour: 30,
+            ..crate::config::Config::default().limits
         }
     }
 
@@ -207,6 +501,123 @@ mod tests {
         assert_eq!(limits.message_keys_count(), 0);
     }
 
+    #[test]
+    fn reconfigure_keeps_state_of_unchanged_limiters() {
+        let old = test_config();
+        let limits = RateLimits::new(&old);
+        let device_id = [3u8; 32];
+        for _ in 0..10 {
+            limits.check_message(&device_id).unwrap();
+        }
+        assert!(limits.check_message(&device_id).is_err());
+
+        // Unrelated change: the exhausted message quota carries over
+        let mut new = old.clone();
+        new.connections_per_ip = 50;
+        let reloaded = limits.reconfigure(&old, &new);
+        assert!(reloaded.check_message(&device_id).is_err());
+
+        // Raised message limit: fresh limiter with the new quota
+        let mut raised = new.clone();
+        raised.messages_per_minute = 20;
+        let reloaded = reloaded.reconfigure(&new, &raised);
+        for _ in 0..20 {
+            reloaded.check_message(&device_id).unwrap();
+        }
+        assert!(reloaded.check_message(&device_id).is_err());
+    }
+
+    #[test]
+    fn group_limit_follows_policy_rate() {
+        let limits = RateLimits::new(&test_config());
+        let group = [4u8; 32];
+        let other = [5u8; 32];
+        let rate = NonZeroU32::new(3).unwrap();
+
+        for _ in 0..3 {
+            limits.check_group(&group, Some(rate)).unwrap();
+        }
+        assert_eq!(
+            limits.check_group(&group, Some(rate)),
+            Err(RateLimitError::GroupLimitExceeded)
+        );
+        // Other groups have their own bucket
+        assert!(limits.check_group(&other, Some(rate)).is_ok());
+
+        // A changed policy rate takes effect immediately
+        let raised = NonZeroU32::new(10).unwrap();
+        assert!(limits.check_group(&group, Some(raised)).is_ok());
+    }
+
+    #[test]
+    fn group_limit_applies_without_policy() {
+        let config = LimitsConfig {
+            group_messages_per_minute: 2,
+            ..test_config()
+        };
+        let limits = RateLimits::new(&config);
+        let group = [6u8; 32];
+
+        limits.check_group(&group, None).unwrap();
+        limits.check_group(&group, None).unwrap();
+        assert_eq!(
+            limits.check_group(&group, None),
+            Err(RateLimitError::GroupLimitExceeded)
+        );
+        assert_eq!(limits.group_keys_count(), 1);
+    }
+
+    #[test]
+    fn bursts_are_configurable() {
+        let config = LimitsConfig {
+            messages_per_minute: 60,
+            message_burst: Some(3),
+            ..test_config()
+        };
+        let limits = RateLimits::new(&config);
+        let device_id = [7u8; 32];
+
+        for _ in 0..3 {
+            limits.check_message(&device_id).unwrap();
+        }
+        assert_eq!(
+            limits.check_message(&device_id),
+            Err(RateLimitError::MessageLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn byte_limits_charge_device_and_group() {
+        let config = LimitsConfig {
+            device_bytes_per_second: 100,
+            group_bytes_per_second: 150,
+            bytes_burst_secs: 1,
+            ..test_config()
+        };
+        let limits = RateLimits::new(&config);
+        let (device_a, device_b, group) = ([8u8; 32], [9u8; 32], [10u8; 32]);
+
+        limits.check_bytes(&device_a, &group, 80).unwrap();
+        assert_eq!(
+            limits.check_bytes(&device_a, &group, 80),
+            Err(RateLimitError::DeviceBandwidthExceeded)
+        );
+        // Another device still has bytes, but the group has only 70 left
+        assert_eq!(
+            limits.check_bytes(&device_b, &group, 80),
+            Err(RateLimitError::GroupBandwidthExceeded)
+        );
+        assert_eq!(
+            RateLimitError::GroupBandwidthExceeded.limiter(),
+            "group_bytes"
+        );
+
+        // Oversized requests drain the bucket rather than failing forever
+        let fresh = RateLimits::new(&config);
+        fresh.check_bytes(&device_a, &group, 1_000_000).unwrap();
+        assert!(fresh.check_bytes(&device_a, &group, 1).is_err());
+    }
+
     #[test]
     fn connection_limit_allows_within_quota() {
         let config = LimitsConfig {
@@ -217,6 +628,8 @@ mod tests {
             max_device_name_len: 256,
             max_pull_limit: 1000,
             global_requests_per_second: 1000,
+            oprf_evaluations_per_hour: 30,
+            ..crate::config::Config::default().limits
         };
         let limits = RateLimits::new(&config);
         let endpoint_id = [1u8; 32];
@@ -243,6 +656,8 @@ mod tests {
             max_device_name_len: 256,
             max_pull_limit: 1000,
             global_requests_per_second: 1000,
+            oprf_evaluations_per_hour: 30,
+            ..crate::config::Config::default().limits
         };
         let limits = RateLimits::new(&config);
         let device_id = [2u8; 32];
@@ -269,6 +684,8 @@ mod tests {
             max_device_name_len: 256,
             max_pull_limit: 1000,
             global_requests_per_second: 1000,
+            oprf_evaluations_per_hour: 30,
+            ..crate::config::Config::default().limits
         };
         let limits = RateLimits::new(&config);
 
@@ -313,6 +730,10 @@ mod tests {
             RateLimitError::GlobalLimitExceeded.to_string(),
             "global rate limit exceeded"
         );
+        assert_eq!(
+            RateLimitError::OprfLimitExceeded.to_string(),
+            "OPRF evaluation rate limit exceeded"
+        );
     }
 
     #[test]
@@ -326,6 +747,8 @@ mod tests {
             max_device_name_len: 256,
             max_pull_limit: 1000,
             global_requests_per_second: 5,
+            oprf_evaluations_per_hour: 30,
+            ..crate::config::Config::default().limits
         };
         let limits = RateLimits::new(&config);
 
@@ -341,6 +764,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn oprf_limit_is_per_key() {
+        let config = LimitsConfig {
+            oprf_evaluations_per_hour: 3,
+            ..test_config()
+        };
+        let limits = RateLimits::new(&config);
+        let key_a = [1u8; 32];
+        let key_b = [2u8; 32];
+
+        for _ in 0..3 {
+            assert!(limits.check_oprf(&key_a).is_ok());
+        }
+        assert_eq!(
+            limits.check_oprf(&key_a),
+            Err(RateLimitError::OprfLimitExceeded)
+        );
+
+        // Other groups' keys are unaffected
+        assert!(limits.check_oprf(&key_b).is_ok());
+    }
+
     #[test]
     fn shrink_does_not_panic() {
         // F-015: Shrink via retain_recent() must be callable without error.
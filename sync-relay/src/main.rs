@@ -3,44 +3,81 @@
 //! Usage:
 //! ```bash
 //! sync-relay --config relay.toml
-//! sync-relay --help
+//! sync-relay backup <path> [--config relay.toml]
+//! sync-relay restore <path> [--force] [--config relay.toml]
 //! ```
 
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use zerok_sync_relay::backup;
 use zerok_sync_relay::cleanup;
 use zerok_sync_relay::config::Config;
 use zerok_sync_relay::http;
 use zerok_sync_relay::protocol::{SyncProtocol, ALPN};
-use zerok_sync_relay::server::SyncRelay;
+use zerok_sync_relay::server::{load_or_create_noise_keypair, SyncRelay};
 use zerok_sync_relay::storage::SqliteStorage;
+#[cfg(feature = "otel")]
+use zerok_sync_relay::telemetry;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("sync_relay=info".parse().unwrap())
-                .add_directive("iroh=warn".parse().unwrap()),
-        )
-        .init();
-
     // Initialize health check start time
     http::health::init_start_time();
 
     // Parse arguments
     let config_path = get_config_path();
+    let command = parse_command()?;
 
-    // Load config
+    // Load config (before tracing, which it configures)
     let config = if config_path.exists() {
-        tracing::info!("Loading config from {:?}", config_path);
         Config::from_file(&config_path)?
     } else {
-        tracing::info!("Using default config (no config file found)");
         Config::default()
     };
+    config.validate()?;
+
+    match command {
+        Command::Serve => {}
+        Command::Backup(dest) => return run_backup(&config, &dest).await,
+        Command::Restore { from, force } => return run_restore(&config, &from, force).await,
+    }
+
+    // Initialize tracing, exporting spans over OTLP if configured
+    let subscriber = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("sync_relay=info".parse().unwrap())
+                .add_directive("iroh=warn".parse().unwrap()),
+        )
+        .with(tracing_subscriber::fmt::layer());
+    #[cfg(feature = "otel")]
+    let tracer_provider = {
+        let provider = telemetry::init_otlp(&config.telemetry)?;
+        subscriber
+            .with(provider.as_ref().map(telemetry::layer))
+            .init();
+        provider
+    };
+    #[cfg(not(feature = "otel"))]
+    {
+        subscriber.init();
+        if config.telemetry.otlp_endpoint.is_some() {
+            tracing::warn!("telemetry.otlp_endpoint is set but sync-relay was built without the `otel` feature");
+        }
+    }
+
+    if config_path.exists() {
+        tracing::info!("Loaded config from {:?}", config_path);
+    } else {
+        tracing::info!("Using default config (no config file found)");
+    }
+    if let Some(endpoint) = &config.telemetry.otlp_endpoint {
+        tracing::info!("OTLP trace export: {}", endpoint);
+    }
 
     tracing::info!("sync-relay v{}", env!("CARGO_PKG_VERSION"));
     tracing::info!("iroh bind: {}", config.server.bind_address);
@@ -52,7 +89,21 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Storage initialized");
 
     // Create relay
-    let relay = Arc::new(SyncRelay::new(config.clone(), storage));
+    let mut relay = SyncRelay::new(config.clone(), storage);
+    if config_path.exists() {
+        relay = relay.with_config_path(&config_path);
+    }
+    match config.server.noise_key_file() {
+        Some(path) => {
+            relay = relay.with_noise_keypair(load_or_create_noise_keypair(&path)?);
+            tracing::info!("Secure channel key: {:?}", path);
+        }
+        None => tracing::warn!(
+            "No server.noise_key_path set; the secure channel key changes on restart and clients cannot pin it"
+        ),
+    }
+    let noise_public_key = hex::encode(relay.noise_keypair().public_key());
+    let relay = Arc::new(relay);
 
     // Create iroh endpoint with default discovery (DNS + Pkarr)
     let endpoint = iroh::Endpoint::builder().bind().await?;
@@ -76,6 +127,9 @@ async fn main() -> anyhow::Result<()> {
     // Start cleanup task
     let cleanup_handle = cleanup::spawn_cleanup_task(relay.storage_arc(), config.cleanup.clone());
 
+    // Start periodic snapshots (if backup.snapshot_dir is set)
+    let snapshot_handle = backup::spawn_snapshot_task(relay.storage_arc(), config.backup.clone());
+
     // Start HTTP server
     let http_addr: SocketAddr = config.http.bind_address.parse()?;
     let http_router = http::build_router(relay.clone());
@@ -87,18 +141,36 @@ async fn main() -> anyhow::Result<()> {
     let mut http_handle =
         tokio::spawn(async move { axum::serve(http_listener, http_router).await });
 
+    // Start admin API on its own listener (refuses to start without a token)
+    let admin_handle = match config.admin.validated_token()? {
+        Some(token) => {
+            let admin_addr: SocketAddr = config.admin.bind_address.parse()?;
+            let admin_router = http::build_admin_router(relay.clone(), token);
+            let admin_listener = tokio::net::TcpListener::bind(admin_addr).await?;
+            tracing::info!("Admin API listening on {}", admin_addr);
+            Some(tokio::spawn(async move {
+                axum::serve(admin_listener, admin_router).await
+            }))
+        }
+        None => None,
+    };
+
+    // Reload limits from the config file on SIGHUP
+    let reload_handle = spawn_reload_on_sighup(relay.clone());
+
     // Print connection info
     println!();
     println!("=== sync-relay running ===");
     println!("Endpoint ID: {}", endpoint_id);
+    println!("Secure channel key: {}", noise_public_key);
     println!("HTTP: http://{}/health", http_addr);
     println!();
     println!("Press Ctrl+C to stop");
 
     // Wait for shutdown signal or unexpected exit
     tokio::select! {
-        _ = tokio::signal::ctrl_c() => {
-            tracing::info!("Received shutdown signal");
+        signal = shutdown_signal() => {
+            tracing::info!("Received {}", signal);
         }
         result = &mut http_handle => {
             match result {
@@ -111,19 +183,179 @@ async fn main() -> anyhow::Result<()> {
 
     // Graceful shutdown
     tracing::info!("Shutting down...");
+    cleanup_handle.abort();
+    if let Some(handle) = snapshot_handle {
+        handle.abort();
+    }
+    if let Some(handle) = reload_handle {
+        handle.abort();
+    }
+
+    // Drain: refuse new connections and requests, tell connected devices to
+    // reconnect elsewhere, and let in-flight requests finish. /health keeps
+    // answering ("draining") until the drain is over.
+    let told = relay.begin_drain();
+    tracing::info!("Draining: sent BYE to {} connected devices", told);
+    let drain_timeout = Duration::from_secs(relay.config().server.drain_timeout_secs);
+    if relay.wait_idle(drain_timeout).await {
+        tracing::info!("Drained all in-flight requests");
+    } else {
+        tracing::warn!(
+            "Drain timed out after {}s with {} requests in flight",
+            drain_timeout.as_secs(),
+            relay.in_flight()
+        );
+    }
 
     // Abort background tasks
-    cleanup_handle.abort();
     http_handle.abort();
+    if let Some(handle) = admin_handle {
+        handle.abort();
+    }
 
-    // Gracefully close router (waits for connections to finish)
+    // Close the router and its connections
     router.shutdown().await?;
 
+    // Flush buffered spans
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider {
+        let _ = tokio::task::spawn_blocking(move || provider.shutdown()).await;
+    }
+
     tracing::info!("Goodbye!");
 
     Ok(())
 }
 
+/// Resolve on ctrl-c or, on Unix, SIGTERM (as sent by container
+/// orchestrators). Returns the signal name.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "ctrl-c",
+                _ = sigterm.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                tracing::warn!("Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "ctrl-c"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "ctrl-c"
+    }
+}
+
+/// Re-read the config file on SIGHUP and apply the reloadable settings.
+#[cfg(unix)]
+fn spawn_reload_on_sighup(relay: Arc<SyncRelay>) -> Option<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sighup = match signal(SignalKind::hangup()) {
+        Ok(sighup) => sighup,
+        Err(e) => {
+            tracing::warn!("Cannot listen for SIGHUP, config reload disabled: {}", e);
+            return None;
+        }
+    };
+    Some(tokio::spawn(async move {
+        while sighup.recv().await.is_some() {
+            match relay.reload_from_file() {
+                Ok(changed) if changed.is_empty() => {
+                    tracing::info!("SIGHUP: config unchanged");
+                }
+                Ok(changed) => {
+                    tracing::info!("SIGHUP: reloaded config ({})", changed.join(", "));
+                }
+                Err(e) => tracing::warn!("SIGHUP: config reload rejected: {}", e),
+            }
+        }
+    }))
+}
+
+#[cfg(not(unix))]
+fn spawn_reload_on_sighup(_relay: Arc<SyncRelay>) -> Option<tokio::task::JoinHandle<()>> {
+    None
+}
+
+/// What the binary was asked to do.
+enum Command {
+    /// Run the relay (no subcommand).
+    Serve,
+    /// Write an online snapshot of the database.
+    Backup(PathBuf),
+    /// Replace the database with a verified backup.
+    Restore {
+        /// Backup to restore from.
+        from: PathBuf,
+        /// Overwrite an existing database.
+        force: bool,
+    },
+}
+
+fn parse_command() -> anyhow::Result<Command> {
+    let mut positional = Vec::new();
+    let mut force = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                args.next();
+            }
+            "--force" => force = true,
+            _ => positional.push(arg),
+        }
+    }
+
+    match positional.as_slice() {
+        [] => Ok(Command::Serve),
+        [cmd, path] if cmd == "backup" => Ok(Command::Backup(PathBuf::from(path))),
+        [cmd, path] if cmd == "restore" => Ok(Command::Restore {
+            from: PathBuf::from(path),
+            force,
+        }),
+        _ => anyhow::bail!(
+            "usage: sync-relay [backup <path> | restore <path> [--force]] [--config relay.toml]"
+        ),
+    }
+}
+
+/// Snapshot the configured database to `dest`. Safe while the relay runs.
+async fn run_backup(config: &Config, dest: &Path) -> anyhow::Result<()> {
+    let database = &config.storage.database;
+    let report = backup::backup_file(database, dest).await?;
+    println!(
+        "Backed up {:?} to {:?} ({} bytes, schema version {})",
+        database, report.path, report.bytes, report.schema_version
+    );
+    Ok(())
+}
+
+/// Replace the configured database with `from`. The relay must be stopped.
+async fn run_restore(config: &Config, from: &Path, force: bool) -> anyhow::Result<()> {
+    let report = backup::restore(from, &config.storage.database, force)
+        .await
+        .map_err(|e| match e {
+            backup::BackupError::Exists(path) => {
+                anyhow::anyhow!(
+                    "{:?} exists; stop the relay and pass --force to replace it",
+                    path
+                )
+            }
+            e => e.into(),
+        })?;
+    println!(
+        "Restored {:?} from {:?} ({} bytes, schema version {})",
+        report.path, from, report.bytes, report.schema_version
+    );
+    Ok(())
+}
+
 fn get_config_path() -> PathBuf {
     std::env::args()
         .skip_while(|arg| arg != "--config")
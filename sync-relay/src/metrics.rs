@@ -0,0 +1,340 @@
+//! Histogram and labeled metric primitives.
+//!
+//! Lock-free building blocks for [`RelayMetrics`](crate::server::RelayMetrics)
+//! and the storage layer, rendered in Prometheus text format by the
+//! `/metrics` endpoint. Label values are always `&'static str` from a fixed
+//! set (message types, error kinds, storage operations), so cardinality is
+//! bounded and nothing client-controlled ends up in a label.
+
+use dashmap::DashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use sync_types::Message;
+
+/// Bucket bounds for latencies, in seconds (0.5 ms to 5 s).
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// Bucket bounds for payload sizes, in bytes (256 B to 4 MiB).
+pub const SIZE_BUCKETS: &[f64] = &[
+    256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0,
+];
+
+/// Fixed-bucket histogram.
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Per-bucket counts (not cumulative); the last slot is `+Inf`.
+    buckets: Box<[AtomicU64]>,
+    count: AtomicU64,
+    /// Sum of observations, stored as `f64` bits.
+    sum_bits: AtomicU64,
+}
+
+impl Histogram {
+    /// Create a histogram with the given upper bounds (ascending).
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_bits: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    /// Record one observation.
+    pub fn observe(&self, value: f64) {
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum_bits
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+
+    /// Record a duration in seconds.
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    /// Number of observations.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Sum of observations.
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum_bits.load(Ordering::Relaxed))
+    }
+
+    /// Append `_bucket`, `_sum` and `_count` series for this histogram.
+    ///
+    /// `labels` is either empty or `key="value"` pairs without braces.
+    fn render_series(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = match self.bounds.get(i) {
+                Some(bound) => bound.to_string(),
+                None => "+Inf".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels}{sep}le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let braces = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        let _ = writeln!(out, "{name}_sum{braces} {}", self.sum());
+        let _ = writeln!(out, "{name}_count{braces} {}", self.count());
+    }
+
+    /// Append this histogram in Prometheus text format.
+    pub fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        self.render_series(out, name, "");
+        out.push('\n');
+    }
+}
+
+/// Counter broken down by one label.
+#[derive(Debug, Default)]
+pub struct LabeledCounter {
+    values: DashMap<&'static str, AtomicU64>,
+}
+
+impl LabeledCounter {
+    /// Increment the counter for `label`.
+    pub fn inc(&self, label: &'static str) {
+        self.values
+            .entry(label)
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current value for `label`.
+    pub fn get(&self, label: &str) -> u64 {
+        self.values
+            .get(label)
+            .map(|v| v.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
+    /// Current values, sorted by label.
+    pub fn snapshot(&self) -> Vec<(&'static str, u64)> {
+        let mut values: Vec<_> = self
+            .values
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().load(Ordering::Relaxed)))
+            .collect();
+        values.sort_unstable();
+        values
+    }
+
+    /// Append this counter in Prometheus text format, one series per label.
+    pub fn render(&self, out: &mut String, name: &str, help: &str, label_name: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} counter");
+        for (label, value) in self.snapshot() {
+            let _ = writeln!(out, "{name}{{{label_name}=\"{label}\"}} {value}");
+        }
+        out.push('\n');
+    }
+}
+
+/// Histogram broken down by one label.
+#[derive(Debug)]
+pub struct LabeledHistogram {
+    bounds: &'static [f64],
+    histograms: DashMap<&'static str, Histogram>,
+}
+
+impl LabeledHistogram {
+    /// Create a labeled histogram with the given bucket bounds.
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            histograms: DashMap::new(),
+        }
+    }
+
+    /// Record one observation for `label`.
+    pub fn observe(&self, label: &'static str, value: f64) {
+        self.histograms
+            .entry(label)
+            .or_insert_with(|| Histogram::new(self.bounds))
+            .observe(value);
+    }
+
+    /// Record a duration in seconds for `label`.
+    pub fn observe_duration(&self, label: &'static str, duration: Duration) {
+        self.observe(label, duration.as_secs_f64());
+    }
+
+    /// Start a timer that records into `label` when dropped.
+    pub fn start_timer(&self, label: &'static str) -> Timer<'_> {
+        Timer {
+            histogram: self,
+            label,
+            start: Instant::now(),
+        }
+    }
+
+    /// Number of observations for `label`.
+    pub fn count(&self, label: &str) -> u64 {
+        self.histograms.get(label).map(|h| h.count()).unwrap_or(0)
+    }
+
+    /// Append this histogram in Prometheus text format, one series set per label.
+    pub fn render(&self, out: &mut String, name: &str, help: &str, label_name: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        let mut labels: Vec<&'static str> = self.histograms.iter().map(|e| *e.key()).collect();
+        labels.sort_unstable();
+        for label in labels {
+            if let Some(histogram) = self.histograms.get(label) {
+                histogram.render_series(out, name, &format!("{label_name}=\"{label}\""));
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// Drop guard that records elapsed time into a [`LabeledHistogram`].
+///
+/// Records on every exit path, including early returns and `?`.
+pub struct Timer<'a> {
+    histogram: &'a LabeledHistogram,
+    label: &'static str,
+    start: Instant,
+}
+
+impl Drop for Timer<'_> {
+    fn drop(&mut self) {
+        self.histogram
+            .observe_duration(self.label, self.start.elapsed());
+    }
+}
+
+/// Storage gauges and query timings, owned by the storage backend.
+///
+/// The gauges are adjusted as blobs are stored and recomputed after bulk
+/// deletes (cleanup, group purge), so scrapes never scan the blobs table.
+#[derive(Debug)]
+pub struct StorageMetrics {
+    /// Blobs currently in the database.
+    pub blobs: AtomicU64,
+    /// Ciphertext bytes currently in the database.
+    pub bytes: AtomicU64,
+    /// Groups with at least one stored blob.
+    pub groups: AtomicU64,
+    /// Query latency by operation.
+    pub query_duration: LabeledHistogram,
+}
+
+impl Default for StorageMetrics {
+    fn default() -> Self {
+        Self {
+            blobs: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            groups: AtomicU64::new(0),
+            query_duration: LabeledHistogram::new(LATENCY_BUCKETS),
+        }
+    }
+}
+
+/// Metric label for a protocol message.
+pub fn message_type(message: &Message) -> &'static str {
+    match message {
+        Message::Hello(_) => "hello",
+        Message::Welcome(_) => "welcome",
+        Message::Challenge(_) => "challenge",
+        Message::ChallengeResponse(_) => "challenge_response",
+        Message::Push(_) => "push",
+        Message::PushAck(_) => "push_ack",
+        Message::Pull(_) => "pull",
+        Message::PullResponse(_) => "pull_response",
+        Message::Notify(_) => "notify",
+        Message::Bye(_) => "bye",
+        Message::ContentRef(_) => "content_ref",
+        Message::ContentAck(_) => "content_ack",
+        Message::ContentPut(_) => "content_put",
+        Message::ContentGet(_) => "content_get",
+        Message::ContentData(_) => "content_data",
+        Message::Handshake(_) => "handshake",
+        Message::MailboxPut(_) => "mailbox_put",
+        Message::MailboxAck(_) => "mailbox_ack",
+        Message::MailboxGet(_) => "mailbox_get",
+        Message::MailboxData(_) => "mailbox_data",
+        Message::OprfEvaluate(_) => "oprf_evaluate",
+        Message::OprfEvaluated(_) => "oprf_evaluated",
+        Message::InviteRegister(_) => "invite_register",
+        Message::InviteRevoke(_) => "invite_revoke",
+        Message::InviteAck(_) => "invite_ack",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let histogram = Histogram::new(&[1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(50.0);
+
+        assert_eq!(histogram.count(), 3);
+        assert_eq!(histogram.sum(), 55.5);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "test_seconds", "Test histogram");
+        assert!(out.contains("# TYPE test_seconds histogram"));
+        assert!(out.contains("test_seconds_bucket{le=\"1\"} 1"));
+        assert!(out.contains("test_seconds_bucket{le=\"10\"} 2"));
+        assert!(out.contains("test_seconds_bucket{le=\"+Inf\"} 3"));
+        assert!(out.contains("test_seconds_sum 55.5"));
+        assert!(out.contains("test_seconds_count 3"));
+    }
+
+    #[test]
+    fn labeled_metrics_render_per_label() {
+        let counter = LabeledCounter::default();
+        counter.inc("push");
+        counter.inc("push");
+        counter.inc("pull");
+        assert_eq!(counter.get("push"), 2);
+        assert_eq!(counter.get("bye"), 0);
+
+        let mut out = String::new();
+        counter.render(&mut out, "test_total", "Test counter", "type");
+        assert!(out.contains("test_total{type=\"pull\"} 1"));
+        assert!(out.contains("test_total{type=\"push\"} 2"));
+
+        let histogram = LabeledHistogram::new(LATENCY_BUCKETS);
+        histogram.observe_duration("push", Duration::from_millis(3));
+        assert_eq!(histogram.count("push"), 1);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "test_seconds", "Test", "type");
+        assert!(out.contains("test_seconds_bucket{type=\"push\",le=\"0.005\"} 1"));
+        assert!(out.contains("test_seconds_count{type=\"push\"} 1"));
+
+        drop(histogram.start_timer("pull"));
+        assert_eq!(histogram.count("pull"), 1);
+    }
+}
@@ -15,6 +15,12 @@ pub const ALPN: &[u8] = b"/0k-sync/1";
 /// Maximum message size (1MB per blob limit from spec).
 pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
 
+/// Maximum size of one mailbox message (invite exchange messages are small).
+pub const MAX_MAILBOX_PAYLOAD: usize = 64 * 1024;
+
+/// Maximum unexpired messages held per mailbox.
+pub const MAX_MAILBOX_MESSAGES: usize = 4;
+
 /// Protocol handler for accepting 0k-Sync connections.
 #[derive(Clone, Debug)]
 pub struct SyncProtocol {
@@ -35,11 +41,17 @@ impl ProtocolHandler for SyncProtocol {
     ) -> impl std::future::Future<Output = Result<(), AcceptError>> + Send {
         let relay = self.relay.clone();
         async move {
+            // Refuse new connections while draining for shutdown
+            if relay.is_draining() {
+                connection.close(3u32.into(), b"relay shutting down");
+                return Ok(());
+            }
+
             // Rate limit check: prevent connection flooding from single device
             let remote_id = connection.remote_id();
             if let Err(e) = relay.rate_limits().check_connection(remote_id.as_bytes()) {
                 tracing::warn!("Connection rate limited for {}: {}", remote_id, e);
-                relay.metrics().rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+                relay.metrics().record_rate_limit(&e);
                 connection.close(1u32.into(), b"rate limited");
                 return Ok(());
             }
@@ -2,22 +2,31 @@
 //!
 //! SyncRelay manages storage, active sessions, and coordinates message routing.
 
-use crate::config::Config;
-use crate::limits::RateLimits;
+use crate::config::{Config, ConfigError};
+use crate::limits::{RateLimitError, RateLimits};
+use crate::metrics::{Histogram, LabeledCounter, LabeledHistogram, LATENCY_BUCKETS, SIZE_BUCKETS};
 use crate::storage::SqliteStorage;
 use dashmap::DashMap;
 use iroh::endpoint::Connection;
 use std::collections::HashSet;
-use std::sync::atomic::AtomicU64;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use sync_types::{Cursor, DeviceId, GroupId, Message, Notify};
+use std::time::{Duration, Instant};
+use sync_core::NoiseKeypair;
+use sync_types::{Bye, Cursor, DeviceId, GroupId, Message, Notify, TraceContext};
 use tokio::sync::RwLock;
+use tracing::Instrument;
+
+/// BYE reason sent to connected devices when the relay drains for shutdown.
+pub const SHUTDOWN_BYE_REASON: &str = "relay shutting down, reconnect elsewhere";
 
 /// Operational metrics for monitoring relay activity.
 ///
 /// All counters are monotonically increasing (reset only on restart).
 /// Thread-safe via `AtomicU64` — no locks needed for incrementing.
-#[derive(Debug, Default)]
+/// Labeled series use fixed `&'static str` labels (message type, error kind).
+#[derive(Debug)]
 pub struct RelayMetrics {
     /// Total PUSH requests handled successfully.
     pub pushes_total: AtomicU64,
@@ -31,10 +40,58 @@ pub struct RelayMetrics {
     pub bytes_sent: AtomicU64,
     /// Total blobs stored in the database.
     pub blobs_stored: AtomicU64,
-    /// Total rate limit rejections (connection + message + global).
+    /// Total rate limit rejections across all limiters.
     pub rate_limit_hits: AtomicU64,
+    /// Rate limit rejections, by limiter (`RateLimitError::limiter`).
+    pub rate_limit_hits_by_limiter: LabeledCounter,
+    /// Total proof-of-work challenges sent in reply to HELLO.
+    pub pow_challenges_total: AtomicU64,
     /// Total protocol errors (invalid messages, auth failures, etc.).
     pub errors_total: AtomicU64,
+    /// Messages received, by message type.
+    pub messages_total: LabeledCounter,
+    /// Protocol errors, by error kind.
+    pub errors_by_kind: LabeledCounter,
+    /// Time to handle a request stream, by message type.
+    pub request_duration: LabeledHistogram,
+    /// Time until NOTIFY has been delivered to all of a group's online
+    /// devices (failed deliveries count when they fail).
+    pub notify_fanout_duration: Histogram,
+    /// Push payload sizes in bytes.
+    pub push_payload_bytes: Histogram,
+    /// Pull response payload sizes in bytes (sum over returned blobs).
+    pub pull_response_bytes: Histogram,
+}
+
+impl Default for RelayMetrics {
+    fn default() -> Self {
+        Self {
+            pushes_total: AtomicU64::new(0),
+            pulls_total: AtomicU64::new(0),
+            connections_total: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            blobs_stored: AtomicU64::new(0),
+            rate_limit_hits: AtomicU64::new(0),
+            rate_limit_hits_by_limiter: LabeledCounter::default(),
+            pow_challenges_total: AtomicU64::new(0),
+            errors_total: AtomicU64::new(0),
+            messages_total: LabeledCounter::default(),
+            errors_by_kind: LabeledCounter::default(),
+            request_duration: LabeledHistogram::new(LATENCY_BUCKETS),
+            notify_fanout_duration: Histogram::new(LATENCY_BUCKETS),
+            push_payload_bytes: Histogram::new(SIZE_BUCKETS),
+            pull_response_bytes: Histogram::new(SIZE_BUCKETS),
+        }
+    }
+}
+
+impl RelayMetrics {
+    /// Count a rate limit rejection, in total and for its limiter.
+    pub fn record_rate_limit(&self, error: &RateLimitError) {
+        self.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+        self.rate_limit_hits_by_limiter.inc(error.limiter());
+    }
 }
 
 /// Active session tracking for a group.
@@ -46,16 +103,45 @@ struct GroupSessions {
 
 /// Main relay server.
 pub struct SyncRelay {
-    config: Config,
+    /// Current configuration, swapped as a whole on reload.
+    config: std::sync::RwLock<Arc<Config>>,
+    /// File the configuration is reloaded from, if any.
+    config_path: Option<PathBuf>,
     storage: Arc<SqliteStorage>,
-    /// Rate limiters for connections and messages.
-    rate_limits: RateLimits,
+    /// Rate limiters for connections and messages, rebuilt on reload.
+    rate_limits: std::sync::RwLock<RateLimits>,
     /// Operational metrics (counters, gauges).
     metrics: RelayMetrics,
     /// Active sessions per group.
     sessions: DashMap<GroupId, Arc<RwLock<GroupSessions>>>,
     /// Active connections for NOTIFY delivery (stored separately to preserve Debug on GroupSessions).
     notify_connections: DashMap<(GroupId, DeviceId), Connection>,
+    /// Static key for the secure channel handshake (random unless loaded
+    /// with [`SyncRelay::with_noise_keypair`]).
+    noise_keypair: NoiseKeypair,
+    /// Set when shutdown begins; new connections and requests are refused.
+    draining: AtomicBool,
+    /// Requests currently being handled.
+    in_flight: AtomicUsize,
+    /// Woken when `in_flight` drops to zero.
+    idle: tokio::sync::Notify,
+}
+
+/// Marks a request as in flight until dropped.
+///
+/// Obtained from [`SyncRelay::begin_request`]; shutdown waits for all
+/// guards to drop before closing connections.
+#[derive(Debug)]
+pub struct RequestGuard {
+    relay: Arc<SyncRelay>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        if self.relay.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.relay.idle.notify_waiters();
+        }
+    }
 }
 
 impl std::fmt::Debug for SyncRelay {
@@ -65,6 +151,7 @@ impl std::fmt::Debug for SyncRelay {
             .field("rate_limits", &self.rate_limits)
             .field("metrics", &self.metrics)
             .field("sessions_count", &self.sessions.len())
+            .field("draining", &self.is_draining())
             .finish_non_exhaustive()
     }
 }
@@ -74,18 +161,81 @@ impl SyncRelay {
     pub fn new(config: Config, storage: SqliteStorage) -> Self {
         let rate_limits = RateLimits::new(&config.limits);
         Self {
-            config,
+            config: std::sync::RwLock::new(Arc::new(config)),
+            config_path: None,
             storage: Arc::new(storage),
-            rate_limits,
+            rate_limits: std::sync::RwLock::new(rate_limits),
             metrics: RelayMetrics::default(),
             sessions: DashMap::new(),
             notify_connections: DashMap::new(),
+            noise_keypair: NoiseKeypair::generate(),
+            draining: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+            idle: tokio::sync::Notify::new(),
         }
     }
 
-    /// Get the relay configuration.
-    pub fn config(&self) -> &Config {
-        &self.config
+    /// Set the file [`reload_from_file`](Self::reload_from_file) re-reads.
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Use a persisted secure channel key instead of a random one.
+    pub fn with_noise_keypair(mut self, keypair: NoiseKeypair) -> Self {
+        self.noise_keypair = keypair;
+        self
+    }
+
+    /// Get the current relay configuration.
+    ///
+    /// Returns a snapshot; a concurrent reload does not change it.
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().expect("config lock poisoned").clone()
+    }
+
+    /// Apply a new configuration to the running relay.
+    ///
+    /// The configuration is validated and compared with the current one.
+    /// If any changed setting requires a restart, nothing is applied.
+    /// Otherwise the configuration is swapped atomically and the rate
+    /// limiters are rebuilt. Returns the keys of the applied changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] if validation fails, or
+    /// [`ConfigError::RestartRequired`] listing the offending keys.
+    pub fn reload_config(&self, new: Config) -> Result<Vec<&'static str>, ConfigError> {
+        new.validate()?;
+        let mut config = self.config.write().expect("config lock poisoned");
+        let changes = config.changes(&new);
+        let restart: Vec<_> = changes
+            .iter()
+            .filter(|change| !change.reloadable)
+            .map(|change| change.key)
+            .collect();
+        if !restart.is_empty() {
+            return Err(ConfigError::RestartRequired(restart));
+        }
+
+        let mut rate_limits = self.rate_limits.write().expect("rate limits lock poisoned");
+        *rate_limits = rate_limits.reconfigure(&config.limits, &new.limits);
+        *config = Arc::new(new);
+        Ok(changes.into_iter().map(|change| change.key).collect())
+    }
+
+    /// Re-read the configuration file and apply it with
+    /// [`reload_config`](Self::reload_config).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no config file is set, it cannot be read or
+    /// parsed, or the new configuration is rejected.
+    pub fn reload_from_file(&self) -> Result<Vec<&'static str>, ConfigError> {
+        let path = self.config_path.as_deref().ok_or_else(|| {
+            ConfigError::Invalid("relay was started without a config file".into())
+        })?;
+        self.reload_config(Config::from_file(path)?)
     }
 
     /// Get access to the storage layer.
@@ -98,9 +248,15 @@ impl SyncRelay {
         self.storage.clone()
     }
 
-    /// Get access to the rate limiters.
-    pub fn rate_limits(&self) -> &RateLimits {
-        &self.rate_limits
+    /// Get the current rate limiters.
+    ///
+    /// Cheap to clone: the limiter state is shared, so checks against the
+    /// returned value count against the live limits.
+    pub fn rate_limits(&self) -> RateLimits {
+        self.rate_limits
+            .read()
+            .expect("rate limits lock poisoned")
+            .clone()
     }
 
     /// Get access to the operational metrics.
@@ -108,6 +264,11 @@ impl SyncRelay {
         &self.metrics
     }
 
+    /// Get the static key used in secure channel handshakes.
+    pub fn noise_keypair(&self) -> &NoiseKeypair {
+        &self.noise_keypair
+    }
+
     /// Register a session (device connected to a group).
     pub async fn register_session(&self, group_id: &GroupId, device_id: &DeviceId) {
         let sessions = self
@@ -192,16 +353,33 @@ impl SyncRelay {
     /// Each notification is sent via a server-opened unidirectional QUIC stream.
     /// Clients that don't yet have a NOTIFY listener will have these streams
     /// buffered by the QUIC stack (harmless for small messages).
-    pub async fn notify_group(&self, group_id: &GroupId, sender: &DeviceId, cursor: Cursor) {
+    ///
+    /// `trace` is forwarded in the NOTIFY so recipients can correlate it with
+    /// the push that caused it.
+    pub async fn notify_group(
+        self: &Arc<Self>,
+        group_id: &GroupId,
+        sender: &DeviceId,
+        cursor: Cursor,
+        trace: Option<TraceContext>,
+    ) {
         let online = self.get_online_devices(group_id, sender).await;
 
         if online.is_empty() {
             return;
         }
+        let start = Instant::now();
+        let span = tracing::info_span!(
+            "relay.notify",
+            otel.name = "notify",
+            recipients = online.len()
+        );
+        let _entered = span.enter();
 
         let notify = Message::Notify(Notify {
             latest_cursor: cursor,
             count: 1,
+            trace,
         });
 
         let bytes = match notify.to_bytes() {
@@ -212,20 +390,33 @@ impl SyncRelay {
             }
         };
 
-        let mut sent = 0;
+        let mut deliveries = tokio::task::JoinSet::new();
         for device_id in &online {
             if let Some(conn) = self.notify_connections.get(&(*group_id, *device_id)) {
                 let connection = conn.value().clone();
                 let bytes = bytes.clone();
                 let did = *device_id;
-                tokio::spawn(async move {
-                    if let Err(e) = deliver_notify(&connection, &bytes).await {
-                        tracing::debug!("Failed to notify {:?}: {}", did, e);
+                deliveries.spawn(
+                    async move {
+                        if let Err(e) = deliver_uni(&connection, &bytes).await {
+                            tracing::debug!("Failed to notify {:?}: {}", did, e);
+                        }
                     }
-                });
-                sent += 1;
+                    .instrument(span.clone()),
+                );
             }
         }
+        let sent = deliveries.len();
+
+        // Time the fan-out until every delivery has finished, off the push path
+        let relay = self.clone();
+        tokio::spawn(async move {
+            while deliveries.join_next().await.is_some() {}
+            relay
+                .metrics
+                .notify_fanout_duration
+                .observe_duration(start.elapsed());
+        });
 
         tracing::debug!(
             "Sent NOTIFY to {}/{} devices in {:?} about cursor {}",
@@ -255,13 +446,95 @@ impl SyncRelay {
     pub fn total_groups(&self) -> usize {
         self.sessions.len()
     }
+
+    /// Get the IDs of groups with active sessions.
+    pub fn active_groups(&self) -> Vec<GroupId> {
+        self.sessions.iter().map(|entry| *entry.key()).collect()
+    }
+
+    /// Whether the relay is draining for shutdown.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Mark a request as in flight, or `None` if the relay is draining.
+    ///
+    /// The count is taken before the drain flag is checked, so a request
+    /// admitted here is always seen by [`wait_idle`](Self::wait_idle).
+    pub fn begin_request(self: &Arc<Self>) -> Option<RequestGuard> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = RequestGuard {
+            relay: self.clone(),
+        };
+        if self.is_draining() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Number of requests currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Start draining for shutdown.
+    ///
+    /// New connections and requests are refused from now on, and every
+    /// connected device is sent a BYE with [`SHUTDOWN_BYE_REASON`] so it can
+    /// reconnect to another relay. Returns the number of devices told.
+    pub fn begin_drain(&self) -> usize {
+        self.draining.store(true, Ordering::SeqCst);
+
+        let bye = Message::Bye(Bye {
+            reason: Some(SHUTDOWN_BYE_REASON.to_string()),
+        });
+        let bytes = match bye.to_bytes() {
+            Ok(b) => b,
+            Err(e) => {
+                tracing::error!("Failed to serialize BYE: {}", e);
+                return 0;
+            }
+        };
+
+        let mut sent = 0;
+        for entry in self.notify_connections.iter() {
+            let connection = entry.value().clone();
+            let bytes = bytes.clone();
+            let (_, device_id) = *entry.key();
+            tokio::spawn(async move {
+                if let Err(e) = deliver_uni(&connection, &bytes).await {
+                    tracing::debug!("Failed to send BYE to {:?}: {}", device_id, e);
+                }
+            });
+            sent += 1;
+        }
+        sent
+    }
+
+    /// Wait for in-flight requests to finish, up to `timeout`.
+    ///
+    /// Returns `false` if requests were still running at the deadline.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let idle = async {
+            loop {
+                // Register before checking so a wakeup in between isn't lost
+                let notified = self.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(timeout, idle).await.is_ok()
+    }
 }
 
-/// Deliver a NOTIFY message via a unidirectional QUIC stream.
+/// Deliver a server-initiated message (NOTIFY, BYE) via a unidirectional
+/// QUIC stream.
 ///
 /// Opens a new uni stream on the connection, writes the length-prefixed
-/// NOTIFY message, and finishes the stream. Fire-and-forget.
-async fn deliver_notify(connection: &Connection, message_bytes: &[u8]) -> Result<(), String> {
+/// message, and finishes the stream. Fire-and-forget.
+async fn deliver_uni(connection: &Connection, message_bytes: &[u8]) -> Result<(), String> {
     let mut send = connection
         .open_uni()
         .await
@@ -282,6 +555,40 @@ async fn deliver_notify(connection: &Connection, message_bytes: &[u8]) -> Result
     Ok(())
 }
 
+/// Load the secure channel static key from `path`, creating it if missing.
+///
+/// Clients pin the public key from their invites, so it must survive
+/// restarts. The file holds the raw 32-byte private key (mode 0600 on Unix).
+pub fn load_or_create_noise_keypair(path: &Path) -> std::io::Result<NoiseKeypair> {
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            let private: [u8; 32] = bytes.try_into().map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} is not a 32-byte key", path.display()),
+                )
+            })?;
+            Ok(NoiseKeypair::from_private_bytes(private))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let keypair = NoiseKeypair::generate();
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            std::io::Write::write_all(&mut options.open(path)?, &keypair.private_bytes()[..])?;
+            Ok(keypair)
+        }
+        Err(e) => Err(e),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -367,29 +674,60 @@ mod tests {
         assert!(!relay.notify_connections.contains_key(&(group, device)));
     }
 
+    #[tokio::test]
+    async fn drain_refuses_new_requests_and_waits_for_in_flight() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let relay = Arc::new(SyncRelay::new(test_config(), storage));
+
+        let in_flight = relay.begin_request().expect("admitted before drain");
+        assert_eq!(relay.in_flight(), 1);
+
+        assert_eq!(relay.begin_drain(), 0);
+        assert!(relay.is_draining());
+        assert!(relay.begin_request().is_none());
+        assert_eq!(relay.in_flight(), 1);
+
+        // Still busy: the deadline passes
+        assert!(!relay.wait_idle(Duration::from_millis(20)).await);
+
+        // Finishing the in-flight request wakes the waiter
+        let waiter = {
+            let relay = relay.clone();
+            tokio::spawn(async move { relay.wait_idle(Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(in_flight);
+        assert!(waiter.await.unwrap());
+        assert_eq!(relay.in_flight(), 0);
+    }
+
     #[tokio::test]
     async fn notify_group_skips_when_no_devices() {
         let storage = SqliteStorage::in_memory().await.unwrap();
-        let relay = SyncRelay::new(test_config(), storage);
+        let relay = Arc::new(SyncRelay::new(test_config(), storage));
 
         let group = GroupId::random();
         let sender = DeviceId::random();
 
         // Should not panic or error when no devices online
-        relay.notify_group(&group, &sender, Cursor::new(1)).await;
+        relay
+            .notify_group(&group, &sender, Cursor::new(1), None)
+            .await;
     }
 
     #[tokio::test]
     async fn notify_group_excludes_sender() {
         let storage = SqliteStorage::in_memory().await.unwrap();
-        let relay = SyncRelay::new(test_config(), storage);
+        let relay = Arc::new(SyncRelay::new(test_config(), storage));
 
         let group = GroupId::random();
         let sender = DeviceId::random();
 
         // Only the sender is online — should skip notification
         relay.register_session(&group, &sender).await;
-        relay.notify_group(&group, &sender, Cursor::new(1)).await;
+        relay
+            .notify_group(&group, &sender, Cursor::new(1), None)
+            .await;
 
         // No crash, no notification attempted (no connections stored)
     }
@@ -399,6 +737,7 @@ mod tests {
         let notify = Message::Notify(Notify {
             latest_cursor: Cursor::new(42),
             count: 3,
+            trace: None,
         });
 
         let bytes = notify.to_bytes().unwrap();
@@ -425,4 +764,18 @@ mod tests {
 
         assert_eq!(relay.total_sessions(), 3);
     }
+
+    #[test]
+    fn noise_keypair_persists_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys").join("secret.noise");
+
+        let created = load_or_create_noise_keypair(&path).unwrap();
+        let loaded = load_or_create_noise_keypair(&path).unwrap();
+        assert_eq!(loaded.public_key(), created.public_key());
+
+        std::fs::write(&path, [1u8; 16]).unwrap();
+        let err = load_or_create_noise_keypair(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
 }
@@ -1,16 +1,35 @@
 //! Per-connection session management.
 //!
 //! Each connection gets a Session that tracks state and handles messages.
+//!
+//! Clients may open with the hybrid secure channel handshake (see
+//! `sync_core::channel`) before HELLO. Once established, every request and
+//! response on the connection is sealed by the channel. NOTIFY hints, sent
+//! on separate unidirectional streams, carry only a cursor and stay unsealed.
+//!
+//! Mailbox messages (invite exchange) and OPRF evaluations (passphrase
+//! hardening) are accepted before HELLO: the client has no group to say
+//! HELLO to yet. When the relay asks for proof of work, the first of these
+//! requests (or HELLO) on a connection is challenged like HELLO is.
 
 use crate::error::{ProtocolError, ProtocolResult, RelayError, StorageError};
-use crate::protocol::MAX_MESSAGE_SIZE;
+use crate::metrics;
+use crate::protocol::{MAX_MAILBOX_MESSAGES, MAX_MAILBOX_PAYLOAD, MAX_MESSAGE_SIZE};
 use crate::server::SyncRelay;
-use crate::storage::{BlobStorage, StoreBlobRequest, StoredBlob};
+use crate::storage::{BlobStorage, GroupPolicy, InviteRedemption, StoreBlobRequest, StoredBlob};
+use crate::telemetry;
 use iroh::endpoint::Connection;
+use std::num::NonZeroU32;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use sync_types::{Cursor, DeviceId, GroupId, Message, PullBlob, PullResponse, PushAck, Welcome};
+use sync_core::{pow_nonce, verify_pow, HandshakeResponder, OprfServerKey, SecureChannel};
+use sync_types::{
+    Challenge, ChallengeResponse, ContentAck, ContentData, Cursor, DeviceId, GroupId, Handshake,
+    InviteAck, MailboxAck, MailboxData, Message, OprfEvaluated, PullBlob, PullResponse, PushAck,
+    Welcome,
+};
+use tracing::Instrument;
 
 /// Session state machine states.
 #[derive(Debug, Clone)]
@@ -37,6 +56,14 @@ pub struct Session {
     relay: Arc<SyncRelay>,
     connection: Connection,
     state: SessionState,
+    /// Secure channel handshake awaiting the client's final message.
+    handshake: Option<HandshakeResponder>,
+    /// Established secure channel; once set, every frame is sealed.
+    channel: Option<SecureChannel>,
+    /// Outstanding proof-of-work challenge and the request it interrupted.
+    challenge: Option<(Challenge, Message)>,
+    /// Whether this connection has already solved a challenge.
+    pow_solved: bool,
 }
 
 impl Session {
@@ -46,6 +73,10 @@ impl Session {
             relay,
             connection,
             state: SessionState::AwaitingHello,
+            handshake: None,
+            channel: None,
+            challenge: None,
+            pow_solved: false,
         }
     }
 
@@ -91,6 +122,9 @@ impl Session {
             // Handle the stream
             if let Err(e) = self.handle_stream(send, recv).await {
                 tracing::warn!("Stream error: {}", e);
+                let metrics = self.relay.metrics();
+                metrics.errors_total.fetch_add(1, Ordering::Relaxed);
+                metrics.errors_by_kind.inc(e.kind());
                 // Continue accepting new streams unless connection is broken
                 if matches!(self.state, SessionState::Closing) {
                     break;
@@ -118,24 +152,64 @@ impl Session {
         mut recv: iroh::endpoint::RecvStream,
     ) -> ProtocolResult<()> {
         // Read message with length prefix
-        let message = self.read_message(&mut recv).await?;
+        let mut message = self.read_message(&mut recv).await?;
+
+        // Refuse new requests while draining for shutdown (BYE still allowed);
+        // admitted requests hold the guard until their response is written
+        let _request = match self.relay.begin_request() {
+            Some(guard) => Some(guard),
+            None if matches!(message, Message::Bye(_)) => None,
+            None => return Err(ProtocolError::ShuttingDown),
+        };
+
+        // Secure channel handshake (before HELLO): the first message gets a
+        // plaintext reply; the final one establishes the channel and carries
+        // the HELLO, which is then handled like any other.
+        if let (SessionState::AwaitingHello, None, Message::Handshake(handshake)) =
+            (&self.state, &self.channel, &message)
+        {
+            let payload = handshake.payload.clone();
+            match self.handshake.take() {
+                None => {
+                    let reply = self.start_handshake(&payload)?;
+                    self.write_message(&mut send, &reply).await?;
+                    send.finish()
+                        .map_err(|e| ProtocolError::Stream(e.to_string()))?;
+                    return Ok(());
+                }
+                Some(responder) => message = self.finish_handshake(responder, &payload)?,
+            }
+        }
 
-        // Rate limit check for Active state operations (PUSH, PULL)
+        let message_type = metrics::message_type(&message);
+        self.relay.metrics().messages_total.inc(message_type);
+        let relay = self.relay.clone();
+        let _timer = relay.metrics().request_duration.start_timer(message_type);
+
+        // Rate limit check for Active state operations (PUSH, PULL, CONTENT)
         // HELLO is not rate limited here (connection rate limit handles that)
         // BYE is not rate limited (we always allow graceful disconnect)
         if let SessionState::Active { device_id, .. } = &self.state {
-            if matches!(message, Message::Push(_) | Message::Pull(_)) {
+            if matches!(
+                message,
+                Message::Push(_)
+                    | Message::Pull(_)
+                    | Message::ContentPut(_)
+                    | Message::ContentGet(_)
+                    | Message::InviteRegister(_)
+                    | Message::InviteRevoke(_)
+            ) {
                 // SR-001: Global rate limit check (aggregate across all clients)
                 if let Err(e) = self.relay.rate_limits().check_global() {
                     tracing::warn!("Global rate limit exceeded: {}", e);
-                    self.relay.metrics().rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+                    self.relay.metrics().record_rate_limit(&e);
                     return Err(ProtocolError::RateLimited {
                         reason: e.to_string(),
                     });
                 }
                 if let Err(e) = self.relay.rate_limits().check_message(device_id.as_bytes()) {
                     tracing::warn!("Message rate limited for device {:?}: {}", device_id, e);
-                    self.relay.metrics().rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+                    self.relay.metrics().record_rate_limit(&e);
                     return Err(ProtocolError::RateLimited {
                         reason: e.to_string(),
                     });
@@ -143,16 +217,83 @@ impl Session {
             }
         }
 
+        // Mailbox and OPRF operations may precede HELLO, so they are limited
+        // per connection rather than per device
+        if is_pre_hello_request(&message) {
+            if let Err(e) = self.relay.rate_limits().check_global() {
+                tracing::warn!("Global rate limit exceeded: {}", e);
+                self.relay.metrics().record_rate_limit(&e);
+                return Err(ProtocolError::RateLimited {
+                    reason: e.to_string(),
+                });
+            }
+            let remote_id = self.connection.remote_id();
+            if let Err(e) = self.relay.rate_limits().check_message(remote_id.as_bytes()) {
+                tracing::warn!("Pre-HELLO request rate limited for {}: {}", remote_id, e);
+                self.relay.metrics().record_rate_limit(&e);
+                return Err(ProtocolError::RateLimited {
+                    reason: e.to_string(),
+                });
+            }
+        }
+
+        // Refuse anything this state does not handle (see `accepts`)
+        if !accepts(&self.state, &message) {
+            return match self.state {
+                SessionState::AwaitingHello => Err(ProtocolError::NotAuthenticated),
+                SessionState::Active { .. } => Err(ProtocolError::UnexpectedMessage {
+                    expected: expected_message_types(&self.state),
+                    actual: format!("{:?}", std::mem::discriminant(&message)),
+                }),
+                SessionState::Closing => Ok(()),
+            };
+        }
+
         // Handle message based on state
         let response = match (&self.state, &message) {
             (SessionState::AwaitingHello, Message::Hello(hello)) => {
                 self.handle_hello(hello.clone()).await?
             }
+            (SessionState::AwaitingHello, Message::ChallengeResponse(response)) => {
+                self.handle_challenge_response(response.clone()).await?
+            }
             (SessionState::Active { .. }, Message::Push(push)) => {
-                self.handle_push(push.clone()).await?
+                self.handle_push(push.clone())
+                    .instrument(telemetry::request_span("push", push.trace.as_ref()))
+                    .await?
             }
             (SessionState::Active { .. }, Message::Pull(pull)) => {
-                self.handle_pull(pull.clone()).await?
+                self.handle_pull(pull.clone())
+                    .instrument(telemetry::request_span("pull", pull.trace.as_ref()))
+                    .await?
+            }
+            (SessionState::Active { .. }, Message::ContentPut(put)) => {
+                self.handle_content_put(put.clone()).await?
+            }
+            (SessionState::Active { .. }, Message::ContentGet(get)) => {
+                self.handle_content_get(get.clone()).await?
+            }
+            // Pre-HELLO requests pay the same proof of work as HELLO
+            (SessionState::AwaitingHello, _) if is_pre_hello_request(&message) => {
+                match self.pow_challenge(&message) {
+                    Some(challenge) => challenge,
+                    None => self.dispatch_pre_hello(message.clone()).await?,
+                }
+            }
+            (SessionState::Active { .. }, Message::MailboxPut(put)) => {
+                self.handle_mailbox_put(put.clone()).await?
+            }
+            (SessionState::Active { .. }, Message::MailboxGet(get)) => {
+                self.handle_mailbox_get(get.clone()).await?
+            }
+            (SessionState::Active { .. }, Message::OprfEvaluate(evaluate)) => {
+                self.handle_oprf_evaluate(evaluate.clone()).await?
+            }
+            (SessionState::Active { .. }, Message::InviteRegister(register)) => {
+                self.handle_invite_register(register.clone()).await?
+            }
+            (SessionState::Active { .. }, Message::InviteRevoke(revoke)) => {
+                self.handle_invite_revoke(revoke.clone()).await?
             }
             (SessionState::Active { .. }, Message::Bye(bye)) => {
                 self.handle_bye(bye.clone()).await?;
@@ -167,7 +308,7 @@ impl Session {
             }
             (_, msg) => {
                 return Err(ProtocolError::UnexpectedMessage {
-                    expected: self.expected_message_types(),
+                    expected: expected_message_types(&self.state),
                     actual: format!("{:?}", std::mem::discriminant(msg)),
                 });
             }
@@ -184,7 +325,12 @@ impl Session {
     }
 
     /// Read a length-prefixed message from the stream.
-    async fn read_message(&self, recv: &mut iroh::endpoint::RecvStream) -> ProtocolResult<Message> {
+    ///
+    /// Opens the frame first if the secure channel is established.
+    async fn read_message(
+        &mut self,
+        recv: &mut iroh::endpoint::RecvStream,
+    ) -> ProtocolResult<Message> {
         // Read 4-byte length prefix (big-endian)
         let mut len_buf = [0u8; 4];
         recv.read_exact(&mut len_buf)
@@ -205,6 +351,12 @@ impl Session {
             .await
             .map_err(|e| ProtocolError::Stream(e.to_string()))?;
 
+        if let Some(channel) = self.channel.as_mut() {
+            buf = channel
+                .open(&buf)
+                .map_err(|e| ProtocolError::SecureChannel(e.to_string()))?;
+        }
+
         // Deserialize
         Message::from_bytes(&buf).map_err(|e| ProtocolError::InvalidMessage {
             reason: e.to_string(),
@@ -212,17 +364,25 @@ impl Session {
     }
 
     /// Write a length-prefixed message to the stream.
+    ///
+    /// Seals the frame if the secure channel is established.
     async fn write_message(
-        &self,
+        &mut self,
         send: &mut iroh::endpoint::SendStream,
         message: &Message,
     ) -> ProtocolResult<()> {
-        let bytes = message
+        let mut bytes = message
             .to_bytes()
             .map_err(|e| ProtocolError::InvalidMessage {
                 reason: e.to_string(),
             })?;
 
+        if let Some(channel) = self.channel.as_mut() {
+            bytes = channel
+                .seal(&bytes)
+                .map_err(|e| ProtocolError::SecureChannel(e.to_string()))?;
+        }
+
         // Write 4-byte length prefix
         let len = bytes.len() as u32;
         send.write_all(&len.to_be_bytes())
@@ -237,8 +397,37 @@ impl Session {
         Ok(())
     }
 
+    /// Process the client's first handshake message and build the reply.
+    fn start_handshake(&mut self, payload: &[u8]) -> ProtocolResult<Message> {
+        let (responder, reply) = HandshakeResponder::respond(self.relay.noise_keypair(), payload)
+            .map_err(|e| ProtocolError::SecureChannel(e.to_string()))?;
+        self.handshake = Some(responder);
+        Ok(Message::Handshake(Handshake { payload: reply }))
+    }
+
+    /// Complete the handshake and return the message it carried (the HELLO).
+    fn finish_handshake(
+        &mut self,
+        responder: HandshakeResponder,
+        payload: &[u8],
+    ) -> ProtocolResult<Message> {
+        let (channel, inner) = responder
+            .finish(payload)
+            .map_err(|e| ProtocolError::SecureChannel(e.to_string()))?;
+        self.channel = Some(channel);
+        tracing::debug!("Secure channel established");
+
+        Message::from_bytes(&inner).map_err(|e| ProtocolError::InvalidMessage {
+            reason: e.to_string(),
+        })
+    }
+
     /// Handle HELLO message.
     async fn handle_hello(&mut self, hello: sync_types::Hello) -> ProtocolResult<Message> {
+        if self.channel.is_none() && self.relay.config().server.require_secure_channel {
+            return Err(ProtocolError::SecureChannelRequired);
+        }
+
         // Validate protocol version
         if hello.version != 1 {
             return Err(ProtocolError::VersionMismatch {
@@ -247,6 +436,80 @@ impl Session {
             });
         }
 
+        if let Some(challenge) = self.pow_challenge(&Message::Hello(hello.clone())) {
+            return Ok(challenge);
+        }
+
+        self.establish_session(hello).await
+    }
+
+    /// Challenge `request` if this connection still owes proof of work.
+    ///
+    /// Endpoint IDs are free to mint, so under load a new connection pays in
+    /// CPU before it touches storage. The challenge is paid once per
+    /// connection; `request` is held until it is answered.
+    fn pow_challenge(&mut self, request: &Message) -> Option<Message> {
+        if self.pow_solved {
+            return None;
+        }
+        let difficulty = self
+            .relay
+            .config()
+            .limits
+            .pow_difficulty(self.relay.total_sessions());
+        if difficulty == 0 {
+            return None;
+        }
+        let challenge = Challenge {
+            nonce: pow_nonce(),
+            difficulty,
+        };
+        self.challenge = Some((challenge.clone(), request.clone()));
+        self.relay
+            .metrics()
+            .pow_challenges_total
+            .fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("Sent {}-bit proof-of-work challenge", difficulty);
+        Some(Message::Challenge(challenge))
+    }
+
+    /// Handle the answer to a proof-of-work challenge and complete the
+    /// request it interrupted. Each challenge is answered at most once.
+    async fn handle_challenge_response(
+        &mut self,
+        response: ChallengeResponse,
+    ) -> ProtocolResult<Message> {
+        let Some((challenge, request)) = self.challenge.take() else {
+            return Err(ProtocolError::ProofOfWorkInvalid);
+        };
+        if response.nonce != challenge.nonce
+            || !verify_pow(&challenge.nonce, challenge.difficulty, response.counter)
+        {
+            tracing::warn!("Proof of work failed for {}", self.connection.remote_id());
+            return Err(ProtocolError::ProofOfWorkInvalid);
+        }
+        self.pow_solved = true;
+        match request {
+            Message::Hello(hello) => self.establish_session(hello).await,
+            request => self.dispatch_pre_hello(request).await,
+        }
+    }
+
+    /// Handle a mailbox or OPRF request made before HELLO.
+    async fn dispatch_pre_hello(&self, request: Message) -> ProtocolResult<Message> {
+        match request {
+            Message::MailboxPut(put) => self.handle_mailbox_put(put).await,
+            Message::MailboxGet(get) => self.handle_mailbox_get(get).await,
+            Message::OprfEvaluate(evaluate) => self.handle_oprf_evaluate(evaluate).await,
+            other => Err(ProtocolError::UnexpectedMessage {
+                expected: "MailboxPut, MailboxGet, OprfEvaluate".to_string(),
+                actual: format!("{:?}", std::mem::discriminant(&other)),
+            }),
+        }
+    }
+
+    /// Admit the device named by HELLO and activate the session.
+    async fn establish_session(&mut self, hello: sync_types::Hello) -> ProtocolResult<Message> {
         // Get device ID from connection
         let remote_id = self.connection.remote_id();
 
@@ -258,6 +521,8 @@ impl Session {
             }
         })?;
 
+        self.admit_device(&hello, &device_id).await?;
+
         // Get pending count and max cursor
         let pending_count = self
             .relay
@@ -307,6 +572,62 @@ impl Session {
         }))
     }
 
+    /// Check that a device may join the group in its HELLO.
+    ///
+    /// Groups without tracked invites are open. Once a group has one, only
+    /// known members and devices redeeming a valid invite are admitted;
+    /// each redemption uses up one of the invite's uses.
+    async fn admit_device(
+        &self,
+        hello: &sync_types::Hello,
+        device_id: &DeviceId,
+    ) -> ProtocolResult<()> {
+        let storage = self.relay.storage();
+        let internal = |e: StorageError| ProtocolError::Internal(e.to_string());
+
+        if storage
+            .is_group_member(&hello.group_id, device_id)
+            .await
+            .map_err(internal)?
+        {
+            return Ok(());
+        }
+
+        if storage
+            .is_group_invite_only(&hello.group_id)
+            .await
+            .map_err(internal)?
+        {
+            let Some(invite_id) = hello.invite_id else {
+                return Err(ProtocolError::InviteRejected {
+                    reason: "group requires an invite".to_string(),
+                });
+            };
+            let reason = match storage
+                .redeem_invite(&hello.group_id, &invite_id)
+                .await
+                .map_err(internal)?
+            {
+                InviteRedemption::Accepted => None,
+                InviteRedemption::Unknown => Some("unknown invite"),
+                InviteRedemption::Revoked => Some("invite revoked"),
+                InviteRedemption::Expired => Some("invite expired"),
+                InviteRedemption::Exhausted => Some("invite already used"),
+            };
+            if let Some(reason) = reason {
+                tracing::warn!("Rejected join for device {:?}: {}", device_id, reason);
+                return Err(ProtocolError::InviteRejected {
+                    reason: reason.to_string(),
+                });
+            }
+        }
+
+        storage
+            .add_group_member(&hello.group_id, device_id)
+            .await
+            .map_err(internal)
+    }
+
     /// Handle PUSH message.
     async fn handle_push(&self, push: sync_types::Push) -> ProtocolResult<Message> {
         let (group_id, device_id) = self.get_active_state()?;
@@ -322,8 +643,13 @@ impl Session {
             });
         }
 
+        // Group policy overrides, falling back to the global config
+        let policy = self.group_policy(&group_id).await?;
+        self.check_group_rate(&group_id, &policy)?;
+        let config = self.relay.config();
+
         // Validate payload size against configured blob limit
-        let max_blob_size = self.relay.config().storage.max_blob_size;
+        let max_blob_size = policy.max_blob_size(&config.storage);
         if push.payload.len() > max_blob_size {
             return Err(ProtocolError::BlobTooLarge {
                 size: push.payload.len(),
@@ -331,8 +657,10 @@ impl Session {
             });
         }
 
+        self.check_bandwidth(&group_id, &device_id, push.payload.len())?;
+
         // Check group storage quota
-        let max_group_storage = self.relay.config().storage.max_group_storage;
+        let max_group_storage = policy.max_storage_bytes(&config.storage);
         let current_storage = self
             .relay
             .storage()
@@ -348,12 +676,8 @@ impl Session {
             });
         }
 
-        // Store the blob
-        let ttl = if push.ttl == 0 {
-            self.relay.config().storage.default_ttl
-        } else {
-            push.ttl as u64
-        };
+        // Store the blob, with the TTL clamped to the group's bounds
+        let ttl = policy.blob_ttl(push.ttl, &config.storage);
 
         let payload_len = push.payload.len() as u64;
         let blob_id = push.blob_id;
@@ -384,9 +708,12 @@ impl Session {
         self.relay.metrics().pushes_total.fetch_add(1, Ordering::Relaxed);
         self.relay.metrics().bytes_received.fetch_add(payload_len, Ordering::Relaxed);
         self.relay.metrics().blobs_stored.fetch_add(1, Ordering::Relaxed);
+        self.relay.metrics().push_payload_bytes.observe(payload_len as f64);
 
         // Notify other online devices (fire and forget)
-        self.relay.notify_group(&group_id, &device_id, cursor).await;
+        self.relay
+            .notify_group(&group_id, &device_id, cursor, push.trace)
+            .await;
 
         Ok(Message::PushAck(PushAck {
             blob_id,
@@ -398,6 +725,9 @@ impl Session {
     async fn handle_pull(&self, pull: sync_types::Pull) -> ProtocolResult<Message> {
         let (group_id, device_id) = self.get_active_state()?;
 
+        let policy = self.group_policy(&group_id).await?;
+        self.check_group_rate(&group_id, &policy)?;
+
         // F-013: Clamp pull limit to configured maximum
         let max_pull = self.relay.config().limits.max_pull_limit;
         let limit = clamp_pull_limit(pull.limit, max_pull);
@@ -409,6 +739,9 @@ impl Session {
             .await
             .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
 
+        let response_bytes: usize = blobs.iter().map(|b| b.payload.len()).sum();
+        self.check_bandwidth(&group_id, &device_id, response_bytes)?;
+
         // Mark blobs as delivered (batched)
         let blob_ids: Vec<_> = blobs.iter().map(|b| b.blob_id).collect();
         let _ = self
@@ -443,6 +776,10 @@ impl Session {
         // Update operational metrics
         self.relay.metrics().pulls_total.fetch_add(1, Ordering::Relaxed);
         self.relay.metrics().bytes_sent.fetch_add(total_bytes_sent, Ordering::Relaxed);
+        self.relay
+            .metrics()
+            .pull_response_bytes
+            .observe(total_bytes_sent as f64);
 
         Ok(Message::PullResponse(PullResponse {
             blobs: pull_blobs,
@@ -451,6 +788,257 @@ impl Session {
         }))
     }
 
+    /// Handle CONTENT_PUT message.
+    ///
+    /// Holds a ciphertext content blob so a `ContentRef` can still be
+    /// resolved after the device that added it goes offline.
+    async fn handle_content_put(&self, put: sync_types::ContentPut) -> ProtocolResult<Message> {
+        let (group_id, device_id) = self.get_active_state()?;
+        let storage_config = &self.relay.config().storage;
+
+        if !storage_config.content_enabled {
+            return Err(ProtocolError::ContentHostingDisabled);
+        }
+
+        if put.data.len() > storage_config.max_blob_size {
+            return Err(ProtocolError::BlobTooLarge {
+                size: put.data.len(),
+                limit: storage_config.max_blob_size,
+            });
+        }
+
+        // Content is addressed by hash: never store data under a hash it doesn't match
+        if !content_hash_matches(&put.content_hash, &put.data) {
+            return Err(ProtocolError::InvalidMessage {
+                reason: "content hash does not match data".to_string(),
+            });
+        }
+
+        self.check_bandwidth(&group_id, &device_id, put.data.len())?;
+
+        // Check group content quota (separate from the blob quota)
+        let max_content_storage = storage_config.max_group_content_storage;
+        let current_storage = self
+            .relay
+            .storage()
+            .get_group_content_storage(&group_id)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        if current_storage as usize + put.data.len() > max_content_storage {
+            return Err(ProtocolError::QuotaExceeded {
+                current: current_storage,
+                requested: put.data.len(),
+                limit: max_content_storage,
+            });
+        }
+
+        let ttl = capped_ttl(put.ttl, storage_config.content_ttl);
+
+        let data_len = put.data.len() as u64;
+        let stored = self
+            .relay
+            .storage()
+            .store_content(&group_id, &put.content_hash, &put.data, ttl)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        tracing::debug!(
+            "Content put for group {:?} ({} bytes, new: {})",
+            group_id,
+            data_len,
+            stored
+        );
+
+        self.relay
+            .metrics()
+            .bytes_received
+            .fetch_add(data_len, Ordering::Relaxed);
+
+        Ok(Message::ContentAck(ContentAck {
+            content_hash: put.content_hash,
+        }))
+    }
+
+    /// Handle CONTENT_GET message.
+    async fn handle_content_get(&self, get: sync_types::ContentGet) -> ProtocolResult<Message> {
+        let (group_id, device_id) = self.get_active_state()?;
+
+        if !self.relay.config().storage.content_enabled {
+            return Err(ProtocolError::ContentHostingDisabled);
+        }
+
+        let data = self
+            .relay
+            .storage()
+            .get_content(&group_id, &get.content_hash)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        if let Some(data) = &data {
+            self.check_bandwidth(&group_id, &device_id, data.len())?;
+            self.relay
+                .metrics()
+                .bytes_sent
+                .fetch_add(data.len() as u64, Ordering::Relaxed);
+        }
+
+        Ok(Message::ContentData(ContentData {
+            content_hash: get.content_hash,
+            data,
+        }))
+    }
+
+    /// Handle MAILBOX_PUT message.
+    ///
+    /// Mailboxes are not tied to a group: the address is the only
+    /// capability, and messages expire within `mailbox_ttl`.
+    async fn handle_mailbox_put(&self, put: sync_types::MailboxPut) -> ProtocolResult<Message> {
+        if put.payload.len() > MAX_MAILBOX_PAYLOAD {
+            return Err(ProtocolError::BlobTooLarge {
+                size: put.payload.len(),
+                limit: MAX_MAILBOX_PAYLOAD,
+            });
+        }
+
+        let ttl = capped_ttl(put.ttl, self.relay.config().storage.mailbox_ttl);
+        let stored = self
+            .relay
+            .storage()
+            .put_mailbox(&put.mailbox, &put.payload, ttl, MAX_MAILBOX_MESSAGES)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        if !stored {
+            return Err(ProtocolError::MailboxFull {
+                limit: MAX_MAILBOX_MESSAGES,
+            });
+        }
+
+        tracing::debug!("Mailbox put ({} bytes, ttl {}s)", put.payload.len(), ttl);
+
+        Ok(Message::MailboxAck(MailboxAck {
+            mailbox: put.mailbox,
+        }))
+    }
+
+    /// Handle MAILBOX_GET message (removes the returned message).
+    async fn handle_mailbox_get(&self, get: sync_types::MailboxGet) -> ProtocolResult<Message> {
+        let payload = self
+            .relay
+            .storage()
+            .take_mailbox(&get.mailbox)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        Ok(Message::MailboxData(MailboxData {
+            mailbox: get.mailbox,
+            payload,
+        }))
+    }
+
+    /// Handle OPRF_EVALUATE message.
+    ///
+    /// Each evaluation is one passphrase guess for the group behind
+    /// `key_id`, so evaluations are rate-limited per key across all
+    /// connections. Keys are created on first use.
+    async fn handle_oprf_evaluate(
+        &self,
+        evaluate: sync_types::OprfEvaluate,
+    ) -> ProtocolResult<Message> {
+        if let Err(e) = self.relay.rate_limits().check_oprf(&evaluate.key_id) {
+            tracing::warn!("OPRF evaluations rate limited: {}", e);
+            self.relay.metrics().record_rate_limit(&e);
+            return Err(ProtocolError::RateLimited {
+                reason: e.to_string(),
+            });
+        }
+
+        let candidate = OprfServerKey::generate().to_bytes();
+        let stored = self
+            .relay
+            .storage()
+            .get_or_create_oprf_key(&evaluate.key_id, &candidate)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+        let key = OprfServerKey::from_bytes(&stored)
+            .map_err(|e| ProtocolError::Internal(e.to_string()))?;
+
+        let evaluated =
+            key.evaluate(&evaluate.blinded)
+                .map_err(|e| ProtocolError::InvalidMessage {
+                    reason: e.to_string(),
+                })?;
+
+        Ok(Message::OprfEvaluated(OprfEvaluated {
+            key_id: evaluate.key_id,
+            evaluated,
+        }))
+    }
+
+    /// Handle INVITE_REGISTER message.
+    async fn handle_invite_register(
+        &self,
+        register: sync_types::InviteRegister,
+    ) -> ProtocolResult<Message> {
+        let (group_id, _) = self.get_active_state()?;
+
+        let registered = self
+            .relay
+            .storage()
+            .register_invite(
+                &group_id,
+                &register.invite_id,
+                register.max_uses,
+                register.expires_at as i64,
+            )
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        if !registered {
+            return Err(ProtocolError::InvalidMessage {
+                reason: "invite ID already registered".to_string(),
+            });
+        }
+
+        tracing::info!(
+            "Invite registered for group {:?} ({} uses)",
+            group_id,
+            register.max_uses
+        );
+
+        Ok(Message::InviteAck(InviteAck {
+            invite_id: register.invite_id,
+        }))
+    }
+
+    /// Handle INVITE_REVOKE message.
+    async fn handle_invite_revoke(
+        &self,
+        revoke: sync_types::InviteRevoke,
+    ) -> ProtocolResult<Message> {
+        let (group_id, _) = self.get_active_state()?;
+
+        let revoked = self
+            .relay
+            .storage()
+            .revoke_invite(&group_id, &revoke.invite_id)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))?;
+
+        if !revoked {
+            return Err(ProtocolError::InviteRejected {
+                reason: "unknown invite".to_string(),
+            });
+        }
+
+        tracing::info!("Invite revoked for group {:?}", group_id);
+
+        Ok(Message::InviteAck(InviteAck {
+            invite_id: revoke.invite_id,
+        }))
+    }
+
     /// Handle BYE message.
     async fn handle_bye(&self, bye: sync_types::Bye) -> ProtocolResult<()> {
         tracing::info!(
@@ -460,6 +1048,54 @@ impl Session {
         Ok(())
     }
 
+    /// Load the operator's policy overrides for a group.
+    async fn group_policy(&self, group_id: &GroupId) -> ProtocolResult<GroupPolicy> {
+        self.relay
+            .storage()
+            .get_group_policy(group_id)
+            .await
+            .map_err(|e: StorageError| ProtocolError::Internal(e.to_string()))
+    }
+
+    /// Enforce the group's message rate (policy override, else the global
+    /// per-group rate).
+    fn check_group_rate(&self, group_id: &GroupId, policy: &GroupPolicy) -> ProtocolResult<()> {
+        let policy_rate = policy.messages_per_minute.and_then(NonZeroU32::new);
+        if let Err(e) = self
+            .relay
+            .rate_limits()
+            .check_group(group_id.as_bytes(), policy_rate)
+        {
+            tracing::warn!("Group rate limited for {:?}: {}", group_id, e);
+            self.relay.metrics().record_rate_limit(&e);
+            return Err(ProtocolError::RateLimited {
+                reason: e.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Charge ciphertext bytes to the device's and group's byte rates.
+    fn check_bandwidth(
+        &self,
+        group_id: &GroupId,
+        device_id: &DeviceId,
+        bytes: usize,
+    ) -> ProtocolResult<()> {
+        if let Err(e) =
+            self.relay
+                .rate_limits()
+                .check_bytes(device_id.as_bytes(), group_id.as_bytes(), bytes)
+        {
+            tracing::warn!("Bandwidth limited for {:?}: {}", device_id, e);
+            self.relay.metrics().record_rate_limit(&e);
+            return Err(ProtocolError::RateLimited {
+                reason: e.to_string(),
+            });
+        }
+        Ok(())
+    }
+
     /// Get active state or return error.
     fn get_active_state(&self) -> ProtocolResult<(GroupId, DeviceId)> {
         match &self.state {
@@ -471,14 +1107,60 @@ impl Session {
             _ => Err(ProtocolError::NotAuthenticated),
         }
     }
+}
 
-    /// Get expected message types for current state.
-    fn expected_message_types(&self) -> String {
-        match &self.state {
-            SessionState::AwaitingHello => "Hello".to_string(),
-            SessionState::Active { .. } => "Push, Pull, Bye".to_string(),
-            SessionState::Closing => "none".to_string(),
+/// Requests a client may make before HELLO, without joining a group.
+fn is_pre_hello_request(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::MailboxPut(_) | Message::MailboxGet(_) | Message::OprfEvaluate(_)
+    )
+}
+
+/// Whether a session in `state` handles `message`.
+///
+/// Mirrors the dispatch in `handle_stream`, which refuses anything else
+/// before it is handled. A HANDSHAKE is only consumed before the secure
+/// channel is established.
+fn accepts(state: &SessionState, message: &Message) -> bool {
+    match state {
+        SessionState::AwaitingHello => matches!(
+            message,
+            Message::Handshake(_)
+                | Message::Hello(_)
+                | Message::ChallengeResponse(_)
+                | Message::MailboxPut(_)
+                | Message::MailboxGet(_)
+                | Message::OprfEvaluate(_)
+        ),
+        SessionState::Active { .. } => matches!(
+            message,
+            Message::Push(_)
+                | Message::Pull(_)
+                | Message::ContentPut(_)
+                | Message::ContentGet(_)
+                | Message::MailboxPut(_)
+                | Message::MailboxGet(_)
+                | Message::OprfEvaluate(_)
+                | Message::InviteRegister(_)
+                | Message::InviteRevoke(_)
+                | Message::Bye(_)
+        ),
+        SessionState::Closing => false,
+    }
+}
+
+/// Get expected message types for a session state.
+fn expected_message_types(state: &SessionState) -> String {
+    match state {
+        SessionState::AwaitingHello => {
+            "Handshake, Hello, ChallengeResponse, MailboxPut, MailboxGet, OprfEvaluate".to_string()
         }
+        SessionState::Active { .. } => {
+            "Push, Pull, ContentPut, ContentGet, MailboxPut, MailboxGet, OprfEvaluate, InviteRegister, InviteRevoke, Bye"
+                .to_string()
+        }
+        SessionState::Closing => "none".to_string(),
     }
 }
 
@@ -493,6 +1175,17 @@ fn truncate_device_name(name: &str, max_chars: usize) -> String {
     }
 }
 
+/// Resolve a client-requested TTL against the relay maximum.
+///
+/// Zero means "use the maximum"; larger requests are clamped down.
+fn capped_ttl(requested: u32, max: u64) -> u64 {
+    if requested == 0 {
+        max
+    } else {
+        (requested as u64).min(max)
+    }
+}
+
 /// Clamp a pull limit to the configured range.
 ///
 /// Zero is treated as "use default" (100). Values above max are clamped down.
@@ -504,6 +1197,11 @@ fn clamp_pull_limit(limit: u32, max: u32) -> u32 {
     }
 }
 
+/// Check that `data` hashes to `content_hash` (BLAKE3).
+fn content_hash_matches(content_hash: &[u8; 32], data: &[u8]) -> bool {
+    blake3::hash(data).as_bytes() == content_hash
+}
+
 fn current_timestamp() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -514,6 +1212,7 @@ fn current_timestamp() -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
 
     #[test]
     fn session_state_transitions() {
@@ -581,12 +1280,375 @@ mod tests {
 
     #[test]
     fn expected_message_types_by_state() {
-        // Create a mock to test expected_message_types logic
-        // (We can't easily create a Session without a connection)
-        let expected_hello = "Hello";
-        let expected_active = "Push, Pull, Bye";
+        use sync_types::{
+            BlobId, Bye, ContentGet, ContentPut, Hello, InviteRegister, InviteRevoke, MailboxGet,
+            MailboxPut, Notify, OprfEvaluate, Pull, Push,
+        };
+
+        // One of every message a client can send, plus relay-only replies
+        let messages = vec![
+            Message::Handshake(Handshake { payload: vec![] }),
+            Message::Hello(Hello {
+                version: 1,
+                device_name: "test".to_string(),
+                group_id: GroupId::random(),
+                last_cursor: Cursor::zero(),
+                invite_id: None,
+            }),
+            Message::ChallengeResponse(ChallengeResponse {
+                nonce: [0; 32],
+                counter: 0,
+            }),
+            Message::Push(Push {
+                blob_id: BlobId::new(),
+                payload: vec![],
+                ttl: 0,
+                trace: None,
+            }),
+            Message::Pull(Pull {
+                after_cursor: Cursor::zero(),
+                limit: 0,
+                trace: None,
+            }),
+            Message::ContentPut(ContentPut {
+                content_hash: [0; 32],
+                data: vec![],
+                ttl: 0,
+            }),
+            Message::ContentGet(ContentGet {
+                content_hash: [0; 32],
+            }),
+            Message::MailboxPut(MailboxPut {
+                mailbox: [0; 32],
+                payload: vec![],
+                ttl: 0,
+            }),
+            Message::MailboxGet(MailboxGet { mailbox: [0; 32] }),
+            Message::OprfEvaluate(OprfEvaluate {
+                key_id: [0; 32],
+                blinded: [0; 32],
+            }),
+            Message::InviteRegister(InviteRegister {
+                invite_id: [0; 16],
+                max_uses: 1,
+                expires_at: 0,
+            }),
+            Message::InviteRevoke(InviteRevoke { invite_id: [0; 16] }),
+            Message::Bye(Bye { reason: None }),
+            Message::Welcome(Welcome {
+                version: 1,
+                max_cursor: Cursor::zero(),
+                pending_count: 0,
+            }),
+            Message::Notify(Notify {
+                latest_cursor: Cursor::zero(),
+                count: 0,
+                trace: None,
+            }),
+        ];
+        let states = [
+            SessionState::AwaitingHello,
+            SessionState::Active {
+                group_id: GroupId::random(),
+                device_id: DeviceId::random(),
+                device_name: "test".to_string(),
+                last_cursor: Cursor::zero(),
+            },
+            SessionState::Closing,
+        ];
+
+        for state in &states {
+            let expected = expected_message_types(state);
+            let listed: Vec<&str> = expected.split(", ").collect();
+            for message in &messages {
+                let debug = format!("{:?}", message);
+                let name = debug.split('(').next().unwrap();
+                assert_eq!(
+                    accepts(state, message),
+                    listed.contains(&name),
+                    "{} in {:?}: accepted vs listed in {:?}",
+                    name,
+                    state,
+                    expected
+                );
+            }
+        }
+        assert!(expected_message_types(&SessionState::AwaitingHello).contains("ChallengeResponse"));
+    }
+
+    /// Relay on a loopback endpoint.
+    struct Loopback {
+        relay: Arc<SyncRelay>,
+        router: iroh::protocol::Router,
+        addr: iroh::EndpointAddr,
+    }
+
+    /// A raw client connection to a [`Loopback`] relay.
+    struct Client {
+        /// Keeps the client endpoint (and so `connection`) open.
+        _endpoint: iroh::Endpoint,
+        connection: Connection,
+    }
+
+    impl Loopback {
+        async fn start(config: Config) -> Self {
+            use crate::protocol::{SyncProtocol, ALPN};
+            use crate::storage::SqliteStorage;
+            use iroh::{Endpoint, EndpointAddr, RelayMode};
+
+            let storage = SqliteStorage::in_memory().await.unwrap();
+            let relay = Arc::new(SyncRelay::new(config, storage));
+            let server = Endpoint::builder()
+                .relay_mode(RelayMode::Disabled)
+                .bind()
+                .await
+                .unwrap();
+            let port = server
+                .bound_sockets()
+                .into_iter()
+                .find(|addr| addr.is_ipv4())
+                .unwrap()
+                .port();
+            let addr = EndpointAddr::new(server.id())
+                .with_ip_addr((std::net::Ipv4Addr::LOCALHOST, port).into());
+            let router = iroh::protocol::Router::builder(server)
+                .accept(ALPN, SyncProtocol::new(relay.clone()))
+                .spawn();
+            Self {
+                relay,
+                router,
+                addr,
+            }
+        }
+
+        /// Connect a new client endpoint (fresh identity unless `secret_key`).
+        async fn connect(&self, secret_key: Option<[u8; 32]>) -> Client {
+            use crate::protocol::ALPN;
+            use iroh::{Endpoint, RelayMode, SecretKey};
+
+            let mut builder = Endpoint::builder().relay_mode(RelayMode::Disabled);
+            if let Some(secret_key) = secret_key {
+                builder = builder.secret_key(SecretKey::from_bytes(&secret_key));
+            }
+            let endpoint = builder.bind().await.unwrap();
+            let connection = endpoint.connect(self.addr.clone(), ALPN).await.unwrap();
+            Client {
+                _endpoint: endpoint,
+                connection,
+            }
+        }
+    }
+
+    impl Client {
+        /// Send `message` on a new stream; `None` if the relay refused it.
+        async fn request(&self, message: Message) -> Option<Message> {
+            let (mut send, mut recv) = self.connection.open_bi().await.unwrap();
+            let bytes = message.to_bytes().unwrap();
+            send.write_all(&(bytes.len() as u32).to_be_bytes())
+                .await
+                .unwrap();
+            send.write_all(&bytes).await.unwrap();
+            send.finish().unwrap();
+            let reply = recv.read_to_end(MAX_MESSAGE_SIZE + 4).await.ok()?;
+            (reply.len() > 4).then(|| Message::from_bytes(&reply[4..]).unwrap())
+        }
+
+        /// Say HELLO to `group_id`; `None` if the relay refused the device.
+        async fn hello(&self, group_id: GroupId, invite_id: Option<[u8; 16]>) -> Option<Message> {
+            self.request(Message::Hello(sync_types::Hello {
+                version: 1,
+                device_name: "test".to_string(),
+                group_id,
+                last_cursor: Cursor::zero(),
+                invite_id,
+            }))
+            .await
+        }
+    }
+
+    fn pow_config(difficulty: u8) -> Config {
+        let mut config = Config::default();
+        config.limits.pow_min_difficulty = difficulty;
+        config.limits.pow_max_difficulty = difficulty;
+        config
+    }
+
+    #[tokio::test]
+    async fn pre_hello_requests_pay_proof_of_work() {
+        use sync_core::solve_pow;
+        use sync_types::MailboxGet;
+
+        let server = Loopback::start(pow_config(8)).await;
+        let client = server.connect(None).await;
+        let get = Message::MailboxGet(MailboxGet { mailbox: [7; 32] });
+
+        let Some(Message::Challenge(challenge)) = client.request(get.clone()).await else {
+            panic!("mailbox request before HELLO should be challenged");
+        };
+        assert_eq!(challenge.difficulty, 8);
+
+        // A wrong answer is refused and the request is dropped
+        let wrong = (0..)
+            .find(|c| !verify_pow(&challenge.nonce, 8, *c))
+            .unwrap();
+        let response = Message::ChallengeResponse(ChallengeResponse {
+            nonce: challenge.nonce,
+            counter: wrong,
+        });
+        assert_eq!(client.request(response).await, None);
+
+        // The right answer completes the held request
+        let Some(Message::Challenge(challenge)) = client.request(get.clone()).await else {
+            panic!("request should be challenged until solved");
+        };
+        let response = Message::ChallengeResponse(ChallengeResponse {
+            nonce: challenge.nonce,
+            counter: solve_pow(&challenge.nonce, challenge.difficulty).unwrap(),
+        });
+        let expected = Message::MailboxData(MailboxData {
+            mailbox: [7; 32],
+            payload: None,
+        });
+        assert_eq!(client.request(response).await, Some(expected.clone()));
+
+        // The connection has paid; later requests go straight through
+        assert_eq!(client.request(get).await, Some(expected));
+
+        server.router.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pre_hello_requests_unchallenged_without_pow() {
+        use sync_types::MailboxGet;
+
+        let server = Loopback::start(pow_config(0)).await;
+        let client = server.connect(None).await;
+        let get = Message::MailboxGet(MailboxGet { mailbox: [7; 32] });
+        assert!(matches!(
+            client.request(get).await,
+            Some(Message::MailboxData(_))
+        ));
+
+        server.router.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn member_reconnects_with_same_key_without_invite() {
+        use sync_types::InviteRegister;
+
+        let server = Loopback::start(Config::default()).await;
+        let group = GroupId::random();
+        let (owner_key, member_key) = ([0x01; 32], [0x02; 32]);
+
+        // The owner joins the open group and makes it invite-only
+        let owner = server.connect(Some(owner_key)).await;
+        assert!(matches!(
+            owner.hello(group, None).await,
+            Some(Message::Welcome(_))
+        ));
+        let invite_id = [0x42; 16];
+        let register = Message::InviteRegister(InviteRegister {
+            invite_id,
+            max_uses: 1,
+            expires_at: u64::MAX / 2,
+        });
+        assert!(matches!(
+            owner.request(register).await,
+            Some(Message::InviteAck(_))
+        ));
+
+        // A new device joins once with the invite
+        let member = server.connect(Some(member_key)).await;
+        assert!(matches!(
+            member.hello(group, Some(invite_id)).await,
+            Some(Message::Welcome(_))
+        ));
+        drop(member);
+
+        // A new transport with the same key is still a member
+        let member = server.connect(Some(member_key)).await;
+        assert!(matches!(
+            member.hello(group, None).await,
+            Some(Message::Welcome(_))
+        ));
+        let device_id =
+            DeviceId::from_bytes(iroh::SecretKey::from_bytes(&member_key).public().as_bytes())
+                .unwrap();
+        assert!(server
+            .relay
+            .storage()
+            .is_group_member(&group, &device_id)
+            .await
+            .unwrap());
+
+        // A fresh key is a new device and needs an invite (now used up)
+        let stranger = server.connect(None).await;
+        assert_eq!(stranger.hello(group, None).await, None);
+        assert_eq!(stranger.hello(group, Some(invite_id)).await, None);
+
+        server.router.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn drain_sends_bye_and_refuses_requests() {
+        use crate::server::SHUTDOWN_BYE_REASON;
+
+        let server = Loopback::start(Config::default()).await;
+        let client = server.connect(None).await;
+        let group = GroupId::random();
+        assert!(matches!(
+            client.hello(group, None).await,
+            Some(Message::Welcome(_))
+        ));
+
+        // The connected device is told on a relay-opened stream
+        assert_eq!(server.relay.begin_drain(), 1);
+        let mut recv = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            client.connection.accept_uni(),
+        )
+        .await
+        .expect("BYE should arrive before the timeout")
+        .unwrap();
+        let frame = recv.read_to_end(MAX_MESSAGE_SIZE + 4).await.unwrap();
+        match Message::from_bytes(&frame[4..]).unwrap() {
+            Message::Bye(bye) => assert_eq!(bye.reason.as_deref(), Some(SHUTDOWN_BYE_REASON)),
+            other => panic!("expected Bye, got: {:?}", other),
+        }
+
+        // New requests on the open session are refused
+        let pull = Message::Pull(sync_types::Pull {
+            after_cursor: Cursor::zero(),
+            limit: 10,
+            trace: None,
+        });
+        assert_eq!(client.request(pull).await, None);
+
+        server.router.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn mailbox_ttl_capped_at_relay_max() {
+        assert_eq!(capped_ttl(0, 600), 600, "zero uses the maximum");
+        assert_eq!(capped_ttl(60, 600), 60);
+        assert_eq!(capped_ttl(86400, 600), 600);
+    }
+
+    #[test]
+    fn content_ttl_capped_at_relay_max() {
+        let content_ttl = 7 * 24 * 60 * 60;
+        assert_eq!(capped_ttl(0, content_ttl), content_ttl);
+        assert_eq!(capped_ttl(3600, content_ttl), 3600);
+        assert_eq!(capped_ttl(u32::MAX, content_ttl), content_ttl);
+    }
+
+    #[test]
+    fn content_hash_must_match_data() {
+        let data = b"ciphertext bytes";
+        let hash = *blake3::hash(data).as_bytes();
 
-        assert_eq!(expected_hello, "Hello");
-        assert_eq!(expected_active, "Push, Pull, Bye");
+        assert!(content_hash_matches(&hash, data));
+        assert!(!content_hash_matches(&hash, b"tampered bytes"));
+        assert!(!content_hash_matches(&[0u8; 32], data));
     }
 }
@@ -0,0 +1,423 @@
+//! Versioned schema migrations for the SQLite backend.
+//!
+//! Each [`Migration`] runs once, in version order, inside its own
+//! transaction, and is recorded in the `schema_version` table. Databases
+//! created before versioning have no `schema_version` table and start at
+//! version 0; migration 1 uses `IF NOT EXISTS` so it adopts their tables
+//! as they are. New columns and tables go in a new migration at the end of
+//! [`MIGRATIONS`] — never edit one that has shipped.
+
+use crate::error::StorageError;
+use sqlx::SqlitePool;
+
+/// A forward-only schema change.
+#[derive(Debug)]
+pub struct Migration {
+    /// Version the schema is at once this migration has run.
+    pub version: i64,
+    /// Short description, stored alongside the version.
+    pub description: &'static str,
+    /// Statements to execute, in order.
+    pub statements: &'static [&'static str],
+}
+
+/// All migrations, ordered by version (contiguous from 1).
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial layout",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS group_cursors (
+                group_id BLOB PRIMARY KEY,
+                next_cursor INTEGER NOT NULL DEFAULT 1
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS blobs (
+                blob_id BLOB PRIMARY KEY,
+                group_id BLOB NOT NULL,
+                cursor INTEGER NOT NULL,
+                sender_id BLOB NOT NULL,
+                payload BLOB NOT NULL,
+                timestamp INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+                UNIQUE(group_id, cursor)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS deliveries (
+                blob_id BLOB NOT NULL,
+                device_id BLOB NOT NULL,
+                delivered_at INTEGER,
+                PRIMARY KEY (blob_id, device_id)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS content_blobs (
+                group_id BLOB NOT NULL,
+                content_hash BLOB NOT NULL,
+                data BLOB NOT NULL,
+                expires_at INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+                PRIMARY KEY (group_id, content_hash)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS mailbox_messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                mailbox BLOB NOT NULL,
+                payload BLOB NOT NULL,
+                expires_at INTEGER NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS oprf_keys (
+                key_id BLOB PRIMARY KEY,
+                secret BLOB NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS invites (
+                invite_id BLOB PRIMARY KEY,
+                group_id BLOB NOT NULL,
+                uses_remaining INTEGER NOT NULL,
+                revoked INTEGER NOT NULL DEFAULT 0,
+                expires_at INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            )
+            "#,
+            // Groups that have ever registered an invite only admit known members
+            r#"
+            CREATE TABLE IF NOT EXISTS invite_only_groups (
+                group_id BLOB PRIMARY KEY,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS group_members (
+                group_id BLOB NOT NULL,
+                device_id BLOB NOT NULL,
+                joined_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+                PRIMARY KEY (group_id, device_id)
+            )
+            "#,
+            // Operator-set storage quotas (replaced by group_policies in 2)
+            r#"
+            CREATE TABLE IF NOT EXISTS group_quotas (
+                group_id BLOB PRIMARY KEY,
+                max_storage_bytes INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_blobs_group_cursor ON blobs(group_id, cursor)",
+            "CREATE INDEX IF NOT EXISTS idx_blobs_expires ON blobs(expires_at)",
+            "CREATE INDEX IF NOT EXISTS idx_blobs_group_id ON blobs(group_id)",
+            "CREATE INDEX IF NOT EXISTS idx_content_blobs_expires ON content_blobs(expires_at)",
+            "CREATE INDEX IF NOT EXISTS idx_mailbox_messages_mailbox ON mailbox_messages(mailbox, id)",
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "per-group policies",
+        statements: &[
+            // NULL columns fall back to [storage]
+            r#"
+            CREATE TABLE IF NOT EXISTS group_policies (
+                group_id BLOB PRIMARY KEY,
+                max_storage_bytes INTEGER,
+                max_blob_size INTEGER,
+                min_ttl_secs INTEGER,
+                max_ttl_secs INTEGER,
+                messages_per_minute INTEGER,
+                updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            )
+            "#,
+            r#"
+            INSERT OR IGNORE INTO group_policies (group_id, max_storage_bytes, updated_at)
+            SELECT group_id, max_storage_bytes, updated_at FROM group_quotas
+            "#,
+            "DROP TABLE group_quotas",
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "backfill group members",
+        statements: &[
+            // Devices seen before membership was tracked would otherwise be
+            // locked out once their group registers an invite
+            r#"
+            INSERT OR IGNORE INTO group_members (group_id, device_id)
+            SELECT DISTINCT group_id, sender_id FROM blobs
+            "#,
+            r#"
+            INSERT OR IGNORE INTO group_members (group_id, device_id)
+            SELECT DISTINCT blobs.group_id, deliveries.device_id
+            FROM deliveries JOIN blobs ON blobs.blob_id = deliveries.blob_id
+            "#,
+        ],
+    },
+];
+
+/// Schema version this build migrates to. Databases at a newer version
+/// are refused rather than opened.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Read the schema version of an open database (0 if it predates
+/// versioning). Only reads, so it works on read-only connections.
+pub(crate) async fn current_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    let versioned: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !versioned {
+        return Ok(0);
+    }
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await
+}
+
+/// Apply every pending migration. Returns the number applied.
+///
+/// # Errors
+///
+/// Returns [`StorageError::SchemaTooNew`] if the database was written by a
+/// newer relay, or a database error if a migration fails (that migration
+/// is rolled back and later ones are not attempted).
+pub(crate) async fn migrate(pool: &SqlitePool) -> Result<usize, StorageError> {
+    migrate_to(pool, SCHEMA_VERSION).await
+}
+
+async fn migrate_to(pool: &SqlitePool, target: i64) -> Result<usize, StorageError> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let current = current_version(pool).await?;
+    if current > SCHEMA_VERSION {
+        return Err(StorageError::SchemaTooNew {
+            found: current,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    let mut applied = 0;
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current && m.version <= target)
+    {
+        let mut tx = pool.begin().await?;
+        for statement in migration.statements {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?1, ?2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            version = migration.version,
+            description = migration.description,
+            "Applied schema migration"
+        );
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{BlobStorage, SqliteStorage, StoreBlobRequest};
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use std::path::Path;
+    use sync_types::{BlobId, DeviceId, GroupId};
+
+    /// Layout written by relays before schema versioning.
+    const UNVERSIONED_FIXTURE: &[&str] = &[
+        "CREATE TABLE group_cursors (group_id BLOB PRIMARY KEY, \
+         next_cursor INTEGER NOT NULL DEFAULT 1)",
+        "CREATE TABLE blobs (blob_id BLOB PRIMARY KEY, group_id BLOB NOT NULL, \
+         cursor INTEGER NOT NULL, sender_id BLOB NOT NULL, payload BLOB NOT NULL, \
+         timestamp INTEGER NOT NULL, expires_at INTEGER NOT NULL, \
+         created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')), \
+         UNIQUE(group_id, cursor))",
+        "CREATE TABLE deliveries (blob_id BLOB NOT NULL, device_id BLOB NOT NULL, \
+         delivered_at INTEGER, PRIMARY KEY (blob_id, device_id))",
+        "CREATE TABLE group_quotas (group_id BLOB PRIMARY KEY, \
+         max_storage_bytes INTEGER NOT NULL, \
+         updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')))",
+    ];
+
+    async fn fixture(path: &Path, statements: &[&str]) -> SqlitePool {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap();
+        for statement in statements {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn table_exists(pool: &SqlitePool, name: &str) -> bool {
+        sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        )
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn versions_are_contiguous() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i64 + 1, "{}", migration.description);
+        }
+        assert_eq!(SCHEMA_VERSION, MIGRATIONS.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn fresh_database_is_current_and_rerun_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = fixture(&dir.path().join("relay.db"), &[]).await;
+        assert_eq!(current_version(&pool).await.unwrap(), 0);
+
+        assert_eq!(migrate(&pool).await.unwrap(), MIGRATIONS.len());
+        assert_eq!(current_version(&pool).await.unwrap(), SCHEMA_VERSION);
+        assert_eq!(migrate(&pool).await.unwrap(), 0);
+        assert!(!table_exists(&pool, "group_quotas").await);
+    }
+
+    #[tokio::test]
+    async fn upgrades_unversioned_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relay.db");
+        let group = GroupId::random();
+        let blob_id = BlobId::new();
+        let sender = DeviceId::random();
+        let receiver = DeviceId::random();
+
+        let pool = fixture(&path, UNVERSIONED_FIXTURE).await;
+        sqlx::query("INSERT INTO group_cursors VALUES (?1, 2)")
+            .bind(group.as_bytes().as_slice())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO blobs (blob_id, group_id, cursor, sender_id, payload, timestamp, \
+             expires_at) VALUES (?1, ?2, 1, ?3, x'0102', 0, 4102444800)",
+        )
+        .bind(blob_id.as_bytes())
+        .bind(group.as_bytes().as_slice())
+        .bind(sender.as_bytes().as_slice())
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO deliveries VALUES (?1, ?2, 0)")
+            .bind(blob_id.as_bytes())
+            .bind(receiver.as_bytes().as_slice())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO group_quotas VALUES (?1, 2048, 0)")
+            .bind(group.as_bytes().as_slice())
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let storage = SqliteStorage::new(&path).await.unwrap();
+        assert_eq!(storage.schema_version().await.unwrap(), SCHEMA_VERSION);
+        assert_eq!(
+            storage.get_blob(&blob_id).await.unwrap().unwrap().payload,
+            vec![1, 2]
+        );
+        assert_eq!(
+            storage
+                .get_group_policy(&group)
+                .await
+                .unwrap()
+                .max_storage_bytes,
+            Some(2048)
+        );
+
+        // Devices that already took part in the group stay members, so
+        // registering an invite later does not lock them out
+        for device in [&sender, &receiver] {
+            assert!(storage.is_group_member(&group, device).await.unwrap());
+        }
+        assert!(!storage
+            .is_group_member(&group, &DeviceId::random())
+            .await
+            .unwrap());
+
+        // Cursors continue where the old database left off
+        let cursor = storage
+            .store_blob(StoreBlobRequest {
+                blob_id: BlobId::new(),
+                group_id: group,
+                sender_id: DeviceId::random(),
+                payload: vec![3],
+                timestamp: 0,
+                ttl_secs: 60,
+            })
+            .await
+            .unwrap();
+        assert_eq!(cursor.value(), 2);
+    }
+
+    #[tokio::test]
+    async fn upgrades_each_intermediate_version() {
+        for start in 1..SCHEMA_VERSION {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("relay.db");
+            let pool = fixture(&path, &[]).await;
+            migrate_to(&pool, start).await.unwrap();
+            assert_eq!(current_version(&pool).await.unwrap(), start);
+            pool.close().await;
+
+            let storage = SqliteStorage::new(&path).await.unwrap();
+            assert_eq!(storage.schema_version().await.unwrap(), SCHEMA_VERSION);
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relay.db");
+        let pool = fixture(&path, &[]).await;
+        migrate(&pool).await.unwrap();
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?1, 'future')")
+            .bind(SCHEMA_VERSION + 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        assert!(matches!(
+            SqliteStorage::new(&path).await,
+            Err(StorageError::SchemaTooNew { found, supported })
+                if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+    }
+}
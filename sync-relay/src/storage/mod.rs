@@ -2,12 +2,16 @@
 //!
 //! Provides blob storage with cursor-based ordering.
 
+pub(crate) mod migrations;
 mod sqlite;
 
+pub use migrations::{Migration, MIGRATIONS, SCHEMA_VERSION};
 pub use sqlite::SqliteStorage;
 
+use crate::config::StorageConfig;
 use crate::error::StorageError;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use sync_types::{BlobId, Cursor, DeviceId, GroupId};
 
 /// A blob stored in the relay with cursor ordering.
@@ -46,6 +50,120 @@ pub struct StoreBlobRequest {
     pub ttl_secs: u64,
 }
 
+/// Per-group usage summary for operators.
+///
+/// Counts only; never payload contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupStats {
+    /// Group identifier.
+    pub group_id: GroupId,
+    /// Number of stored blobs.
+    pub blob_count: u64,
+    /// Bytes of stored blob payloads.
+    pub storage_bytes: u64,
+    /// Bytes of relay-hosted content.
+    pub content_bytes: u64,
+    /// Storage quota override, if one is set.
+    pub quota_bytes: Option<u64>,
+}
+
+/// Operator-set overrides for one group.
+///
+/// Unset fields fall back to the global `[storage]` settings; the message
+/// rate has no global per-group equivalent and is unlimited when unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroupPolicy {
+    /// Total blob storage in bytes (overrides `storage.max_group_storage`).
+    pub max_storage_bytes: Option<u64>,
+    /// Largest accepted blob in bytes (overrides `storage.max_blob_size`).
+    pub max_blob_size: Option<u64>,
+    /// Shortest blob TTL in seconds; shorter requests are raised to it.
+    pub min_ttl_secs: Option<u64>,
+    /// Longest blob TTL in seconds; longer requests are capped to it.
+    pub max_ttl_secs: Option<u64>,
+    /// PUSH and PULL requests per minute across all devices in the group.
+    pub messages_per_minute: Option<u32>,
+}
+
+impl GroupPolicy {
+    /// Whether no override is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check that the overrides are usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns a reason if a limit is zero or the TTL bounds are inverted.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_blob_size == Some(0) {
+            return Err("max_blob_size must be > 0".into());
+        }
+        if self.messages_per_minute == Some(0) {
+            return Err("messages_per_minute must be > 0".into());
+        }
+        if let (Some(min), Some(max)) = (self.min_ttl_secs, self.max_ttl_secs) {
+            if min > max {
+                return Err("min_ttl_secs must not exceed max_ttl_secs".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Group storage quota in bytes.
+    pub fn max_storage_bytes(&self, config: &StorageConfig) -> usize {
+        self.max_storage_bytes
+            .map_or(config.max_group_storage, |limit| limit as usize)
+    }
+
+    /// Largest accepted blob in bytes.
+    pub fn max_blob_size(&self, config: &StorageConfig) -> usize {
+        self.max_blob_size
+            .map_or(config.max_blob_size, |limit| limit as usize)
+    }
+
+    /// TTL for a blob pushed with `requested` seconds (0 = default).
+    pub fn blob_ttl(&self, requested: u32, config: &StorageConfig) -> u64 {
+        let ttl = if requested == 0 {
+            config.default_ttl
+        } else {
+            requested as u64
+        };
+        let ttl = self.max_ttl_secs.map_or(ttl, |max| ttl.min(max));
+        self.min_ttl_secs.map_or(ttl, |min| ttl.max(min))
+    }
+}
+
+/// What [`SqliteStorage::purge_group`] deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgeReport {
+    /// Blobs deleted.
+    pub blobs: u64,
+    /// Content blobs deleted.
+    pub content: u64,
+    /// Invites deleted.
+    pub invites: u64,
+    /// Member records deleted.
+    pub members: u64,
+}
+
+/// Outcome of redeeming a tracked invite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InviteRedemption {
+    /// One use was consumed; the device may join.
+    Accepted,
+    /// No invite with this ID for the group.
+    Unknown,
+    /// The invite was revoked.
+    Revoked,
+    /// The invite has expired.
+    Expired,
+    /// All uses have been consumed.
+    Exhausted,
+}
+
 /// Trait for blob storage backends.
 #[async_trait]
 pub trait BlobStorage: Send + Sync {
@@ -100,4 +218,147 @@ pub trait BlobStorage: Send + Sync {
 
     /// Get a specific blob by ID.
     async fn get_blob(&self, blob_id: &BlobId) -> Result<Option<StoredBlob>, StorageError>;
+
+    /// Store a content blob addressed by its BLAKE3 hash.
+    ///
+    /// Content is scoped to a group. Storing content that already exists
+    /// extends its expiry instead of duplicating it.
+    ///
+    /// Returns `true` if the content was newly stored.
+    async fn store_content(
+        &self,
+        group_id: &GroupId,
+        content_hash: &[u8; 32],
+        data: &[u8],
+        ttl_secs: u64,
+    ) -> Result<bool, StorageError>;
+
+    /// Get an unexpired content blob by hash.
+    async fn get_content(
+        &self,
+        group_id: &GroupId,
+        content_hash: &[u8; 32],
+    ) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Get total content storage used by a group in bytes.
+    async fn get_group_content_storage(&self, group_id: &GroupId) -> Result<u64, StorageError>;
+
+    /// Remove expired content blobs.
+    ///
+    /// Returns the number of content blobs deleted.
+    async fn cleanup_expired_content(&self) -> Result<u64, StorageError>;
+
+    /// Append a message to a mailbox.
+    ///
+    /// Returns `false` (without storing) if the mailbox already holds
+    /// `max_messages` unexpired messages.
+    async fn put_mailbox(
+        &self,
+        mailbox: &[u8; 32],
+        payload: &[u8],
+        ttl_secs: u64,
+        max_messages: usize,
+    ) -> Result<bool, StorageError>;
+
+    /// Remove and return the oldest unexpired message in a mailbox.
+    async fn take_mailbox(&self, mailbox: &[u8; 32]) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Remove expired mailbox messages.
+    ///
+    /// Returns the number of messages deleted.
+    async fn cleanup_expired_mailbox(&self) -> Result<u64, StorageError>;
+
+    /// Get the OPRF key stored under `key_id`, storing `candidate` first
+    /// if there is none.
+    ///
+    /// Returns the stored key; concurrent callers all get the same one.
+    async fn get_or_create_oprf_key(
+        &self,
+        key_id: &[u8; 32],
+        candidate: &[u8; 32],
+    ) -> Result<[u8; 32], StorageError>;
+
+    /// Start tracking an invite for a group.
+    ///
+    /// Also marks the group as invite-only (see [`is_group_invite_only`](Self::is_group_invite_only)).
+    /// Returns `false` if the invite ID is already registered.
+    async fn register_invite(
+        &self,
+        group_id: &GroupId,
+        invite_id: &[u8; 16],
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<bool, StorageError>;
+
+    /// Revoke a group's invite. Returns `false` if the group has no such invite.
+    async fn revoke_invite(
+        &self,
+        group_id: &GroupId,
+        invite_id: &[u8; 16],
+    ) -> Result<bool, StorageError>;
+
+    /// Consume one use of a group's invite, if it is still valid.
+    async fn redeem_invite(
+        &self,
+        group_id: &GroupId,
+        invite_id: &[u8; 16],
+    ) -> Result<InviteRedemption, StorageError>;
+
+    /// Whether the group only admits known members and invite holders.
+    async fn is_group_invite_only(&self, group_id: &GroupId) -> Result<bool, StorageError>;
+
+    /// Record a device as a member of a group (idempotent).
+    async fn add_group_member(
+        &self,
+        group_id: &GroupId,
+        device_id: &DeviceId,
+    ) -> Result<(), StorageError>;
+
+    /// Whether a device has joined the group before.
+    async fn is_group_member(
+        &self,
+        group_id: &GroupId,
+        device_id: &DeviceId,
+    ) -> Result<bool, StorageError>;
+
+    /// Remove expired invites.
+    ///
+    /// Returns the number of invites deleted. Groups stay invite-only.
+    async fn cleanup_expired_invites(&self) -> Result<u64, StorageError>;
+
+    /// Get the group's policy overrides (empty if none are set).
+    async fn get_group_policy(&self, group_id: &GroupId) -> Result<GroupPolicy, StorageError>;
+
+    /// Replace the group's policy overrides; an empty policy clears them.
+    async fn set_group_policy(
+        &self,
+        group_id: &GroupId,
+        policy: &GroupPolicy,
+    ) -> Result<(), StorageError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_policy_falls_back_to_global_config() {
+        let config = crate::config::Config::default().storage;
+        let empty = GroupPolicy::default();
+        assert_eq!(empty.max_blob_size(&config), config.max_blob_size);
+        assert_eq!(empty.max_storage_bytes(&config), config.max_group_storage);
+        assert_eq!(empty.blob_ttl(0, &config), config.default_ttl);
+        assert_eq!(empty.blob_ttl(60, &config), 60);
+
+        let policy = GroupPolicy {
+            max_blob_size: Some(4096),
+            min_ttl_secs: Some(300),
+            max_ttl_secs: Some(3600),
+            ..GroupPolicy::default()
+        };
+        assert_eq!(policy.max_blob_size(&config), 4096);
+        assert_eq!(policy.blob_ttl(0, &config), 3600, "default capped to max");
+        assert_eq!(policy.blob_ttl(60, &config), 300, "raised to min");
+        assert_eq!(policy.blob_ttl(600, &config), 600);
+    }
 }
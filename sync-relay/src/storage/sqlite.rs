@@ -1,11 +1,18 @@
 //! SQLite storage backend for sync-relay.
 
-use super::{BlobStorage, StoreBlobRequest, StoredBlob};
+use super::migrations;
+use super::{
+    BlobStorage, GroupPolicy, GroupStats, InviteRedemption, PurgeReport, StoreBlobRequest,
+    StoredBlob,
+};
 use crate::error::StorageError;
+use crate::metrics::StorageMetrics;
 use async_trait::async_trait;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use sync_types::{BlobId, Cursor, DeviceId, GroupId};
 
@@ -15,6 +22,7 @@ use sync_types::{BlobId, Cursor, DeviceId, GroupId};
 #[derive(Clone)]
 pub struct SqliteStorage {
     pool: SqlitePool,
+    metrics: Arc<StorageMetrics>,
 }
 
 impl SqliteStorage {
@@ -35,8 +43,12 @@ impl SqliteStorage {
             .await
             .map_err(StorageError::Database)?;
 
-        let storage = Self { pool };
+        let storage = Self {
+            pool,
+            metrics: Arc::default(),
+        };
         storage.run_migrations().await?;
+        storage.refresh_gauges().await?;
         Ok(storage)
     }
 
@@ -53,75 +65,41 @@ impl SqliteStorage {
             .await
             .map_err(StorageError::Database)?;
 
-        let storage = Self { pool };
+        let storage = Self {
+            pool,
+            metrics: Arc::default(),
+        };
         storage.run_migrations().await?;
+        storage.refresh_gauges().await?;
         Ok(storage)
     }
 
-    /// Run database migrations.
+    /// Bring the schema up to [`SCHEMA_VERSION`](super::SCHEMA_VERSION).
+    ///
+    /// Refuses databases written by a newer relay.
     async fn run_migrations(&self) -> Result<(), StorageError> {
-        // Create tables
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS group_cursors (
-                group_id BLOB PRIMARY KEY,
-                next_cursor INTEGER NOT NULL DEFAULT 1
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(StorageError::Database)?;
-
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS blobs (
-                blob_id BLOB PRIMARY KEY,
-                group_id BLOB NOT NULL,
-                cursor INTEGER NOT NULL,
-                sender_id BLOB NOT NULL,
-                payload BLOB NOT NULL,
-                timestamp INTEGER NOT NULL,
-                expires_at INTEGER NOT NULL,
-                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
-                UNIQUE(group_id, cursor)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(StorageError::Database)?;
-
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS deliveries (
-                blob_id BLOB NOT NULL,
-                device_id BLOB NOT NULL,
-                delivered_at INTEGER,
-                PRIMARY KEY (blob_id, device_id)
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(StorageError::Database)?;
-
-        // Create indexes
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_blobs_group_cursor ON blobs(group_id, cursor)")
-            .execute(&self.pool)
-            .await
-            .map_err(StorageError::Database)?;
+        migrations::migrate(&self.pool).await?;
+        Ok(())
+    }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_blobs_expires ON blobs(expires_at)")
-            .execute(&self.pool)
+    /// Schema version of the open database.
+    pub async fn schema_version(&self) -> Result<i64, StorageError> {
+        migrations::current_version(&self.pool)
             .await
-            .map_err(StorageError::Database)?;
+            .map_err(StorageError::Database)
+    }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_blobs_group_id ON blobs(group_id)")
+    /// Write a consistent, compacted copy of the database to `dest`.
+    ///
+    /// Uses `VACUUM INTO`, which reads inside one transaction, so the relay
+    /// keeps serving while the snapshot is written. `dest` must not exist.
+    pub async fn snapshot(&self, dest: &Path) -> Result<(), StorageError> {
+        let _timer = self.metrics.query_duration.start_timer("snapshot");
+        sqlx::query("VACUUM INTO ?1")
+            .bind(dest.to_string_lossy().as_ref())
             .execute(&self.pool)
             .await
             .map_err(StorageError::Database)?;
-
         Ok(())
     }
 
@@ -151,6 +129,25 @@ impl SqliteStorage {
             .unwrap_or(0)
     }
 
+    /// Storage gauges and query timings.
+    pub fn metrics(&self) -> &StorageMetrics {
+        &self.metrics
+    }
+
+    /// Recompute the storage gauges from the database.
+    ///
+    /// Called at startup and after bulk deletes; `store_blob` keeps the
+    /// gauges current in between.
+    pub async fn refresh_gauges(&self) -> Result<(), StorageError> {
+        let blobs = self.get_total_blobs().await?;
+        let bytes = self.get_total_storage_bytes().await?;
+        let groups = self.get_total_groups_with_data().await?;
+        self.metrics.blobs.store(blobs, Ordering::Relaxed);
+        self.metrics.bytes.store(bytes, Ordering::Relaxed);
+        self.metrics.groups.store(groups, Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Get total number of blobs across all groups.
     pub async fn get_total_blobs(&self) -> Result<u64, StorageError> {
         let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM blobs")
@@ -179,14 +176,101 @@ impl SqliteStorage {
                 .map_err(StorageError::Database)?;
         Ok(count as u64)
     }
+
+    /// List every group the relay holds state for, with usage totals.
+    ///
+    /// Includes groups with no stored blobs (e.g., invite-only groups or
+    /// groups with a policy override). Ordered by group ID.
+    pub async fn list_groups(&self) -> Result<Vec<GroupStats>, StorageError> {
+        let rows: Vec<GroupStatsRow> = sqlx::query_as(
+            r#"
+            SELECT g.group_id,
+                   (SELECT COUNT(*) FROM blobs b WHERE b.group_id = g.group_id),
+                   (SELECT COALESCE(SUM(LENGTH(payload)), 0) FROM blobs b
+                     WHERE b.group_id = g.group_id),
+                   (SELECT COALESCE(SUM(LENGTH(data)), 0) FROM content_blobs c
+                     WHERE c.group_id = g.group_id),
+                   (SELECT max_storage_bytes FROM group_policies p
+                     WHERE p.group_id = g.group_id)
+            FROM (
+                SELECT group_id FROM group_cursors
+                UNION SELECT group_id FROM content_blobs
+                UNION SELECT group_id FROM invite_only_groups
+                UNION SELECT group_id FROM group_policies
+            ) g
+            ORDER BY g.group_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        rows.into_iter()
+            .map(|(group_id, blobs, bytes, content, quota)| {
+                Ok(GroupStats {
+                    group_id: GroupId::from_bytes(&group_id).ok_or_else(|| {
+                        StorageError::Database(sqlx::Error::Decode("invalid group_id".into()))
+                    })?,
+                    blob_count: blobs as u64,
+                    storage_bytes: bytes as u64,
+                    content_bytes: content as u64,
+                    quota_bytes: quota.map(|q| q as u64),
+                })
+            })
+            .collect()
+    }
+
+    /// Delete everything stored for a group: blobs and their delivery
+    /// records, content, invites, membership and policy overrides.
+    ///
+    /// The group's cursor sequence is kept so cursors stay monotonic if
+    /// the group pushes again.
+    pub async fn purge_group(&self, group_id: &GroupId) -> Result<PurgeReport, StorageError> {
+        let group = group_id.as_bytes().as_slice();
+        let mut tx = self.pool.begin().await.map_err(StorageError::Database)?;
+
+        sqlx::query(
+            "DELETE FROM deliveries WHERE blob_id IN (SELECT blob_id FROM blobs WHERE group_id = ?1)",
+        )
+        .bind(group)
+        .execute(&mut *tx)
+        .await
+        .map_err(StorageError::Database)?;
+
+        let mut report = PurgeReport::default();
+        for (table, count) in [
+            ("blobs", &mut report.blobs),
+            ("content_blobs", &mut report.content),
+            ("invites", &mut report.invites),
+            ("group_members", &mut report.members),
+            ("invite_only_groups", &mut 0),
+            ("group_policies", &mut 0),
+        ] {
+            *count = sqlx::query(&format!("DELETE FROM {} WHERE group_id = ?1", table))
+                .bind(group)
+                .execute(&mut *tx)
+                .await
+                .map_err(StorageError::Database)?
+                .rows_affected();
+        }
+
+        tx.commit().await.map_err(StorageError::Database)?;
+        self.refresh_gauges().await?;
+        Ok(report)
+    }
 }
 
 #[async_trait]
 impl BlobStorage for SqliteStorage {
     async fn store_blob(&self, req: StoreBlobRequest) -> Result<Cursor, StorageError> {
+        let _timer = self.metrics.query_duration.start_timer("store_blob");
         let cursor = self.next_cursor(&req.group_id).await?;
         let expires_at = Self::current_timestamp() + req.ttl_secs as i64;
 
+        // Insert first so the write lock is held while checking whether this
+        // is the group's first blob; concurrent pushes cannot both count it
+        let mut tx = self.pool.begin().await.map_err(StorageError::Database)?;
+
         sqlx::query(
             r#"
             INSERT INTO blobs (blob_id, group_id, cursor, sender_id, payload, timestamp, expires_at)
@@ -200,10 +284,29 @@ impl BlobStorage for SqliteStorage {
         .bind(&req.payload)
         .bind(req.timestamp)
         .bind(expires_at)
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await
+        .map_err(StorageError::Database)?;
+
+        let first_in_group: bool = sqlx::query_scalar(
+            "SELECT NOT EXISTS(SELECT 1 FROM blobs WHERE group_id = ?1 AND blob_id != ?2)",
+        )
+        .bind(req.group_id.as_bytes().as_slice())
+        .bind(req.blob_id.as_bytes())
+        .fetch_one(&mut *tx)
         .await
         .map_err(StorageError::Database)?;
 
+        tx.commit().await.map_err(StorageError::Database)?;
+
+        self.metrics.blobs.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .bytes
+            .fetch_add(req.payload.len() as u64, Ordering::Relaxed);
+        if first_in_group {
+            self.metrics.groups.fetch_add(1, Ordering::Relaxed);
+        }
+
         Ok(cursor)
     }
 
@@ -213,6 +316,7 @@ impl BlobStorage for SqliteStorage {
         after: Cursor,
         limit: u32,
     ) -> Result<Vec<StoredBlob>, StorageError> {
+        let _timer = self.metrics.query_duration.start_timer("get_blobs_after");
         let rows = sqlx::query_as::<_, BlobRow>(
             r#"
             SELECT blob_id, group_id, cursor, sender_id, payload, timestamp, expires_at
@@ -278,6 +382,10 @@ impl BlobStorage for SqliteStorage {
         if blob_ids.is_empty() {
             return Ok(());
         }
+        let _timer = self
+            .metrics
+            .query_duration
+            .start_timer("mark_delivered_batch");
 
         let now = Self::current_timestamp();
         let device_bytes = device_id.as_bytes();
@@ -332,6 +440,7 @@ impl BlobStorage for SqliteStorage {
     }
 
     async fn cleanup_expired(&self) -> Result<u64, StorageError> {
+        let _timer = self.metrics.query_duration.start_timer("cleanup_expired");
         let now = Self::current_timestamp();
 
         // Delete deliveries for expired blobs using subquery (avoids N+1)
@@ -358,10 +467,14 @@ impl BlobStorage for SqliteStorage {
         .await
         .map_err(StorageError::Database)?;
 
+        if result.rows_affected() > 0 {
+            self.refresh_gauges().await?;
+        }
         Ok(result.rows_affected())
     }
 
     async fn get_group_storage(&self, group_id: &GroupId) -> Result<u64, StorageError> {
+        let _timer = self.metrics.query_duration.start_timer("get_group_storage");
         let size: Option<i64> = sqlx::query_scalar(
             r#"
             SELECT SUM(LENGTH(payload)) FROM blobs WHERE group_id = ?1
@@ -393,8 +506,416 @@ impl BlobStorage for SqliteStorage {
             None => Ok(None),
         }
     }
+
+    async fn store_content(
+        &self,
+        group_id: &GroupId,
+        content_hash: &[u8; 32],
+        data: &[u8],
+        ttl_secs: u64,
+    ) -> Result<bool, StorageError> {
+        let expires_at = Self::current_timestamp() + ttl_secs as i64;
+
+        // Content is immutable (hash-addressed): re-uploads only extend expiry
+        let result = sqlx::query(
+            r#"
+            INSERT INTO content_blobs (group_id, content_hash, data, expires_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(group_id, content_hash) DO NOTHING
+            "#,
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .bind(content_hash.as_slice())
+        .bind(data)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        if result.rows_affected() > 0 {
+            return Ok(true);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE content_blobs SET expires_at = MAX(expires_at, ?3)
+            WHERE group_id = ?1 AND content_hash = ?2
+            "#,
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .bind(content_hash.as_slice())
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(false)
+    }
+
+    async fn get_content(
+        &self,
+        group_id: &GroupId,
+        content_hash: &[u8; 32],
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT data FROM content_blobs
+            WHERE group_id = ?1 AND content_hash = ?2 AND expires_at > ?3
+            "#,
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .bind(content_hash.as_slice())
+        .bind(Self::current_timestamp())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(StorageError::Database)
+    }
+
+    async fn get_group_content_storage(&self, group_id: &GroupId) -> Result<u64, StorageError> {
+        let size: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT SUM(LENGTH(data)) FROM content_blobs WHERE group_id = ?1
+            "#,
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(size.unwrap_or(0) as u64)
+    }
+
+    async fn cleanup_expired_content(&self) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM content_blobs WHERE expires_at <= ?1
+            "#,
+        )
+        .bind(Self::current_timestamp())
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn put_mailbox(
+        &self,
+        mailbox: &[u8; 32],
+        payload: &[u8],
+        ttl_secs: u64,
+        max_messages: usize,
+    ) -> Result<bool, StorageError> {
+        let now = Self::current_timestamp();
+
+        // Single statement so concurrent puts can't overshoot the cap
+        let result = sqlx::query(
+            r#"
+            INSERT INTO mailbox_messages (mailbox, payload, expires_at)
+            SELECT ?1, ?2, ?3
+            WHERE (
+                SELECT COUNT(*) FROM mailbox_messages
+                WHERE mailbox = ?1 AND expires_at > ?4
+            ) < ?5
+            "#,
+        )
+        .bind(mailbox.as_slice())
+        .bind(payload)
+        .bind(now + ttl_secs as i64)
+        .bind(now)
+        .bind(max_messages as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn take_mailbox(&self, mailbox: &[u8; 32]) -> Result<Option<Vec<u8>>, StorageError> {
+        sqlx::query_scalar(
+            r#"
+            DELETE FROM mailbox_messages
+            WHERE id = (
+                SELECT id FROM mailbox_messages
+                WHERE mailbox = ?1 AND expires_at > ?2
+                ORDER BY id LIMIT 1
+            )
+            RETURNING payload
+            "#,
+        )
+        .bind(mailbox.as_slice())
+        .bind(Self::current_timestamp())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(StorageError::Database)
+    }
+
+    async fn cleanup_expired_mailbox(&self) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM mailbox_messages WHERE expires_at <= ?1
+            "#,
+        )
+        .bind(Self::current_timestamp())
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_or_create_oprf_key(
+        &self,
+        key_id: &[u8; 32],
+        candidate: &[u8; 32],
+    ) -> Result<[u8; 32], StorageError> {
+        sqlx::query("INSERT OR IGNORE INTO oprf_keys (key_id, secret) VALUES (?1, ?2)")
+            .bind(key_id.as_slice())
+            .bind(candidate.as_slice())
+            .execute(&self.pool)
+            .await
+            .map_err(StorageError::Database)?;
+
+        let secret: Vec<u8> = sqlx::query_scalar("SELECT secret FROM oprf_keys WHERE key_id = ?1")
+            .bind(key_id.as_slice())
+            .fetch_one(&self.pool)
+            .await
+            .map_err(StorageError::Database)?;
+
+        secret.try_into().map_err(|_| {
+            StorageError::Database(sqlx::Error::Decode("OPRF key must be 32 bytes".into()))
+        })
+    }
+
+    async fn register_invite(
+        &self,
+        group_id: &GroupId,
+        invite_id: &[u8; 16],
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<bool, StorageError> {
+        let mut tx = self.pool.begin().await.map_err(StorageError::Database)?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO invites (invite_id, group_id, uses_remaining, expires_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(invite_id.as_slice())
+        .bind(group_id.as_bytes().as_slice())
+        .bind(max_uses as i64)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(StorageError::Database)?;
+
+        sqlx::query("INSERT OR IGNORE INTO invite_only_groups (group_id) VALUES (?1)")
+            .bind(group_id.as_bytes().as_slice())
+            .execute(&mut *tx)
+            .await
+            .map_err(StorageError::Database)?;
+
+        tx.commit().await.map_err(StorageError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_invite(
+        &self,
+        group_id: &GroupId,
+        invite_id: &[u8; 16],
+    ) -> Result<bool, StorageError> {
+        let result =
+            sqlx::query("UPDATE invites SET revoked = 1 WHERE invite_id = ?1 AND group_id = ?2")
+                .bind(invite_id.as_slice())
+                .bind(group_id.as_bytes().as_slice())
+                .execute(&self.pool)
+                .await
+                .map_err(StorageError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn redeem_invite(
+        &self,
+        group_id: &GroupId,
+        invite_id: &[u8; 16],
+    ) -> Result<InviteRedemption, StorageError> {
+        let now = Self::current_timestamp();
+
+        // Single conditional decrement so concurrent joins can't overspend
+        let result = sqlx::query(
+            r#"
+            UPDATE invites SET uses_remaining = uses_remaining - 1
+            WHERE invite_id = ?1 AND group_id = ?2
+              AND revoked = 0 AND expires_at > ?3 AND uses_remaining > 0
+            "#,
+        )
+        .bind(invite_id.as_slice())
+        .bind(group_id.as_bytes().as_slice())
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        if result.rows_affected() > 0 {
+            return Ok(InviteRedemption::Accepted);
+        }
+
+        // Work out why it was rejected
+        let row: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT revoked, expires_at FROM invites WHERE invite_id = ?1 AND group_id = ?2",
+        )
+        .bind(invite_id.as_slice())
+        .bind(group_id.as_bytes().as_slice())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(match row {
+            None => InviteRedemption::Unknown,
+            Some((revoked, _)) if revoked != 0 => InviteRedemption::Revoked,
+            Some((_, expires_at)) if expires_at <= now => InviteRedemption::Expired,
+            Some(_) => InviteRedemption::Exhausted,
+        })
+    }
+
+    async fn is_group_invite_only(&self, group_id: &GroupId) -> Result<bool, StorageError> {
+        let found: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM invite_only_groups WHERE group_id = ?1")
+                .bind(group_id.as_bytes().as_slice())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(StorageError::Database)?;
+
+        Ok(found.is_some())
+    }
+
+    async fn add_group_member(
+        &self,
+        group_id: &GroupId,
+        device_id: &DeviceId,
+    ) -> Result<(), StorageError> {
+        sqlx::query("INSERT OR IGNORE INTO group_members (group_id, device_id) VALUES (?1, ?2)")
+            .bind(group_id.as_bytes().as_slice())
+            .bind(device_id.as_bytes().as_slice())
+            .execute(&self.pool)
+            .await
+            .map_err(StorageError::Database)?;
+
+        Ok(())
+    }
+
+    async fn is_group_member(
+        &self,
+        group_id: &GroupId,
+        device_id: &DeviceId,
+    ) -> Result<bool, StorageError> {
+        let found: Option<i64> = sqlx::query_scalar(
+            "SELECT 1 FROM group_members WHERE group_id = ?1 AND device_id = ?2",
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .bind(device_id.as_bytes().as_slice())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(found.is_some())
+    }
+
+    async fn cleanup_expired_invites(&self) -> Result<u64, StorageError> {
+        let result = sqlx::query("DELETE FROM invites WHERE expires_at <= ?1")
+            .bind(Self::current_timestamp())
+            .execute(&self.pool)
+            .await
+            .map_err(StorageError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_group_policy(&self, group_id: &GroupId) -> Result<GroupPolicy, StorageError> {
+        let row: Option<GroupPolicyRow> = sqlx::query_as(
+            r#"
+            SELECT max_storage_bytes, max_blob_size, min_ttl_secs, max_ttl_secs,
+                   messages_per_minute
+            FROM group_policies WHERE group_id = ?1
+            "#,
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(row
+            .map(|(storage, blob, min_ttl, max_ttl, rate)| GroupPolicy {
+                max_storage_bytes: storage.map(|v| v as u64),
+                max_blob_size: blob.map(|v| v as u64),
+                min_ttl_secs: min_ttl.map(|v| v as u64),
+                max_ttl_secs: max_ttl.map(|v| v as u64),
+                messages_per_minute: rate.map(|v| v as u32),
+            })
+            .unwrap_or_default())
+    }
+
+    async fn set_group_policy(
+        &self,
+        group_id: &GroupId,
+        policy: &GroupPolicy,
+    ) -> Result<(), StorageError> {
+        if policy.is_empty() {
+            sqlx::query("DELETE FROM group_policies WHERE group_id = ?1")
+                .bind(group_id.as_bytes().as_slice())
+                .execute(&self.pool)
+                .await
+                .map_err(StorageError::Database)?;
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO group_policies (group_id, max_storage_bytes, max_blob_size,
+                                        min_ttl_secs, max_ttl_secs, messages_per_minute,
+                                        updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(group_id) DO UPDATE
+            SET max_storage_bytes = excluded.max_storage_bytes,
+                max_blob_size = excluded.max_blob_size,
+                min_ttl_secs = excluded.min_ttl_secs,
+                max_ttl_secs = excluded.max_ttl_secs,
+                messages_per_minute = excluded.messages_per_minute,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(group_id.as_bytes().as_slice())
+        .bind(policy.max_storage_bytes.map(|v| v as i64))
+        .bind(policy.max_blob_size.map(|v| v as i64))
+        .bind(policy.min_ttl_secs.map(|v| v as i64))
+        .bind(policy.max_ttl_secs.map(|v| v as i64))
+        .bind(policy.messages_per_minute.map(i64::from))
+        .bind(Self::current_timestamp())
+        .execute(&self.pool)
+        .await
+        .map_err(StorageError::Database)?;
+
+        Ok(())
+    }
 }
 
+/// Row for [`SqliteStorage::list_groups`]:
+/// (group_id, blob count, blob bytes, content bytes, quota override).
+type GroupStatsRow = (Vec<u8>, i64, i64, i64, Option<i64>);
+
+/// Row for [`BlobStorage::get_group_policy`]: (storage, blob size, min TTL,
+/// max TTL, message rate), each `NULL` when not overridden.
+type GroupPolicyRow = (
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+);
+
 /// Internal row type for SQLite queries.
 #[derive(sqlx::FromRow)]
 struct BlobRow {
@@ -769,6 +1290,97 @@ mod tests {
         assert!(storage.get_blob(&fresh_id).await.unwrap().is_some());
     }
 
+    #[tokio::test]
+    async fn group_policy_roundtrip() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group = GroupId::random();
+        assert!(storage.get_group_policy(&group).await.unwrap().is_empty());
+
+        let policy = GroupPolicy {
+            max_blob_size: Some(4096),
+            max_ttl_secs: Some(3600),
+            messages_per_minute: Some(600),
+            ..GroupPolicy::default()
+        };
+        storage.set_group_policy(&group, &policy).await.unwrap();
+        assert_eq!(storage.get_group_policy(&group).await.unwrap(), policy);
+
+        storage
+            .set_group_policy(&group, &GroupPolicy::default())
+            .await
+            .unwrap();
+        assert!(storage.list_groups().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn storage_gauges_track_writes_and_deletes() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_a = GroupId::random();
+        let group_b = GroupId::random();
+        let device_id = DeviceId::random();
+
+        let mut req = make_request(&group_a, &device_id, b"expired");
+        req.ttl_secs = 0;
+        storage.store_blob(req).await.unwrap();
+        storage
+            .store_blob(make_request(&group_a, &device_id, b"fresh"))
+            .await
+            .unwrap();
+        storage
+            .store_blob(make_request(&group_b, &device_id, b"other"))
+            .await
+            .unwrap();
+
+        let gauges = storage.metrics();
+        assert_eq!(gauges.blobs.load(Ordering::Relaxed), 3);
+        assert_eq!(gauges.bytes.load(Ordering::Relaxed), 17);
+        assert_eq!(gauges.groups.load(Ordering::Relaxed), 2);
+        assert_eq!(gauges.query_duration.count("store_blob"), 3);
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        storage.cleanup_expired().await.unwrap();
+        assert_eq!(gauges.blobs.load(Ordering::Relaxed), 2);
+        assert_eq!(gauges.bytes.load(Ordering::Relaxed), 10);
+
+        storage.purge_group(&group_b).await.unwrap();
+        assert_eq!(gauges.blobs.load(Ordering::Relaxed), 1);
+        assert_eq!(gauges.groups.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            gauges.blobs.load(Ordering::Relaxed),
+            storage.get_total_blobs().await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn groups_gauge_counts_concurrent_first_pushes_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(
+            SqliteStorage::new(&dir.path().join("relay.db"))
+                .await
+                .unwrap(),
+        );
+        let group_id = GroupId::random();
+        let device_id = DeviceId::random();
+
+        let pushes: Vec<_> = (0..8)
+            .map(|_| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    storage
+                        .store_blob(make_request(&group_id, &device_id, b"racing"))
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for push in pushes {
+            push.await.unwrap();
+        }
+
+        assert_eq!(storage.metrics().groups.load(Ordering::Relaxed), 1);
+        assert_eq!(storage.metrics().blobs.load(Ordering::Relaxed), 8);
+    }
+
     #[tokio::test]
     async fn cleanup_removes_deliveries_for_expired_blobs() {
         let storage = SqliteStorage::in_memory().await.unwrap();
@@ -790,4 +1402,286 @@ mod tests {
         // Blob and delivery are gone
         assert!(storage.get_blob(&blob_id).await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn store_and_get_content() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let hash = [0x11; 32];
+
+        assert!(storage
+            .store_content(&group_id, &hash, b"cipher", 3600)
+            .await
+            .unwrap());
+        let data = storage.get_content(&group_id, &hash).await.unwrap();
+        assert_eq!(data, Some(b"cipher".to_vec()));
+
+        // Content is scoped to its group
+        let other = GroupId::random();
+        assert!(storage.get_content(&other, &hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn store_content_is_idempotent() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let hash = [0x22; 32];
+
+        assert!(storage
+            .store_content(&group_id, &hash, b"data", 3600)
+            .await
+            .unwrap());
+        assert!(!storage
+            .store_content(&group_id, &hash, b"data", 3600)
+            .await
+            .unwrap());
+
+        // Counted once toward the group's content quota
+        let size = storage.get_group_content_storage(&group_id).await.unwrap();
+        assert_eq!(size, 4);
+    }
+
+    #[tokio::test]
+    async fn content_storage_is_separate_from_blob_storage() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let device_id = DeviceId::random();
+
+        storage
+            .store_blob(make_request(&group_id, &device_id, b"12345"))
+            .await
+            .unwrap();
+        storage
+            .store_content(&group_id, &[0x33; 32], b"abc", 3600)
+            .await
+            .unwrap();
+
+        assert_eq!(storage.get_group_storage(&group_id).await.unwrap(), 5);
+        assert_eq!(
+            storage.get_group_content_storage(&group_id).await.unwrap(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn cleanup_expired_content_removes_old_content() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let expired = [0x44; 32];
+        let fresh = [0x55; 32];
+
+        storage
+            .store_content(&group_id, &expired, b"old", 0)
+            .await
+            .unwrap();
+        storage
+            .store_content(&group_id, &fresh, b"new", 3600)
+            .await
+            .unwrap();
+
+        // Expired content is not served even before cleanup runs
+        assert!(storage
+            .get_content(&group_id, &expired)
+            .await
+            .unwrap()
+            .is_none());
+
+        let deleted = storage.cleanup_expired_content().await.unwrap();
+        assert_eq!(deleted, 1);
+        assert!(storage
+            .get_content(&group_id, &fresh)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            storage.get_group_content_storage(&group_id).await.unwrap(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn mailbox_delivers_in_order_once() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let mailbox = [0x44; 32];
+
+        assert!(storage
+            .put_mailbox(&mailbox, b"first", 600, 4)
+            .await
+            .unwrap());
+        assert!(storage
+            .put_mailbox(&mailbox, b"second", 600, 4)
+            .await
+            .unwrap());
+
+        // Other mailboxes are unaffected
+        assert!(storage.take_mailbox(&[0x55; 32]).await.unwrap().is_none());
+
+        assert_eq!(
+            storage.take_mailbox(&mailbox).await.unwrap().as_deref(),
+            Some(&b"first"[..])
+        );
+        assert_eq!(
+            storage.take_mailbox(&mailbox).await.unwrap().as_deref(),
+            Some(&b"second"[..])
+        );
+        assert!(storage.take_mailbox(&mailbox).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn mailbox_rejects_puts_when_full() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let mailbox = [0x44; 32];
+
+        assert!(storage.put_mailbox(&mailbox, b"a", 600, 2).await.unwrap());
+        assert!(storage.put_mailbox(&mailbox, b"b", 600, 2).await.unwrap());
+        assert!(!storage.put_mailbox(&mailbox, b"c", 600, 2).await.unwrap());
+
+        // Taking a message frees a slot
+        storage.take_mailbox(&mailbox).await.unwrap();
+        assert!(storage.put_mailbox(&mailbox, b"c", 600, 2).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn cleanup_expired_mailbox_removes_old_messages() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let mailbox = [0x44; 32];
+
+        storage.put_mailbox(&mailbox, b"old", 0, 4).await.unwrap();
+        storage.put_mailbox(&mailbox, b"new", 600, 4).await.unwrap();
+
+        let deleted = storage.cleanup_expired_mailbox().await.unwrap();
+        assert_eq!(deleted, 1);
+        assert_eq!(
+            storage.take_mailbox(&mailbox).await.unwrap().as_deref(),
+            Some(&b"new"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn oprf_key_created_once() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let key_id = [0x66; 32];
+
+        let first = storage
+            .get_or_create_oprf_key(&key_id, &[1u8; 32])
+            .await
+            .unwrap();
+        assert_eq!(first, [1u8; 32]);
+
+        // Existing key wins over later candidates
+        let second = storage
+            .get_or_create_oprf_key(&key_id, &[2u8; 32])
+            .await
+            .unwrap();
+        assert_eq!(second, [1u8; 32]);
+
+        let other = storage
+            .get_or_create_oprf_key(&[0x77; 32], &[3u8; 32])
+            .await
+            .unwrap();
+        assert_eq!(other, [3u8; 32]);
+    }
+
+    #[tokio::test]
+    async fn invite_uses_are_limited() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let invite_id = [0x11; 16];
+        let expires_at = SqliteStorage::current_timestamp() + 600;
+
+        assert!(!storage.is_group_invite_only(&group_id).await.unwrap());
+        assert!(storage
+            .register_invite(&group_id, &invite_id, 2, expires_at)
+            .await
+            .unwrap());
+        assert!(storage.is_group_invite_only(&group_id).await.unwrap());
+
+        // Re-registering the same ID doesn't reset its uses
+        assert!(!storage
+            .register_invite(&group_id, &invite_id, 5, expires_at)
+            .await
+            .unwrap());
+
+        for _ in 0..2 {
+            assert_eq!(
+                storage.redeem_invite(&group_id, &invite_id).await.unwrap(),
+                InviteRedemption::Accepted
+            );
+        }
+        assert_eq!(
+            storage.redeem_invite(&group_id, &invite_id).await.unwrap(),
+            InviteRedemption::Exhausted
+        );
+
+        // Invites are scoped to their group
+        assert_eq!(
+            storage
+                .redeem_invite(&GroupId::random(), &invite_id)
+                .await
+                .unwrap(),
+            InviteRedemption::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn revoked_and_expired_invites_rejected() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let now = SqliteStorage::current_timestamp();
+
+        storage
+            .register_invite(&group_id, &[0x01; 16], 5, now + 600)
+            .await
+            .unwrap();
+        assert!(storage.revoke_invite(&group_id, &[0x01; 16]).await.unwrap());
+        assert!(!storage
+            .revoke_invite(&GroupId::random(), &[0x01; 16])
+            .await
+            .unwrap());
+        assert_eq!(
+            storage.redeem_invite(&group_id, &[0x01; 16]).await.unwrap(),
+            InviteRedemption::Revoked
+        );
+
+        storage
+            .register_invite(&group_id, &[0x02; 16], 5, now - 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.redeem_invite(&group_id, &[0x02; 16]).await.unwrap(),
+            InviteRedemption::Expired
+        );
+
+        // Cleanup drops expired invites but the group stays invite-only
+        assert_eq!(storage.cleanup_expired_invites().await.unwrap(), 1);
+        assert!(storage.is_group_invite_only(&group_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn group_membership_is_recorded() {
+        let storage = SqliteStorage::in_memory().await.unwrap();
+        let group_id = GroupId::random();
+        let device_id = DeviceId::random();
+
+        assert!(!storage
+            .is_group_member(&group_id, &device_id)
+            .await
+            .unwrap());
+        storage
+            .add_group_member(&group_id, &device_id)
+            .await
+            .unwrap();
+        storage
+            .add_group_member(&group_id, &device_id)
+            .await
+            .unwrap();
+        assert!(storage
+            .is_group_member(&group_id, &device_id)
+            .await
+            .unwrap());
+        assert!(!storage
+            .is_group_member(&GroupId::random(), &device_id)
+            .await
+            .unwrap());
+    }
 }
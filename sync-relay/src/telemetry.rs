@@ -0,0 +1,167 @@
+//! Request spans and optional OTLP trace export.
+//!
+//! PUSH and PULL are handled inside a `relay.request` span carrying the
+//! message type and the client's trace ID from the message's `trace`
+//! metadata; the NOTIFY fan-out a push triggers runs in a child span. With
+//! the `otel` feature, spans are exported over OTLP/HTTP and parented to the
+//! client span, so a client push, the relay store and the NOTIFY it causes
+//! appear as one trace.
+//!
+//! Span fields are limited to message types, sizes, counts and trace IDs.
+//! Payloads, keys and group secrets are never recorded.
+
+use sync_types::TraceContext;
+use tracing::Span;
+
+#[cfg(feature = "otel")]
+pub use otlp::{init_otlp, layer};
+
+/// Span for handling one request of `message_type`.
+///
+/// Joins the client's trace when the request carries trace metadata.
+pub fn request_span(message_type: &'static str, trace: Option<&TraceContext>) -> Span {
+    let span = tracing::info_span!(
+        "relay.request",
+        otel.name = message_type,
+        message_type,
+        trace_id = tracing::field::Empty,
+    );
+    if let Some(trace) = trace {
+        span.record("trace_id", trace.trace_id_hex().as_str());
+        #[cfg(feature = "otel")]
+        otlp::set_remote_parent(&span, trace);
+    }
+    span
+}
+
+#[cfg(feature = "otel")]
+mod otlp {
+    use crate::config::TelemetryConfig;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, TracerProvider as _,
+    };
+    use opentelemetry_otlp::{ExporterBuildError, SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+    use opentelemetry_sdk::Resource;
+    use sync_types::TraceContext;
+    use tracing::{Span, Subscriber};
+    use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Build a tracer provider exporting to the configured OTLP/HTTP endpoint.
+    ///
+    /// Returns `None` if no endpoint is configured. Call
+    /// `SdkTracerProvider::shutdown` on exit to flush buffered spans.
+    pub fn init_otlp(
+        config: &TelemetryConfig,
+    ) -> Result<Option<SdkTracerProvider>, ExporterBuildError> {
+        let Some(endpoint) = config.otlp_endpoint.as_deref() else {
+            return Ok(None);
+        };
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()?;
+        let resource = Resource::builder()
+            .with_service_name(config.service_name.clone())
+            .build();
+        Ok(Some(
+            SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(resource)
+                .build(),
+        ))
+    }
+
+    /// `tracing` layer that forwards spans to `provider`.
+    pub fn layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("sync-relay"))
+    }
+
+    /// Parent `span` to the client span described by `trace`.
+    pub(super) fn set_remote_parent(span: &Span, trace: &TraceContext) {
+        let remote = SpanContext::new(
+            TraceId::from_bytes(trace.trace_id),
+            SpanId::from_bytes(trace.span_id),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        if remote.is_valid() {
+            let _ = span.set_parent(opentelemetry::Context::new().with_remote_span_context(remote));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::telemetry::request_span;
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        /// Accept one HTTP request, reply 200 and return (request line, body).
+        fn collector_stand_in(listener: TcpListener) -> (String, Vec<u8>) {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut stream = reader.into_inner();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            (request_line, body)
+        }
+
+        #[test]
+        fn exports_request_spans_to_collector() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+            let collector = std::thread::spawn(move || collector_stand_in(listener));
+
+            let config = TelemetryConfig {
+                otlp_endpoint: Some(endpoint),
+                service_name: "relay-under-test".to_string(),
+            };
+            let provider = init_otlp(&config).unwrap().unwrap();
+            let subscriber = tracing_subscriber::registry().with(layer(&provider));
+
+            let trace = TraceContext {
+                trace_id: [0x5a; 16],
+                span_id: [0x17; 8],
+            };
+            tracing::subscriber::with_default(subscriber, || {
+                let span = request_span("push", Some(&trace));
+                let _entered = span.enter();
+            });
+            provider.force_flush().unwrap();
+
+            let (request_line, body) = collector.join().unwrap();
+            assert!(request_line.starts_with("POST /v1/traces "));
+            // The exported span belongs to the client's trace
+            assert!(body.windows(16).any(|w| w == trace.trace_id));
+            assert!(body.windows(16).any(|w| w == b"relay-under-test"));
+            let _ = provider.shutdown();
+        }
+    }
+}
@@ -20,6 +20,8 @@ pub use envelope::Envelope;
 pub use error::SyncError;
 pub use ids::{BlobId, Cursor, DeviceId, GroupId};
 pub use messages::{
-    Bye, ContentAck, ContentRef, Hello, Message, MessageType, Notify, Pull, PullBlob, PullResponse,
-    Push, PushAck, Welcome,
+    Bye, Challenge, ChallengeResponse, ContentAck, ContentData, ContentGet, ContentPut, ContentRef,
+    EncryptionMode, Handshake, Hello, InviteAck, InviteRegister, InviteRevoke, MailboxAck,
+    MailboxData, MailboxGet, MailboxPut, Message, MessageType, Notify, OprfEvaluate, OprfEvaluated,
+    Pull, PullBlob, PullResponse, Push, PushAck, TraceContext, Welcome,
 };
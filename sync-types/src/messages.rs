@@ -18,6 +18,10 @@ pub enum Message {
     Hello(Hello),
     /// Server response to Hello
     Welcome(Welcome),
+    /// Proof-of-work challenge in place of a reply (relay under load)
+    Challenge(Challenge),
+    /// Solution to a Challenge; completes the interrupted request
+    ChallengeResponse(ChallengeResponse),
     /// Push a blob
     Push(Push),
     /// Acknowledge a push
@@ -34,6 +38,32 @@ pub enum Message {
     ContentRef(ContentRef),
     /// Acknowledge content transfer complete
     ContentAck(ContentAck),
+    /// Upload encrypted content to the relay for offline recipients
+    ContentPut(ContentPut),
+    /// Request relay-hosted content by hash
+    ContentGet(ContentGet),
+    /// Response to ContentGet
+    ContentData(ContentData),
+    /// Hybrid secure channel handshake message (precedes HELLO)
+    Handshake(Handshake),
+    /// Leave a message in a relay mailbox (invite exchange)
+    MailboxPut(MailboxPut),
+    /// Acknowledge a mailbox put
+    MailboxAck(MailboxAck),
+    /// Take the oldest message from a relay mailbox
+    MailboxGet(MailboxGet),
+    /// Response to MailboxGet
+    MailboxData(MailboxData),
+    /// Ask the relay to evaluate the group's OPRF on a blinded element
+    OprfEvaluate(OprfEvaluate),
+    /// Response to OprfEvaluate
+    OprfEvaluated(OprfEvaluated),
+    /// Start tracking an invite for the session's group
+    InviteRegister(InviteRegister),
+    /// Revoke a tracked invite
+    InviteRevoke(InviteRevoke),
+    /// Acknowledge InviteRegister or InviteRevoke
+    InviteAck(InviteAck),
 }
 
 impl Message {
@@ -69,6 +99,9 @@ pub struct Hello {
     pub group_id: GroupId,
     /// Client's last known cursor (for resumption)
     pub last_cursor: Cursor,
+    /// Invite being redeemed on first join (groups with tracked invites)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_id: Option<[u8; 16]>,
 }
 
 /// Server response to Hello handshake.
@@ -82,6 +115,30 @@ pub struct Welcome {
     pub pending_count: u32,
 }
 
+/// Proof-of-work challenge sent in reply to HELLO, or to a mailbox or OPRF
+/// request made before HELLO.
+///
+/// The relay holds the request until the client answers with a
+/// [`ChallengeResponse`], then sends the reply it interrupted (Welcome for
+/// HELLO). One solved challenge covers the rest of the connection. See
+/// `sync_core::pow` for the puzzle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Challenge {
+    /// Random nonce, valid for this connection only
+    pub nonce: [u8; 32],
+    /// Required leading zero bits
+    pub difficulty: u8,
+}
+
+/// Solution to a [`Challenge`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeResponse {
+    /// Nonce from the challenge being answered
+    pub nonce: [u8; 32],
+    /// Counter found by the client
+    pub counter: u64,
+}
+
 /// Push a blob to the sync group.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Push {
@@ -91,6 +148,9 @@ pub struct Push {
     pub payload: Vec<u8>,
     /// Time-to-live in seconds (0 = no expiry)
     pub ttl: u32,
+    /// Trace context of the client operation (tracing only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceContext>,
 }
 
 /// Acknowledgement that a push was received and stored.
@@ -109,6 +169,9 @@ pub struct Pull {
     pub after_cursor: Cursor,
     /// Maximum number of blobs to return (0 = no limit)
     pub limit: u32,
+    /// Trace context of the client operation (tracing only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceContext>,
 }
 
 /// A single blob in a pull response.
@@ -142,6 +205,29 @@ pub struct Notify {
     pub latest_cursor: Cursor,
     /// Number of new blobs since client's last known cursor
     pub count: u32,
+    /// Trace context of the push that triggered this notification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceContext>,
+}
+
+/// W3C trace-context identifiers carried in request metadata.
+///
+/// Lets relay spans join the trace of the client operation that caused
+/// them. Holds random identifiers only — nothing derived from payloads,
+/// keys or group IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TraceContext {
+    /// 128-bit trace ID shared by every span in the trace
+    pub trace_id: [u8; 16],
+    /// 64-bit ID of the client span that sent the message
+    pub span_id: [u8; 8],
+}
+
+impl TraceContext {
+    /// Trace ID as 32 lowercase hex characters (W3C `traceparent` form).
+    pub fn trace_id_hex(&self) -> String {
+        self.trace_id.iter().map(|b| format!("{:02x}", b)).collect()
+    }
 }
 
 /// Graceful disconnect message.
@@ -156,7 +242,7 @@ pub struct Bye {
 /// Small sync messages (<64KB) go through the relay directly.
 /// Large content (photos, documents, audio) is stored in iroh-blobs
 /// and only the ContentRef is sent through the relay.
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Deserialize)]
 pub struct ContentRef {
     /// BLAKE3 hash of ciphertext (content address for iroh-blobs)
     pub content_hash: [u8; 32],
@@ -166,6 +252,68 @@ pub struct ContentRef {
     pub content_size: u64,
     /// Ciphertext size in bytes (content_size + 16 byte auth tag)
     pub encrypted_size: u64,
+    /// How the content key and nonce were derived.
+    /// Absent in older refs, which predate convergent encryption (`Random`).
+    #[serde(default)]
+    pub mode: EncryptionMode,
+    /// Whether the plaintext carries a compression header (see
+    /// `sync_core::compress`). Absent in older refs, which predate
+    /// compression (`false`).
+    #[serde(default)]
+    pub compressed: bool,
+}
+
+// MessagePack encodes structs as arrays, so an optional field can only be
+// left out if every field after it is too. Defaults are omitted from the
+// end, keeping refs that use neither option in the original encoding.
+impl Serialize for ContentRef {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let with_compressed = self.compressed;
+        let with_mode = with_compressed || !self.mode.is_random();
+        let len = 4 + usize::from(with_mode) + usize::from(with_compressed);
+
+        let mut state = serializer.serialize_struct("ContentRef", len)?;
+        state.serialize_field("content_hash", &self.content_hash)?;
+        state.serialize_field("encryption_nonce", &self.encryption_nonce)?;
+        state.serialize_field("content_size", &self.content_size)?;
+        state.serialize_field("encrypted_size", &self.encrypted_size)?;
+        if with_mode {
+            state.serialize_field("mode", &self.mode)?;
+        } else {
+            state.skip_field("mode")?;
+        }
+        if with_compressed {
+            state.serialize_field("compressed", &self.compressed)?;
+        } else {
+            state.skip_field("compressed")?;
+        }
+        state.end()
+    }
+}
+
+/// How a [`ContentRef`]'s content key and nonce are derived.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EncryptionMode {
+    /// Key from GroupSecret + blob_id, random nonce (default).
+    ///
+    /// Every `add()` produces fresh ciphertext, even for identical content.
+    #[default]
+    Random,
+    /// Key and nonce from GroupSecret + keyed hash of the plaintext.
+    ///
+    /// Identical content within a group dedups to one stored blob. Group
+    /// members can confirm guesses of stored content; outsiders and the
+    /// relay cannot.
+    Convergent,
+}
+
+impl EncryptionMode {
+    /// Whether this is the default [`Random`](Self::Random) mode.
+    pub fn is_random(&self) -> bool {
+        *self == EncryptionMode::Random
+    }
 }
 
 impl std::fmt::Debug for ContentRef {
@@ -175,6 +323,8 @@ impl std::fmt::Debug for ContentRef {
             .field("encryption_nonce", &"[REDACTED]")
             .field("content_size", &self.content_size)
             .field("encrypted_size", &self.encrypted_size)
+            .field("mode", &self.mode)
+            .field("compressed", &self.compressed)
             .finish()
     }
 }
@@ -186,6 +336,185 @@ pub struct ContentAck {
     pub content_hash: [u8; 32],
 }
 
+/// Upload encrypted content to the relay.
+///
+/// Fallback for when the device that added content may go offline before
+/// peers fetch it. The relay verifies `content_hash` against `data` and
+/// replies with [`ContentAck`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentPut {
+    /// BLAKE3 hash of `data` (content address)
+    pub content_hash: [u8; 32],
+    /// Ciphertext (opaque to relay)
+    pub data: Vec<u8>,
+    /// Time-to-live in seconds (0 = relay default)
+    pub ttl: u32,
+}
+
+impl std::fmt::Debug for ContentPut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentPut")
+            .field("content_hash", &"[REDACTED]")
+            .field("data", &format!("[{} bytes]", self.data.len()))
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+/// Request relay-hosted content by hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentGet {
+    /// BLAKE3 hash of the requested ciphertext
+    pub content_hash: [u8; 32],
+}
+
+/// Relay-hosted content returned for a [`ContentGet`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentData {
+    /// BLAKE3 hash of the requested ciphertext
+    pub content_hash: [u8; 32],
+    /// Ciphertext, or `None` if the relay does not hold it
+    pub data: Option<Vec<u8>>,
+}
+
+impl std::fmt::Debug for ContentData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentData")
+            .field("content_hash", &"[REDACTED]")
+            .field(
+                "data",
+                &self.data.as_ref().map(|d| format!("[{} bytes]", d.len())),
+            )
+            .finish()
+    }
+}
+
+/// Secure channel handshake message.
+///
+/// Carries one Noise message of the hybrid X25519 + ML-KEM-768 handshake.
+/// The client's final handshake message carries its HELLO; once it is
+/// processed, every frame in both directions is sealed by the channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handshake {
+    /// Raw Noise handshake message
+    pub payload: Vec<u8>,
+}
+
+/// Leave a message in a relay mailbox.
+///
+/// Mailboxes let two devices that don't share a group yet (an inviter and a
+/// joiner) exchange a few small messages through the relay. They are allowed
+/// before HELLO. The mailbox address is opaque to the relay.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MailboxPut {
+    /// Mailbox address (derived from an out-of-band secret)
+    pub mailbox: [u8; 32],
+    /// Message body (opaque to relay)
+    pub payload: Vec<u8>,
+    /// Time-to-live in seconds (0 = relay default; capped by the relay)
+    pub ttl: u32,
+}
+
+impl std::fmt::Debug for MailboxPut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MailboxPut")
+            .field("mailbox", &"[REDACTED]")
+            .field("payload", &format!("[{} bytes]", self.payload.len()))
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+/// Acknowledgement that a mailbox message was stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MailboxAck {
+    /// Mailbox the message was stored in
+    pub mailbox: [u8; 32],
+}
+
+/// Take the oldest unexpired message from a relay mailbox.
+///
+/// Delivery is single-use: the relay deletes the message it returns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MailboxGet {
+    /// Mailbox address
+    pub mailbox: [u8; 32],
+}
+
+/// Mailbox message returned for a [`MailboxGet`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MailboxData {
+    /// Mailbox address
+    pub mailbox: [u8; 32],
+    /// Oldest message, or `None` if the mailbox is empty
+    pub payload: Option<Vec<u8>>,
+}
+
+impl std::fmt::Debug for MailboxData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MailboxData")
+            .field("mailbox", &"[REDACTED]")
+            .field(
+                "payload",
+                &self
+                    .payload
+                    .as_ref()
+                    .map(|p| format!("[{} bytes]", p.len())),
+            )
+            .finish()
+    }
+}
+
+/// Ask the relay to evaluate its OPRF key on a blinded passphrase.
+///
+/// Used to harden passphrase-derived group secrets against offline brute
+/// force: the relay rate-limits evaluations per key. Allowed before HELLO,
+/// since the group secret isn't known yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OprfEvaluate {
+    /// Relay key ID (derived from the group salt)
+    pub key_id: [u8; 32],
+    /// Blinded group element (ristretto255)
+    pub blinded: [u8; 32],
+}
+
+/// Evaluated element returned for an [`OprfEvaluate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OprfEvaluated {
+    /// Relay key ID the element was evaluated under
+    pub key_id: [u8; 32],
+    /// Evaluated group element (ristretto255)
+    pub evaluated: [u8; 32],
+}
+
+/// Start tracking an invite for the session's group.
+///
+/// Once a group has a tracked invite, the relay only admits devices it has
+/// seen before or that redeem a valid invite in their HELLO.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InviteRegister {
+    /// Random invite ID (carried in the invite)
+    pub invite_id: [u8; 16],
+    /// Number of devices that may join with this invite
+    pub max_uses: u32,
+    /// Unix timestamp after which the invite is rejected
+    pub expires_at: u64,
+}
+
+/// Revoke a tracked invite (remaining uses are discarded).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InviteRevoke {
+    /// Invite ID to revoke
+    pub invite_id: [u8; 16],
+}
+
+/// Acknowledgement of an [`InviteRegister`] or [`InviteRevoke`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InviteAck {
+    /// Invite ID that was registered or revoked
+    pub invite_id: [u8; 16],
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,6 +526,7 @@ mod tests {
             device_name: "Test Device".into(),
             group_id: GroupId::from_secret(b"test"),
             last_cursor: Cursor::new(0),
+            invite_id: None,
         };
 
         let bytes = rmp_serde::to_vec(&hello).unwrap();
@@ -228,6 +558,7 @@ mod tests {
             blob_id: BlobId::new(),
             payload: vec![0u8; 1000],
             ttl: 3600,
+            trace: None,
         };
 
         let bytes = rmp_serde::to_vec(&push).unwrap();
@@ -255,6 +586,7 @@ mod tests {
         let pull = Pull {
             after_cursor: Cursor::new(100),
             limit: 50,
+            trace: None,
         };
 
         let bytes = rmp_serde::to_vec(&pull).unwrap();
@@ -297,6 +629,7 @@ mod tests {
         let notify = Notify {
             latest_cursor: Cursor::new(500),
             count: 10,
+            trace: None,
         };
 
         let bytes = rmp_serde::to_vec(&notify).unwrap();
@@ -334,6 +667,7 @@ mod tests {
             blob_id: BlobId::new(),
             payload: vec![1, 2, 3],
             ttl: 0,
+            trace: None,
         });
 
         let bytes = msg.to_bytes().unwrap();
@@ -349,6 +683,8 @@ mod tests {
             encryption_nonce: [0xCD; 24],
             content_size: 1024 * 1024,        // 1MB
             encrypted_size: 1024 * 1024 + 16, // + auth tag
+            mode: EncryptionMode::Random,
+            compressed: false,
         };
 
         let bytes = rmp_serde::to_vec(&content_ref).unwrap();
@@ -360,6 +696,98 @@ mod tests {
         assert_eq!(content_ref.encrypted_size, restored.encrypted_size);
     }
 
+    #[test]
+    fn content_ref_mode_roundtrip_and_legacy_default() {
+        let convergent = ContentRef {
+            content_hash: [0xAB; 32],
+            encryption_nonce: [0xCD; 24],
+            content_size: 10,
+            encrypted_size: 26,
+            mode: EncryptionMode::Convergent,
+            compressed: false,
+        };
+        let bytes = rmp_serde::to_vec(&convergent).unwrap();
+        let restored: ContentRef = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(restored, convergent);
+
+        // Refs from before the mode field decode as Random
+        #[derive(Serialize)]
+        struct LegacyContentRef {
+            content_hash: [u8; 32],
+            encryption_nonce: [u8; 24],
+            content_size: u64,
+            encrypted_size: u64,
+        }
+        let legacy = LegacyContentRef {
+            content_hash: [0xAB; 32],
+            encryption_nonce: [0xCD; 24],
+            content_size: 10,
+            encrypted_size: 26,
+        };
+        let bytes = rmp_serde::to_vec(&legacy).unwrap();
+        let restored: ContentRef = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(restored.mode, EncryptionMode::Random);
+
+        // Random refs keep the legacy encoding
+        let random = ContentRef {
+            mode: EncryptionMode::Random,
+            ..convergent
+        };
+        assert_eq!(rmp_serde::to_vec(&random).unwrap(), bytes);
+    }
+
+    #[test]
+    fn content_ref_compressed_keeps_mode_position() {
+        let compressed = ContentRef {
+            content_hash: [0xAB; 32],
+            encryption_nonce: [0xCD; 24],
+            content_size: 10,
+            encrypted_size: 27,
+            mode: EncryptionMode::Random,
+            compressed: true,
+        };
+        let bytes = rmp_serde::to_vec(&compressed).unwrap();
+        let restored: ContentRef = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(restored, compressed);
+
+        let convergent = ContentRef {
+            mode: EncryptionMode::Convergent,
+            ..compressed.clone()
+        };
+        let bytes = rmp_serde::to_vec(&convergent).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<ContentRef>(&bytes).unwrap(),
+            convergent
+        );
+
+        // Uncompressed refs keep the encoding from before the field
+        #[derive(Serialize)]
+        struct ModeOnlyContentRef {
+            content_hash: [u8; 32],
+            encryption_nonce: [u8; 24],
+            content_size: u64,
+            encrypted_size: u64,
+            mode: EncryptionMode,
+        }
+        let mode_only = ModeOnlyContentRef {
+            content_hash: [0xAB; 32],
+            encryption_nonce: [0xCD; 24],
+            content_size: 10,
+            encrypted_size: 27,
+            mode: EncryptionMode::Convergent,
+        };
+        let bytes = rmp_serde::to_vec(&mode_only).unwrap();
+        let uncompressed = ContentRef {
+            compressed: false,
+            ..convergent
+        };
+        assert_eq!(rmp_serde::to_vec(&uncompressed).unwrap(), bytes);
+        assert_eq!(
+            rmp_serde::from_slice::<ContentRef>(&bytes).unwrap(),
+            uncompressed
+        );
+    }
+
     #[test]
     fn content_ack_roundtrip() {
         let content_ack = ContentAck {
@@ -379,6 +807,8 @@ mod tests {
             encryption_nonce: [0x22; 24],
             content_size: 5000,
             encrypted_size: 5016,
+            mode: EncryptionMode::Random,
+            compressed: false,
         });
 
         let bytes = msg.to_bytes().unwrap();
@@ -395,6 +825,8 @@ mod tests {
             encryption_nonce: [0xCD; 24],
             content_size: 1024,
             encrypted_size: 1040,
+            mode: EncryptionMode::Random,
+            compressed: false,
         };
         let debug = format!("{:?}", content_ref);
         assert!(
@@ -405,4 +837,206 @@ mod tests {
                                                                             // Size fields are fine to display (metadata, not secrets)
         assert!(debug.contains("1024"), "content_size should be visible");
     }
+
+    #[test]
+    fn content_put_roundtrip() {
+        let msg = Message::ContentPut(ContentPut {
+            content_hash: [0xAB; 32],
+            data: vec![1, 2, 3, 4],
+            ttl: 3600,
+        });
+        let bytes = msg.to_bytes().unwrap();
+        let decoded = Message::from_bytes(&bytes).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn content_get_and_data_roundtrip() {
+        let get = Message::ContentGet(ContentGet {
+            content_hash: [0xCD; 32],
+        });
+        assert_eq!(get, Message::from_bytes(&get.to_bytes().unwrap()).unwrap());
+
+        let found = Message::ContentData(ContentData {
+            content_hash: [0xCD; 32],
+            data: Some(vec![9; 16]),
+        });
+        assert_eq!(
+            found,
+            Message::from_bytes(&found.to_bytes().unwrap()).unwrap()
+        );
+
+        let missing = Message::ContentData(ContentData {
+            content_hash: [0xCD; 32],
+            data: None,
+        });
+        assert_eq!(
+            missing,
+            Message::from_bytes(&missing.to_bytes().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn handshake_roundtrip() {
+        let msg = Message::Handshake(Handshake {
+            payload: vec![0x5A; 1232],
+        });
+        let bytes = msg.to_bytes().unwrap();
+        assert_eq!(msg, Message::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn content_put_debug_redacts_data() {
+        let put = ContentPut {
+            content_hash: [0xAB; 32],
+            data: vec![0x42; 8],
+            ttl: 0,
+        };
+        let debug = format!("{:?}", put);
+        assert!(debug.contains("[8 bytes]"));
+        assert!(!debug.contains("66")); // 0x42 bytes never printed
+    }
+
+    #[test]
+    fn mailbox_roundtrip() {
+        let put = Message::MailboxPut(MailboxPut {
+            mailbox: [0x11; 32],
+            payload: vec![1, 2, 3],
+            ttl: 600,
+        });
+        let bytes = put.to_bytes().unwrap();
+        assert_eq!(put, Message::from_bytes(&bytes).unwrap());
+
+        let data = Message::MailboxData(MailboxData {
+            mailbox: [0x11; 32],
+            payload: None,
+        });
+        let bytes = data.to_bytes().unwrap();
+        assert_eq!(data, Message::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn oprf_roundtrip() {
+        let request = Message::OprfEvaluate(OprfEvaluate {
+            key_id: [0x22; 32],
+            blinded: [0x33; 32],
+        });
+        let bytes = request.to_bytes().unwrap();
+        assert_eq!(request, Message::from_bytes(&bytes).unwrap());
+
+        let response = Message::OprfEvaluated(OprfEvaluated {
+            key_id: [0x22; 32],
+            evaluated: [0x44; 32],
+        });
+        let bytes = response.to_bytes().unwrap();
+        assert_eq!(response, Message::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn invite_messages_roundtrip() {
+        let register = Message::InviteRegister(InviteRegister {
+            invite_id: [0x55; 16],
+            max_uses: 1,
+            expires_at: 1_705_000_600,
+        });
+        let bytes = register.to_bytes().unwrap();
+        assert_eq!(register, Message::from_bytes(&bytes).unwrap());
+
+        let revoke = Message::InviteRevoke(InviteRevoke {
+            invite_id: [0x55; 16],
+        });
+        let bytes = revoke.to_bytes().unwrap();
+        assert_eq!(revoke, Message::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn hello_invite_id_roundtrip() {
+        let hello = Hello {
+            version: 1,
+            device_name: "Phone".into(),
+            group_id: GroupId::random(),
+            last_cursor: Cursor::zero(),
+            invite_id: Some([0x66; 16]),
+        };
+        let msg = Message::Hello(hello);
+        assert_eq!(msg, Message::from_bytes(&msg.to_bytes().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn hello_without_invite_id_matches_baseline_encoding() {
+        // HELLO { version: 1, device_name: "Phone", group_id: [0x11; 32],
+        // last_cursor: 7 } as encoded before invite IDs existed
+        let mut baseline = vec![0x95, 0xa5];
+        baseline.extend_from_slice(b"Hello");
+        baseline.extend_from_slice(&[0x01, 0xa5]);
+        baseline.extend_from_slice(b"Phone");
+        baseline.extend_from_slice(&[0xdc, 0x00, 0x20]);
+        baseline.extend_from_slice(&[0x11; 32]);
+        baseline.push(0x07);
+
+        let hello = Message::Hello(Hello {
+            version: 1,
+            device_name: "Phone".into(),
+            group_id: GroupId::from_bytes(&[0x11; 32]).unwrap(),
+            last_cursor: Cursor::new(7),
+            invite_id: None,
+        });
+        // Older clients' HELLOs decode, and older relays decode ours
+        assert_eq!(Message::from_bytes(&baseline).unwrap(), hello);
+        assert_eq!(hello.to_bytes().unwrap(), baseline);
+    }
+
+    #[test]
+    fn challenge_and_response_roundtrip() {
+        let challenge = Message::Challenge(Challenge {
+            nonce: [0x77; 32],
+            difficulty: 20,
+        });
+        let bytes = challenge.to_bytes().unwrap();
+        assert_eq!(challenge, Message::from_bytes(&bytes).unwrap());
+
+        let response = Message::ChallengeResponse(ChallengeResponse {
+            nonce: [0x77; 32],
+            counter: 123_456,
+        });
+        let bytes = response.to_bytes().unwrap();
+        assert_eq!(response, Message::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn trace_context_is_optional() {
+        let trace = TraceContext {
+            trace_id: [0xab; 16],
+            span_id: [0x01; 8],
+        };
+        assert_eq!(trace.trace_id_hex(), "ab".repeat(16));
+
+        let push = Message::Push(Push {
+            blob_id: BlobId::new(),
+            payload: vec![1, 2, 3],
+            ttl: 0,
+            trace: Some(trace),
+        });
+        assert_eq!(
+            push,
+            Message::from_bytes(&push.to_bytes().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn untraced_pull_matches_baseline_encoding() {
+        // PULL { after_cursor: 5, limit: 10 } as encoded before tracing
+        let mut baseline = vec![0x93, 0xa4];
+        baseline.extend_from_slice(b"Pull");
+        baseline.extend_from_slice(&[0x05, 0x0a]);
+
+        let pull = Message::Pull(Pull {
+            after_cursor: Cursor::new(5),
+            limit: 10,
+            trace: None,
+        });
+        // Older peers' PULLs decode, and untraced PULLs decode on older peers
+        assert_eq!(Message::from_bytes(&baseline).unwrap(), pull);
+        assert_eq!(pull.to_bytes().unwrap(), baseline);
+    }
 }
@@ -4,7 +4,7 @@
 //! for executing CLI commands inside containers, injecting network chaos,
 //! and collecting test state.
 
-use bollard::container::{LogsOptions, RestartContainerOptions};
+use bollard::container::{LogsOptions, RestartContainerOptions, StopContainerOptions};
 use bollard::exec::{CreateExecOptions, StartExecResults};
 use bollard::Docker;
 use futures_util::StreamExt;
@@ -449,6 +449,19 @@ impl ChaosHarness {
         Ok(())
     }
 
+    /// Stop a container gracefully (SIGTERM, then SIGKILL after `timeout_secs`).
+    pub async fn stop_container(
+        &self,
+        service: &str,
+        timeout_secs: i64,
+    ) -> Result<(), HarnessError> {
+        let name = self.container_name(service);
+        self.docker
+            .stop_container(&name, Some(StopContainerOptions { t: timeout_secs }))
+            .await?;
+        Ok(())
+    }
+
     /// Restart a container.
     pub async fn restart_container(&self, service: &str) -> Result<(), HarnessError> {
         let name = self.container_name(service);
@@ -5,7 +5,7 @@
 //! - `encryption` - E-HS-*, E-ENC-*, E-PQ-* (16 tests) - Cryptographic edge cases
 //! - `content` - S-BLOB-*, C-STOR-*, C-COLL-* (10 tests) - Blob integrity
 //! - `transport` - T-LAT-*, T-LOSS-*, T-CONN-*, T-BW-* (16 stubs) - Network chaos
-//! - `sync` - S-SM-*, S-CONC-*, S-CONV-* (12 stubs) - Protocol chaos
+//! - `sync` - S-SM-*, S-CONC-*, S-CONV-* (13 stubs) - Protocol chaos
 //!
 //! ## Phase Status
 //!
@@ -14,7 +14,7 @@
 //! | encryption | 16 | ✅ Runnable | MockTransport |
 //! | content | 10 | ✅ Runnable | MockTransport |
 //! | transport | 16 | ⏳ Stubs | Docker + Toxiproxy + sync-relay |
-//! | sync | 12 | ⏳ Stubs | Docker + sync-relay |
+//! | sync | 13 | ⏳ Stubs | Docker + sync-relay |
 //!
 //! ## Future Modules (Phase 6)
 //!
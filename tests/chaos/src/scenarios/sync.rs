@@ -16,6 +16,8 @@
 
 use std::path::PathBuf;
 
+use sync_client::{ClientError, IrohTransport, SyncClient, SyncConfig};
+
 use crate::assertions::assert_no_plaintext_in_logs;
 use crate::harness::ChaosHarness;
 use crate::netem::NetemConfig;
@@ -34,7 +36,7 @@ async fn setup_paired_harness() -> ChaosHarness {
 }
 
 // ============================================================================
-// S-SM-* Sync State Machine (4 tests)
+// S-SM-* Sync State Machine (5 tests)
 // ============================================================================
 
 /// S-SM-01: Disconnect during PUSH state, resume on reconnect.
@@ -230,6 +232,46 @@ async fn s_sm_04_rapid_state_transitions() {
     harness.teardown().await.expect("teardown failed");
 }
 
+/// S-SM-05: Relay shutdown (SIGTERM) drains with BYE; the client surfaces it.
+#[tokio::test]
+#[ignore = "requires docker"]
+async fn s_sm_05_relay_drain_sends_bye() {
+    let mut harness = ChaosHarness::new(compose_file()).expect("harness creation failed");
+    harness.setup().await.expect("harness setup failed");
+    let relay_id = harness
+        .relay_endpoint_id()
+        .expect("relay endpoint ID not found")
+        .to_string();
+
+    let (config, _salt) = SyncConfig::new("sm05-drain", &relay_id);
+    let transport = IrohTransport::new()
+        .await
+        .expect("transport creation failed");
+    let client = SyncClient::new(config, transport);
+    client.connect().await.expect("connect failed");
+
+    // The relay sends BYE while it drains, before the container exits
+    let (stopped, event) = tokio::join!(
+        harness.stop_container("relay", 30),
+        tokio::time::timeout(tokio::time::Duration::from_secs(20), client.next_event())
+    );
+    stopped.expect("stop relay failed");
+
+    // No other relay is configured, so failover gives up with the BYE reason
+    // (sync-relay's SHUTDOWN_BYE_REASON)
+    match event.expect("BYE should arrive before the timeout") {
+        Err(ClientError::AllRelaysFailed(msg)) => assert!(
+            msg.contains("relay shutting down"),
+            "Should surface the relay's BYE reason: {}",
+            msg
+        ),
+        other => panic!("expected AllRelaysFailed, got: {:?}", other),
+    }
+    assert!(!client.is_connected().await);
+
+    harness.teardown().await.expect("teardown failed");
+}
+
 // ============================================================================
 // S-CONC-* Concurrent Operations (4 tests)
 // ============================================================================